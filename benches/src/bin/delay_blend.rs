@@ -27,7 +27,10 @@ use core::mem::MaybeUninit;
 use daisy::hal::prelude::_stm32h7xx_hal_rng_RngExt;
 use sirena::memory_manager::MemoryManager;
 
-use kaseta_dsp::delay::{Attributes, Delay, FilterPlacement, HeadAttributes, WowFlutterPlacement};
+use kaseta_dsp::delay::{
+    Attributes, Delay, FeedbackTopology, FilterPlacement, HeadAttributes, PanLaw, PositionMode,
+    WowFlutterPlacement,
+};
 use kaseta_dsp::random::Random;
 use kaseta_dsp::tone::Tone2;
 use kaseta_dsp::wow_flutter::WowFlutter;
@@ -73,9 +76,9 @@ fn main() -> ! {
     cp.SCB.enable_dcache(&mut cp.CPUID);
 
     let mut randomizer = dp.RNG.constrain(ccdr.peripheral.RNG, &ccdr.clocks);
-    let mut delay = Delay::new(SAMPLE_RATE, &mut sdram_manager);
+    let mut delay = Delay::new(SAMPLE_RATE, &mut sdram_manager).unwrap();
     let mut tone = Tone2::new(SAMPLE_RATE);
-    let mut wow_flutter = WowFlutter::new(48_000, &mut stack_manager);
+    let mut wow_flutter = WowFlutter::new(48_000, &mut stack_manager).unwrap();
 
     let cycles = op_cyccnt_diff!(cp, {
         for i in 0..STEPS {
@@ -87,40 +90,70 @@ fn main() -> ! {
                         feedback: 0.3,
                         volume: 0.8,
                         pan: 0.5,
+                        fine_offset_ms: 0.0,
                         rewind_backward: None,
                         rewind_forward: None,
+                        feedback_invert: false,
+                        feedback_highpass: 0.0,
                     },
                     HeadAttributes {
                         position: i as f32 / STEPS as f32,
                         feedback: 0.0,
                         volume: 0.0,
                         pan: 0.5,
+                        fine_offset_ms: 0.0,
                         rewind_backward: None,
                         rewind_forward: None,
+                        feedback_invert: false,
+                        feedback_highpass: 0.0,
                     },
                     HeadAttributes {
                         position: i as f32 / STEPS as f32,
                         feedback: 0.0,
                         volume: 0.0,
                         pan: 0.5,
+                        fine_offset_ms: 0.0,
                         rewind_backward: None,
                         rewind_forward: None,
+                        feedback_invert: false,
+                        feedback_highpass: 0.0,
                     },
                     HeadAttributes {
                         position: i as f32 / STEPS as f32,
                         feedback: 0.0,
                         volume: 0.0,
                         pan: 0.5,
+                        fine_offset_ms: 0.0,
                         rewind_backward: None,
                         rewind_forward: None,
+                        feedback_invert: false,
+                        feedback_highpass: 0.0,
                     },
                 ],
                 reset_impulse: false,
                 random_impulse: false,
+                impulse_accent: 0.0,
+                impulse_threshold: 0.01,
+                impulse_subdivisions: 1,
                 filter_placement: FilterPlacement::Both,
                 wow_flutter_placement: WowFlutterPlacement::Both,
+                flutter_placement: None,
+                pan_law: PanLaw::Linear,
+                feedback_topology: FeedbackTopology::Cumulative,
+                feedback_character: 0.0,
+                stereo_offset_ms: 0.0,
+                position_mode: PositionMode::Proportional,
+                beat: None,
+                rewind_beat: None,
+                gate_width: 0.0,
+                separate_heads: false,
+                ping_pong: false,
                 reset_buffer: false,
+                splice_buffer: false,
                 paused: false,
+                freeze: false,
+                position_crossfade: None,
+                oversampled_read: false,
             });
             let mut input: [f32; BUFFER_SIZE] = random_buffer(&mut randomizer);
             let mut output_left: [f32; BUFFER_SIZE] = [0.0; BUFFER_SIZE];
@@ -20,7 +20,7 @@ use daisy::hal::prelude::_stm32h7xx_hal_rng_RngExt;
 use kaseta_benches as _;
 use kaseta_benches::{op_cyccnt_diff, random_buffer};
 
-use kaseta_dsp::hysteresis::{Attributes, Hysteresis};
+use kaseta_dsp::hysteresis::{Attributes, Hysteresis, Recovery};
 
 #[cortex_m_rt::entry]
 fn main() -> ! {
@@ -39,10 +39,12 @@ fn main() -> ! {
 
     let mut hysteresis = Hysteresis::new(48_000.0);
     hysteresis.set_attributes(Attributes {
-        dry_wet: 0.5,
         drive: 0.5,
         saturation: 0.5,
         width: 0.5,
+        auto_makeup: false,
+        limit: None,
+        recovery: Recovery::Reset,
     });
 
     let mut buffer: [f32; BUFFER_SIZE] = random_buffer(&mut randomizer);
@@ -28,7 +28,11 @@ fn main() -> ! {
     cp.SCB.enable_dcache(&mut cp.CPUID);
 
     let mut oscillator = Oscillator::new(48_000.0);
-    oscillator.set_attributes(&Attributes { frequency: 220.0 });
+    oscillator.set_attributes(&Attributes {
+        frequency: 220.0,
+        slope: 0.0,
+        drive: 0.0,
+    });
 
     let mut buffer = [0.0; BUFFER_SIZE];
 
@@ -55,8 +55,8 @@ fn main() -> ! {
     cp.SCB.enable_dcache(&mut cp.CPUID);
 
     let mut randomizer = dp.RNG.constrain(ccdr.peripheral.RNG, &ccdr.clocks);
-    let mut upsampler = Upsampler4::new_4(&mut memory_manager);
-    let mut downsampler = Downsampler4::new_4(&mut memory_manager);
+    let mut upsampler = Upsampler4::new_4(&mut memory_manager).unwrap();
+    let mut downsampler = Downsampler4::new_4(&mut memory_manager).unwrap();
 
     let cycles = op_cyccnt_diff!(cp, {
         for _ in 0..300 {
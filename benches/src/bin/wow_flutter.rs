@@ -56,17 +56,20 @@ fn main() -> ! {
     cp.SCB.enable_icache();
     cp.SCB.enable_dcache(&mut cp.CPUID);
 
-    let mut wow_flutter = WowFlutter::new(48_000, &mut memory_manager);
+    let mut wow_flutter = WowFlutter::new(48_000, &mut memory_manager).unwrap();
     wow_flutter.set_attributes(Attributes {
         wow_depth: 1.0,
         flutter_depth: 1.0,
         flutter_chance: 1.0,
+        scrape_depth: 0.0,
+        decorrelated: false,
+        frozen: false,
     });
 
     let mut buffer: [f32; BUFFER_SIZE] = random_buffer(&mut randomizer);
 
     let cycles = op_cyccnt_diff!(cp, {
-        let mut wow_flutter_delays = [0.0; 32];
+        let mut wow_flutter_delays = [0.0; BUFFER_SIZE];
         wow_flutter.populate_delays(&mut wow_flutter_delays[..], &mut RandomStub);
         for _ in 0..300 {
             wow_flutter.process(&mut buffer, &wow_flutter_delays);
@@ -3,6 +3,11 @@
 #[allow(unused_imports)]
 use micromath::F32Ext;
 
+// Bounds of the accepted distance between the two calibrated octaves. The
+// ideal distance is 1.0, matching 1V/oct.
+const MIN_DISTANCE: f32 = 0.5;
+const MAX_DISTANCE: f32 = 1.9;
+
 /// Use to manage calibration of a control input.
 ///
 /// This structure is calculates needed offset and scaling to adjust given
@@ -26,7 +31,7 @@ impl Calibration {
         };
 
         let distance = top - bottom;
-        if !(0.5..=1.9).contains(&distance) {
+        if !(MIN_DISTANCE..=MAX_DISTANCE).contains(&distance) {
             return None;
         }
 
@@ -45,6 +50,22 @@ impl Calibration {
     pub fn apply(self, value: f32) -> f32 {
         value * self.scaling + self.offset
     }
+
+    /// Estimate how well the calibrated input followed the 1V/oct line.
+    ///
+    /// Returns 1.0 when the measured octave spanned exactly one volt, falling
+    /// linearly to 0.0 towards the accepted bounds of the distance. Low values
+    /// suggest a marginal calibration, e.g. an octave off or a noisy source.
+    pub fn confidence(self) -> f32 {
+        let distance = 1.0 / self.scaling;
+        let residual = distance - 1.0;
+        let tolerance = if residual < 0.0 {
+            1.0 - MIN_DISTANCE
+        } else {
+            MAX_DISTANCE - 1.0
+        };
+        (1.0 - residual.abs() / tolerance).clamp(0.0, 1.0)
+    }
 }
 
 impl Default for Calibration {
@@ -60,6 +81,11 @@ impl Default for Calibration {
 mod tests {
     use super::*;
 
+    #[test]
+    fn when_not_calibrated_it_reports_full_confidence() {
+        assert_relative_eq!(Calibration::default().confidence(), 1.0);
+    }
+
     #[cfg(test)]
     mod with_octave_2_above_octave_1 {
         use super::*;
@@ -76,6 +102,20 @@ mod tests {
             assert!(Calibration::try_new(1.1, 1.3).is_none());
         }
 
+        #[test]
+        fn when_octaves_are_one_volt_apart_it_reports_high_confidence() {
+            let calibration = Calibration::try_new(1.1, 2.11).expect("Calibration failed");
+            assert!(calibration.confidence() > 0.95);
+        }
+
+        #[test]
+        fn when_octaves_are_close_to_the_bounds_it_reports_low_confidence() {
+            let too_close = Calibration::try_new(1.1, 1.65).expect("Calibration failed");
+            assert!(too_close.confidence() < 0.2);
+            let too_far = Calibration::try_new(1.1, 2.9).expect("Calibration failed");
+            assert!(too_far.confidence() < 0.2);
+        }
+
         #[test]
         fn when_sets_second_octave_too_far_it_fails() {
             assert!(Calibration::try_new(1.3, 3.3).is_none());
@@ -98,6 +138,12 @@ mod tests {
             assert!(Calibration::try_new(1.3, 1.1).is_none());
         }
 
+        #[test]
+        fn when_octaves_are_one_volt_apart_it_reports_high_confidence() {
+            let calibration = Calibration::try_new(2.11, 1.1).expect("Calibration failed");
+            assert!(calibration.confidence() > 0.95);
+        }
+
         #[test]
         fn when_sets_second_octave_too_far_it_fails() {
             assert!(Calibration::try_new(3.3, 1.3).is_none());
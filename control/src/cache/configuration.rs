@@ -1,3 +1,6 @@
+use super::quantization::Quantization;
+use crate::input::control::UnpluggedValue;
+
 /// Tweaking of the default module configuration.
 ///
 /// This is mean to allow tweaking of some more niche configuration of the
@@ -9,9 +12,34 @@
 pub struct Configuration {
     pub rewind_speed: [(usize, usize); 4],
     pub default_display_page: DisplayPage,
+    // NOTE: Mappings and capture settings are kept small so the save still
+    // fits into a single flash page.
     pub position_reset_mapping: PositionResetMapping,
     pub pause_resume_mapping: PauseResumeMapping,
-    pub tap_interval_denominator: usize,
+    pub tap_interval_denominator: u8,
+    pub capture_mapping: CaptureMapping,
+    pub tap_mapping: TapMapping,
+    pub capture_length: u8,
+    pub capture_repeats: u8,
+    pub multi_tap_pattern: u8,
+    pub output_level: u8,
+    pub cv_attenuversion: [u8; 4],
+    pub feedback_topology: FeedbackTopology,
+    pub gate_head: GateHead,
+    pub gate_width: u8,
+    pub quantization_grid: u8,
+    pub tape_age: u8,
+    pub spread: u8,
+    pub trails: bool,
+    pub tap_mode: TapMode,
+    pub impulse_threshold: u8,
+    pub cv_interpolation: bool,
+    pub position_crossfade: u8,
+    pub click_level: u8,
+    pub speed_smoothing: u8,
+    pub rewind_beat_lock: bool,
+    pub oversampled_read: bool,
+    pub unplugged_hold: bool,
 }
 
 #[derive(Debug, Clone, Copy, PartialEq, Eq)]
@@ -19,16 +47,175 @@ pub struct Configuration {
 pub enum DisplayPage {
     Heads,
     Position,
+    Speed,
+    Feedback,
+    Spread,
+    InputLevel,
+}
+
+/// Whether the tone in feedback darkens every repetition further or filters
+/// all echoes equally.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+#[cfg_attr(feature = "defmt", derive(defmt::Format))]
+pub enum FeedbackTopology {
+    Cumulative,
+    SinglePass,
+}
+
+/// Whether tapping the button sets the tempo or places heads on a rhythm.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+#[cfg_attr(feature = "defmt", derive(defmt::Format))]
+pub enum TapMode {
+    Tempo,
+    Rhythm,
 }
 
-pub type PositionResetMapping = Option<usize>;
+pub type PositionResetMapping = Option<u8>;
+
+pub type PauseResumeMapping = Option<u8>;
+
+pub type CaptureMapping = Option<u8>;
+
+/// Control input whose rising edges tap the tempo, along with the button.
+pub type TapMapping = Option<u8>;
 
-pub type PauseResumeMapping = Option<usize>;
+/// Head whose crossings open a gate on the impulse output. When `None`, the
+/// output sends short triggers for all heads.
+pub type GateHead = Option<u8>;
+
+/// Returned when a configuration holds an index outside of the range of its
+/// parameter.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+#[cfg_attr(feature = "defmt", derive(defmt::Format))]
+pub struct InvalidConfiguration;
 
 impl Configuration {
+    /// Check that all indices point into their tables.
+    ///
+    /// Configuration built by the module itself is always valid. This is
+    /// meant for configurations arriving from the outside, e.g. from an
+    /// editor, which would otherwise panic on access.
+    pub fn validate(&self) -> Result<(), InvalidConfiguration> {
+        let mappings = [
+            self.position_reset_mapping,
+            self.pause_resume_mapping,
+            self.capture_mapping,
+            self.tap_mapping,
+            self.gate_head,
+        ];
+        let valid = self
+            .rewind_speed
+            .iter()
+            .all(|(rewind, fast_forward)| *rewind < 4 && *fast_forward < 4)
+            && mappings.iter().flatten().all(|i| *i < 4)
+            && matches!(self.tap_interval_denominator, 1 | 4 | 8 | 16)
+            && self.capture_length < 4
+            && self.capture_repeats < 4
+            && self.multi_tap_pattern < 4
+            && self.output_level < 8
+            && self.cv_attenuversion.iter().all(|i| *i <= 8)
+            && self.gate_width < 4
+            && self.quantization_grid < 8
+            && self.tape_age < 8
+            && self.spread < 5
+            && self.impulse_threshold < 8
+            && self.position_crossfade < 5
+            && self.click_level < 4
+            && self.speed_smoothing < 4;
+        if valid {
+            Ok(())
+        } else {
+            Err(InvalidConfiguration)
+        }
+    }
+
+    /// Drop mappings of control inputs that do not exist.
+    ///
+    /// Unlike `validate`, this is meant for saves, which should be recovered
+    /// as much as possible. When other indices are out of range too, the
+    /// default configuration is returned instead.
+    pub(crate) fn sanitized(mut self) -> Self {
+        for mapping in [
+            &mut self.position_reset_mapping,
+            &mut self.pause_resume_mapping,
+            &mut self.capture_mapping,
+            &mut self.tap_mapping,
+            &mut self.gate_head,
+        ] {
+            if mapping.is_some_and(|i| i >= 4) {
+                *mapping = None;
+            }
+        }
+        if self.validate().is_ok() {
+            self
+        } else {
+            Self::default()
+        }
+    }
+
     pub(crate) fn rewind_speed(&self) -> [(f32, f32); 4] {
         rewind_indices_to_speeds(self.rewind_speed)
     }
+
+    pub(crate) fn capture_length(&self) -> f32 {
+        capture_length_index_to_seconds(self.capture_length)
+    }
+
+    pub(crate) fn capture_repeats(&self) -> usize {
+        capture_repeats_index_to_count(self.capture_repeats)
+    }
+
+    pub(crate) fn multi_tap_pattern(&self) -> Option<[f32; 4]> {
+        multi_tap_pattern_index_to_ratios(self.multi_tap_pattern)
+    }
+
+    pub(crate) fn output_level(&self) -> f32 {
+        output_level_index_to_gain(self.output_level)
+    }
+
+    pub(crate) fn gate_width(&self) -> f32 {
+        gate_width_index_to_portion(self.gate_width)
+    }
+
+    pub(crate) fn quantization_grid(&self) -> Quantization {
+        quantization_grid_index_to_quantization(self.quantization_grid)
+    }
+
+    pub(crate) fn tape_age(&self) -> f32 {
+        tape_age_index_to_age(self.tape_age)
+    }
+
+    pub(crate) fn spread(&self) -> f32 {
+        spread_index_to_width(self.spread)
+    }
+
+    pub(crate) fn impulse_threshold(&self) -> f32 {
+        impulse_threshold_index_to_volume(self.impulse_threshold)
+    }
+
+    pub(crate) fn position_crossfade(&self) -> f32 {
+        position_crossfade_index_to_seconds(self.position_crossfade)
+    }
+
+    pub(crate) fn unplugged_value(&self) -> UnpluggedValue {
+        if self.unplugged_hold {
+            UnpluggedValue::Hold
+        } else {
+            UnpluggedValue::Zero
+        }
+    }
+
+    pub(crate) fn click_level(&self) -> f32 {
+        click_level_index_to_gain(self.click_level)
+    }
+
+    pub(crate) fn speed_smoothing(&self) -> f32 {
+        speed_smoothing_index_to_dead_band(self.speed_smoothing)
+    }
+
+    pub(crate) fn cv_attenuversion(&self, control: usize) -> f32 {
+        cv_attenuversion_index_to_amount(self.cv_attenuversion[control])
+    }
 }
 
 impl Default for Configuration {
@@ -39,6 +226,29 @@ impl Default for Configuration {
             position_reset_mapping: None,
             pause_resume_mapping: None,
             tap_interval_denominator: 1,
+            capture_mapping: None,
+            tap_mapping: None,
+            capture_length: 1,
+            capture_repeats: 0,
+            multi_tap_pattern: 0,
+            output_level: 7,
+            cv_attenuversion: [8; 4],
+            feedback_topology: FeedbackTopology::Cumulative,
+            gate_head: None,
+            gate_width: 1,
+            quantization_grid: 0,
+            tape_age: 0,
+            spread: 0,
+            trails: false,
+            tap_mode: TapMode::Tempo,
+            impulse_threshold: 1,
+            cv_interpolation: false,
+            position_crossfade: 2,
+            click_level: 0,
+            speed_smoothing: 1,
+            rewind_beat_lock: false,
+            oversampled_read: false,
+            unplugged_hold: false,
         }
     }
 }
@@ -51,6 +261,34 @@ impl DisplayPage {
     pub fn is_position(&self) -> bool {
         matches!(self, Self::Position)
     }
+
+    pub fn is_speed(&self) -> bool {
+        matches!(self, Self::Speed)
+    }
+
+    pub fn is_feedback(&self) -> bool {
+        matches!(self, Self::Feedback)
+    }
+
+    pub fn is_spread(&self) -> bool {
+        matches!(self, Self::Spread)
+    }
+
+    pub fn is_input_level(&self) -> bool {
+        matches!(self, Self::InputLevel)
+    }
+}
+
+impl FeedbackTopology {
+    pub fn is_single_pass(&self) -> bool {
+        matches!(self, Self::SinglePass)
+    }
+}
+
+impl TapMode {
+    pub fn is_rhythm(&self) -> bool {
+        matches!(self, Self::Rhythm)
+    }
 }
 
 fn rewind_indices_to_speeds(x: [(usize, usize); 4]) -> [(f32, f32); 4] {
@@ -79,3 +317,76 @@ fn rewind_index_to_speed(i: usize) -> f32 {
         1.4999, // One octave up backwards. NOTE: Slightly less than 1.5 to avoid bumps while crossing samples
     ][i]
 }
+
+fn capture_length_index_to_seconds(i: u8) -> f32 {
+    [0.025, 0.05, 0.1, 0.2][i as usize]
+}
+
+fn capture_repeats_index_to_count(i: u8) -> usize {
+    [1, 2, 4, 8][i as usize]
+}
+
+fn multi_tap_pattern_index_to_ratios(i: u8) -> Option<[f32; 4]> {
+    [
+        None,                                                    // Set manually
+        Some([1.0 / 6.0, 1.0 / 3.0, 2.0 / 3.0, 1.0]),            // Triplet spread
+        Some([0.236_068, 0.381_966, 0.618_034, 1.0]),            // Golden ratio
+        Some([3.0 / 16.0, 6.0 / 16.0, 9.0 / 16.0, 12.0 / 16.0]), // Dotted cascade
+    ][i as usize]
+}
+
+fn output_level_index_to_gain(i: u8) -> f32 {
+    f32::from(i + 1) / 8.0
+}
+
+fn gate_width_index_to_portion(i: u8) -> f32 {
+    [1.0 / 16.0, 1.0 / 8.0, 1.0 / 4.0, 1.0 / 2.0][i as usize]
+}
+
+// NOTE: The first index leaves quantization to the options.
+fn quantization_grid_index_to_quantization(i: u8) -> Quantization {
+    [
+        Quantization::None,
+        Quantization::Steps(3),
+        Quantization::Steps(4),
+        Quantization::Steps(6),
+        Quantization::Steps(8),
+        Quantization::Steps(12),
+        Quantization::Steps(16),
+        Quantization::Both,
+    ][i as usize]
+}
+
+fn tape_age_index_to_age(i: u8) -> f32 {
+    f32::from(i) / 7.0
+}
+
+fn spread_index_to_width(i: u8) -> f32 {
+    f32::from(i) / 4.0
+}
+
+// NOTE: The first index lets even muted heads send impulses.
+fn impulse_threshold_index_to_volume(i: u8) -> f32 {
+    [0.0, 0.01, 0.05, 0.1, 0.2, 0.3, 0.5, 0.7][i as usize]
+}
+
+// NOTE: The middle index is close to the crossfade of freely moving heads.
+fn position_crossfade_index_to_seconds(i: u8) -> f32 {
+    [0.005, 0.015, 0.035, 0.1, 0.3][i as usize]
+}
+
+// NOTE: The first index turns the click off.
+fn click_level_index_to_gain(i: u8) -> f32 {
+    [0.0, 0.1, 0.25, 0.5][i as usize]
+}
+
+// NOTE: The first index shows every change of speed, the dead band of the
+// others stays below the width of a single LED.
+fn speed_smoothing_index_to_dead_band(i: u8) -> f32 {
+    [0.0, 0.02, 0.04, 0.08][i as usize]
+}
+
+// NOTE: The middle index turns the CV off, the lowest inverts it.
+fn cv_attenuversion_index_to_amount(i: u8) -> f32 {
+    (f32::from(i) - 4.0) / 4.0
+}
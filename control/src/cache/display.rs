@@ -7,7 +7,9 @@ use core::mem;
 #[derive(Debug)]
 #[cfg_attr(feature = "defmt", derive(defmt::Format))]
 pub struct Display {
-    pub prioritized: [Option<Screen>; 8],
+    pub prioritized: [Option<Screen>; 11],
+    /// Change of speed needed for the shown value to follow it.
+    pub speed_dead_band: f32,
 }
 
 #[derive(Debug, Clone, Copy)]
@@ -19,7 +21,10 @@ pub enum Screen {
     Attribute(u32, AttributeScreen),
     Clipping(u32),
     Paused(u32),
+    Bypassed(u32),
     BufferReset(u32),
+    MonoIncompatible(u32),
+    RunawayFeedback(u32),
 }
 
 #[derive(Debug, Clone, Copy)]
@@ -44,6 +49,27 @@ pub enum ConfigurationScreen {
     DefaultScreen(usize),
     ControlMapping(Option<usize>),
     TapIntervalDenominator(usize),
+    CaptureLength(usize),
+    CaptureRepeats(usize),
+    MultiTapPattern(usize),
+    OutputLevel(usize),
+    CvAttenuversion(usize),
+    FeedbackTopology(usize),
+    GateHead(Option<usize>),
+    GateWidth(usize),
+    QuantizationGrid(usize),
+    TapeAge(usize),
+    Spread(usize),
+    Trails(usize),
+    TapMode(usize),
+    ImpulseThreshold(usize),
+    CvInterpolation(usize),
+    RewindBeatLock(usize),
+    OversampledRead(usize),
+    UnpluggedHold(usize),
+    ClickLevel(usize),
+    PositionCrossfade(usize),
+    SpeedSmoothing(usize),
 }
 
 #[derive(Debug, Clone, Copy)]
@@ -54,6 +80,12 @@ pub enum AltAttributeScreen {
     FilterPlacement(FilterPlacement),
     HysteresisRange(HysteresisRange),
     WowFlutterPlacement(WowFlutterPlacement),
+    FineOffset(usize, f32),
+    PanLaw(PanLaw),
+    FeedbackPolarity(FeedbackPolarity),
+    MasterFeedback(f32),
+    SpeedGear(SpeedGear),
+    TapMapping(Option<usize>),
 }
 
 #[derive(Debug, Clone, Copy)]
@@ -86,6 +118,28 @@ pub enum HysteresisRange {
     Limited,
 }
 
+#[derive(Debug, Clone, Copy)]
+#[cfg_attr(feature = "defmt", derive(defmt::Format))]
+pub enum PanLaw {
+    Linear,
+    EqualPower,
+}
+
+#[derive(Debug, Clone, Copy)]
+#[cfg_attr(feature = "defmt", derive(defmt::Format))]
+pub enum FeedbackPolarity {
+    Unipolar,
+    Bipolar,
+}
+
+#[derive(Debug, Clone, Copy)]
+#[cfg_attr(feature = "defmt", derive(defmt::Format))]
+pub enum SpeedGear {
+    Half,
+    Normal,
+    Double,
+}
+
 #[derive(Debug, Clone, Copy)]
 #[cfg_attr(feature = "defmt", derive(defmt::Format))]
 pub enum WowFlutterPlacement {
@@ -98,6 +152,9 @@ pub enum WowFlutterPlacement {
 #[cfg_attr(feature = "defmt", derive(defmt::Format))]
 pub enum AttributeScreen {
     HeadsOverview(HeadsOverview),
+    FeedbackOverview([f32; 4]),
+    HeadsSpread(HeadsSpread),
+    InputLevel([usize; 2]),
     Position(usize),
     OctaveOffset(usize),
     OscillatorTone(f32),
@@ -116,6 +173,9 @@ pub enum AttributeScreen {
 
 pub type HeadsOverview = ([bool; 4], [bool; 4]);
 
+/// Position of each audible head within the delay length.
+pub type HeadsSpread = [Option<f32>; 4];
+
 impl Default for Display {
     fn default() -> Self {
         Self {
@@ -127,8 +187,12 @@ impl Default for Display {
                 None,
                 None,
                 None,
+                None,
+                None,
+                None,
                 Some(Screen::Attribute(0, AttributeScreen::Position(0))),
             ],
+            speed_dead_band: 0.0,
         }
     }
 }
@@ -171,6 +235,7 @@ impl Display {
     }
 
     pub fn force_attribute(&mut self, attribute: AttributeScreen) {
+        let attribute = self.steadied_attribute(4, attribute);
         self.set_screen(4, Screen::Attribute(0, attribute));
     }
 
@@ -195,6 +260,7 @@ impl Display {
             (false, 0)
         };
         if same_type {
+            let attribute = self.steadied_attribute(4, attribute);
             self.set_screen(4, Screen::Attribute(age, attribute));
         }
     }
@@ -207,19 +273,60 @@ impl Display {
         self.reset_screen(5);
     }
 
-    pub fn set_paused(&mut self) {
+    pub fn set_bypassed(&mut self) {
         match self.prioritized[6] {
+            Some(Screen::Bypassed(_)) => (),
+            _ => self.set_screen(6, Screen::Bypassed(0)),
+        }
+    }
+
+    pub fn reset_bypassed(&mut self) {
+        self.reset_screen(6);
+    }
+
+    pub fn set_paused(&mut self) {
+        match self.prioritized[7] {
             Some(Screen::Paused(_)) => (),
-            _ => self.set_screen(6, Screen::Paused(0)),
+            _ => self.set_screen(7, Screen::Paused(0)),
         }
     }
 
     pub fn reset_paused(&mut self) {
-        self.reset_screen(6);
+        self.reset_screen(7);
+    }
+
+    pub fn set_mono_incompatible(&mut self) {
+        match self.prioritized[8] {
+            Some(Screen::MonoIncompatible(_)) => (),
+            _ => self.set_screen(8, Screen::MonoIncompatible(0)),
+        }
+    }
+
+    pub fn set_runaway_feedback(&mut self) {
+        match self.prioritized[9] {
+            Some(Screen::RunawayFeedback(_)) => (),
+            _ => self.set_screen(9, Screen::RunawayFeedback(0)),
+        }
     }
 
     pub fn set_fallback_attribute(&mut self, attribute: AttributeScreen) {
-        self.set_screen(7, Screen::Attribute(0, attribute));
+        let attribute = self.steadied_attribute(10, attribute);
+        self.set_screen(10, Screen::Attribute(0, attribute));
+    }
+
+    // NOTE: Speed jittering around the boundary of two LEDs would make them
+    // chatter. The shown value is kept until the new one leaves its dead band.
+    fn steadied_attribute(&self, priority: usize, attribute: AttributeScreen) -> AttributeScreen {
+        if let (
+            AttributeScreen::Speed(phase),
+            Some(Screen::Attribute(_, AttributeScreen::Speed(shown))),
+        ) = (attribute, self.prioritized[priority])
+        {
+            if (phase - shown).abs() < self.speed_dead_band {
+                return AttributeScreen::Speed(shown);
+            }
+        }
+        attribute
     }
 
     fn set_screen(&mut self, priority: usize, screen: Screen) {
@@ -240,7 +347,10 @@ impl Screen {
             Self::Attribute(_, attribute) => leds_for_attribute(*attribute),
             Self::Clipping(cycles) => leds_for_clipping(*cycles),
             Self::Paused(cycles) => leds_for_paused(*cycles),
+            Self::Bypassed(cycles) => leds_for_bypassed(*cycles),
             Self::BufferReset(progress) => leds_for_buffer_reset(*progress),
+            Self::MonoIncompatible(cycles) => leds_for_mono_incompatible(*cycles),
+            Self::RunawayFeedback(cycles) => leds_for_runaway_feedback(*cycles),
         }
     }
 
@@ -252,7 +362,10 @@ impl Screen {
             Screen::Attribute(age, attribute) => ticked_attribute(age, attribute),
             Screen::Clipping(age) => ticked_clipping(age),
             Screen::Paused(cycles) => ticked_paused(cycles),
+            Screen::Bypassed(cycles) => ticked_bypassed(cycles),
             Screen::BufferReset(_) => Some(self),
+            Screen::MonoIncompatible(age) => ticked_mono_incompatible(age),
+            Screen::RunawayFeedback(age) => ticked_runaway_feedback(age),
         }
     }
 }
@@ -264,6 +377,27 @@ fn ticked_dialog(menu: DialogScreen) -> DialogScreen {
             ConfigurationScreen::DefaultScreen(_) => menu,
             ConfigurationScreen::ControlMapping(_) => menu,
             ConfigurationScreen::TapIntervalDenominator(_) => menu,
+            ConfigurationScreen::CaptureLength(_) => menu,
+            ConfigurationScreen::CaptureRepeats(_) => menu,
+            ConfigurationScreen::MultiTapPattern(_) => menu,
+            ConfigurationScreen::OutputLevel(_) => menu,
+            ConfigurationScreen::CvAttenuversion(_) => menu,
+            ConfigurationScreen::FeedbackTopology(_) => menu,
+            ConfigurationScreen::GateHead(_) => menu,
+            ConfigurationScreen::GateWidth(_) => menu,
+            ConfigurationScreen::QuantizationGrid(_) => menu,
+            ConfigurationScreen::TapeAge(_) => menu,
+            ConfigurationScreen::Spread(_) => menu,
+            ConfigurationScreen::Trails(_) => menu,
+            ConfigurationScreen::TapMode(_) => menu,
+            ConfigurationScreen::ImpulseThreshold(_) => menu,
+            ConfigurationScreen::CvInterpolation(_) => menu,
+            ConfigurationScreen::RewindBeatLock(_) => menu,
+            ConfigurationScreen::OversampledRead(_) => menu,
+            ConfigurationScreen::UnpluggedHold(_) => menu,
+            ConfigurationScreen::ClickLevel(_) => menu,
+            ConfigurationScreen::PositionCrossfade(_) => menu,
+            ConfigurationScreen::SpeedSmoothing(_) => menu,
         },
         DialogScreen::Calibration(calibration) => match calibration {
             CalibrationScreen::SelectOctave1(i, cycles) => ticked_calibration_1(i, cycles),
@@ -324,11 +458,32 @@ fn ticked_clipping(age: u32) -> Option<Screen> {
     }
 }
 
+fn ticked_mono_incompatible(age: u32) -> Option<Screen> {
+    if age > 120 {
+        None
+    } else {
+        Some(Screen::MonoIncompatible(age + 1))
+    }
+}
+
+fn ticked_runaway_feedback(age: u32) -> Option<Screen> {
+    if age > 120 {
+        None
+    } else {
+        Some(Screen::RunawayFeedback(age + 1))
+    }
+}
+
 fn ticked_paused(mut cycles: u32) -> Option<Screen> {
     cycles = if cycles > 240 * 8 { 0 } else { cycles + 1 };
     Some(Screen::Paused(cycles))
 }
 
+fn ticked_bypassed(mut cycles: u32) -> Option<Screen> {
+    cycles = if cycles > 500 * 2 { 0 } else { cycles + 1 };
+    Some(Screen::Bypassed(cycles))
+}
+
 fn leds_for_failure(cycles: u32) -> [bool; 8] {
     const INTERVAL_ON: u32 = 80;
     const INTERVAL_OFF: u32 = INTERVAL_ON * 2;
@@ -408,6 +563,15 @@ fn leds_for_configuration(configuration: &ConfigurationScreen) -> [bool; 8] {
             }
             leds
         }
+        // NOTE: Heads are laid out in columns, as on the heads overview. The
+        // selected one lights up its volume LED on the top row.
+        ConfigurationScreen::GateHead(head) => {
+            let mut leds = [false; 8];
+            if let Some(index) = head {
+                leds[*index] = true;
+            }
+            leds
+        }
         ConfigurationScreen::TapIntervalDenominator(denominator) => {
             let index = match *denominator {
                 16 => 0,
@@ -418,9 +582,64 @@ fn leds_for_configuration(configuration: &ConfigurationScreen) -> [bool; 8] {
             };
             index_to_leds(index)
         }
+        ConfigurationScreen::CaptureLength(index)
+        | ConfigurationScreen::CaptureRepeats(index)
+        | ConfigurationScreen::MultiTapPattern(index)
+        | ConfigurationScreen::FeedbackTopology(index)
+        | ConfigurationScreen::Trails(index)
+        | ConfigurationScreen::TapMode(index)
+        | ConfigurationScreen::CvInterpolation(index)
+        | ConfigurationScreen::RewindBeatLock(index)
+        | ConfigurationScreen::OversampledRead(index)
+        | ConfigurationScreen::UnpluggedHold(index)
+        | ConfigurationScreen::PositionCrossfade(index)
+        | ConfigurationScreen::SpeedSmoothing(index)
+        | ConfigurationScreen::GateWidth(index)
+        | ConfigurationScreen::QuantizationGrid(index) => index_to_leds(*index),
+        ConfigurationScreen::OutputLevel(index)
+        | ConfigurationScreen::TapeAge(index)
+        | ConfigurationScreen::ImpulseThreshold(index)
+        | ConfigurationScreen::ClickLevel(index) => {
+            let mut leds = [false; 8];
+            for led in leds.iter_mut().take(index + 1) {
+                *led = true;
+            }
+            leds
+        }
+        ConfigurationScreen::CvAttenuversion(index) => cv_attenuversion_to_leds(*index),
+        ConfigurationScreen::Spread(index) => spread_to_leds(*index),
     }
 }
 
+// NOTE: The spread widens from the middle of the top row, continuing to the
+// bottom row once the top is full.
+fn spread_to_leds(index: usize) -> [bool; 8] {
+    const STEPS: [[bool; 8]; 5] = [
+        [false, false, false, false, false, false, false, false],
+        [false, true, true, false, false, false, false, false],
+        [true, true, true, true, false, false, false, false],
+        [true, true, true, true, false, true, true, false],
+        [true, true, true, true, true, true, true, true],
+    ];
+    STEPS[index]
+}
+
+// NOTE: Inverted amount grows on the top row from right to left, the positive
+// one on the bottom row from left to right.
+fn cv_attenuversion_to_leds(index: usize) -> [bool; 8] {
+    let mut leds = [false; 8];
+    if index < 4 {
+        for led in leds.iter_mut().take(4).skip(index) {
+            *led = true;
+        }
+    } else {
+        for led in leds.iter_mut().skip(4).take(index - 4) {
+            *led = true;
+        }
+    }
+    leds
+}
+
 fn leds_for_alt_attribute(alt_attribute: AltAttributeScreen) -> [bool; 8] {
     match alt_attribute {
         AltAttributeScreen::PreAmpMode(mode) => match mode {
@@ -446,6 +665,29 @@ fn leds_for_alt_attribute(alt_attribute: AltAttributeScreen) -> [bool; 8] {
             WowFlutterPlacement::Read => [false, true, true, true, false, true, true, true],
             WowFlutterPlacement::Both => [true, true, true, true, true, true, true, true],
         },
+        AltAttributeScreen::FineOffset(position, phase) => pan_to_leds(position, phase),
+        AltAttributeScreen::PanLaw(law) => match law {
+            PanLaw::Linear => [true, false, false, true, true, false, false, true],
+            PanLaw::EqualPower => [false, true, true, false, false, true, true, false],
+        },
+        AltAttributeScreen::MasterFeedback(phase) => phase_to_leds(phase),
+        AltAttributeScreen::TapMapping(mapping) => {
+            let mut leds = [false; 8];
+            if let Some(index) = mapping {
+                leds[index] = true;
+                leds[index + 4] = true;
+            }
+            leds
+        }
+        AltAttributeScreen::FeedbackPolarity(polarity) => match polarity {
+            FeedbackPolarity::Unipolar => [false, false, true, true, false, false, true, true],
+            FeedbackPolarity::Bipolar => [true, true, false, false, false, false, true, true],
+        },
+        AltAttributeScreen::SpeedGear(gear) => match gear {
+            SpeedGear::Half => [true, true, false, false, true, true, false, false],
+            SpeedGear::Normal => [false, true, true, false, false, true, true, false],
+            SpeedGear::Double => [false, false, true, true, false, false, true, true],
+        },
     }
 }
 
@@ -454,6 +696,9 @@ fn leds_for_attribute(attribute: AttributeScreen) -> [bool; 8] {
         AttributeScreen::HeadsOverview((top, bottom)) => [
             top[0], top[1], top[2], top[3], bottom[0], bottom[1], bottom[2], bottom[3],
         ],
+        AttributeScreen::FeedbackOverview(feedback) => feedback_overview_to_leds(feedback),
+        AttributeScreen::HeadsSpread(spread) => heads_spread_to_leds(spread),
+        AttributeScreen::InputLevel(bars) => input_level_to_leds(bars),
         AttributeScreen::Position(position) => position_to_leds(position),
         AttributeScreen::OctaveOffset(offset) => {
             let mut leds = [false; 8];
@@ -493,6 +738,20 @@ fn leds_for_paused(cycles: u32) -> [bool; 8] {
     leds
 }
 
+// NOTE: Only the outer LEDs blink, slower than when paused, so the two
+// are easy to tell apart.
+fn leds_for_bypassed(cycles: u32) -> [bool; 8] {
+    const INTERVAL: u32 = 500;
+    let mut leds = [false; 8];
+    if cycles / INTERVAL == 0 {
+        leds[0] = true;
+        leds[3] = true;
+        leds[4] = true;
+        leds[7] = true;
+    }
+    leds
+}
+
 fn leds_for_buffer_reset(progress: u32) -> [bool; 8] {
     if progress < 4 {
         let mut leds = [true; 8];
@@ -622,6 +881,40 @@ fn feedback_to_leds(position: usize, phase: f32) -> [bool; 8] {
     leds
 }
 
+fn feedback_overview_to_leds(feedback: [f32; 4]) -> [bool; 8] {
+    let mut leds = [false; 8];
+    for (i, phase) in feedback.iter().enumerate() {
+        leds[4 + i] = *phase > f32::EPSILON;
+        leds[i] = *phase > 0.5;
+    }
+    leds
+}
+
+// NOTE: Heads are placed on the same round-robin as the position within the
+// loop, so the two pages can be read the same way.
+fn heads_spread_to_leds(spread: HeadsSpread) -> [bool; 8] {
+    let mut leds = [false; 8];
+    for position in spread.iter().flatten() {
+        let index = ((position * 8.0) as usize).min(7);
+        let head_leds = position_to_leds(index);
+        for (led, head_led) in leds.iter_mut().zip(head_leds) {
+            *led |= head_led;
+        }
+    }
+    leds
+}
+
+// NOTE: Left channel is shown on the top row, right on the bottom.
+fn input_level_to_leds(bars: [usize; 2]) -> [bool; 8] {
+    let mut leds = [false; 8];
+    for (row, bar) in bars.iter().enumerate() {
+        for led in leds.iter_mut().skip(row * 4).take(*bar) {
+            *led = true;
+        }
+    }
+    leds
+}
+
 fn pan_to_leds(position: usize, phase: f32) -> [bool; 8] {
     let mut leds = [false, false, false, false, true, true, true, true];
     leds[position] = true;
@@ -649,6 +942,28 @@ fn leds_for_clipping(cycles: u32) -> [bool; 8] {
     }
 }
 
+// NOTE: Unlike clipping, which fills the display from both sides, halves
+// are lit in turns, hinting that the channels work against each other.
+fn leds_for_mono_incompatible(cycles: u32) -> [bool; 8] {
+    if cycles < 40 || (80..120).contains(&cycles) {
+        [true, true, true, true, false, false, false, false]
+    } else {
+        [false, false, false, false, true, true, true, true]
+    }
+}
+
+// NOTE: The feedback is pulled back, so the display shrinks from its edges
+// towards the middle.
+fn leds_for_runaway_feedback(cycles: u32) -> [bool; 8] {
+    if cycles < 40 {
+        [true, true, true, true, true, true, true, true]
+    } else if cycles < 80 {
+        [false, true, true, true, true, true, true, false]
+    } else {
+        [false, false, true, true, true, true, false, false]
+    }
+}
+
 impl DialogScreen {
     pub fn configuration() -> Self {
         DialogScreen::Configuration(ConfigurationScreen::Idle(0))
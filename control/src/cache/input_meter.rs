@@ -0,0 +1,85 @@
+//! Input level meter holding peaks for a moment before they decay.
+
+// Peaks are held for half a second at the 1 kHz control rate.
+const HOLD_TICKS: u32 = 500;
+
+// Once the hold is over, the level falls by about 26 dB per second.
+const RELEASE: f32 = 0.997;
+
+// Levels lighting up each LED of the bar: -24, -12, -6 and -1 dBFS.
+const THRESHOLDS: [f32; 4] = [0.063, 0.251, 0.501, 0.891];
+
+/// Meter of left and right input levels.
+#[derive(Debug, Default)]
+#[cfg_attr(feature = "defmt", derive(defmt::Format))]
+pub struct InputMeter {
+    levels: [f32; 2],
+    held: [u32; 2],
+}
+
+impl InputMeter {
+    pub fn update(&mut self, peaks: [f32; 2]) {
+        for ((level, held), peak) in self.levels.iter_mut().zip(&mut self.held).zip(peaks) {
+            if peak >= *level {
+                *level = peak;
+                *held = 0;
+            }
+        }
+    }
+
+    pub fn tick(&mut self) {
+        for (level, held) in self.levels.iter_mut().zip(&mut self.held) {
+            if *held < HOLD_TICKS {
+                *held += 1;
+            } else {
+                *level *= RELEASE;
+            }
+        }
+    }
+
+    /// Number of LEDs lit for the left and right channel, from 0 to 4.
+    pub fn bars(&self) -> [usize; 2] {
+        self.levels.map(|level| {
+            THRESHOLDS
+                .iter()
+                .filter(|threshold| level >= **threshold)
+                .count()
+        })
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn when_peak_arrives_it_is_held_and_then_decays() {
+        let mut meter = InputMeter::default();
+
+        meter.update([0.6, 0.1]);
+        assert_eq!(meter.bars(), [3, 1]);
+
+        for _ in 0..HOLD_TICKS {
+            meter.tick();
+            meter.update([0.0, 0.0]);
+        }
+        assert_eq!(meter.bars(), [3, 1]);
+
+        for _ in 0..1000 {
+            meter.tick();
+            meter.update([0.0, 0.0]);
+        }
+        assert_eq!(meter.bars(), [0, 0]);
+    }
+
+    #[test]
+    fn when_louder_peak_arrives_during_hold_it_replaces_the_held_one() {
+        let mut meter = InputMeter::default();
+
+        meter.update([0.1, 0.1]);
+        meter.tick();
+        meter.update([1.0, 0.05]);
+
+        assert_eq!(meter.bars(), [4, 1]);
+    }
+}
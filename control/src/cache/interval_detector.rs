@@ -13,12 +13,26 @@ use core::ops::Range;
 #[cfg_attr(feature = "defmt", derive(defmt::Format))]
 pub struct IntervalDetector {
     trigger_age: [u32; 3],
+    triggers: u8,
+    forgive_outlier: bool,
     pub tempo: Option<u32>,
     pub just_detected: bool,
     pub first_beat_after_detection: bool,
 }
 
 impl IntervalDetector {
+    /// Detector ignoring the oldest interval if it does not fit.
+    ///
+    /// This is useful for human input, where the first tap is often off.
+    /// The interval must still be delimited by two triggers, so the time
+    /// passed before the first trigger does not count as one.
+    pub fn forgiving() -> Self {
+        Self {
+            forgive_outlier: true,
+            ..Self::default()
+        }
+    }
+
     pub fn trigger(&mut self) {
         let minus_1 = self.trigger_age[2];
         let minus_2 = self.trigger_age[1];
@@ -28,7 +42,8 @@ impl IntervalDetector {
         let allowed_range = toleration(distance);
         if distance > 100
             && allowed_range.contains(&(minus_2 - minus_1))
-            && allowed_range.contains(&(minus_3 - minus_2))
+            && (allowed_range.contains(&(minus_3 - minus_2))
+                || (self.forgive_outlier && self.triggers >= 3))
         {
             self.tempo = Some(distance);
             self.just_detected = true;
@@ -39,10 +54,12 @@ impl IntervalDetector {
         self.trigger_age[0] = self.trigger_age[1];
         self.trigger_age[1] = self.trigger_age[2];
         self.trigger_age[2] = 0;
+        self.triggers = self.triggers.saturating_add(1);
     }
 
     pub fn reset(&mut self) {
         self.trigger_age = [0, 0, 0];
+        self.triggers = 0;
         self.tempo = None;
         self.just_detected = false;
     }
@@ -57,6 +74,15 @@ impl IntervalDetector {
             self.first_beat_after_detection = self.trigger_age[2] == tempo;
         }
     }
+
+    /// Position within the current beat, between 0.0 and 1.0.
+    ///
+    /// Returns `None` until tempo is detected. If the clock stops, the
+    /// phase keeps wrapping with the last detected tempo.
+    pub fn phase(&self) -> Option<f32> {
+        self.tempo
+            .map(|tempo| (self.trigger_age[2] % tempo) as f32 / tempo as f32)
+    }
 }
 
 fn toleration(distance: u32) -> Range<u32> {
@@ -99,6 +125,44 @@ mod tests {
         assert_eq!(detector.tempo, Some(2000));
     }
 
+    #[test]
+    fn when_tempo_is_not_detected_phase_is_none() {
+        let mut detector = IntervalDetector::default();
+        for _ in 0..2 {
+            for _ in 0..2000 {
+                detector.tick();
+            }
+            detector.trigger();
+        }
+        assert_eq!(detector.phase(), None);
+    }
+
+    #[test]
+    fn given_steady_clock_phase_advances_linearly_and_wraps_on_beat() {
+        let mut detector = IntervalDetector::default();
+        for _ in 0..4 {
+            for _ in 0..2000 {
+                detector.tick();
+            }
+            detector.trigger();
+        }
+        assert_relative_eq!(detector.phase().unwrap(), 0.0);
+
+        for i in 1..2000 {
+            detector.tick();
+            assert_relative_eq!(detector.phase().unwrap(), i as f32 / 2000.0);
+        }
+
+        detector.tick();
+        detector.trigger();
+        assert_relative_eq!(detector.phase().unwrap(), 0.0);
+
+        for _ in 0..2500 {
+            detector.tick();
+        }
+        assert_relative_eq!(detector.phase().unwrap(), 0.25);
+    }
+
     #[test]
     fn when_triggered_too_fast_it_does_not_detect_tempo() {
         let mut detector = IntervalDetector::default();
@@ -111,6 +175,81 @@ mod tests {
         assert_eq!(detector.tempo, None);
     }
 
+    #[test]
+    fn when_first_interval_is_off_it_does_not_detect_tempo() {
+        let mut detector = IntervalDetector::default();
+        detector.trigger();
+        for _ in 0..700 {
+            detector.tick();
+        }
+        detector.trigger();
+        for _ in 0..2000 {
+            detector.tick();
+        }
+        detector.trigger();
+        for _ in 0..2000 {
+            detector.tick();
+        }
+        detector.trigger();
+        assert_eq!(detector.tempo, None);
+    }
+
+    #[test]
+    fn given_forgiving_detector_when_first_interval_is_off_it_detects_tempo() {
+        let mut detector = IntervalDetector::forgiving();
+        detector.trigger();
+        for _ in 0..700 {
+            detector.tick();
+        }
+        detector.trigger();
+        for _ in 0..2000 {
+            detector.tick();
+        }
+        detector.trigger();
+        for _ in 0..2000 {
+            detector.tick();
+        }
+        detector.trigger();
+        assert_eq!(detector.tempo, Some(2000));
+    }
+
+    #[test]
+    fn given_forgiving_detector_when_triggered_only_three_times_it_does_not_detect_tempo() {
+        let mut detector = IntervalDetector::forgiving();
+        for _ in 0..5000 {
+            detector.tick();
+        }
+        detector.trigger();
+        for _ in 0..2000 {
+            detector.tick();
+        }
+        detector.trigger();
+        for _ in 0..2000 {
+            detector.tick();
+        }
+        detector.trigger();
+        assert_eq!(detector.tempo, None);
+    }
+
+    #[test]
+    fn given_forgiving_detector_when_triggered_in_unequal_interval_it_does_not_detect_tempo() {
+        let mut detector = IntervalDetector::forgiving();
+        detector.trigger();
+        for _ in 0..2000 {
+            detector.tick();
+        }
+        detector.trigger();
+        for _ in 0..2000 {
+            detector.tick();
+        }
+        detector.trigger();
+        for _ in 0..1083 {
+            detector.tick();
+        }
+        detector.trigger();
+        assert_eq!(detector.tempo, None);
+    }
+
     #[test]
     fn when_triggered_in_unequal_interval_it_does_not_detect_tempo() {
         let mut detector = IntervalDetector::default();
@@ -1,9 +1,16 @@
-//! Led abstraction keeping it lit.
+//! Led abstraction keeping it lit and fading it out.
+
+/// Number of ticks the led stays fully lit after being triggered.
+const LIT_TICKS: u32 = 20;
+
+/// Number of ticks it takes the led to fade out after being lit.
+const FADE_TICKS: u32 = 60;
 
 /// Abstraction of leds.
 ///
 /// This is useful when a led blink is triggered by a control loop
-/// and it should remain lit for a moment.
+/// and it should remain lit for a moment. Instead of turning off
+/// abruptly, it then fades out, for the blink to stay visible.
 #[derive(Debug, Default)]
 #[cfg_attr(feature = "defmt", derive(defmt::Format))]
 pub struct Led {
@@ -19,7 +26,14 @@ impl Led {
         self.since = self.since.saturating_add(1);
     }
 
-    pub fn triggered(&self) -> bool {
-        self.since < 20
+    /// Brightness of the led in range between 0.0 and 1.0.
+    pub fn brightness(&self) -> f32 {
+        if self.since < LIT_TICKS {
+            1.0
+        } else if self.since < LIT_TICKS + FADE_TICKS {
+            1.0 - (self.since - LIT_TICKS) as f32 / FADE_TICKS as f32
+        } else {
+            0.0
+        }
     }
 }
@@ -35,4 +35,12 @@ impl AttributeIdentifier {
     pub fn is_none(&self) -> bool {
         matches!(self, Self::None)
     }
+
+    /// Whether the identifier refers to one of the existing heads, if any.
+    pub fn is_valid(&self) -> bool {
+        match self {
+            Self::Position(i) | Self::Volume(i) | Self::Feedback(i) | Self::Pan(i) => *i < 4,
+            _ => true,
+        }
+    }
 }
@@ -1,29 +1,38 @@
 pub mod calibration;
 pub mod configuration;
 pub mod display;
+mod input_meter;
 mod interval_detector;
 mod led;
 pub mod mapping;
 mod quantization;
 mod reconcile;
+mod rhythm_tap;
+mod scene;
 mod tap_clock_detector;
+mod tap_detector;
 mod trigger;
 
 use heapless::FnvIndexSet;
-use kaseta_dsp::processor::{Attributes as DSPAttributes, AttributesHead as DSPAttributesHead};
+use kaseta_dsp::processor::{
+    Attributes as DSPAttributes, AttributesHead as DSPAttributesHead, InputSource,
+};
 
 use self::calibration::Calibration;
 pub use self::configuration::Configuration;
 use self::display::Display;
+use self::input_meter::InputMeter;
 use self::led::Led;
 use self::mapping::{AttributeIdentifier, Mapping};
-use self::tap_clock_detector::{
-    TapClockDetector as TapDetector, TapClockDetector as ClockDetector,
-};
+use self::quantization::Quantization;
+use self::rhythm_tap::RhythmTap;
+pub use self::scene::Scene;
+use self::tap_clock_detector::TapClockDetector as ClockDetector;
+use self::tap_detector::TapDetector;
 use self::trigger::Trigger;
 use crate::log;
 use crate::output::DesiredOutput;
-use crate::save::Save;
+use crate::save::{Save, VERSION as SAVE_VERSION};
 
 /// Cache keeping internal attributes.
 ///
@@ -39,10 +48,21 @@ pub struct Cache {
     pub clock_detectors: ClockDetectors,
     pub tap_detector: TapDetector,
     pub tapped_tempo: TappedTempo,
+    /// Nudge of the tapped tempo in progress while the button is held.
+    pub tempo_nudge: Option<TempoNudge>,
+    pub rhythm_tap: RhythmTap,
+    /// Head positions placed by the tapped rhythm until their pots move.
+    pub tapped_positions: [Option<f32>; 4],
+    pub scene: Option<Scene>,
+    /// Heads holding attributes recalled from the scene until any of their
+    /// pots moves.
+    pub recalled_heads: [bool; 4],
     pub requests: Requests,
     pub attributes: Attributes,
     pub impulse_trigger: Trigger,
+    pub impulse_gate: bool,
     pub impulse_led: Led,
+    pub input_meter: InputMeter,
     pub display: Display,
 }
 
@@ -70,6 +90,10 @@ pub struct Options {
     pub filter_placement: FilterPlacement,
     pub wow_flutter_placement: WowFlutterPlacement,
     pub unlimited: bool,
+    pub pan_law: PanLaw,
+    pub speed_gear: SpeedGear,
+    pub bipolar_feedback: bool,
+    pub master_feedback: MasterFeedback,
 }
 
 /// Range of the delay time.
@@ -143,9 +167,76 @@ impl WowFlutterPlacement {
     }
 }
 
+/// Distribution of head's signal between left and right channels.
+#[derive(Clone, Copy, Debug, PartialEq)]
+#[cfg_attr(feature = "defmt", derive(defmt::Format))]
+pub enum PanLaw {
+    Linear,
+    EqualPower,
+}
+
+impl Default for PanLaw {
+    fn default() -> Self {
+        Self::Linear
+    }
+}
+
+impl PanLaw {
+    pub fn is_equal_power(self) -> bool {
+        matches!(self, Self::EqualPower)
+    }
+}
+
+/// Discrete speed of the tape, applied on top of the continuous speed.
+///
+/// `Half` runs the tape at half speed, doubling the delay and dropping the
+/// echo an octave down. `Double` does the opposite.
+#[derive(Clone, Copy, Debug, PartialEq)]
+#[cfg_attr(feature = "defmt", derive(defmt::Format))]
+pub enum SpeedGear {
+    Half,
+    Normal,
+    Double,
+}
+
+impl Default for SpeedGear {
+    fn default() -> Self {
+        Self::Normal
+    }
+}
+
+impl SpeedGear {
+    pub fn length_multiplier(self) -> f32 {
+        match self {
+            Self::Half => 2.0,
+            Self::Normal => 1.0,
+            Self::Double => 0.5,
+        }
+    }
+}
+
+/// Scale of the feedback of all the heads, keeping their ratios.
+#[derive(Clone, Copy, Debug, PartialEq)]
+#[cfg_attr(feature = "defmt", derive(defmt::Format))]
+pub struct MasterFeedback(pub f32);
+
+impl Default for MasterFeedback {
+    fn default() -> Self {
+        Self(1.0)
+    }
+}
+
 /// Storing tempo if it was tapped in using the button.
 pub type TappedTempo = Option<f32>;
 
+/// Speed pot position and tapped tempo from where the nudge started.
+#[derive(Clone, Copy, Debug, PartialEq)]
+#[cfg_attr(feature = "defmt", derive(defmt::Format))]
+pub struct TempoNudge {
+    pub pot: f32,
+    pub tempo: f32,
+}
+
 /// Interpreted attributes for the DSP.
 ///
 /// This structure can be directly translated to DSP configuration, used
@@ -155,6 +246,7 @@ pub type TappedTempo = Option<f32>;
 pub struct Attributes {
     pub pre_amp: f32,
     pub oscillator: f32,
+    pub oscillator_slope: f32,
     pub drive: f32,
     pub saturation: f32,
     pub bias: f32,
@@ -166,6 +258,9 @@ pub struct Attributes {
     pub tone: f32,
     pub head: [AttributesHead; 4],
     pub paused_delay: bool,
+    pub frozen_delay: bool,
+    pub bypass: bool,
+    pub tempo: Option<f32>,
 }
 
 #[derive(Clone, Copy, Debug, Default)]
@@ -175,6 +270,7 @@ pub struct AttributesHead {
     pub volume: f32,
     pub feedback: f32,
     pub pan: f32,
+    pub fine_offset_ms: f32,
 }
 
 /// Transient requests for change of the internal state.
@@ -187,53 +283,96 @@ pub struct Requests {
     pub clear_buffer: bool,
     pub reset_position: bool,
     pub reset_impulse: bool,
+    pub capture: bool,
 }
 
 impl Cache {
     pub fn build_dsp_attributes(&mut self) -> DSPAttributes {
+        let pans = self.head_pans();
         DSPAttributes {
             pre_amp: self.attributes.pre_amp,
             oscillator: self.attributes.oscillator,
+            oscillator_slope: self.attributes.oscillator_slope,
+            oscillator_drive: 0.0,
             drive: self.attributes.drive,
             saturation: self.attributes.saturation,
             bias: self.attributes.bias,
             dry_wet: self.attributes.dry_wet,
+            dry_wet_curve: 0,
+            saturation_knee: None,
+            auto_makeup: false,
+            hysteresis_limit: None,
+            hysteresis_recovery: 0,
+            tape_bypass: false,
+            stereo_tape: false,
+            bypass: self.attributes.bypass,
+            trails: self.configuration.trails,
+            dc_blocker_enabled: true,
+            input_source: InputSource::Right,
             wow: self.attributes.wow,
             flutter_depth: self.attributes.flutter_depth,
             flutter_chance: self.attributes.flutter_chance,
+            scrape_depth: 0.0,
+            wow_flutter_decorrelated: false,
+            wow_flutter_frozen: false,
             speed: self.attributes.speed,
             tone: self.attributes.tone,
+            tone_filter_type: 0,
+            tone_slew: None,
+            tone_loudness_compensation: false,
+            tone_cutoff: None,
             head: [
                 DSPAttributesHead {
                     position: self.attributes.head[0].position,
                     volume: self.attributes.head[0].volume,
-                    feedback: self.attributes.head[0].feedback,
-                    pan: self.attributes.head[0].pan,
+                    feedback: self.attributes.head[0].feedback * self.options.master_feedback.0,
+                    pan: pans[0],
+                    fine_offset_ms: self.attributes.head[0].fine_offset_ms,
+                    feedback_invert: false,
+                    feedback_highpass: 0.0,
                 },
                 DSPAttributesHead {
                     position: self.attributes.head[1].position,
                     volume: self.attributes.head[1].volume,
-                    feedback: self.attributes.head[1].feedback,
-                    pan: self.attributes.head[1].pan,
+                    feedback: self.attributes.head[1].feedback * self.options.master_feedback.0,
+                    pan: pans[1],
+                    fine_offset_ms: self.attributes.head[1].fine_offset_ms,
+                    feedback_invert: false,
+                    feedback_highpass: 0.0,
                 },
                 DSPAttributesHead {
                     position: self.attributes.head[2].position,
                     volume: self.attributes.head[2].volume,
-                    feedback: self.attributes.head[2].feedback,
-                    pan: self.attributes.head[2].pan,
+                    feedback: self.attributes.head[2].feedback * self.options.master_feedback.0,
+                    pan: pans[2],
+                    fine_offset_ms: self.attributes.head[2].fine_offset_ms,
+                    feedback_invert: false,
+                    feedback_highpass: 0.0,
                 },
                 DSPAttributesHead {
                     position: self.attributes.head[3].position,
                     volume: self.attributes.head[3].volume,
-                    feedback: self.attributes.head[3].feedback,
-                    pan: self.attributes.head[3].pan,
+                    feedback: self.attributes.head[3].feedback * self.options.master_feedback.0,
+                    pan: pans[3],
+                    fine_offset_ms: self.attributes.head[3].fine_offset_ms,
+                    feedback_invert: false,
+                    feedback_highpass: 0.0,
                 },
             ],
             rewind: self.options.rewind,
             enable_oscillator: self.options.enable_oscillator,
+            oscillator_mix: 0.0,
             rewind_speed: self.configuration.rewind_speed(),
+            beat: self.attributes.tempo,
+            rewind_beat: self
+                .attributes
+                .tempo
+                .filter(|_| self.configuration.rewind_beat_lock),
             reset_impulse: self.requests.reset_impulse,
             random_impulse: self.options.random_impulse,
+            impulse_accent: 0.0,
+            impulse_threshold: self.configuration.impulse_threshold(),
+            impulse_subdivisions: 1,
             filter_placement: if self.options.filter_placement.is_input() {
                 0
             } else if self.options.filter_placement.is_feedback() {
@@ -248,33 +387,114 @@ impl Cache {
             } else {
                 2
             },
+            flutter_placement: None,
+            pan_law: u8::from(self.options.pan_law.is_equal_power()),
+            feedback_topology: u8::from(self.configuration.feedback_topology.is_single_pass()),
+            feedback_character: 0.0,
+            stereo_offset_ms: 0.0,
+            position_mode: 0,
             clear_buffer: self.requests.clear_buffer,
+            splice_buffer: false,
             paused_delay: self.attributes.paused_delay,
+            frozen_delay: self.attributes.frozen_delay,
+            capture: self.requests.capture,
+            capture_length: self.configuration.capture_length(),
+            capture_repeats: self.configuration.capture_repeats(),
+            output_level: self.configuration.output_level(),
+            clip_threshold: None,
+            limiter: false,
+            compressor_attack: None,
+            compressor_release: None,
+            compressor_detection: 0,
+            noise_reduction: 0.0,
+            click_level: self.configuration.click_level(),
+            swap_outputs: false,
+            band_metering: false,
+            gate_width: self.configuration.gate_width(),
+            position_crossfade: self.position_crossfade(),
+            oversampled_read: self.configuration.oversampled_read
+                && self.options.delay_range.is_audio(),
+            separate_heads: false,
+            ping_pong: false,
         }
     }
 
+    // NOTE: When the spread is engaged, heads are distributed evenly from
+    // left to right around the center, overriding their individual pans.
+    fn head_pans(&self) -> [f32; 4] {
+        let spread = self.configuration.spread();
+        if spread <= 0.0 {
+            return self.attributes.head.map(|head| head.pan);
+        }
+        core::array::from_fn(|i| 0.5 + spread * (i as f32 / 3.0 - 0.5))
+    }
+
+    // NOTE: The grid from the configuration takes precedence over the grid
+    // of the options.
+    pub(crate) fn position_quantization(&self) -> Quantization {
+        match self.configuration.quantization_grid() {
+            Quantization::None => {
+                Quantization::from((self.options.quantize_6, self.options.quantize_8))
+            }
+            grid => grid,
+        }
+    }
+
+    // NOTE: Only jumps between quantized slots use the configured crossfade,
+    // heads moving freely or following a multi-tap pattern keep the default.
+    fn position_crossfade(&self) -> Option<f32> {
+        let quantized = self.configuration.multi_tap_pattern().is_none()
+            && !matches!(self.position_quantization(), Quantization::None);
+        quantized.then(|| self.configuration.position_crossfade())
+    }
+
+    pub fn mappings(&self) -> [(usize, AttributeIdentifier); 4] {
+        let mut mappings = [(0, AttributeIdentifier::None); 4];
+        for (i, attribute) in self.mapping.iter().enumerate() {
+            mappings[i] = (i, *attribute);
+        }
+        mappings
+    }
+
+    pub fn control_for(&self, attribute: AttributeIdentifier) -> Option<usize> {
+        // NOTE: Unmapped controls are marked as `None`, they should not be
+        // mistaken for a mapping.
+        if attribute.is_none() {
+            return None;
+        }
+        self.mapping.iter().position(|a| *a == attribute)
+    }
+
     pub fn save(&self) -> Save {
         Save {
+            version: SAVE_VERSION,
             mapping: self.mapping,
             calibrations: self.calibrations,
             options: self.options,
             configuration: self.configuration,
             tapped_tempo: self.tapped_tempo,
+            scene: self.scene,
         }
     }
 
     pub fn tick(&mut self) -> DesiredOutput {
         let output = DesiredOutput {
             display: self.display.active_screen().leds(),
-            impulse_trigger: self.impulse_trigger.triggered(),
-            impulse_led: self.impulse_led.triggered(),
+            impulse_trigger: if self.configuration.gate_head.is_some() {
+                self.impulse_gate
+            } else {
+                self.impulse_trigger.triggered()
+            },
+            impulse_led: self.impulse_led.brightness(),
         };
 
         self.impulse_trigger.tick();
         self.impulse_led.tick();
+        self.input_meter.tick();
         self.display.tick();
 
         self.tap_detector.tick();
+        self.rhythm_tap.tick();
         self.clock_detectors.iter_mut().for_each(|d| d.tick());
 
         output
@@ -1,9 +1,8 @@
 //! Quantize position potentiometer into even blocks.
 
-#[derive(Clone, Copy)]
+#[derive(Clone, Copy, Debug)]
 pub enum Quantization {
-    Six,
-    Eight,
+    Steps(u8),
     Both,
     None,
 }
@@ -12,14 +11,15 @@ impl From<(bool, bool)> for Quantization {
     fn from(source: (bool, bool)) -> Self {
         match source {
             (true, true) => Self::Both,
-            (true, false) => Self::Six,
-            (false, true) => Self::Eight,
+            (true, false) => Self::Steps(6),
+            (false, true) => Self::Steps(8),
             (false, false) => Self::None,
         }
     }
 }
 
-// Each beat divided into 1/6 or 1/8 notes.
+// Each beat divided into given number of steps, e.g. 1/6 or 1/8 notes. The
+// combination of both 6 and 8 is handled separately.
 //
 // LCM(6, 8) = 24
 //
@@ -29,42 +29,7 @@ impl From<(bool, bool)> for Quantization {
 //       ||  --  ||  --  ||  --   (6)
 pub fn quantize(x: f32, quantization: Quantization) -> f32 {
     match quantization {
-        Quantization::Six => {
-            const STEP: f32 = 1.0 / 6.0;
-            if x < STEP {
-                0.0 * STEP
-            } else if x < 2.0 * STEP {
-                1.0 * STEP
-            } else if x < 3.0 * STEP {
-                2.0 * STEP
-            } else if x < 4.0 * STEP {
-                3.0 * STEP
-            } else if x < 5.0 * STEP {
-                4.0 * STEP
-            } else {
-                5.0 * STEP
-            }
-        }
-        Quantization::Eight => {
-            const STEP: f32 = 1.0 / 8.0;
-            if x < STEP {
-                0.0 * STEP
-            } else if x < 2.0 * STEP {
-                1.0 * STEP
-            } else if x < 3.0 * STEP {
-                2.0 * STEP
-            } else if x < 4.0 * STEP {
-                3.0 * STEP
-            } else if x < 5.0 * STEP {
-                4.0 * STEP
-            } else if x < 6.0 * STEP {
-                5.0 * STEP
-            } else if x < 7.0 * STEP {
-                6.0 * STEP
-            } else {
-                7.0 * STEP
-            }
-        }
+        Quantization::Steps(steps) => quantize_to_steps(x, steps),
         Quantization::Both => {
             const STEP: f32 = 1.0 / 12.0;
             const EIGHTH: f32 = 1.0 / 8.0;
@@ -98,3 +63,79 @@ pub fn quantize(x: f32, quantization: Quantization) -> f32 {
         Quantization::None => x,
     }
 }
+
+fn quantize_to_steps(x: f32, steps: u8) -> f32 {
+    if steps == 0 {
+        return x;
+    }
+    let index = ((x * f32::from(steps)) as u8).min(steps - 1);
+    f32::from(index) / f32::from(steps)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn distinct_positions_on_ramp(quantization: Quantization) -> usize {
+        let mut positions = 0;
+        let mut last = -1.0;
+        for i in 0..=1000 {
+            let x = quantize(i as f32 / 1000.0, quantization);
+            if x > last {
+                positions += 1;
+            }
+            last = x;
+        }
+        positions
+    }
+
+    #[test]
+    fn when_quantizing_ramp_it_snaps_to_given_number_of_positions() {
+        for steps in [3, 4, 6, 8, 12, 16] {
+            assert_eq!(
+                distinct_positions_on_ramp(Quantization::Steps(steps)),
+                steps as usize
+            );
+        }
+    }
+
+    #[test]
+    fn when_quantizing_to_steps_positions_are_evenly_spread_from_zero() {
+        for steps in [3, 4, 12, 16] {
+            for i in 0..steps {
+                let position = f32::from(i) / f32::from(steps);
+                let x = position + 0.5 / f32::from(steps);
+                assert_relative_eq!(quantize(x, Quantization::Steps(steps)), position);
+            }
+            assert_relative_eq!(
+                quantize(1.0, Quantization::Steps(steps)),
+                f32::from(steps - 1) / f32::from(steps)
+            );
+        }
+    }
+
+    #[test]
+    fn when_quantizing_to_six_and_eight_blocks_are_kept_unchanged() {
+        for (x, expected) in [
+            (0.1, 0.0),
+            (0.2, 1.0 / 6.0),
+            (0.49, 2.0 / 6.0),
+            (0.99, 5.0 / 6.0),
+        ] {
+            assert_relative_eq!(quantize(x, Quantization::from((true, false))), expected);
+        }
+        for (x, expected) in [
+            (0.1, 0.0),
+            (0.2, 1.0 / 8.0),
+            (0.49, 3.0 / 8.0),
+            (0.99, 7.0 / 8.0),
+        ] {
+            assert_relative_eq!(quantize(x, Quantization::from((false, true))), expected);
+        }
+    }
+
+    #[test]
+    fn when_quantizing_to_both_it_snaps_to_union_of_six_and_eight() {
+        assert_eq!(distinct_positions_on_ramp(Quantization::Both), 12);
+    }
+}
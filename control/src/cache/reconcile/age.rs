@@ -0,0 +1,118 @@
+//! Age of the tape, degrading it all at once.
+//!
+//! The age sets the least amount of wow and flutter and the narrowest
+//! bandwidth the machine can have. Pots can still push the degradation
+//! further, but not below what the age dictates.
+
+use super::calculate;
+use super::taper;
+use crate::Store;
+
+const WOW_DEPTH_RANGE: (f32, f32) = (0.0, 0.08);
+const FLUTTER_DEPTH_RANGE: (f32, f32) = (0.0, 0.003);
+const FLUTTER_CHANCE_RANGE: (f32, f32) = (0.0, 0.0004);
+// Tone below 0.4 closes the low-pass filter. Tone of 0.3 places its
+// cutoff at around 3.5 kHz.
+const TONE_CEILING_RANGE: (f32, f32) = (0.4, 0.3);
+const TONE_HIGH_PASS_START: f32 = 0.6;
+
+#[derive(Debug, Clone, Copy)]
+struct Degradation {
+    wow: f32,
+    flutter_depth: f32,
+    flutter_chance: f32,
+    tone_ceiling: f32,
+}
+
+impl Store {
+    pub fn reconcile_age(&mut self) {
+        let age = self.cache.configuration.tape_age();
+        if age <= 0.0 {
+            return;
+        }
+
+        let degradation = age_to_degradation(age);
+        let attributes = &mut self.cache.attributes;
+        attributes.wow = attributes.wow.max(degradation.wow);
+        attributes.flutter_depth = attributes.flutter_depth.max(degradation.flutter_depth);
+        attributes.flutter_chance = attributes.flutter_chance.max(degradation.flutter_chance);
+        // NOTE: High-pass filtering is left untouched, the worn tape loses
+        // the treble only.
+        if attributes.tone < TONE_HIGH_PASS_START {
+            attributes.tone = attributes.tone.min(degradation.tone_ceiling);
+        }
+    }
+}
+
+// NOTE: Wow and flutter are tapered, so the first steps of the age only
+// subtly unsettle the pitch, while the last ones make the deck wobble.
+fn age_to_degradation(age: f32) -> Degradation {
+    Degradation {
+        wow: calculate(age, None, WOW_DEPTH_RANGE, Some(taper::log)),
+        flutter_depth: calculate(age, None, FLUTTER_DEPTH_RANGE, None),
+        flutter_chance: calculate(age, None, FLUTTER_CHANCE_RANGE, Some(taper::log)),
+        tone_ceiling: calculate(age, None, TONE_CEILING_RANGE, None),
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn when_age_is_zero_it_does_not_degrade_the_tape() {
+        let degradation = age_to_degradation(0.0);
+
+        assert_relative_eq!(degradation.wow, 0.0);
+        assert_relative_eq!(degradation.flutter_depth, 0.0);
+        assert_relative_eq!(degradation.flutter_chance, 0.0);
+        assert_relative_eq!(degradation.tone_ceiling, 0.4);
+    }
+
+    #[test]
+    fn when_age_is_full_it_degrades_the_tape_the_most() {
+        let degradation = age_to_degradation(1.0);
+
+        assert_relative_eq!(degradation.wow, WOW_DEPTH_RANGE.1);
+        assert_relative_eq!(degradation.flutter_depth, FLUTTER_DEPTH_RANGE.1);
+        assert_relative_eq!(degradation.flutter_chance, FLUTTER_CHANCE_RANGE.1);
+        assert_relative_eq!(degradation.tone_ceiling, 0.3);
+    }
+
+    #[test]
+    fn when_age_increases_all_the_degradation_grows_along_its_curve() {
+        let mut previous = age_to_degradation(0.0);
+        for i in 1..=10 {
+            let age = i as f32 / 10.0;
+            let degradation = age_to_degradation(age);
+
+            assert!(degradation.wow > previous.wow);
+            assert!(degradation.flutter_depth > previous.flutter_depth);
+            assert!(degradation.flutter_chance > previous.flutter_chance);
+            assert!(degradation.tone_ceiling < previous.tone_ceiling);
+
+            assert_relative_eq!(
+                degradation.wow,
+                taper::log(age) * WOW_DEPTH_RANGE.1,
+                epsilon = 0.000_001
+            );
+            assert_relative_eq!(
+                degradation.flutter_depth,
+                age * FLUTTER_DEPTH_RANGE.1,
+                epsilon = 0.000_001
+            );
+            assert_relative_eq!(
+                degradation.flutter_chance,
+                taper::log(age) * FLUTTER_CHANCE_RANGE.1,
+                epsilon = 0.000_001
+            );
+            assert_relative_eq!(
+                degradation.tone_ceiling,
+                0.4 - age * 0.1,
+                epsilon = 0.000_001
+            );
+
+            previous = degradation;
+        }
+    }
+}
@@ -1,29 +1,105 @@
+#[allow(unused_imports)]
+use micromath::F32Ext;
+
 use super::{calculate, taper};
-use crate::cache::display::AttributeScreen;
+use crate::cache::display::{
+    AltAttributeScreen, AttributeScreen, FeedbackPolarity as FeedbackPolarityScreen,
+};
 use crate::cache::mapping::AttributeIdentifier;
-use crate::cache::quantization::{quantize, Quantization};
+use crate::cache::quantization::quantize;
+use crate::cache::MasterFeedback;
+use crate::log;
+use crate::store::State;
 use crate::Store;
 
+pub const FEEDBACK_RANGE: (f32, f32) = (0.0, 1.2);
+
+// Master feedback is set in steps, so a jittering pot does not keep
+// triggering saves.
+const MASTER_FEEDBACK_STEPS: f32 = 32.0;
+
+// Bipolar feedback stays at zero while the pot is this close to the middle.
+const BIPOLAR_FEEDBACK_DEAD_ZONE: f32 = 0.02;
+
+// The top limit is made to match compressor's treshold.
+pub const VOLUME_RANGE: (f32, f32) = (0.0, 0.25);
+
+// With unlimited range, heads can be pushed past unity into the compressor
+// and the output clipper, overdriving the feedback and the output.
+pub const UNLIMITED_VOLUME_RANGE: (f32, f32) = (0.0, 2.0);
+
+// Fine offset in milliseconds, to be applied in both directions.
+pub const FINE_OFFSET_RANGE: f32 = 10.0;
+
+// Offset of a head from its multi-tap pattern position, relative to the
+// length of the delay, to be applied in both directions.
+const PATTERN_OFFSET_RANGE: f32 = 0.25;
+
 impl Store {
-    pub fn reconcile_heads(&mut self) {
+    pub fn reconcile_heads(&mut self, needs_save: &mut bool) {
+        self.release_recalled_heads();
+        self.reconcile_feedback_polarity(needs_save);
+        self.reconcile_master_feedback(needs_save);
+        self.reconcile_tap_mapping(needs_save);
+
         for i in 0..4 {
             self.reconcile_position(i);
         }
 
-        let default_display_heads = self.cache.configuration.default_display_page.is_heads();
+        let default_display_page = self.cache.configuration.default_display_page;
         let in_audio_range = self.cache.options.delay_range.is_audio();
-        if default_display_heads || in_audio_range {
+        if default_display_page.is_heads() || (default_display_page.is_position() && in_audio_range)
+        {
             self.set_screen_for_heads_overview();
         }
 
         for i in 0..4 {
+            if self.cache.recalled_heads[i] {
+                continue;
+            }
             self.reconcile_volume(i);
             self.reconcile_feedback(i);
             self.reconcile_pan(i);
+            self.reconcile_fine_offset(i);
+        }
+
+        if default_display_page.is_feedback() {
+            self.set_screen_for_feedback_overview();
+        } else if default_display_page.is_spread() {
+            self.set_screen_for_heads_spread();
+        }
+    }
+
+    // NOTE: Once any pot of a head is touched, the whole head follows its pots
+    // again, so there is no mix of recalled and live attributes.
+    fn release_recalled_heads(&mut self) {
+        for (recalled, head) in self
+            .cache
+            .recalled_heads
+            .iter_mut()
+            .zip(self.input.head.iter())
+        {
+            let moved = head.position.activation_movement()
+                || head.volume.activation_movement()
+                || head.feedback.activation_movement()
+                || head.pan.activation_movement();
+            *recalled &= !moved;
         }
     }
 
     fn reconcile_position(&mut self, i: usize) {
+        if self.cache.recalled_heads[i] {
+            return;
+        }
+
+        if self.input.head[i].position.activation_movement() {
+            self.cache.tapped_positions[i] = None;
+        }
+        if let Some(position) = self.cache.tapped_positions[i] {
+            self.cache.attributes.head[i].position = position;
+            return;
+        }
+
         // NOTE: Snap to the beginning and end to make sure it is possible to
         // get to these extremes.
         let pot_raw = self.input.head[i].position.last_value_above_noise;
@@ -32,7 +108,7 @@ impl Store {
         let control_index = self.control_index_for_attribute(AttributeIdentifier::Position(i));
         let cv = if let Some(i) = control_index {
             let control = &self.input.control[i];
-            if control.is_plugged {
+            if control.is_plugged || control.is_holding() {
                 Some(control.last_value_above_noise / 5.0)
             } else {
                 None
@@ -41,10 +117,19 @@ impl Store {
             None
         };
 
-        self.cache.attributes.head[i].position = quantize(
-            calculate(pot, cv, (0.0, 1.0), None),
-            Quantization::from((self.cache.options.quantize_6, self.cache.options.quantize_8)),
-        );
+        // NOTE: The pattern defines the rhythm on its own, quantization
+        // would only pull the heads out of it.
+        self.cache.attributes.head[i].position = if let Some(pattern) =
+            self.cache.configuration.multi_tap_pattern()
+        {
+            let offset = calculate(pot, cv, (-PATTERN_OFFSET_RANGE, PATTERN_OFFSET_RANGE), None);
+            (pattern[i] + offset).clamp(0.0, 1.0)
+        } else {
+            quantize(
+                calculate(pot, cv, (0.0, 1.0), None),
+                self.cache.position_quantization(),
+            )
+        };
     }
 
     fn reconcile_volume(&mut self, i: usize) {
@@ -53,9 +138,13 @@ impl Store {
             self.control_value_for_attribute(AttributeIdentifier::Volume(i))
                 .map(|x| x / 5.0),
         );
-        // The top limit is made to match compressor's treshold.
+        let range = if self.cache.options.unlimited {
+            UNLIMITED_VOLUME_RANGE
+        } else {
+            VOLUME_RANGE
+        };
         self.cache.attributes.head[i].volume =
-            super::calculate_from_sum(volume_sum, (0.0, 0.25), Some(taper::log));
+            super::calculate_from_sum(volume_sum, range, Some(taper::log));
         let screen = AttributeScreen::Volume(i, volume_sum);
         if self.input.head[i].volume.activation_movement() {
             self.cache.display.force_attribute(screen);
@@ -64,14 +153,126 @@ impl Store {
         }
     }
 
+    fn reconcile_feedback_polarity(&mut self, needs_save: &mut bool) {
+        let original_bipolar_feedback = self.cache.options.bipolar_feedback;
+
+        for head in &self.input.head {
+            let pot = &head.feedback;
+            if self.input.button.pressed && pot.activation_movement() {
+                let (bipolar, screen) = if pot.value() < 0.5 {
+                    (false, FeedbackPolarityScreen::Unipolar)
+                } else {
+                    (true, FeedbackPolarityScreen::Bipolar)
+                };
+                self.cache.options.bipolar_feedback = bipolar;
+                self.cache
+                    .display
+                    .set_alt_menu(AltAttributeScreen::FeedbackPolarity(screen));
+            }
+        }
+
+        let bipolar_feedback = self.cache.options.bipolar_feedback;
+        if bipolar_feedback != original_bipolar_feedback {
+            *needs_save |= true;
+            if bipolar_feedback {
+                log::info!("Setting feedback polarity=bipolar");
+            } else {
+                log::info!("Setting feedback polarity=unipolar");
+            }
+        }
+    }
+
+    fn reconcile_master_feedback(&mut self, needs_save: &mut bool) {
+        let original_master_feedback = self.cache.options.master_feedback;
+
+        for head in &self.input.head {
+            let pot = &head.volume;
+            if self.input.button.pressed && pot.activation_movement() {
+                let scale = (pot.value() * MASTER_FEEDBACK_STEPS).round() / MASTER_FEEDBACK_STEPS;
+                self.cache.options.master_feedback = MasterFeedback(scale);
+                self.cache
+                    .display
+                    .set_alt_menu(AltAttributeScreen::MasterFeedback(scale));
+            }
+        }
+
+        if self.cache.options.master_feedback != original_master_feedback {
+            *needs_save |= true;
+            log::info!(
+                "Setting master feedback={}",
+                self.cache.options.master_feedback.0
+            );
+        }
+    }
+
+    // NOTE: Like in the mapping of configuration, the lowest fifth of the
+    // pot disables it and the rest is split between the four controls.
+    // While configuring, the mapping is set through the draft instead, see
+    // `update_tap_mapping`, so it is not overwritten once the draft is
+    // applied.
+    fn reconcile_tap_mapping(&mut self, needs_save: &mut bool) {
+        if self.state != State::Normal {
+            return;
+        }
+
+        let original_tap_mapping = self.cache.configuration.tap_mapping;
+
+        for head in &self.input.head {
+            let pot = &head.position;
+            if self.input.button.pressed && pot.activation_movement() {
+                let pot_value = pot.value();
+                let mapping = if pot_value < 1.0 / 5.0 {
+                    None
+                } else {
+                    let phase = (pot_value - 1.0 / 4.0) * (5.0 / 4.0);
+                    Some((phase * 3.999) as usize)
+                };
+                self.cache.configuration.tap_mapping = mapping.map(|i| i as u8);
+                self.cache
+                    .display
+                    .set_alt_menu(AltAttributeScreen::TapMapping(mapping));
+            }
+        }
+
+        if self.cache.configuration.tap_mapping != original_tap_mapping {
+            *needs_save |= true;
+            log::info!(
+                "Setting tap mapping={:?}",
+                self.cache.configuration.tap_mapping
+            );
+        }
+    }
+
     fn reconcile_feedback(&mut self, i: usize) {
+        let bipolar = self.cache.options.bipolar_feedback;
+        // NOTE: The unipolar feedback has its zero at the bottom of the pot.
+        // A small gap there keeps it from picking up noise.
+        let pot = if bipolar {
+            self.input.head[i].feedback.value()
+        } else {
+            (self.input.head[i].feedback.value() - 0.02) / 0.98
+        };
         let feedback_sum = super::sum(
-            (self.input.head[i].feedback.value() - 0.02) / 0.98,
+            pot,
             self.control_value_for_attribute(AttributeIdentifier::Feedback(i))
                 .map(|x| x / 5.0),
         );
-        self.cache.attributes.head[i].feedback =
-            super::calculate_from_sum(feedback_sum, (0.0, 1.2), None);
+        self.cache.attributes.head[i].feedback = if bipolar {
+            // NOTE: The middle of the pot is zero, turning it left feeds the
+            // signal back with flipped polarity, thinning out the sound.
+            let signed = feedback_sum * 2.0 - 1.0;
+            let magnitude = ((signed.abs() - BIPOLAR_FEEDBACK_DEAD_ZONE)
+                / (1.0 - BIPOLAR_FEEDBACK_DEAD_ZONE))
+                .max(0.0);
+            let feedback = super::calculate_from_sum(magnitude, FEEDBACK_RANGE, None);
+            if signed < 0.0 {
+                -feedback
+            } else {
+                feedback
+            }
+        } else {
+            super::calculate_from_sum(feedback_sum, FEEDBACK_RANGE, None)
+        };
         let screen = AttributeScreen::Feedback(i, feedback_sum);
         if self.input.head[i].feedback.activation_movement() {
             self.cache.display.force_attribute(screen);
@@ -95,6 +296,17 @@ impl Store {
         }
     }
 
+    fn reconcile_fine_offset(&mut self, i: usize) {
+        let pot = &self.input.head[i].pan;
+        if self.input.button.pressed && pot.activation_movement() {
+            let phase = pot.value();
+            self.cache.attributes.head[i].fine_offset_ms = (phase * 2.0 - 1.0) * FINE_OFFSET_RANGE;
+            self.cache
+                .display
+                .set_alt_menu(AltAttributeScreen::FineOffset(i, phase));
+        }
+    }
+
     fn set_screen_for_heads_overview(&mut self) {
         let screen_for_heads_overview = self.screen_for_heads_overview();
         let touched_position = self
@@ -116,6 +328,29 @@ impl Store {
             .set_fallback_attribute(screen_for_heads_overview);
     }
 
+    fn set_screen_for_feedback_overview(&mut self) {
+        let mut feedback = [0.0; 4];
+        for (i, head) in self.cache.attributes.head.iter().enumerate() {
+            feedback[i] =
+                head.feedback.abs() * self.cache.options.master_feedback.0 / FEEDBACK_RANGE.1;
+        }
+        self.cache
+            .display
+            .set_fallback_attribute(AttributeScreen::FeedbackOverview(feedback));
+    }
+
+    fn set_screen_for_heads_spread(&mut self) {
+        let mut spread = [None; 4];
+        for (i, head) in self.cache.attributes.head.iter().enumerate() {
+            if head.volume > 0.0 || head.feedback.abs() > 0.0 {
+                spread[i] = Some(head.position);
+            }
+        }
+        self.cache
+            .display
+            .set_fallback_attribute(AttributeScreen::HeadsSpread(spread));
+    }
+
     fn screen_for_heads_overview(&self) -> AttributeScreen {
         // TODO: Handle hysteresis for position
         AttributeScreen::HeadsOverview((
@@ -126,10 +361,10 @@ impl Store {
                 self.cache.attributes.head[3].volume > 0.00,
             ],
             [
-                self.cache.attributes.head[0].feedback > 0.00,
-                self.cache.attributes.head[1].feedback > 0.00,
-                self.cache.attributes.head[2].feedback > 0.00,
-                self.cache.attributes.head[3].feedback > 0.00,
+                self.cache.attributes.head[0].feedback.abs() > 0.00,
+                self.cache.attributes.head[1].feedback.abs() > 0.00,
+                self.cache.attributes.head[2].feedback.abs() > 0.00,
+                self.cache.attributes.head[3].feedback.abs() > 0.00,
             ],
         ))
     }
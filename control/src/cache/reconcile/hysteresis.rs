@@ -3,7 +3,9 @@ use super::taper;
 use crate::cache::display::AltAttributeScreen;
 use crate::cache::display::AttributeScreen;
 use crate::cache::display::HysteresisRange::{Limited, Unlimited};
+use crate::cache::display::PanLaw as PanLawScreen;
 use crate::cache::mapping::AttributeIdentifier;
+use crate::cache::PanLaw;
 use crate::log;
 use crate::Store;
 
@@ -15,6 +17,7 @@ const BIAS_RANGE: (f32, f32) = (0.01, 1.0);
 impl Store {
     pub fn reconcile_hysteresis(&mut self, needs_save: &mut bool) {
         self.reconcile_range_limitation(needs_save);
+        self.reconcile_pan_law(needs_save);
         self.reconcile_dry_wet();
         self.reconcile_drive_and_saturation();
         self.reconcile_bias();
@@ -40,9 +43,35 @@ impl Store {
         if original_unlimited != unlimited {
             *needs_save |= true;
             if unlimited {
-                log::info!("Enabling unlimited hysteresis");
+                log::info!("Enabling unlimited range");
             } else {
-                log::info!("Disabling unlimited hysteresis");
+                log::info!("Disabling unlimited range");
+            }
+        }
+    }
+
+    fn reconcile_pan_law(&mut self, needs_save: &mut bool) {
+        let original_pan_law = self.cache.options.pan_law;
+
+        if self.input.button.pressed && self.input.dry_wet.activation_movement() {
+            let (pan_law, screen) = if self.input.dry_wet.value() < 0.5 {
+                (PanLaw::Linear, PanLawScreen::Linear)
+            } else {
+                (PanLaw::EqualPower, PanLawScreen::EqualPower)
+            };
+            self.cache.options.pan_law = pan_law;
+            self.cache
+                .display
+                .set_alt_menu(AltAttributeScreen::PanLaw(screen));
+        }
+
+        let pan_law = self.cache.options.pan_law;
+        if pan_law != original_pan_law {
+            *needs_save |= true;
+            if pan_law.is_equal_power() {
+                log::info!("Setting pan law=equal power");
+            } else {
+                log::info!("Setting pan law=linear");
             }
         }
     }
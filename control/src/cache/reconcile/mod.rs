@@ -1,3 +1,4 @@
+mod age;
 mod heads;
 mod hysteresis;
 mod pre_amp;
@@ -6,6 +7,8 @@ mod taper;
 mod tone;
 mod wow_flutter;
 
+pub use self::heads::{FEEDBACK_RANGE, FINE_OFFSET_RANGE, VOLUME_RANGE};
+
 #[allow(clippy::let_and_return)]
 pub fn calculate(
     pot: f32,
@@ -12,6 +12,9 @@ use crate::Store;
 // Pre-amp scales up to +28 dB.
 const PRE_AMP_RANGE: (f32, f32) = (0.0, 25.0);
 
+// Rate at which control inputs are sampled.
+const SNAPSHOTS_PER_SECOND: f32 = 1000.0;
+
 impl Store {
     pub fn reconcile_pre_amp(&mut self, needs_save: &mut bool) {
         let original_enable_oscillator = self.cache.options.enable_oscillator;
@@ -72,7 +75,17 @@ impl Store {
             pot * 5.0 + 2.0
         };
         let a = 27.5;
-        self.cache.attributes.oscillator = a * powf(2.0, voct);
+        let frequency = a * powf(2.0, voct);
+
+        // NOTE: With interpolation enabled, the DSP glides to the new frequency
+        // over the time until the next snapshot instead of stepping to it.
+        self.cache.attributes.oscillator_slope =
+            if control.is_some() && self.cache.configuration.cv_interpolation {
+                (frequency - self.cache.attributes.oscillator) * SNAPSHOTS_PER_SECOND
+            } else {
+                0.0
+            };
+        self.cache.attributes.oscillator = frequency;
     }
 
     fn set_pre_amp(&mut self) {
@@ -1,14 +1,21 @@
+use crate::cache::display::SpeedGear as SpeedGearScreen;
 use crate::cache::display::{AltAttributeScreen, AttributeScreen, SpeedRange};
 use crate::cache::mapping::AttributeIdentifier;
-use crate::cache::DelayRange;
+use crate::cache::{DelayRange, SpeedGear};
 use crate::log;
 use crate::Store;
 
+const TEMPO_DIVISIONS: [f32; 5] = [1.0, 1.0 / 2.0, 1.0 / 4.0, 1.0 / 8.0, 1.0 / 16.0];
+
 impl Store {
     pub fn reconcile_speed(&mut self, needs_save: &mut bool) {
         let original_delay_range = self.cache.options.delay_range;
 
-        if self.input.button.pressed && self.input.speed.activation_movement() {
+        // NOTE: With a tapped tempo, the same gesture nudges the tempo.
+        if self.input.button.pressed
+            && self.input.speed.activation_movement()
+            && self.cache.tapped_tempo.is_none()
+        {
             let value = self.input.speed.value();
             let (option, display) = if value < 0.5 {
                 (DelayRange::Long, SpeedRange::Long)
@@ -29,6 +36,10 @@ impl Store {
             log::info!("Setting delay range={}", delay_range);
         }
 
+        self.reconcile_speed_gear(needs_save);
+
+        self.cache.display.speed_dead_band = self.cache.configuration.speed_smoothing();
+
         let control_index = self.control_index_for_attribute(AttributeIdentifier::Speed);
         let clock_detector = control_index.map(|i| &self.cache.clock_detectors[i]);
         let clock_tempo = clock_detector.and_then(|d| d.detected_tempo());
@@ -44,13 +55,21 @@ impl Store {
             self.cache.display.reset_paused();
         }
 
+        // NOTE: Clock detected on the control input takes priority over the
+        // tapped tempo. Until it is detected, the control input selects the
+        // division of the tapped tempo.
         if let Some(clock_tempo) = clock_tempo {
-            let c_i = f32_to_usize_5(self.input.speed.value());
-            let coefficient = [1.0, 1.0 / 2.0, 1.0 / 4.0, 1.0 / 8.0, 1.0 / 16.0][c_i];
+            let coefficient = TEMPO_DIVISIONS[f32_to_usize_5(self.input.speed.value())];
+            self.cache.attributes.tempo = Some(clock_tempo as f32 / 1000.0);
             self.cache.attributes.speed = (clock_tempo as f32 / 1000.0) * coefficient;
         } else if let Some(tapped_tempo) = self.cache.tapped_tempo {
-            self.cache.attributes.speed =
-                tapped_tempo * self.cache.configuration.tap_interval_denominator as f32;
+            self.cache.attributes.tempo = Some(tapped_tempo);
+            let coefficient = self
+                .control_value_for_attribute(AttributeIdentifier::Speed)
+                .map_or(1.0, |x| TEMPO_DIVISIONS[f32_to_usize_5(x / 5.0)]);
+            self.cache.attributes.speed = tapped_tempo
+                * f32::from(self.cache.configuration.tap_interval_denominator)
+                * coefficient;
         } else {
             let (speed, display) = match self.cache.options.delay_range {
                 DelayRange::Long => self.speed_for_long_range(),
@@ -63,8 +82,65 @@ impl Store {
             if !default_display_position || in_audio_range {
                 self.show_length_on_display(display);
             }
+            self.cache.attributes.tempo = None;
             self.cache.attributes.speed = speed;
         }
+
+        // NOTE: The gear jumps the speed instantly. With rewind enabled, the
+        // heads glide to their new position the same way they do with the pot.
+        self.cache.attributes.speed *= self.cache.options.speed_gear.length_multiplier();
+
+        if self.cache.configuration.default_display_page.is_speed() {
+            self.set_screen_for_speed_overview();
+        }
+    }
+
+    fn reconcile_speed_gear(&mut self, needs_save: &mut bool) {
+        let original_speed_gear = self.cache.options.speed_gear;
+
+        if self.input.button.pressed && self.input.bias.activation_movement() {
+            let value = self.input.bias.value();
+            let (gear, screen) = if value < 1.0 / 3.0 {
+                (SpeedGear::Half, SpeedGearScreen::Half)
+            } else if value < 2.0 / 3.0 {
+                (SpeedGear::Normal, SpeedGearScreen::Normal)
+            } else {
+                (SpeedGear::Double, SpeedGearScreen::Double)
+            };
+            self.cache.options.speed_gear = gear;
+            self.cache
+                .display
+                .set_alt_menu(AltAttributeScreen::SpeedGear(screen));
+        }
+
+        let speed_gear = self.cache.options.speed_gear;
+        if speed_gear != original_speed_gear {
+            *needs_save |= true;
+            match speed_gear {
+                SpeedGear::Half => {
+                    log::info!("Setting speed gear=half");
+                }
+                SpeedGear::Normal => {
+                    log::info!("Setting speed gear=normal");
+                }
+                SpeedGear::Double => {
+                    log::info!("Setting speed gear=double");
+                }
+            }
+        }
+    }
+
+    fn set_screen_for_speed_overview(&mut self) {
+        // NOTE: While following tempo, this shows the position of the speed
+        // knob, which selects the tempo multiple.
+        let (_, phase) = match self.cache.options.delay_range {
+            DelayRange::Long => self.speed_for_long_range(),
+            DelayRange::Short => self.speed_for_short_range(),
+            DelayRange::Audio => self.speed_for_audio_range(),
+        };
+        self.cache
+            .display
+            .set_fallback_attribute(AttributeScreen::Speed(1.0 - phase));
     }
 
     fn speed_for_audio_range(&mut self) -> (f32, f32) {
@@ -0,0 +1,135 @@
+//! Rhythm tapped in by the user, to be played by the heads.
+
+/// Follows taps of a single rhythm.
+///
+/// The first tap starts the rhythm, each of the following taps marks an
+/// onset of the next head. The rhythm is over once all the heads are
+/// placed, or when it gets abandoned.
+#[derive(Debug, Default)]
+#[cfg_attr(feature = "defmt", derive(defmt::Format))]
+pub struct RhythmTap {
+    elapsed: Option<u32>,
+    next_head: usize,
+    pressed: bool,
+}
+
+impl RhythmTap {
+    /// Register a press that may turn out to be a tap.
+    pub fn press(&mut self) {
+        self.pressed = true;
+    }
+
+    /// Drop the pending press, it was used for another gesture.
+    pub fn cancel_press(&mut self) {
+        self.pressed = false;
+    }
+
+    /// Register the release of the pending press, held for the given number
+    /// of milliseconds, taking it as a tap.
+    pub fn release(&mut self, held: u32) -> Option<(usize, u32)> {
+        if self.pressed {
+            self.pressed = false;
+            self.trigger(held)
+        } else {
+            None
+        }
+    }
+
+    /// Register a tap made the given number of milliseconds ago, returning
+    /// the head it places together with its onset in milliseconds since the
+    /// first tap.
+    fn trigger(&mut self, ago: u32) -> Option<(usize, u32)> {
+        if let Some(elapsed) = self.elapsed {
+            let head = self.next_head;
+            self.next_head += 1;
+            if self.next_head == 4 {
+                self.reset();
+            }
+            Some((head, elapsed.saturating_sub(ago)))
+        } else {
+            self.elapsed = Some(ago);
+            self.next_head = 0;
+            None
+        }
+    }
+
+    pub fn tick(&mut self) {
+        if let Some(elapsed) = &mut self.elapsed {
+            *elapsed += 1;
+        }
+    }
+
+    pub fn reset(&mut self) {
+        self.elapsed = None;
+    }
+
+    /// Milliseconds since the first tap of the ongoing rhythm.
+    pub fn elapsed(&self) -> Option<u32> {
+        self.elapsed
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn tick_for(rhythm_tap: &mut RhythmTap, ticks: u32) {
+        for _ in 0..ticks {
+            rhythm_tap.tick();
+        }
+    }
+
+    #[test]
+    fn when_taps_follow_the_first_one_they_place_heads_in_order() {
+        let mut rhythm_tap = RhythmTap::default();
+
+        assert_eq!(rhythm_tap.trigger(0), None);
+        tick_for(&mut rhythm_tap, 250);
+        assert_eq!(rhythm_tap.trigger(0), Some((0, 250)));
+        tick_for(&mut rhythm_tap, 125);
+        assert_eq!(rhythm_tap.trigger(0), Some((1, 375)));
+        tick_for(&mut rhythm_tap, 125);
+        assert_eq!(rhythm_tap.trigger(0), Some((2, 500)));
+        tick_for(&mut rhythm_tap, 500);
+        assert_eq!(rhythm_tap.trigger(0), Some((3, 1000)));
+
+        assert_eq!(rhythm_tap.elapsed(), None);
+    }
+
+    #[test]
+    fn when_rhythm_is_reset_next_tap_starts_a_new_one() {
+        let mut rhythm_tap = RhythmTap::default();
+
+        rhythm_tap.trigger(0);
+        tick_for(&mut rhythm_tap, 250);
+        rhythm_tap.trigger(0);
+        rhythm_tap.reset();
+
+        assert_eq!(rhythm_tap.trigger(0), None);
+        tick_for(&mut rhythm_tap, 100);
+        assert_eq!(rhythm_tap.trigger(0), Some((0, 100)));
+    }
+
+    #[test]
+    fn when_tap_is_released_later_its_onset_is_taken_from_its_press() {
+        let mut rhythm_tap = RhythmTap::default();
+
+        rhythm_tap.press();
+        tick_for(&mut rhythm_tap, 20);
+        assert_eq!(rhythm_tap.release(20), None);
+        tick_for(&mut rhythm_tap, 230);
+        rhythm_tap.press();
+        tick_for(&mut rhythm_tap, 50);
+        assert_eq!(rhythm_tap.release(50), Some((0, 250)));
+    }
+
+    #[test]
+    fn when_press_is_cancelled_its_release_is_not_a_tap() {
+        let mut rhythm_tap = RhythmTap::default();
+
+        rhythm_tap.press();
+        rhythm_tap.cancel_press();
+        assert_eq!(rhythm_tap.release(500), None);
+        assert_eq!(rhythm_tap.elapsed(), None);
+    }
+}
@@ -0,0 +1,89 @@
+//! Snapshot of head attributes, so a save can recall the full sound.
+
+use super::reconcile::{FEEDBACK_RANGE, FINE_OFFSET_RANGE, VOLUME_RANGE};
+use super::AttributesHead;
+
+/// Attributes of all heads at the moment the scene was captured.
+///
+/// Each value is quantized into a single byte, so the save still fits into
+/// a single flash page.
+#[derive(Debug, Default, Clone, Copy, PartialEq, Eq)]
+#[cfg_attr(feature = "defmt", derive(defmt::Format))]
+pub struct Scene {
+    heads: [SceneHead; 4],
+}
+
+#[derive(Debug, Default, Clone, Copy, PartialEq, Eq)]
+#[cfg_attr(feature = "defmt", derive(defmt::Format))]
+struct SceneHead {
+    position: u8,
+    volume: u8,
+    feedback: u8,
+    pan: u8,
+    fine_offset: u8,
+}
+
+impl Scene {
+    pub(crate) const SIZE: usize = 4 * 5;
+
+    pub fn capture(heads: &[AttributesHead; 4]) -> Self {
+        Self {
+            heads: heads.map(|head| SceneHead {
+                position: to_byte(head.position, (0.0, 1.0)),
+                // NOTE: Volume pushed past unity with unlimited range is
+                // recalled at the top of the limited range.
+                volume: to_byte(head.volume, VOLUME_RANGE),
+                feedback: to_byte(head.feedback, FEEDBACK_RANGE),
+                pan: to_byte(head.pan, (0.0, 1.0)),
+                fine_offset: to_byte(head.fine_offset_ms, (-FINE_OFFSET_RANGE, FINE_OFFSET_RANGE)),
+            }),
+        }
+    }
+
+    pub fn recall(&self) -> [AttributesHead; 4] {
+        self.heads.map(|head| AttributesHead {
+            position: from_byte(head.position, (0.0, 1.0)),
+            volume: from_byte(head.volume, VOLUME_RANGE),
+            feedback: from_byte(head.feedback, FEEDBACK_RANGE),
+            pan: from_byte(head.pan, (0.0, 1.0)),
+            fine_offset_ms: from_byte(head.fine_offset, (-FINE_OFFSET_RANGE, FINE_OFFSET_RANGE)),
+        })
+    }
+
+    pub(crate) fn to_bytes(self) -> [u8; Self::SIZE] {
+        let mut bytes = [0; Self::SIZE];
+        for (chunk, head) in bytes.chunks_exact_mut(5).zip(self.heads) {
+            chunk.copy_from_slice(&[
+                head.position,
+                head.volume,
+                head.feedback,
+                head.pan,
+                head.fine_offset,
+            ]);
+        }
+        bytes
+    }
+
+    pub(crate) fn from_bytes(bytes: [u8; Self::SIZE]) -> Self {
+        let mut heads = [SceneHead::default(); 4];
+        for (head, chunk) in heads.iter_mut().zip(bytes.chunks_exact(5)) {
+            *head = SceneHead {
+                position: chunk[0],
+                volume: chunk[1],
+                feedback: chunk[2],
+                pan: chunk[3],
+                fine_offset: chunk[4],
+            };
+        }
+        Self { heads }
+    }
+}
+
+fn to_byte(x: f32, range: (f32, f32)) -> u8 {
+    let phase = ((x - range.0) / (range.1 - range.0)).clamp(0.0, 1.0);
+    (phase * 255.0 + 0.5) as u8
+}
+
+fn from_byte(x: u8, range: (f32, f32)) -> f32 {
+    range.0 + f32::from(x) / 255.0 * (range.1 - range.0)
+}
@@ -7,6 +7,8 @@ use super::interval_detector::IntervalDetector;
 ///
 /// This has been created as a merge of the original `TapDetector`
 /// and `ClockDetector` after any difference between them was removed.
+/// Since then, `TapDetector` was split out again to forgive an erratic
+/// first tap.
 #[derive(Debug, Default)]
 #[cfg_attr(feature = "defmt", derive(defmt::Format))]
 pub struct TapClockDetector {
@@ -33,7 +35,9 @@ impl TapClockDetector {
     pub fn detected_tempo(&self) -> Option<u32> {
         self.detector.tempo
     }
-    pub fn first_beat_after_detection(&self) -> bool {
-        self.detector.first_beat_after_detection
+
+    /// Position within the beat of the detected clock, from 0.0 to 1.0.
+    pub fn phase(&self) -> Option<f32> {
+        self.detector.phase()
     }
 }
@@ -4,15 +4,29 @@ use super::interval_detector::IntervalDetector;
 ///
 /// This is only a momentary detector. To persist once detected
 /// tempo, the result needs to be snapshotted and stored elsewhere.
-#[derive(Debug, Default)]
+///
+/// Unlike clock detection, an erratic first tap is ignored, so the
+/// tempo locks once the three following taps are consistent.
+#[derive(Debug)]
 #[cfg_attr(feature = "defmt", derive(defmt::Format))]
 pub struct TapDetector {
     detector: IntervalDetector,
+    unadopted: bool,
+}
+
+impl Default for TapDetector {
+    fn default() -> Self {
+        Self {
+            detector: IntervalDetector::forgiving(),
+            unadopted: false,
+        }
+    }
 }
 
 impl TapDetector {
     pub fn trigger(&mut self) {
         self.detector.trigger();
+        self.unadopted |= self.detector.just_detected;
     }
 
     pub fn just_detected(&self) -> bool {
@@ -25,9 +39,23 @@ impl TapDetector {
 
     pub fn reset(&mut self) {
         self.detector.reset();
+        self.unadopted = false;
+    }
+
+    /// Tempo detected by a tap since the last call.
+    ///
+    /// The detected tempo is held until the next tap. Taking it only once
+    /// allows the adopted tempo to be adjusted afterwards.
+    pub fn take_detected_tempo(&mut self) -> Option<u32> {
+        if self.unadopted {
+            self.unadopted = false;
+            self.detector.tempo
+        } else {
+            None
+        }
     }
 
-    pub fn detected_tempo(&self) -> Option<u32> {
-        self.detector.tempo
+    pub fn first_beat_after_detection(&self) -> bool {
+        self.detector.first_beat_after_detection
     }
 }
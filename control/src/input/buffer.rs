@@ -2,6 +2,9 @@
 
 use core::{ptr, sync::atomic};
 
+#[allow(unused_imports)]
+use micromath::F32Ext;
+
 /// Buffer meant for smoothening and history tracking.
 ///
 /// This is not optimized for large buffers, but should be ok for smoothening
@@ -74,6 +77,25 @@ impl<const N: usize> Buffer<N> {
         let oldest = self.pointer;
         self.buffer[newest] - self.buffer[oldest]
     }
+
+    /// Root mean square of differences between samples half of the buffer
+    /// apart.
+    ///
+    /// Unlike `traveled`, this takes all the samples into account. A single
+    /// outlier affects only one of the differences, while a steady movement
+    /// shows in all of them.
+    #[must_use]
+    pub fn traveled_rms(&self) -> f32 {
+        let half = N / 2;
+        let sum: f32 = (0..half)
+            .map(|i| {
+                let older = self.buffer[self.pointer.wrapping_add(i) & self.mask];
+                let newer = self.buffer[self.pointer.wrapping_add(i + half) & self.mask];
+                (newer - older) * (newer - older)
+            })
+            .sum();
+        (sum / half as f32).sqrt()
+    }
 }
 
 fn is_power_of_2(n: usize) -> bool {
@@ -125,6 +147,23 @@ mod tests {
         assert_relative_eq!(buffer.traveled(), -3.0);
     }
 
+    #[test]
+    fn when_measures_traveled_rms_it_compares_samples_half_buffer_apart() {
+        let mut buffer: Buffer<4> = Buffer::new();
+
+        buffer.write(1.0);
+        buffer.write(2.0);
+        buffer.write(3.0);
+        buffer.write(4.0);
+        assert_relative_eq!(buffer.traveled_rms(), 2.0);
+
+        buffer.write(4.0);
+        buffer.write(4.0);
+        buffer.write(4.0);
+        buffer.write(8.0);
+        assert_relative_eq!(buffer.traveled_rms(), 8.0_f32.sqrt());
+    }
+
     #[test]
     fn when_reset_it_returns_zero() {
         let mut buffer: Buffer<4> = Buffer::new();
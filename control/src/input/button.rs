@@ -9,6 +9,9 @@ pub struct Button {
     pub pressed: bool,
     pub clicked: bool,
     pub held: u32,
+    /// Number of cycles the button was held for before it got released
+    /// in this cycle. Zero when it was not released.
+    pub released_after: u32,
 }
 
 impl Button {
@@ -16,6 +19,11 @@ impl Button {
         let was_pressed = self.pressed;
         self.pressed = down;
         self.clicked = !was_pressed && self.pressed;
+        self.released_after = if was_pressed && !self.pressed {
+            self.held
+        } else {
+            0
+        };
         self.held = if self.pressed {
             self.held.saturating_add(1)
         } else {
@@ -55,4 +63,17 @@ mod tests {
         button.update(false);
         assert_eq!(button.held, 0);
     }
+
+    #[test]
+    fn when_is_released_it_reports_how_long_it_was_held() {
+        let mut button = Button::default();
+        button.update(true);
+        button.update(true);
+        button.update(true);
+        assert_eq!(button.released_after, 0);
+        button.update(false);
+        assert_eq!(button.released_after, 3);
+        button.update(false);
+        assert_eq!(button.released_after, 0);
+    }
 }
@@ -16,9 +16,22 @@ pub struct Control {
     pub was_plugged: bool,
     pub was_unplugged: bool,
     pub last_value_above_noise: f32,
+    pub unplugged_value: UnpluggedValue,
+    holding: bool,
     buffer: Buffer<4>,
 }
 
+/// Value the input reports once it gets unplugged.
+#[derive(Debug, Default, Clone, Copy, PartialEq, Eq)]
+#[cfg_attr(feature = "defmt", derive(defmt::Format))]
+pub enum UnpluggedValue {
+    /// Drop to zero, leaving the attribute to the pot alone.
+    #[default]
+    Zero,
+    /// Freeze on the last value read while plugged.
+    Hold,
+}
+
 impl Control {
     pub fn update(&mut self, value: Option<f32>) {
         let was_plugged = self.is_plugged;
@@ -27,10 +40,15 @@ impl Control {
             self.buffer.write(value);
         } else {
             self.is_plugged = false;
-            self.buffer.reset();
+            if self.unplugged_value == UnpluggedValue::Zero {
+                self.buffer.reset();
+            }
         }
         self.was_plugged = !was_plugged && self.is_plugged;
         self.was_unplugged = was_plugged && !self.is_plugged;
+        self.holding = !self.is_plugged
+            && self.unplugged_value == UnpluggedValue::Hold
+            && (self.holding || self.was_unplugged);
 
         let value = self.buffer.read();
         let diff = (self.last_value_above_noise - value).abs();
@@ -43,6 +61,12 @@ impl Control {
         }
     }
 
+    /// Whether the input was unplugged, but it keeps reporting the last
+    /// value it read before.
+    pub fn is_holding(&self) -> bool {
+        self.holding
+    }
+
     pub fn value(&self) -> f32 {
         self.buffer.read()
     }
@@ -76,6 +100,37 @@ mod tests {
         assert_relative_eq!(cv.value(), 0.0);
     }
 
+    #[test]
+    fn when_none_is_written_in_hold_mode_its_value_should_stay_at_last_reading() {
+        let mut cv = Control {
+            unplugged_value: UnpluggedValue::Hold,
+            ..Control::default()
+        };
+        for _ in 0..4 {
+            cv.update(Some(3.0));
+        }
+        cv.update(None);
+        assert!(!cv.is_plugged);
+        assert!(cv.is_holding());
+        assert_relative_eq!(cv.value(), 3.0);
+        assert_relative_eq!(cv.value_raw(), 3.0);
+    }
+
+    #[test]
+    fn when_hold_mode_is_left_while_unplugged_its_value_should_drop_to_zero() {
+        let mut cv = Control {
+            unplugged_value: UnpluggedValue::Hold,
+            ..Control::default()
+        };
+        cv.update(Some(3.0));
+        cv.update(None);
+
+        cv.unplugged_value = UnpluggedValue::Zero;
+        cv.update(None);
+        assert!(!cv.is_holding());
+        assert_relative_eq!(cv.value(), 0.0);
+    }
+
     #[test]
     fn when_some_is_being_written_its_value_should_eventually_reach_it() {
         let mut cv = Control::default();
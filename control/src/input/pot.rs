@@ -5,6 +5,11 @@ use micromath::F32Ext;
 
 use super::buffer::Buffer;
 
+// RMS of the travel within the buffer above which the pot is considered
+// moving. A turn over the whole range taking about two seconds gets above
+// it, while a single sample off by 0.03 does not.
+const ACTIVATION_TRAVEL: f32 = 0.009;
+
 /// Abstraction of a potentiometer.
 ///
 /// Use it to smoothen the value received from pots and detect their
@@ -21,7 +26,7 @@ impl Pot {
     pub fn update(&mut self, value: f32) {
         self.buffer.write(value);
 
-        self.last_activation_movement = if self.traveled_more_than(0.02) {
+        self.last_activation_movement = if self.buffer.traveled_rms() > ACTIVATION_TRAVEL {
             0
         } else {
             self.last_activation_movement.saturating_add(1)
@@ -45,10 +50,6 @@ impl Pot {
     pub fn activation_movement(&self) -> bool {
         self.last_activation_movement == 0
     }
-
-    fn traveled_more_than(&self, toleration: f32) -> bool {
-        self.buffer.traveled().abs() > toleration
-    }
 }
 
 #[cfg(test)]
@@ -73,6 +74,46 @@ mod tests {
         panic!("Control have not reached the target {}", value);
     }
 
+    #[test]
+    fn when_pot_steps_it_gets_activated_within_few_samples() {
+        let mut pot = Pot::default();
+        for _ in 0..4 {
+            pot.update(0.02);
+        }
+        assert!(pot.activation_movement());
+    }
+
+    #[test]
+    fn when_pot_is_turned_slowly_it_gets_activated() {
+        let mut pot = Pot::default();
+
+        // NOTE: Too slow to travel 0.02 between the oldest and the newest
+        // sample of the buffer.
+        let mut activated = [false; 128];
+        for (i, activated) in activated.iter_mut().enumerate() {
+            pot.update(i as f32 * 0.0006);
+            assert!(pot.buffer.traveled() < 0.02);
+            *activated = pot.activation_movement();
+        }
+
+        assert!(activated[32..].iter().all(|a| *a));
+    }
+
+    #[test]
+    fn when_pot_reading_spikes_for_single_sample_it_is_not_activated() {
+        let mut pot = Pot::default();
+        for _ in 0..64 {
+            pot.update(0.5);
+        }
+        assert!(!pot.activation_movement());
+
+        pot.update(0.53);
+        for _ in 0..64 {
+            assert!(!pot.activation_movement());
+            pot.update(0.5);
+        }
+    }
+
     #[test]
     fn when_pot_moves_near_zero_it_snaps_to_it_despite_not_traveling_enough_distance() {
         let mut pot = Pot::default();
@@ -3,6 +3,20 @@
 //! It is mainly targetted to run in a firmware with multiple loops running in
 //! different frequencies, passing messages from one to another. However, parts
 //! of it may be useful in software as well.
+//!
+//! To drive it from a host program, such as a simulator, follow what the
+//! firmware does:
+//!
+//! 1. Feed `Store::warm_up` with a few `InputSnapshot`s after start.
+//! 2. Pass each new `InputSnapshot` to `Store::apply_input_snapshot` and
+//!    forward the returned `ApplyInputSnapshotResult` to the DSP and storage.
+//!    Once the storage accepts a save, confirm it through
+//!    `Store::confirm_save`, otherwise it is returned again.
+//! 3. Return the reaction of the DSP through `Store::apply_dsp_reaction`.
+//! 4. Call `Store::tick` once per millisecond and render the returned
+//!    `DesiredOutput`.
+//!
+//! `Store::status` tells which state the module is in.
 
 #![no_std]
 #![allow(clippy::items_after_statements)]
@@ -28,10 +42,14 @@ mod output;
 mod save;
 mod store;
 
+pub use crate::cache::configuration::{
+    Configuration, DisplayPage, FeedbackTopology, InvalidConfiguration, TapMode,
+};
+pub use crate::cache::mapping::AttributeIdentifier;
 pub use crate::input::snapshot::{Snapshot as InputSnapshot, SnapshotHead as InputSnapshotHead};
 pub use crate::output::DesiredOutput;
-pub use crate::save::{Save, Store as SaveStore};
-pub use crate::store::Store;
+pub use crate::save::{Save, Store as SaveStore, VERSION as SAVE_VERSION};
+pub use crate::store::{ApplyInputSnapshotResult, Status as StoreStatus, Store};
 
 // Public for the sake of benchmarks
 pub use crate::input::buffer::Buffer;
@@ -6,6 +6,7 @@
 #[cfg_attr(feature = "defmt", derive(defmt::Format))]
 pub struct DesiredOutput {
     pub display: [bool; 8],
-    pub impulse_led: bool,
+    /// Brightness of the impulse LED between 0.0 and 1.0.
+    pub impulse_led: f32,
     pub impulse_trigger: bool,
 }
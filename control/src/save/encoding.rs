@@ -0,0 +1,373 @@
+//! Explicit byte layout of the save.
+//!
+//! The save is written field by field instead of being transmuted as a
+//! whole. That way no padding byte ever reaches the flash or the CRC, and
+//! bytes that do not map to any value, e.g. an unknown enum variant, are
+//! rejected while decoding.
+
+use crate::cache::calibration::Calibration;
+use crate::cache::configuration::{DisplayPage, FeedbackTopology, TapMode};
+use crate::cache::mapping::AttributeIdentifier;
+use crate::cache::{
+    Configuration, DelayRange, FilterPlacement, MasterFeedback, Options, PanLaw, Scene, SpeedGear,
+    WowFlutterPlacement,
+};
+
+use super::Save;
+
+pub struct Writer<'a> {
+    bytes: &'a mut [u8],
+    position: usize,
+}
+
+impl<'a> Writer<'a> {
+    pub fn new(bytes: &'a mut [u8]) -> Self {
+        Self { bytes, position: 0 }
+    }
+
+    pub fn write(&mut self, bytes: &[u8]) {
+        self.bytes[self.position..self.position + bytes.len()].copy_from_slice(bytes);
+        self.position += bytes.len();
+    }
+
+    // NOTE: Skipped bytes are left as they were, zeroed by the caller.
+    fn skip(&mut self, length: usize) {
+        self.position += length;
+    }
+}
+
+pub struct Reader<'a> {
+    bytes: &'a [u8],
+    position: usize,
+}
+
+impl<'a> Reader<'a> {
+    pub fn new(bytes: &'a [u8]) -> Self {
+        Self { bytes, position: 0 }
+    }
+
+    pub fn read<const N: usize>(&mut self) -> [u8; N] {
+        let bytes = self.bytes[self.position..self.position + N]
+            .try_into()
+            .unwrap();
+        self.position += N;
+        bytes
+    }
+
+    fn skip(&mut self, length: usize) {
+        self.position += length;
+    }
+}
+
+/// Structure that can be written into the save with a fixed size.
+pub trait Encode: Sized {
+    const SIZE: usize;
+
+    fn encode(&self, writer: &mut Writer);
+
+    fn decode(reader: &mut Reader) -> Option<Self>;
+}
+
+impl Encode for u8 {
+    const SIZE: usize = 1;
+
+    fn encode(&self, writer: &mut Writer) {
+        writer.write(&[*self]);
+    }
+
+    fn decode(reader: &mut Reader) -> Option<Self> {
+        Some(reader.read::<1>()[0])
+    }
+}
+
+// NOTE: All the indices stored in the save are small, so they are kept in
+// a single byte.
+impl Encode for usize {
+    const SIZE: usize = 1;
+
+    fn encode(&self, writer: &mut Writer) {
+        (*self as u8).encode(writer);
+    }
+
+    fn decode(reader: &mut Reader) -> Option<Self> {
+        u8::decode(reader).map(usize::from)
+    }
+}
+
+impl Encode for bool {
+    const SIZE: usize = 1;
+
+    fn encode(&self, writer: &mut Writer) {
+        u8::from(*self).encode(writer);
+    }
+
+    fn decode(reader: &mut Reader) -> Option<Self> {
+        match u8::decode(reader)? {
+            0 => Some(false),
+            1 => Some(true),
+            _ => None,
+        }
+    }
+}
+
+impl Encode for f32 {
+    const SIZE: usize = 4;
+
+    fn encode(&self, writer: &mut Writer) {
+        writer.write(&self.to_le_bytes());
+    }
+
+    fn decode(reader: &mut Reader) -> Option<Self> {
+        Some(Self::from_le_bytes(reader.read()))
+    }
+}
+
+// NOTE: The value takes its space even when it is missing, so the size of
+// the save stays fixed.
+impl<T: Encode> Encode for Option<T> {
+    const SIZE: usize = 1 + T::SIZE;
+
+    fn encode(&self, writer: &mut Writer) {
+        self.is_some().encode(writer);
+        match self {
+            Some(value) => value.encode(writer),
+            None => writer.skip(T::SIZE),
+        }
+    }
+
+    fn decode(reader: &mut Reader) -> Option<Self> {
+        if bool::decode(reader)? {
+            T::decode(reader).map(Some)
+        } else {
+            reader.skip(T::SIZE);
+            Some(None)
+        }
+    }
+}
+
+impl<T: Encode, U: Encode> Encode for (T, U) {
+    const SIZE: usize = T::SIZE + U::SIZE;
+
+    fn encode(&self, writer: &mut Writer) {
+        self.0.encode(writer);
+        self.1.encode(writer);
+    }
+
+    fn decode(reader: &mut Reader) -> Option<Self> {
+        Some((T::decode(reader)?, U::decode(reader)?))
+    }
+}
+
+impl<T: Encode + Copy + Default> Encode for [T; 4] {
+    const SIZE: usize = 4 * T::SIZE;
+
+    fn encode(&self, writer: &mut Writer) {
+        for value in self {
+            value.encode(writer);
+        }
+    }
+
+    fn decode(reader: &mut Reader) -> Option<Self> {
+        let mut values = [T::default(); 4];
+        for value in &mut values {
+            *value = T::decode(reader)?;
+        }
+        Some(values)
+    }
+}
+
+/// Store fieldless enums as the index of their variant in the given list.
+macro_rules! encode_enum {
+    ($type:ident { $($variant:ident),* $(,)? }) => {
+        impl Encode for $type {
+            const SIZE: usize = 1;
+
+            fn encode(&self, writer: &mut Writer) {
+                const VARIANTS: &[$type] = &[$($type::$variant),*];
+                let index = VARIANTS.iter().position(|v| v == self).unwrap();
+                (index as u8).encode(writer);
+            }
+
+            fn decode(reader: &mut Reader) -> Option<Self> {
+                const VARIANTS: &[$type] = &[$($type::$variant),*];
+                VARIANTS.get(usize::decode(reader)?).copied()
+            }
+        }
+    };
+}
+
+/// Store structures field after field, in the given order.
+///
+/// All fields must be listed, otherwise the decoding does not compile.
+macro_rules! encode_struct {
+    ($type:ident { $($field:ident: $field_type:ty),* $(,)? }) => {
+        impl Encode for $type {
+            const SIZE: usize = 0 $(+ <$field_type>::SIZE)*;
+
+            fn encode(&self, writer: &mut Writer) {
+                $(self.$field.encode(writer);)*
+            }
+
+            fn decode(reader: &mut Reader) -> Option<Self> {
+                Some(Self {
+                    $($field: <$field_type>::decode(reader)?,)*
+                })
+            }
+        }
+    };
+}
+
+encode_enum!(DelayRange { Long, Short, Audio });
+encode_enum!(FilterPlacement {
+    Input,
+    Feedback,
+    Both
+});
+encode_enum!(WowFlutterPlacement { Input, Read, Both });
+encode_enum!(PanLaw { Linear, EqualPower });
+encode_enum!(SpeedGear {
+    Half,
+    Normal,
+    Double
+});
+encode_enum!(DisplayPage {
+    Heads,
+    Position,
+    Speed,
+    Feedback,
+    Spread,
+    InputLevel,
+});
+encode_enum!(FeedbackTopology {
+    Cumulative,
+    SinglePass
+});
+encode_enum!(TapMode { Tempo, Rhythm });
+
+impl Encode for AttributeIdentifier {
+    const SIZE: usize = 2;
+
+    fn encode(&self, writer: &mut Writer) {
+        let (tag, index): (u8, usize) = match *self {
+            Self::PreAmp => (0, 0),
+            Self::Drive => (1, 0),
+            Self::Bias => (2, 0),
+            Self::DryWet => (3, 0),
+            Self::WowFlut => (4, 0),
+            Self::Speed => (5, 0),
+            Self::Tone => (6, 0),
+            Self::Position(i) => (7, i),
+            Self::Volume(i) => (8, i),
+            Self::Feedback(i) => (9, i),
+            Self::Pan(i) => (10, i),
+            Self::None => (11, 0),
+        };
+        (tag, index).encode(writer);
+    }
+
+    fn decode(reader: &mut Reader) -> Option<Self> {
+        let (tag, index) = <(u8, usize)>::decode(reader)?;
+        let attribute = match tag {
+            0 => Self::PreAmp,
+            1 => Self::Drive,
+            2 => Self::Bias,
+            3 => Self::DryWet,
+            4 => Self::WowFlut,
+            5 => Self::Speed,
+            6 => Self::Tone,
+            7 => Self::Position(index),
+            8 => Self::Volume(index),
+            9 => Self::Feedback(index),
+            10 => Self::Pan(index),
+            11 => Self::None,
+            _ => return None,
+        };
+        Some(attribute)
+    }
+}
+
+impl Encode for MasterFeedback {
+    const SIZE: usize = f32::SIZE;
+
+    fn encode(&self, writer: &mut Writer) {
+        self.0.encode(writer);
+    }
+
+    fn decode(reader: &mut Reader) -> Option<Self> {
+        f32::decode(reader).map(Self)
+    }
+}
+
+impl Encode for Scene {
+    const SIZE: usize = Scene::SIZE;
+
+    fn encode(&self, writer: &mut Writer) {
+        writer.write(&self.to_bytes());
+    }
+
+    fn decode(reader: &mut Reader) -> Option<Self> {
+        Some(Self::from_bytes(reader.read()))
+    }
+}
+
+encode_struct!(Calibration {
+    offset: f32,
+    scaling: f32,
+});
+
+encode_struct!(Options {
+    quantize_8: bool,
+    quantize_6: bool,
+    delay_range: DelayRange,
+    rewind: bool,
+    enable_oscillator: bool,
+    random_impulse: bool,
+    filter_placement: FilterPlacement,
+    wow_flutter_placement: WowFlutterPlacement,
+    unlimited: bool,
+    pan_law: PanLaw,
+    speed_gear: SpeedGear,
+    bipolar_feedback: bool,
+    master_feedback: MasterFeedback,
+});
+
+encode_struct!(Configuration {
+    rewind_speed: [(usize, usize); 4],
+    default_display_page: DisplayPage,
+    position_reset_mapping: Option<u8>,
+    pause_resume_mapping: Option<u8>,
+    tap_interval_denominator: u8,
+    capture_mapping: Option<u8>,
+    tap_mapping: Option<u8>,
+    capture_length: u8,
+    capture_repeats: u8,
+    multi_tap_pattern: u8,
+    output_level: u8,
+    cv_attenuversion: [u8; 4],
+    feedback_topology: FeedbackTopology,
+    gate_head: Option<u8>,
+    gate_width: u8,
+    quantization_grid: u8,
+    tape_age: u8,
+    spread: u8,
+    trails: bool,
+    tap_mode: TapMode,
+    impulse_threshold: u8,
+    cv_interpolation: bool,
+    position_crossfade: u8,
+    click_level: u8,
+    speed_smoothing: u8,
+    rewind_beat_lock: bool,
+    oversampled_read: bool,
+    unplugged_hold: bool,
+});
+
+encode_struct!(Save {
+    version: u8,
+    mapping: [AttributeIdentifier; 4],
+    calibrations: [Calibration; 4],
+    options: Options,
+    configuration: Configuration,
+    tapped_tempo: Option<f32>,
+    scene: Option<Scene>,
+});
@@ -0,0 +1,448 @@
+use crc::{Crc, CRC_16_USB};
+
+mod encoding;
+mod v5;
+
+use crate::cache::mapping::Mapping;
+use crate::cache::{Calibrations, Configuration, Options, Scene, TappedTempo};
+
+use self::encoding::{Encode, Reader, Writer};
+use self::v5::{SaveV5, StoreV5};
+
+/// Version of the save layout. It must be bumped whenever any of the saved
+/// structures changes. Saves of older versions are migrated while loading
+/// when their layout is known, see `v5`, otherwise they are discarded.
+pub const VERSION: u8 = 23;
+
+/// Subset of control structures needed for recovery after restart.
+#[derive(Debug, Clone, Copy, PartialEq)]
+#[cfg_attr(feature = "defmt", derive(defmt::Format))]
+pub struct Save {
+    /// Version of the layout this save was originally stored with.
+    pub version: u8,
+    pub mapping: Mapping,
+    pub calibrations: Calibrations,
+    pub options: Options,
+    pub configuration: Configuration,
+    pub tapped_tempo: TappedTempo,
+    /// Head attributes to recall, if the scene was captured.
+    pub scene: Option<Scene>,
+}
+
+impl Default for Save {
+    fn default() -> Self {
+        Self {
+            version: VERSION,
+            mapping: Mapping::default(),
+            calibrations: Calibrations::default(),
+            options: Options::default(),
+            configuration: Configuration::default(),
+            tapped_tempo: TappedTempo::default(),
+            scene: None,
+        }
+    }
+}
+
+impl Save {
+    const SIZE: usize = <Self as Encode>::SIZE;
+
+    fn from_bytes(bytes: &[u8; Self::SIZE]) -> Option<Self> {
+        Self::decode(&mut Reader::new(bytes))
+    }
+
+    fn to_bytes(self) -> [u8; Self::SIZE] {
+        let mut bytes = [0; Self::SIZE];
+        self.encode(&mut Writer::new(&mut bytes));
+        bytes
+    }
+}
+
+const CRC: Crc<u16> = Crc::<u16>::new(&CRC_16_USB);
+pub struct InvalidData;
+
+/// Save wrapped with its version, token and CRC, as it is kept in the flash.
+///
+/// The bytes are laid out explicitly: the version, the token, the save and
+/// the CRC, all little endian.
+#[derive(Clone, Copy)]
+pub struct Store {
+    version: u32,
+    save: Save,
+}
+
+impl Store {
+    const LAYOUT_SIZE: usize = 4 + 2 + Save::SIZE + 2;
+
+    // NOTE: Stores of older layouts were larger, the read bytes must be
+    // able to hold them too.
+    pub const SIZE: usize = if Self::LAYOUT_SIZE > StoreV5::SIZE {
+        Self::LAYOUT_SIZE
+    } else {
+        StoreV5::SIZE
+    };
+
+    #[must_use]
+    pub fn new(save: Save, version: u32) -> Self {
+        Self { version, save }
+    }
+
+    /// # Errors
+    ///
+    /// This fails with `InvalidData` when recovered save does not pass CRC
+    /// check or when its version is not known.
+    pub fn from_bytes(bytes: [u8; Self::SIZE]) -> Result<Self, InvalidData> {
+        let mut reader = Reader::new(&bytes);
+        let version = u32::from_le_bytes(reader.read());
+        let token = u16::from_le_bytes(reader.read());
+        let save_raw: [u8; Save::SIZE] = reader.read();
+        let crc = u16::from_le_bytes(reader.read());
+
+        if token != u16::from(VERSION) {
+            return Self::from_legacy_bytes(&bytes);
+        }
+
+        if crc != CRC.checksum(&save_raw) {
+            return Err(InvalidData);
+        }
+
+        let save = Save::from_bytes(&save_raw).ok_or(InvalidData)?;
+        Ok(Self::new(save, version))
+    }
+
+    // NOTE: Older saves are found at the start of the given bytes, the
+    // rest of them is left unused.
+    fn from_legacy_bytes(bytes: &[u8; Self::SIZE]) -> Result<Self, InvalidData> {
+        let legacy_bytes = bytes[..StoreV5::SIZE].try_into().unwrap();
+        let legacy = StoreV5::from_bytes(legacy_bytes);
+
+        if legacy.token != u16::from(v5::VERSION) {
+            return Err(InvalidData);
+        }
+
+        let crc = CRC.checksum(&legacy.save_raw);
+        if crc == legacy.crc {
+            let save = SaveV5::from_bytes(legacy.save_raw).into();
+            Ok(Self::new(save, legacy.version))
+        } else {
+            Err(InvalidData)
+        }
+    }
+
+    #[must_use]
+    pub fn to_bytes(self) -> [u8; Self::SIZE] {
+        let save_raw = self.save.to_bytes();
+        let mut bytes = [0; Self::SIZE];
+        let mut writer = Writer::new(&mut bytes);
+        writer.write(&self.version.to_le_bytes());
+        writer.write(&u16::from(VERSION).to_le_bytes());
+        writer.write(&save_raw);
+        writer.write(&CRC.checksum(&save_raw).to_le_bytes());
+        bytes
+    }
+
+    #[must_use]
+    pub fn save(&self) -> Save {
+        self.save
+    }
+
+    #[must_use]
+    pub fn version(&self) -> u32 {
+        self.version
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::v5::{
+        AttributeIdentifierV5, CalibrationV5, ConfigurationV5, DelayRangeV5, DisplayPageV5,
+        OptionsV5,
+    };
+    use super::*;
+    use crate::cache::calibration::Calibration;
+    use crate::cache::configuration::DisplayPage;
+    use crate::cache::DelayRange;
+    use crate::AttributeIdentifier;
+
+    fn v5_store_bytes(save: SaveV5, token: u16, corrupt: bool) -> [u8; Store::SIZE] {
+        let save_raw = save.to_bytes();
+        let mut crc = CRC.checksum(&save_raw);
+        if corrupt {
+            crc = crc.wrapping_add(1);
+        }
+        let legacy = StoreV5 {
+            version: 10,
+            token,
+            save_raw,
+            crc,
+        };
+
+        // NOTE: The rest of the flash page is left erased.
+        let mut bytes = [0xFF; Store::SIZE];
+        bytes[..StoreV5::SIZE].copy_from_slice(&legacy.to_bytes());
+        bytes
+    }
+
+    fn v5_save() -> SaveV5 {
+        SaveV5 {
+            mapping: [
+                AttributeIdentifierV5::Drive,
+                AttributeIdentifierV5::Position(2),
+                AttributeIdentifierV5::None,
+                AttributeIdentifierV5::Tone,
+            ],
+            calibrations: [CalibrationV5 {
+                offset: 0.1,
+                scaling: 1.2,
+            }; 4],
+            options: OptionsV5 {
+                quantize_6: true,
+                delay_range: DelayRangeV5::Short,
+                rewind: true,
+                ..OptionsV5::default()
+            },
+            configuration: ConfigurationV5 {
+                rewind_speed: [(3, 2), (1, 0), (2, 2), (0, 3)],
+                default_display_page: DisplayPageV5::Heads,
+                position_reset_mapping: Some(2),
+                pause_resume_mapping: Some(3),
+                tap_interval_denominator: 8,
+            },
+            tapped_tempo: Some(1.5),
+        }
+    }
+
+    #[test]
+    fn initialize_store() {
+        let _store = Store::new(Save::default(), 0);
+    }
+
+    #[test]
+    fn get_save_from_store() {
+        let save = Save::default();
+        let store = Store::new(save, 0);
+        assert!(store.save() == save);
+    }
+
+    #[test]
+    fn get_version_from_store() {
+        let store = Store::new(Save::default(), 10);
+        assert_eq!(store.version(), 10);
+    }
+
+    #[test]
+    fn initialize_store_from_bytes() {
+        let store_a = Store::new(Save::default(), 0);
+        let bytes = store_a.to_bytes();
+        let store_b = Store::from_bytes(bytes).ok().unwrap();
+        assert!(store_a.save() == store_b.save());
+    }
+
+    #[test]
+    fn detect_invalid_crc_while_initializing_from_bytes() {
+        let store = Store::new(Save::default(), 0);
+        let mut bytes = store.to_bytes();
+        bytes[10] = 0x13;
+        assert!(Store::from_bytes(bytes).is_err());
+    }
+
+    #[test]
+    fn detect_unknown_value_while_initializing_from_bytes() {
+        let mut save_raw = Save::default().to_bytes();
+        // NOTE: The first mapping follows right after the version.
+        save_raw[1] = 0xFF;
+
+        let mut bytes = [0; Store::SIZE];
+        let mut writer = Writer::new(&mut bytes);
+        writer.write(&0_u32.to_le_bytes());
+        writer.write(&u16::from(VERSION).to_le_bytes());
+        writer.write(&save_raw);
+        writer.write(&CRC.checksum(&save_raw).to_le_bytes());
+
+        assert!(Store::from_bytes(bytes).is_err());
+    }
+
+    #[test]
+    fn dump_store_as_bytes() {
+        let save_a = Save {
+            tapped_tempo: Some(1.0),
+            ..Save::default()
+        };
+        let store_a = Store::new(save_a, 0);
+        let bytes_a = store_a.to_bytes();
+
+        let save_b = Save {
+            tapped_tempo: Some(2.0),
+            ..Save::default()
+        };
+        let store_b = Store::new(save_b, 0);
+        let bytes_b = store_b.to_bytes();
+
+        assert!(bytes_a != bytes_b);
+    }
+
+    #[test]
+    fn get_save_with_scene_from_store_bytes() {
+        let save = Save {
+            scene: Some(Scene::default()),
+            ..Save::default()
+        };
+        let bytes = Store::new(save, 0).to_bytes();
+        let store = Store::from_bytes(bytes).ok().unwrap();
+        assert!(store.save() == save);
+    }
+
+    #[test]
+    fn get_save_with_beat_locked_rewind_from_store_bytes() {
+        let save = Save {
+            configuration: Configuration {
+                rewind_beat_lock: true,
+                ..Configuration::default()
+            },
+            ..Save::default()
+        };
+        let bytes = Store::new(save, 0).to_bytes();
+        let store = Store::from_bytes(bytes).ok().unwrap();
+        assert!(store.save().configuration.rewind_beat_lock);
+    }
+
+    #[test]
+    fn given_v5_store_bytes_it_migrates_stored_fields() {
+        let bytes = v5_store_bytes(v5_save(), 5, false);
+
+        let store = Store::from_bytes(bytes).ok().unwrap();
+        let save = store.save();
+
+        assert_eq!(store.version(), 10);
+        assert_eq!(save.version, 5);
+        assert_eq!(
+            save.mapping,
+            [
+                AttributeIdentifier::Drive,
+                AttributeIdentifier::Position(2),
+                AttributeIdentifier::None,
+                AttributeIdentifier::Tone,
+            ]
+        );
+        assert_eq!(
+            save.calibrations,
+            [Calibration {
+                offset: 0.1,
+                scaling: 1.2,
+            }; 4]
+        );
+        assert!(save.options.quantize_6);
+        assert!(save.options.rewind);
+        assert_eq!(save.options.delay_range, DelayRange::Short);
+        assert_eq!(
+            save.configuration.rewind_speed,
+            [(3, 2), (1, 0), (2, 2), (0, 3)]
+        );
+        assert_eq!(save.configuration.default_display_page, DisplayPage::Heads);
+        assert_eq!(save.configuration.position_reset_mapping, Some(2));
+        assert_eq!(save.configuration.pause_resume_mapping, Some(3));
+        assert_eq!(save.configuration.tap_interval_denominator, 8);
+        assert_eq!(save.tapped_tempo, Some(1.5));
+    }
+
+    #[test]
+    fn given_v5_store_bytes_it_defaults_fields_introduced_later() {
+        let bytes = v5_store_bytes(v5_save(), 5, false);
+
+        let save = Store::from_bytes(bytes).ok().unwrap().save();
+
+        let default = Save::default();
+        assert_eq!(save.options.pan_law, default.options.pan_law);
+        assert_eq!(save.options.speed_gear, default.options.speed_gear);
+        assert_eq!(
+            save.options.bipolar_feedback,
+            default.options.bipolar_feedback
+        );
+        assert_eq!(
+            save.options.master_feedback,
+            default.options.master_feedback
+        );
+        assert_eq!(
+            save.configuration.capture_mapping,
+            default.configuration.capture_mapping
+        );
+        assert_eq!(
+            save.configuration.output_level,
+            default.configuration.output_level
+        );
+        assert_eq!(save.configuration.tape_age, default.configuration.tape_age);
+        assert_eq!(save.configuration.spread, default.configuration.spread);
+        assert_eq!(save.configuration.trails, default.configuration.trails);
+        assert_eq!(save.configuration.tap_mode, default.configuration.tap_mode);
+        assert_eq!(
+            save.configuration.impulse_threshold,
+            default.configuration.impulse_threshold
+        );
+        assert_eq!(
+            save.configuration.cv_interpolation,
+            default.configuration.cv_interpolation
+        );
+        assert_eq!(
+            save.configuration.tap_mapping,
+            default.configuration.tap_mapping
+        );
+        assert_eq!(
+            save.configuration.position_crossfade,
+            default.configuration.position_crossfade
+        );
+        assert_eq!(
+            save.configuration.click_level,
+            default.configuration.click_level
+        );
+        assert_eq!(
+            save.configuration.speed_smoothing,
+            default.configuration.speed_smoothing
+        );
+        assert_eq!(
+            save.configuration.rewind_beat_lock,
+            default.configuration.rewind_beat_lock
+        );
+        assert_eq!(
+            save.configuration.oversampled_read,
+            default.configuration.oversampled_read
+        );
+        assert_eq!(
+            save.configuration.unplugged_hold,
+            default.configuration.unplugged_hold
+        );
+        assert_eq!(save.scene, None);
+    }
+
+    #[test]
+    fn given_migrated_save_when_stored_again_it_has_the_current_version() {
+        let bytes = v5_store_bytes(v5_save(), 5, false);
+        let migrated = Store::from_bytes(bytes).ok().unwrap().save();
+
+        let save = Save {
+            version: VERSION,
+            ..migrated
+        };
+        let bytes = Store::new(save, 11).to_bytes();
+        let store = Store::from_bytes(bytes).ok().unwrap();
+
+        assert!(store.save() == save);
+    }
+
+    #[test]
+    fn detect_invalid_crc_while_migrating_from_bytes() {
+        let bytes = v5_store_bytes(v5_save(), 5, true);
+        assert!(Store::from_bytes(bytes).is_err());
+    }
+
+    #[test]
+    fn detect_unknown_version_while_initializing_from_bytes() {
+        let bytes = v5_store_bytes(v5_save(), 4, false);
+        assert!(Store::from_bytes(bytes).is_err());
+    }
+
+    #[test]
+    fn store_fits_into_one_page() {
+        let page_size = 256;
+        let store_size = Store::SIZE;
+        assert!(store_size < page_size);
+    }
+}
@@ -0,0 +1,295 @@
+//! Layout of the save as it was stored by the version 5.
+//!
+//! These structures are frozen copies of the ones stored back then. They must
+//! not be changed, otherwise the old saves would not be recognized anymore.
+//! That holds even for those that still match their live counterparts, the
+//! live ones are free to evolve.
+
+use core::mem;
+
+use crate::cache::calibration::Calibration;
+use crate::cache::configuration::DisplayPage;
+use crate::cache::mapping::AttributeIdentifier;
+use crate::cache::{Configuration, DelayRange, FilterPlacement, Options, WowFlutterPlacement};
+
+use super::Save;
+
+pub const VERSION: u8 = 5;
+
+#[derive(Debug, Default, Clone, Copy, PartialEq)]
+pub struct SaveV5 {
+    pub mapping: MappingV5,
+    pub calibrations: CalibrationsV5,
+    pub options: OptionsV5,
+    pub configuration: ConfigurationV5,
+    pub tapped_tempo: TappedTempoV5,
+}
+
+pub type MappingV5 = [AttributeIdentifierV5; 4];
+
+#[derive(Debug, Default, Clone, Copy, PartialEq, Eq)]
+pub enum AttributeIdentifierV5 {
+    PreAmp,
+    Drive,
+    Bias,
+    DryWet,
+    WowFlut,
+    Speed,
+    Tone,
+    Position(usize),
+    Volume(usize),
+    Feedback(usize),
+    Pan(usize),
+    #[default]
+    None,
+}
+
+pub type CalibrationsV5 = [CalibrationV5; 4];
+
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub struct CalibrationV5 {
+    pub offset: f32,
+    pub scaling: f32,
+}
+
+pub type TappedTempoV5 = Option<f32>;
+
+#[allow(clippy::struct_excessive_bools)]
+#[derive(Debug, Default, Clone, Copy, PartialEq)]
+pub struct OptionsV5 {
+    pub quantize_8: bool,
+    pub quantize_6: bool,
+    pub delay_range: DelayRangeV5,
+    pub rewind: bool,
+    pub enable_oscillator: bool,
+    pub random_impulse: bool,
+    pub filter_placement: FilterPlacementV5,
+    pub wow_flutter_placement: WowFlutterPlacementV5,
+    pub unlimited: bool,
+}
+
+#[derive(Debug, Default, Clone, Copy, PartialEq, Eq)]
+pub enum DelayRangeV5 {
+    #[default]
+    Long,
+    Short,
+    Audio,
+}
+
+#[derive(Debug, Default, Clone, Copy, PartialEq, Eq)]
+pub enum FilterPlacementV5 {
+    Input,
+    Feedback,
+    #[default]
+    Both,
+}
+
+#[derive(Debug, Default, Clone, Copy, PartialEq, Eq)]
+pub enum WowFlutterPlacementV5 {
+    Input,
+    Read,
+    #[default]
+    Both,
+}
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub struct ConfigurationV5 {
+    pub rewind_speed: [(usize, usize); 4],
+    pub default_display_page: DisplayPageV5,
+    pub position_reset_mapping: Option<usize>,
+    pub pause_resume_mapping: Option<usize>,
+    pub tap_interval_denominator: usize,
+}
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum DisplayPageV5 {
+    Heads,
+    Position,
+}
+
+#[derive(Clone, Copy)]
+pub struct StoreV5 {
+    pub version: u32,
+    pub token: u16,
+    pub save_raw: [u8; SaveV5::SIZE],
+    pub crc: u16,
+}
+
+impl StoreV5 {
+    pub const SIZE: usize = mem::size_of::<Self>();
+
+    pub fn from_bytes(bytes: [u8; Self::SIZE]) -> Self {
+        unsafe { mem::transmute(bytes) }
+    }
+
+    #[cfg(test)]
+    pub fn to_bytes(self) -> [u8; Self::SIZE] {
+        unsafe { mem::transmute(self) }
+    }
+}
+
+impl SaveV5 {
+    pub const SIZE: usize = mem::size_of::<Self>();
+
+    pub fn from_bytes(bytes: [u8; Self::SIZE]) -> Self {
+        unsafe { mem::transmute(bytes) }
+    }
+
+    // NOTE: The layout holds padding. To keep it initialized, the save is
+    // written field by field into zeroed memory instead of being moved
+    // there as a whole.
+    #[cfg(test)]
+    pub fn to_bytes(self) -> [u8; Self::SIZE] {
+        use core::mem::MaybeUninit;
+        use core::ptr::addr_of_mut;
+
+        let mut save = MaybeUninit::<Self>::zeroed();
+        let save_ptr = save.as_mut_ptr();
+        unsafe {
+            for (i, attribute) in self.mapping.into_iter().enumerate() {
+                addr_of_mut!((*save_ptr).mapping[i]).write(attribute);
+            }
+            addr_of_mut!((*save_ptr).calibrations).write(self.calibrations);
+            addr_of_mut!((*save_ptr).options).write(self.options);
+            let configuration = addr_of_mut!((*save_ptr).configuration);
+            addr_of_mut!((*configuration).rewind_speed).write(self.configuration.rewind_speed);
+            addr_of_mut!((*configuration).default_display_page)
+                .write(self.configuration.default_display_page);
+            addr_of_mut!((*configuration).position_reset_mapping)
+                .write(self.configuration.position_reset_mapping);
+            addr_of_mut!((*configuration).pause_resume_mapping)
+                .write(self.configuration.pause_resume_mapping);
+            addr_of_mut!((*configuration).tap_interval_denominator)
+                .write(self.configuration.tap_interval_denominator);
+            addr_of_mut!((*save_ptr).tapped_tempo).write(self.tapped_tempo);
+            mem::transmute(save)
+        }
+    }
+}
+
+impl Default for CalibrationV5 {
+    fn default() -> Self {
+        Self {
+            offset: 0.0,
+            scaling: 1.0,
+        }
+    }
+}
+
+impl Default for ConfigurationV5 {
+    fn default() -> Self {
+        Self {
+            rewind_speed: [(0, 0), (1, 1), (2, 2), (3, 3)],
+            default_display_page: DisplayPageV5::Position,
+            position_reset_mapping: None,
+            pause_resume_mapping: None,
+            tap_interval_denominator: 1,
+        }
+    }
+}
+
+// NOTE: Fields introduced after the version 5 are left on their defaults.
+impl From<SaveV5> for Save {
+    fn from(other: SaveV5) -> Self {
+        Self {
+            version: VERSION,
+            mapping: other.mapping.map(Into::into),
+            calibrations: other.calibrations.map(Into::into),
+            options: other.options.into(),
+            configuration: other.configuration.into(),
+            tapped_tempo: other.tapped_tempo,
+            ..Self::default()
+        }
+    }
+}
+
+impl From<OptionsV5> for Options {
+    fn from(other: OptionsV5) -> Self {
+        Self {
+            quantize_8: other.quantize_8,
+            quantize_6: other.quantize_6,
+            delay_range: other.delay_range.into(),
+            rewind: other.rewind,
+            enable_oscillator: other.enable_oscillator,
+            random_impulse: other.random_impulse,
+            filter_placement: other.filter_placement.into(),
+            wow_flutter_placement: other.wow_flutter_placement.into(),
+            unlimited: other.unlimited,
+            ..Self::default()
+        }
+    }
+}
+
+impl From<AttributeIdentifierV5> for AttributeIdentifier {
+    fn from(other: AttributeIdentifierV5) -> Self {
+        match other {
+            AttributeIdentifierV5::PreAmp => Self::PreAmp,
+            AttributeIdentifierV5::Drive => Self::Drive,
+            AttributeIdentifierV5::Bias => Self::Bias,
+            AttributeIdentifierV5::DryWet => Self::DryWet,
+            AttributeIdentifierV5::WowFlut => Self::WowFlut,
+            AttributeIdentifierV5::Speed => Self::Speed,
+            AttributeIdentifierV5::Tone => Self::Tone,
+            AttributeIdentifierV5::Position(i) => Self::Position(i),
+            AttributeIdentifierV5::Volume(i) => Self::Volume(i),
+            AttributeIdentifierV5::Feedback(i) => Self::Feedback(i),
+            AttributeIdentifierV5::Pan(i) => Self::Pan(i),
+            AttributeIdentifierV5::None => Self::None,
+        }
+    }
+}
+
+impl From<CalibrationV5> for Calibration {
+    fn from(other: CalibrationV5) -> Self {
+        Self {
+            offset: other.offset,
+            scaling: other.scaling,
+        }
+    }
+}
+
+impl From<DelayRangeV5> for DelayRange {
+    fn from(other: DelayRangeV5) -> Self {
+        match other {
+            DelayRangeV5::Long => Self::Long,
+            DelayRangeV5::Short => Self::Short,
+            DelayRangeV5::Audio => Self::Audio,
+        }
+    }
+}
+
+impl From<FilterPlacementV5> for FilterPlacement {
+    fn from(other: FilterPlacementV5) -> Self {
+        match other {
+            FilterPlacementV5::Input => Self::Input,
+            FilterPlacementV5::Feedback => Self::Feedback,
+            FilterPlacementV5::Both => Self::Both,
+        }
+    }
+}
+
+impl From<WowFlutterPlacementV5> for WowFlutterPlacement {
+    fn from(other: WowFlutterPlacementV5) -> Self {
+        match other {
+            WowFlutterPlacementV5::Input => Self::Input,
+            WowFlutterPlacementV5::Read => Self::Read,
+            WowFlutterPlacementV5::Both => Self::Both,
+        }
+    }
+}
+
+impl From<ConfigurationV5> for Configuration {
+    fn from(other: ConfigurationV5) -> Self {
+        Self {
+            rewind_speed: other.rewind_speed,
+            default_display_page: match other.default_display_page {
+                DisplayPageV5::Heads => DisplayPage::Heads,
+                DisplayPageV5::Position => DisplayPage::Position,
+            },
+            position_reset_mapping: other.position_reset_mapping.map(|i| i as u8),
+            pause_resume_mapping: other.pause_resume_mapping.map(|i| i as u8),
+            tap_interval_denominator: other.tap_interval_denominator as u8,
+            ..Self::default()
+        }
+    }
+}
@@ -9,10 +9,10 @@ use kaseta_dsp::processor::{Attributes as DSPAttributes, Reaction as DSPReaction
 
 use crate::action::{ControlAction, Queue};
 use crate::cache::calibration::Calibration;
-use crate::cache::configuration::DisplayPage;
+use crate::cache::configuration::{DisplayPage, FeedbackTopology, InvalidConfiguration, TapMode};
 use crate::cache::display::{AttributeScreen, ConfigurationScreen, DialogScreen, Screen};
 use crate::cache::mapping::AttributeIdentifier;
-use crate::cache::{Cache, Configuration};
+use crate::cache::{Cache, Configuration, Scene, TempoNudge};
 use crate::input::pot::Pot;
 use crate::input::snapshot::Snapshot as InputSnapshot;
 use crate::input::store::Store as Input;
@@ -20,6 +20,17 @@ use crate::log;
 use crate::output::DesiredOutput;
 use crate::save::Save;
 
+// Calibration gets cancelled after a minute without any button clicks, so the
+// module does not get stuck there when the user walks away.
+const CALIBRATION_TIMEOUT: u32 = 60_000;
+
+// Presses shorter than this are taken as taps, longer ones freeze the delay.
+const FREEZE_HOLD: u32 = 300;
+
+// Holds reaching this are no longer a freeze, they toggle the bypass once
+// released, or go on to reset the buffer and open the configuration.
+const BYPASS_HOLD: u32 = 1_000;
+
 /// The main store of peripheral abstraction and module configuration.
 ///
 /// This struct is the central piece of the control module. It takes
@@ -29,10 +40,17 @@ use crate::save::Save;
 #[derive(Debug)]
 #[cfg_attr(feature = "defmt", derive(defmt::Format))]
 pub struct Store {
-    state: State,
+    pub(crate) state: State,
     pub(crate) input: Input,
     pub(crate) queue: Queue,
     pub(crate) cache: Cache,
+    calibration_idle: u32,
+    // NOTE: The last save confirmed to be persisted. New saves are emitted
+    // only when they differ from it, to spare the flash.
+    last_save: Save,
+    // NOTE: A save was handed over, but it was not confirmed yet. It keeps
+    // being emitted with each input snapshot until it is.
+    save_pending: bool,
 }
 
 /// The current state of the control state machine.
@@ -72,6 +90,26 @@ pub(crate) struct StateMapping {
 #[cfg_attr(feature = "defmt", derive(defmt::Format))]
 pub(crate) struct StateConfiguring {
     draft: Configuration,
+    // NOTE: Set while the button was pressed within the menu and no pot was
+    // turned since, so its release leaves the menu. The press opening the
+    // menu does not count.
+    exit_armed: bool,
+}
+
+/// Coarse view of the current state, exposed to the caller.
+///
+/// It is meant for simulators and other software frontends that need to
+/// follow what the module is waiting for, without access to its internals.
+#[derive(Debug, PartialEq, Eq, Clone, Copy)]
+#[cfg_attr(feature = "defmt", derive(defmt::Format))]
+pub enum Status {
+    Normal,
+    /// Waiting for a pot to be turned to map the given control input.
+    Mapping(usize),
+    /// Waiting for octaves to be sent to the given control input.
+    Calibrating(usize),
+    /// The configuration menu is open.
+    Configuring,
 }
 
 /// Response of control store after processing new input snapshot.
@@ -89,11 +127,15 @@ pub struct ApplyInputSnapshotResult {
 impl Store {
     #[must_use]
     pub fn new() -> Self {
+        let cache = Cache::default();
         Self {
             input: Input::default(),
             state: State::default(),
             queue: Queue::default(),
-            cache: Cache::default(),
+            last_save: cache.save(),
+            save_pending: false,
+            cache,
+            calibration_idle: 0,
         }
     }
 
@@ -104,7 +146,17 @@ impl Store {
         self.input.update(snapshot);
     }
 
+    /// DSP attributes reflecting the restored save, to be set on the
+    /// processor before it warms up.
+    pub fn dsp_attributes(&mut self) -> DSPAttributes {
+        self.cache.build_dsp_attributes()
+    }
+
     pub fn apply_input_snapshot(&mut self, snapshot: InputSnapshot) -> ApplyInputSnapshotResult {
+        let unplugged_value = self.cache.configuration.unplugged_value();
+        for control in &mut self.input.control {
+            control.unplugged_value = unplugged_value;
+        }
         self.input.update(snapshot);
         let save = self.converge_internal_state();
         let dsp_attributes = self.cache.build_dsp_attributes();
@@ -115,18 +167,36 @@ impl Store {
     }
 
     pub fn apply_dsp_reaction(&mut self, dsp_reaction: DSPReaction) {
+        self.cache.input_meter.update(dsp_reaction.input_peak);
+
         if dsp_reaction.delay_impulse {
             self.cache.impulse_trigger.trigger();
             self.cache.impulse_led.trigger();
         }
 
+        if let Some(head) = self.cache.configuration.gate_head {
+            self.cache.impulse_gate = dsp_reaction.head_gates[head as usize];
+        }
+
         if dsp_reaction.hysteresis_clipping {
             self.cache.display.set_clipping();
         }
 
-        let default_display_position = self.cache.configuration.default_display_page.is_position();
+        if dsp_reaction.mono_incompatible {
+            self.cache.display.set_mono_incompatible();
+        }
+
+        if dsp_reaction.runaway_feedback {
+            self.cache.display.set_runaway_feedback();
+        }
+
+        // NOTE: Other pages than position and heads keep their own fallback
+        // screen, see `reconcile_heads` and `reconcile_speed`.
+        let default_display_page = self.cache.configuration.default_display_page;
         let in_audio_range = self.cache.options.delay_range.is_audio();
-        if default_display_position || !in_audio_range {
+        if default_display_page.is_position()
+            || (default_display_page.is_heads() && !in_audio_range)
+        {
             self.cache
                 .display
                 .set_fallback_attribute(AttributeScreen::Position(dsp_reaction.new_position));
@@ -139,11 +209,118 @@ impl Store {
         }
     }
 
+    #[must_use]
+    pub fn status(&self) -> Status {
+        match self.state {
+            State::Normal => Status::Normal,
+            State::Mapping(StateMapping { input }) => Status::Mapping(input),
+            State::Calibrating(StateCalibrating { input, .. }) => Status::Calibrating(input),
+            State::Configuring(_) => Status::Configuring,
+        }
+    }
+
+    /// Pairs of control input indices and attributes they are mapped to.
+    ///
+    /// Controls that are not mapped are paired with `AttributeIdentifier::None`.
+    #[must_use]
+    pub fn mappings(&self) -> [(usize, AttributeIdentifier); 4] {
+        self.cache.mappings()
+    }
+
+    /// Index of the control input mapped to the given attribute, if any.
+    #[must_use]
+    pub fn control_for(&self, attribute: AttributeIdentifier) -> Option<usize> {
+        self.cache.control_for(attribute)
+    }
+
+    /// Position within the beat of the clock driving speed, from 0.0 to 1.0.
+    ///
+    /// Returns `None` when no control is mapped to speed or no clock was
+    /// detected on it yet.
+    #[must_use]
+    pub fn clock_phase(&self) -> Option<f32> {
+        let control_index = self.control_index_for_attribute(AttributeIdentifier::Speed)?;
+        self.cache.clock_detectors[control_index].phase()
+    }
+
+    /// Voltage on the given control input after its calibration is applied.
+    ///
+    /// Useful to confirm the tracking of a calibrated input. Returns `None`
+    /// when nothing is plugged into the input.
+    #[must_use]
+    pub fn calibrated_control_value(&self, control: usize) -> Option<f32> {
+        let input = &self.input.control[control];
+        if input.is_plugged || input.is_holding() {
+            Some(self.cache.calibrations[control].apply(input.value_raw()))
+        } else {
+            None
+        }
+    }
+
+    /// Capture current head attributes into a save, so they get recalled
+    /// together with the rest of the configuration.
+    ///
+    /// The save must be confirmed through `confirm_save` once it is accepted
+    /// for storing, otherwise it is emitted again by `apply_input_snapshot`.
+    pub fn save_scene(&mut self) -> Save {
+        self.cache.scene = Some(Scene::capture(&self.cache.attributes.head));
+        self.save_pending = true;
+        self.cache.save()
+    }
+
+    /// Confirm that the save returned by `apply_input_snapshot`,
+    /// `save_scene` or `apply_configuration` was accepted for storing.
+    /// Until then, it is emitted again with each input snapshot.
+    pub fn confirm_save(&mut self, save: Save) {
+        self.last_save = save;
+        self.save_pending = self.cache.save() != save;
+    }
+
+    /// Snapshot of the current configuration, e.g. to be shown in an editor.
+    pub fn configuration(&self) -> Configuration {
+        self.cache.configuration
+    }
+
+    /// Replace the whole configuration at once.
+    ///
+    /// The configuration is validated first and left untouched if any of
+    /// its parameters is out of range. On success, the save to be persisted
+    /// is returned. Like the one of `save_scene`, it must be confirmed
+    /// through `confirm_save`.
+    ///
+    /// # Errors
+    ///
+    /// Returns `InvalidConfiguration` when any of the indices is out of
+    /// range.
+    pub fn apply_configuration(
+        &mut self,
+        configuration: Configuration,
+    ) -> Result<Save, InvalidConfiguration> {
+        configuration.validate()?;
+        self.cache.configuration = configuration;
+        self.save_pending = true;
+        Ok(self.cache.save())
+    }
+
     pub fn tick(&mut self) -> DesiredOutput {
         self.sustain_alt_menu();
+        if self
+            .cache
+            .configuration
+            .default_display_page
+            .is_input_level()
+        {
+            self.set_screen_for_input_level();
+        }
         self.cache.tick()
     }
 
+    fn set_screen_for_input_level(&mut self) {
+        self.cache
+            .display
+            .set_fallback_attribute(AttributeScreen::InputLevel(self.cache.input_meter.bars()));
+    }
+
     fn sustain_alt_menu(&mut self) {
         if self.input.button.pressed {
             if let Some(Screen::AltAttribute(age, menu)) = self.cache.display.prioritized[2] {
@@ -158,11 +335,14 @@ impl Store {
         let mut needs_save = false;
 
         self.reconcile_controls(&mut needs_save);
+        self.detect_freeze_hold();
 
         match self.state {
             State::Normal => {
                 self.detect_tapped_tempo(&mut needs_save);
+                self.detect_tapped_rhythm();
                 self.detect_reset_buffer_request();
+                self.detect_bypass_toggle();
                 self.converge_from_normal_state();
             }
             State::Configuring(configuring) => {
@@ -179,13 +359,26 @@ impl Store {
         self.reconcile_detectors();
         self.reconcile_attributes(&mut needs_save);
 
-        if needs_save {
-            Some(self.cache.save())
+        if needs_save || self.save_pending {
+            self.save_if_changed()
         } else {
             None
         }
     }
 
+    // NOTE: Changes flagged for save may cancel out before they are
+    // evaluated, e.g. a pot jittering within a single step of a selection.
+    fn save_if_changed(&mut self) -> Option<Save> {
+        let save = self.cache.save();
+        if save == self.last_save {
+            self.save_pending = false;
+            None
+        } else {
+            self.save_pending = true;
+            Some(save)
+        }
+    }
+
     fn reconcile_controls(&mut self, needs_save: &mut bool) {
         let mut controls_to_unmap = FnvIndexSet::<_, 4>::new();
         self.insert_non_plugged_controls(&mut controls_to_unmap);
@@ -198,8 +391,9 @@ impl Store {
     }
 
     fn insert_non_plugged_controls(&self, controls: &mut FnvIndexSet<usize, 4>) {
+        // NOTE: Inputs holding their last value stay mapped.
         for (i, cv) in self.input.control.iter().enumerate() {
-            if !cv.is_plugged {
+            if !cv.is_plugged && !cv.is_holding() {
                 // NOTE: This is safe since the number of controls is equal to the
                 // size of the Set.
                 let _: Result<_, _> = controls.insert(i);
@@ -209,10 +403,16 @@ impl Store {
 
     fn insert_reserved_controls(&self, controls: &mut FnvIndexSet<usize, 4>) {
         if let Some(index) = self.cache.configuration.position_reset_mapping {
-            let _: Result<_, _> = controls.insert(index);
+            let _: Result<_, _> = controls.insert(index as usize);
         }
         if let Some(index) = self.cache.configuration.pause_resume_mapping {
-            let _: Result<_, _> = controls.insert(index);
+            let _: Result<_, _> = controls.insert(index as usize);
+        }
+        if let Some(index) = self.cache.configuration.capture_mapping {
+            let _: Result<_, _> = controls.insert(index as usize);
+        }
+        if let Some(index) = self.cache.configuration.tap_mapping {
+            let _: Result<_, _> = controls.insert(index as usize);
         }
     }
 
@@ -242,12 +442,22 @@ impl Store {
     fn enqueue_controls(&mut self, plugged_controls: &Vec<usize, 4>) {
         for i in plugged_controls {
             if let Some(index) = self.cache.configuration.position_reset_mapping {
-                if index == *i {
+                if index as usize == *i {
                     continue;
                 }
             }
             if let Some(index) = self.cache.configuration.pause_resume_mapping {
-                if index == *i {
+                if index as usize == *i {
+                    continue;
+                }
+            }
+            if let Some(index) = self.cache.configuration.capture_mapping {
+                if index as usize == *i {
+                    continue;
+                }
+            }
+            if let Some(index) = self.cache.configuration.tap_mapping {
+                if index as usize == *i {
                     continue;
                 }
             }
@@ -272,6 +482,7 @@ impl Store {
                 ControlAction::Calibrate(i) => {
                     log::info!("Entering calibration menu for control={:?}", i + 1);
                     self.state = State::calibrating_octave_1(i);
+                    self.calibration_idle = 0;
                     self.cache
                         .display
                         .set_dialog(DialogScreen::calibration_1(i));
@@ -288,19 +499,78 @@ impl Store {
     }
 
     fn detect_tapped_tempo(&mut self, needs_save: &mut bool) {
-        if let Some(detected_tempo) = self.cache.tap_detector.detected_tempo() {
+        if let Some(detected_tempo) = self.cache.tap_detector.take_detected_tempo() {
             let tapped_tempo = detected_tempo as f32 / 1000.0;
+            self.cache.tempo_nudge = None;
             if self.cache.tapped_tempo != Some(tapped_tempo) {
                 log::info!("Setting tapped tempo={:?}", tapped_tempo);
                 *needs_save = true;
                 self.cache.tapped_tempo = Some(tapped_tempo);
             }
         }
-        if self.cache.tapped_tempo.is_some() && self.input.speed.activation_movement() {
-            log::info!("Resetting tapped tempo");
+
+        // NOTE: The nudge is kept until the pot settles after the button
+        // gets released, so it does not reset the tempo right away.
+        let speed_moved = self.input.speed.activation_movement();
+        if let Some(tapped_tempo) = self.cache.tapped_tempo.filter(|_| speed_moved) {
+            if self.input.button.pressed {
+                self.nudge_tapped_tempo(tapped_tempo, needs_save);
+            } else if self.cache.tempo_nudge.is_none() {
+                log::info!("Resetting tapped tempo");
+                *needs_save = true;
+                self.cache.tap_detector.reset();
+                self.cache.tapped_tempo = None;
+            }
+        } else if !self.input.button.pressed {
+            self.cache.tempo_nudge = None;
+        }
+    }
+
+    // NOTE: Turning the pot clockwise speeds the tempo up, the same way it
+    // does with the speed of the tape.
+    fn nudge_tapped_tempo(&mut self, tapped_tempo: f32, needs_save: &mut bool) {
+        const STEP: f32 = 0.005;
+        const POT_TRAVEL_PER_STEP: f32 = 0.02;
+
+        let pot = self.input.speed.value();
+        let nudge = *self.cache.tempo_nudge.get_or_insert(TempoNudge {
+            pot,
+            tempo: tapped_tempo,
+        });
+        let steps = libm::roundf((pot - nudge.pot) / POT_TRAVEL_PER_STEP);
+        let nudged_tempo = nudge.tempo * (1.0 - steps * STEP);
+        if nudged_tempo != tapped_tempo {
+            log::info!("Nudging tapped tempo={:?}", nudged_tempo);
             *needs_save = true;
-            self.cache.tap_detector.reset();
-            self.cache.tapped_tempo = None;
+            self.cache.tapped_tempo = Some(nudged_tempo);
+        }
+    }
+
+    // NOTE: The rhythm starts with the first tap, so a sound played along
+    // with it gets echoed by each head on its tap. Heads cannot be placed
+    // beyond the length of the delay, so the rhythm is abandoned then. A
+    // press is known to be a tap only once it is released before freezing
+    // the delay, its onset is still taken from the moment it went down.
+    fn detect_tapped_rhythm(&mut self) {
+        if !self.cache.configuration.tap_mode.is_rhythm() {
+            return;
+        }
+        let length = self.cache.attributes.speed;
+        if self.input.button.clicked {
+            self.cache.rhythm_tap.press();
+        }
+        let released_after = self.input.button.released_after;
+        if released_after > 0 && self.input.latest_pot_activity() > released_after {
+            if let Some((head, onset)) = self.cache.rhythm_tap.release(released_after) {
+                let position = (onset as f32 / 1000.0 / length).clamp(0.0, 1.0);
+                log::info!("Placing head={:?} position={:?}", head + 1, position);
+                self.cache.tapped_positions[head] = Some(position);
+            }
+        }
+        if let Some(elapsed) = self.cache.rhythm_tap.elapsed() {
+            if elapsed as f32 / 1000.0 > length {
+                self.cache.rhythm_tap.reset();
+            }
         }
     }
 
@@ -316,6 +586,45 @@ impl Store {
         }
     }
 
+    // NOTE: The freeze lasts only while the button is held. Turning a pot
+    // while holding the button opens the alt menu instead, so it releases
+    // the freeze. Holding past the freeze moves on to the longer gestures,
+    // releasing it too. A press that engaged the freeze is consumed by it,
+    // it breaks a sequence of taps and it does not place a head of a rhythm.
+    fn detect_freeze_hold(&mut self) {
+        let held = self.input.button.held;
+        let frozen = matches!(self.state, State::Normal)
+            && (FREEZE_HOLD..BYPASS_HOLD).contains(&held)
+            && self.input.latest_pot_activity() > held;
+        if frozen && !self.cache.attributes.frozen_delay {
+            log::info!("Freezing the delay");
+            self.cache.tap_detector.reset();
+            self.cache.rhythm_tap.cancel_press();
+        } else if !frozen && self.cache.attributes.frozen_delay {
+            log::info!("Releasing the delay");
+        }
+        self.cache.attributes.frozen_delay = frozen;
+    }
+
+    // NOTE: Shorter holds are taken as clicks or freezes and from 5 seconds
+    // on the buffer gets reset, the bypass is toggled by releasing in
+    // between.
+    fn detect_bypass_toggle(&mut self) {
+        let released_after = self.input.button.released_after;
+        let held_between_1_and_5_seconds = (BYPASS_HOLD..5_000).contains(&released_after)
+            && self.input.latest_pot_activity() > released_after;
+        if held_between_1_and_5_seconds {
+            self.cache.attributes.bypass = !self.cache.attributes.bypass;
+            if self.cache.attributes.bypass {
+                log::info!("Bypassing the processing");
+                self.cache.display.set_bypassed();
+            } else {
+                log::info!("Resuming the processing");
+                self.cache.display.reset_bypassed();
+            }
+        }
+    }
+
     fn button_is_held_for_very_long_without_pot_activity(&mut self) -> bool {
         self.input.button.held > 30_000 && self.input.latest_pot_activity() > self.input.button.held
     }
@@ -381,7 +690,13 @@ impl Store {
         if !self.input.control[input].is_plugged {
             self.cache.display.set_failure();
             self.state = State::Normal;
+        } else if self.calibration_idle >= CALIBRATION_TIMEOUT {
+            log::info!("Calibration of control={:?} timed out", input + 1);
+            self.cache.display.set_failure();
+            self.queue.remove_control(input);
+            self.state = State::Normal;
         } else if self.input.button.clicked {
+            self.calibration_idle = 0;
             match phase {
                 CalibrationPhase::Octave1 => {
                     let octave_1 = self.input.control[input].value();
@@ -393,6 +708,11 @@ impl Store {
                 CalibrationPhase::Octave2(octave_1) => {
                     let octave_2 = self.input.control[input].value();
                     if let Some(calibration) = Calibration::try_new(octave_1, octave_2) {
+                        log::info!(
+                            "Calibrated control={:?} with confidence={:?}",
+                            input + 1,
+                            calibration.confidence()
+                        );
                         *needs_save = true;
                         self.cache.calibrations[input] = calibration;
                     } else {
@@ -401,6 +721,8 @@ impl Store {
                     self.state = State::Normal;
                 }
             }
+        } else {
+            self.calibration_idle += 1;
         }
     }
 
@@ -409,7 +731,8 @@ impl Store {
         configuring: StateConfiguring,
         needs_save: &mut bool,
     ) {
-        if self.input.button.clicked {
+        let mut exit_armed = configuring.exit_armed || self.input.button.clicked;
+        if exit_armed && self.input.button.released_after > 0 {
             *needs_save = true;
             self.cache.configuration = configuring.draft;
             self.state = State::Normal;
@@ -419,15 +742,23 @@ impl Store {
                 self.cache
                     .display
                     .set_dialog(DialogScreen::Configuration(screen));
+                exit_armed &= !self.input.button.pressed;
             }
-            self.state = State::Configuring(StateConfiguring { draft });
+            self.state = State::Configuring(StateConfiguring { draft, exit_armed });
         }
     }
 
+    // NOTE: Holding the button while turning a pot reaches the alt layer of
+    // the menu, the same way it does outside of it. The menu is then left
+    // only with a click that does not turn any pot.
     fn updated_configuration_draft(
         &mut self,
         mut draft: Configuration,
     ) -> (Configuration, Option<ConfigurationScreen>) {
+        if self.input.button.pressed {
+            return self.updated_alt_configuration_draft(draft);
+        }
+
         if let Some(screen) =
             update_default_display_configuration(&mut draft, &mut self.input.speed)
         {
@@ -448,6 +779,114 @@ impl Store {
             return (draft, Some(screen));
         }
 
+        if let Some(screen) = update_capture_mapping(&mut draft, &mut self.input.head[2].pan) {
+            return (draft, Some(screen));
+        }
+
+        if let Some(screen) = update_capture_length(&mut draft, &mut self.input.head[3].pan) {
+            return (draft, Some(screen));
+        }
+
+        if let Some(screen) = update_capture_repeats(&mut draft, &mut self.input.head[3].volume) {
+            return (draft, Some(screen));
+        }
+
+        if let Some(screen) = update_multi_tap_pattern(&mut draft, &mut self.input.head[0].volume) {
+            return (draft, Some(screen));
+        }
+
+        if let Some(screen) = update_output_level(&mut draft, &mut self.input.head[1].volume) {
+            return (draft, Some(screen));
+        }
+
+        if let Some(screen) = update_feedback_topology(&mut draft, &mut self.input.head[2].volume) {
+            return (draft, Some(screen));
+        }
+
+        if let Some(screen) = update_gate_head(&mut draft, &mut self.input.pre_amp) {
+            return (draft, Some(screen));
+        }
+
+        if let Some(screen) = update_gate_width(&mut draft, &mut self.input.drive) {
+            return (draft, Some(screen));
+        }
+
+        if let Some(screen) = update_quantization_grid(&mut draft, &mut self.input.head[0].position)
+        {
+            return (draft, Some(screen));
+        }
+
+        if let Some(screen) = update_tape_age(&mut draft, &mut self.input.wow_flut) {
+            return (draft, Some(screen));
+        }
+
+        if let Some(screen) = update_spread(&mut draft, &mut self.input.bias) {
+            return (draft, Some(screen));
+        }
+
+        if let Some(screen) = update_trails(&mut draft, &mut self.input.dry_wet) {
+            return (draft, Some(screen));
+        }
+
+        if let Some(screen) = update_tap_mode(&mut draft, &mut self.input.head[1].position) {
+            return (draft, Some(screen));
+        }
+
+        if let Some(screen) = update_impulse_threshold(&mut draft, &mut self.input.head[2].position)
+        {
+            return (draft, Some(screen));
+        }
+
+        if let Some(screen) = update_cv_interpolation(&mut draft, &mut self.input.head[3].position)
+        {
+            return (draft, Some(screen));
+        }
+
+        for (i, head) in self.input.head.iter_mut().enumerate() {
+            if let Some(screen) = update_cv_attenuversion(&mut draft, &mut head.feedback, i) {
+                return (draft, Some(screen));
+            }
+        }
+
+        (draft, None)
+    }
+
+    fn updated_alt_configuration_draft(
+        &mut self,
+        mut draft: Configuration,
+    ) -> (Configuration, Option<ConfigurationScreen>) {
+        if let Some(screen) = update_speed_smoothing(&mut draft, &mut self.input.speed) {
+            return (draft, Some(screen));
+        }
+
+        if let Some(screen) =
+            update_position_crossfade(&mut draft, &mut self.input.head[0].position)
+        {
+            return (draft, Some(screen));
+        }
+
+        if let Some(screen) = update_rewind_beat_lock(&mut draft, &mut self.input.head[1].position)
+        {
+            return (draft, Some(screen));
+        }
+
+        if let Some(screen) = update_tap_mapping(&mut draft, &mut self.input.head[2].position) {
+            return (draft, Some(screen));
+        }
+
+        if let Some(screen) = update_oversampled_read(&mut draft, &mut self.input.head[3].position)
+        {
+            return (draft, Some(screen));
+        }
+
+        if let Some(screen) = update_click_level(&mut draft, &mut self.input.dry_wet) {
+            return (draft, Some(screen));
+        }
+
+        if let Some(screen) = update_unplugged_hold(&mut draft, &mut self.input.tone) {
+            return (draft, Some(screen));
+        }
+
         (draft, None)
     }
 
@@ -455,25 +894,16 @@ impl Store {
         &self,
         attribute: AttributeIdentifier,
     ) -> Option<f32> {
-        let i = self.control_index_for_attribute(attribute);
-        if let Some(i) = i {
-            let control = &self.input.control[i];
-            if control.is_plugged {
-                let calibration = self.cache.calibrations[i];
-                Some(calibration.apply(control.value_raw()))
-            } else {
-                None
-            }
-        } else {
-            None
-        }
+        let i = self.control_index_for_attribute(attribute)?;
+        let attenuversion = self.cache.configuration.cv_attenuversion(i);
+        self.calibrated_control_value(i).map(|x| x * attenuversion)
     }
 
     pub(crate) fn control_index_for_attribute(
         &self,
         attribute: AttributeIdentifier,
     ) -> Option<usize> {
-        self.cache.mapping.iter().position(|a| *a == attribute)
+        self.cache.control_for(attribute)
     }
 
     fn reconcile_attributes(&mut self, needs_save: &mut bool) {
@@ -481,12 +911,25 @@ impl Store {
         self.reconcile_hysteresis(needs_save);
         self.reconcile_wow_flutter(needs_save);
         self.reconcile_tone(needs_save);
+        self.reconcile_age();
         self.reconcile_speed(needs_save);
-        self.reconcile_heads();
+        self.reconcile_heads(needs_save);
     }
 
     fn reconcile_detectors(&mut self) {
-        if self.input.button.clicked {
+        // NOTE: Taps of a rhythm are handled separately, only in the normal
+        // state, so the click leaving a menu does not start one. The mapped
+        // control always taps the tempo. Taps of the button and the control
+        // merge into a single sequence, those arriving in the same cycle
+        // count as one.
+        let button_tapped =
+            self.input.button.clicked && !self.cache.configuration.tap_mode.is_rhythm();
+        let control_tapped = self
+            .cache
+            .configuration
+            .tap_mapping
+            .is_some_and(|i| self.input.control[i as usize].triggered());
+        if button_tapped || control_tapped {
             self.cache.tap_detector.trigger();
         }
 
@@ -502,14 +945,21 @@ impl Store {
 
         if let Some(position_reset_control_index) = self.cache.configuration.position_reset_mapping
         {
-            let control = &self.input.control[position_reset_control_index];
+            let control = &self.input.control[position_reset_control_index as usize];
             self.cache.requests.reset_position = control.triggered();
         } else {
             self.cache.requests.reset_position = false;
         }
 
+        if let Some(capture_control_index) = self.cache.configuration.capture_mapping {
+            let control = &self.input.control[capture_control_index as usize];
+            self.cache.requests.capture = control.triggered();
+        } else {
+            self.cache.requests.capture = false;
+        }
+
         if let Some(pause_resume_control_index) = self.cache.configuration.pause_resume_mapping {
-            let control = &self.input.control[pause_resume_control_index];
+            let control = &self.input.control[pause_resume_control_index as usize];
             if control.triggered() {
                 self.cache.attributes.paused_delay = !self.cache.attributes.paused_delay;
                 if self.cache.attributes.paused_delay
@@ -539,19 +989,23 @@ fn update_default_display_configuration(
         return None;
     }
 
-    if pot.value() < 0.5 {
-        draft.default_display_page = DisplayPage::Position;
-        Some(ConfigurationScreen::DefaultScreen(0))
-    } else {
-        draft.default_display_page = DisplayPage::Heads;
-        Some(ConfigurationScreen::DefaultScreen(1))
-    }
+    let index = (pot.value() * 5.999) as usize;
+    draft.default_display_page = match index {
+        0 => DisplayPage::Position,
+        1 => DisplayPage::Speed,
+        2 => DisplayPage::Feedback,
+        3 => DisplayPage::Spread,
+        4 => DisplayPage::InputLevel,
+        5 => DisplayPage::Heads,
+        _ => unreachable!(),
+    };
+    Some(ConfigurationScreen::DefaultScreen(index))
 }
 
-fn update_position_reset_mapping(
-    draft: &mut Configuration,
-    pot: &mut Pot,
-) -> Option<ConfigurationScreen> {
+/// Select one of four items, or none with the pot at its lowest fifth.
+///
+/// Returns `None` when the pot was not moved.
+fn optional_selection(pot: &mut Pot) -> Option<Option<usize>> {
     let pot_active = pot.activation_movement();
     if !pot_active {
         return None;
@@ -559,35 +1013,29 @@ fn update_position_reset_mapping(
 
     let pot_value = pot.value();
     if pot_value < 1.0 / 5.0 {
-        draft.position_reset_mapping = None;
-        Some(ConfigurationScreen::ControlMapping(None))
+        Some(None)
     } else {
         let phase = (pot_value - 1.0 / 4.0) * (5.0 / 4.0);
-        let index = (phase * 3.999) as usize;
-        draft.position_reset_mapping = Some(index);
-        Some(ConfigurationScreen::ControlMapping(Some(index)))
+        Some(Some((phase * 3.999) as usize))
     }
 }
 
-fn update_pause_resume_mapping(
+fn update_position_reset_mapping(
     draft: &mut Configuration,
     pot: &mut Pot,
 ) -> Option<ConfigurationScreen> {
-    let pot_active = pot.activation_movement();
-    if !pot_active {
-        return None;
-    }
+    let selection = optional_selection(pot)?;
+    draft.position_reset_mapping = selection.map(|index| index as u8);
+    Some(ConfigurationScreen::ControlMapping(selection))
+}
 
-    let pot_value = pot.value();
-    if pot_value < 1.0 / 5.0 {
-        draft.pause_resume_mapping = None;
-        Some(ConfigurationScreen::ControlMapping(None))
-    } else {
-        let phase = (pot_value - 1.0 / 4.0) * (5.0 / 4.0);
-        let index = (phase * 3.999) as usize;
-        draft.pause_resume_mapping = Some(index);
-        Some(ConfigurationScreen::ControlMapping(Some(index)))
-    }
+fn update_pause_resume_mapping(
+    draft: &mut Configuration,
+    pot: &mut Pot,
+) -> Option<ConfigurationScreen> {
+    let selection = optional_selection(pot)?;
+    draft.pause_resume_mapping = selection.map(|index| index as u8);
+    Some(ConfigurationScreen::ControlMapping(selection))
 }
 
 fn update_tap_interval_denominator(
@@ -609,52 +1057,347 @@ fn update_tap_interval_denominator(
         _ => unreachable!(),
     };
     draft.tap_interval_denominator = denominator;
-    Some(ConfigurationScreen::TapIntervalDenominator(denominator))
+    Some(ConfigurationScreen::TapIntervalDenominator(usize::from(
+        denominator,
+    )))
 }
 
-impl From<Save> for Store {
-    fn from(save: Save) -> Self {
-        let mut store = Self::new();
-        store.cache.mapping = save.mapping;
-        store.cache.calibrations = save.calibrations;
-        store.cache.options = save.options;
-        store.cache.configuration = save.configuration;
-        store.cache.tapped_tempo = save.tapped_tempo;
-        store
-    }
+fn update_capture_mapping(draft: &mut Configuration, pot: &mut Pot) -> Option<ConfigurationScreen> {
+    let selection = optional_selection(pot)?;
+    draft.capture_mapping = selection.map(|index| index as u8);
+    Some(ConfigurationScreen::ControlMapping(selection))
 }
 
-impl State {
-    fn configuring_from_draft(draft: Configuration) -> Self {
-        State::Configuring(StateConfiguring { draft })
+fn update_capture_length(draft: &mut Configuration, pot: &mut Pot) -> Option<ConfigurationScreen> {
+    let pot_active = pot.activation_movement();
+    if !pot_active {
+        return None;
     }
 
-    fn calibrating_octave_1(input: usize) -> Self {
-        State::Calibrating(StateCalibrating {
-            input,
-            phase: CalibrationPhase::Octave1,
-        })
-    }
+    let index = (pot.value() * 3.999) as u8;
+    draft.capture_length = index;
+    Some(ConfigurationScreen::CaptureLength(index as usize))
+}
 
-    fn calibrating_octave_2(input: usize, octave_1: f32) -> Self {
-        State::Calibrating(StateCalibrating {
-            input,
-            phase: CalibrationPhase::Octave2(octave_1),
-        })
+fn update_capture_repeats(draft: &mut Configuration, pot: &mut Pot) -> Option<ConfigurationScreen> {
+    let pot_active = pot.activation_movement();
+    if !pot_active {
+        return None;
     }
 
-    fn mapping(input: usize) -> Self {
-        State::Mapping(StateMapping { input })
-    }
+    let index = (pot.value() * 3.999) as u8;
+    draft.capture_repeats = index;
+    Some(ConfigurationScreen::CaptureRepeats(index as usize))
 }
 
-impl Default for State {
-    fn default() -> Self {
-        Self::Normal
+fn update_multi_tap_pattern(
+    draft: &mut Configuration,
+    pot: &mut Pot,
+) -> Option<ConfigurationScreen> {
+    let pot_active = pot.activation_movement();
+    if !pot_active {
+        return None;
     }
+
+    let index = (pot.value() * 3.999) as u8;
+    draft.multi_tap_pattern = index;
+    Some(ConfigurationScreen::MultiTapPattern(index as usize))
 }
 
-#[cfg(test)]
+fn update_output_level(draft: &mut Configuration, pot: &mut Pot) -> Option<ConfigurationScreen> {
+    let pot_active = pot.activation_movement();
+    if !pot_active {
+        return None;
+    }
+
+    let index = (pot.value() * 7.999) as u8;
+    draft.output_level = index;
+    Some(ConfigurationScreen::OutputLevel(index as usize))
+}
+
+fn update_feedback_topology(
+    draft: &mut Configuration,
+    pot: &mut Pot,
+) -> Option<ConfigurationScreen> {
+    let pot_active = pot.activation_movement();
+    if !pot_active {
+        return None;
+    }
+
+    let index = (pot.value() * 1.999) as usize;
+    draft.feedback_topology = if index == 0 {
+        FeedbackTopology::Cumulative
+    } else {
+        FeedbackTopology::SinglePass
+    };
+    Some(ConfigurationScreen::FeedbackTopology(index))
+}
+
+fn update_gate_head(draft: &mut Configuration, pot: &mut Pot) -> Option<ConfigurationScreen> {
+    let selection = optional_selection(pot)?;
+    draft.gate_head = selection.map(|index| index as u8);
+    Some(ConfigurationScreen::GateHead(selection))
+}
+
+fn update_gate_width(draft: &mut Configuration, pot: &mut Pot) -> Option<ConfigurationScreen> {
+    let pot_active = pot.activation_movement();
+    if !pot_active {
+        return None;
+    }
+
+    let index = (pot.value() * 3.999) as u8;
+    draft.gate_width = index;
+    Some(ConfigurationScreen::GateWidth(index as usize))
+}
+
+fn update_quantization_grid(
+    draft: &mut Configuration,
+    pot: &mut Pot,
+) -> Option<ConfigurationScreen> {
+    let pot_active = pot.activation_movement();
+    if !pot_active {
+        return None;
+    }
+
+    let index = (pot.value() * 7.999) as u8;
+    draft.quantization_grid = index;
+    Some(ConfigurationScreen::QuantizationGrid(index as usize))
+}
+
+fn update_tape_age(draft: &mut Configuration, pot: &mut Pot) -> Option<ConfigurationScreen> {
+    let pot_active = pot.activation_movement();
+    if !pot_active {
+        return None;
+    }
+
+    let index = (pot.value() * 7.999) as u8;
+    draft.tape_age = index;
+    Some(ConfigurationScreen::TapeAge(index as usize))
+}
+
+fn update_spread(draft: &mut Configuration, pot: &mut Pot) -> Option<ConfigurationScreen> {
+    let pot_active = pot.activation_movement();
+    if !pot_active {
+        return None;
+    }
+
+    let index = (pot.value() * 4.999) as u8;
+    draft.spread = index;
+    Some(ConfigurationScreen::Spread(index as usize))
+}
+
+fn update_trails(draft: &mut Configuration, pot: &mut Pot) -> Option<ConfigurationScreen> {
+    let pot_active = pot.activation_movement();
+    if !pot_active {
+        return None;
+    }
+
+    let index = (pot.value() * 1.999) as usize;
+    draft.trails = index == 1;
+    Some(ConfigurationScreen::Trails(index))
+}
+
+fn update_tap_mode(draft: &mut Configuration, pot: &mut Pot) -> Option<ConfigurationScreen> {
+    let pot_active = pot.activation_movement();
+    if !pot_active {
+        return None;
+    }
+
+    let index = (pot.value() * 1.999) as usize;
+    draft.tap_mode = if index == 0 {
+        TapMode::Tempo
+    } else {
+        TapMode::Rhythm
+    };
+    Some(ConfigurationScreen::TapMode(index))
+}
+
+fn update_impulse_threshold(
+    draft: &mut Configuration,
+    pot: &mut Pot,
+) -> Option<ConfigurationScreen> {
+    let pot_active = pot.activation_movement();
+    if !pot_active {
+        return None;
+    }
+
+    let index = (pot.value() * 7.999) as u8;
+    draft.impulse_threshold = index;
+    Some(ConfigurationScreen::ImpulseThreshold(index as usize))
+}
+
+fn update_cv_interpolation(
+    draft: &mut Configuration,
+    pot: &mut Pot,
+) -> Option<ConfigurationScreen> {
+    let pot_active = pot.activation_movement();
+    if !pot_active {
+        return None;
+    }
+
+    let index = (pot.value() * 1.999) as usize;
+    draft.cv_interpolation = index == 1;
+    Some(ConfigurationScreen::CvInterpolation(index))
+}
+
+fn update_speed_smoothing(draft: &mut Configuration, pot: &mut Pot) -> Option<ConfigurationScreen> {
+    let pot_active = pot.activation_movement();
+    if !pot_active {
+        return None;
+    }
+
+    let index = (pot.value() * 3.999) as u8;
+    draft.speed_smoothing = index;
+    Some(ConfigurationScreen::SpeedSmoothing(index as usize))
+}
+
+fn update_position_crossfade(
+    draft: &mut Configuration,
+    pot: &mut Pot,
+) -> Option<ConfigurationScreen> {
+    let pot_active = pot.activation_movement();
+    if !pot_active {
+        return None;
+    }
+
+    let index = (pot.value() * 4.999) as u8;
+    draft.position_crossfade = index;
+    Some(ConfigurationScreen::PositionCrossfade(index as usize))
+}
+
+fn update_rewind_beat_lock(
+    draft: &mut Configuration,
+    pot: &mut Pot,
+) -> Option<ConfigurationScreen> {
+    let pot_active = pot.activation_movement();
+    if !pot_active {
+        return None;
+    }
+
+    let index = (pot.value() * 1.999) as usize;
+    draft.rewind_beat_lock = index == 1;
+    Some(ConfigurationScreen::RewindBeatLock(index))
+}
+
+// NOTE: Outside of the menu, the tap mapping is set by turning any position
+// pot while holding the button, see `reconcile_tap_mapping`.
+fn update_tap_mapping(draft: &mut Configuration, pot: &mut Pot) -> Option<ConfigurationScreen> {
+    let selection = optional_selection(pot)?;
+    draft.tap_mapping = selection.map(|index| index as u8);
+    Some(ConfigurationScreen::ControlMapping(selection))
+}
+
+fn update_oversampled_read(
+    draft: &mut Configuration,
+    pot: &mut Pot,
+) -> Option<ConfigurationScreen> {
+    let pot_active = pot.activation_movement();
+    if !pot_active {
+        return None;
+    }
+
+    let index = (pot.value() * 1.999) as usize;
+    draft.oversampled_read = index == 1;
+    Some(ConfigurationScreen::OversampledRead(index))
+}
+
+fn update_unplugged_hold(draft: &mut Configuration, pot: &mut Pot) -> Option<ConfigurationScreen> {
+    let pot_active = pot.activation_movement();
+    if !pot_active {
+        return None;
+    }
+
+    let index = (pot.value() * 1.999) as usize;
+    draft.unplugged_hold = index == 1;
+    Some(ConfigurationScreen::UnpluggedHold(index))
+}
+
+fn update_click_level(draft: &mut Configuration, pot: &mut Pot) -> Option<ConfigurationScreen> {
+    let pot_active = pot.activation_movement();
+    if !pot_active {
+        return None;
+    }
+
+    let index = (pot.value() * 3.999) as u8;
+    draft.click_level = index;
+    Some(ConfigurationScreen::ClickLevel(index as usize))
+}
+
+fn update_cv_attenuversion(
+    draft: &mut Configuration,
+    pot: &mut Pot,
+    control: usize,
+) -> Option<ConfigurationScreen> {
+    let pot_active = pot.activation_movement();
+    if !pot_active {
+        return None;
+    }
+
+    let index = (pot.value() * 8.999) as u8;
+    draft.cv_attenuversion[control] = index;
+    Some(ConfigurationScreen::CvAttenuversion(index as usize))
+}
+
+impl From<Save> for Store {
+    fn from(save: Save) -> Self {
+        let mut store = Self::new();
+        // NOTE: Saves of other builds may reference inputs or heads that do
+        // not exist. Those are dropped instead of panicking later on access.
+        store.cache.mapping = save.mapping.map(|attribute| {
+            if attribute.is_valid() {
+                attribute
+            } else {
+                AttributeIdentifier::None
+            }
+        });
+        store.cache.calibrations = save.calibrations;
+        store.cache.options = save.options;
+        store.cache.configuration = save.configuration.sanitized();
+        store.cache.tapped_tempo = save.tapped_tempo;
+        if let Some(scene) = save.scene {
+            store.cache.scene = Some(scene);
+            store.cache.attributes.head = scene.recall();
+            store.cache.recalled_heads = [true; 4];
+        }
+        store.last_save = store.cache.save();
+        store
+    }
+}
+
+impl State {
+    fn configuring_from_draft(draft: Configuration) -> Self {
+        State::Configuring(StateConfiguring {
+            draft,
+            exit_armed: false,
+        })
+    }
+
+    fn calibrating_octave_1(input: usize) -> Self {
+        State::Calibrating(StateCalibrating {
+            input,
+            phase: CalibrationPhase::Octave1,
+        })
+    }
+
+    fn calibrating_octave_2(input: usize, octave_1: f32) -> Self {
+        State::Calibrating(StateCalibrating {
+            input,
+            phase: CalibrationPhase::Octave2(octave_1),
+        })
+    }
+
+    fn mapping(input: usize) -> Self {
+        State::Mapping(StateMapping { input })
+    }
+}
+
+impl Default for State {
+    fn default() -> Self {
+        Self::Normal
+    }
+}
+
+#[cfg(test)]
 mod tests {
     #![allow(clippy::field_reassign_with_default)]
     #![allow(clippy::manual_assert)]
@@ -790,6 +1533,62 @@ mod tests {
         panic!("Trigger was not set down within given timeout");
     }
 
+    #[test]
+    fn when_dsp_reports_mono_incompatibility_it_should_show_warning_for_a_while() {
+        let mut store = Store::new();
+
+        store.apply_dsp_reaction(DSPReaction {
+            mono_incompatible: true,
+            ..DSPReaction::default()
+        });
+        store.apply_dsp_reaction(DSPReaction::default());
+
+        assert!(matches!(
+            store.cache.display.active_screen(),
+            Screen::MonoIncompatible(_)
+        ));
+
+        for _ in 0..1000 {
+            store.tick();
+            if !matches!(
+                store.cache.display.active_screen(),
+                Screen::MonoIncompatible(_)
+            ) {
+                return;
+            }
+        }
+
+        panic!("Warning was not hidden within given timeout");
+    }
+
+    #[test]
+    fn when_dsp_reports_runaway_feedback_it_should_show_warning_for_a_while() {
+        let mut store = Store::new();
+
+        store.apply_dsp_reaction(DSPReaction {
+            runaway_feedback: true,
+            ..DSPReaction::default()
+        });
+        store.apply_dsp_reaction(DSPReaction::default());
+
+        assert!(matches!(
+            store.cache.display.active_screen(),
+            Screen::RunawayFeedback(_)
+        ));
+
+        for _ in 0..1000 {
+            store.tick();
+            if !matches!(
+                store.cache.display.active_screen(),
+                Screen::RunawayFeedback(_)
+            ) {
+                return;
+            }
+        }
+
+        panic!("Warning was not hidden within given timeout");
+    }
+
     #[test]
     fn when_dsp_returns_impulse_it_should_lit_impulse_led_for_multiple_cycles() {
         let mut store = Store::new();
@@ -802,13 +1601,37 @@ mod tests {
         store.apply_dsp_reaction(dsp_reaction);
 
         let output = store.tick();
-        assert!(output.impulse_led);
+        assert_relative_eq!(output.impulse_led, 1.0);
         let output = store.tick();
-        assert!(output.impulse_led);
+        assert_relative_eq!(output.impulse_led, 1.0);
+
+        for _ in 0..100 {
+            let output = store.tick();
+            if output.impulse_led == 0.0 {
+                return;
+            }
+        }
+
+        panic!("Led was not turned off within given timeout");
+    }
+
+    #[test]
+    fn when_dsp_returns_impulse_trigger_goes_down_before_led_fades_out() {
+        let mut store = Store::new();
 
+        store.apply_dsp_reaction(DSPReaction {
+            delay_impulse: true,
+            ..DSPReaction::default()
+        });
+        store.apply_dsp_reaction(DSPReaction::default());
+
+        let mut previous_led = 1.0;
         for _ in 0..100 {
             let output = store.tick();
-            if !output.impulse_led {
+            assert!(output.impulse_led <= previous_led);
+            previous_led = output.impulse_led;
+            if !output.impulse_trigger {
+                assert!(output.impulse_led > 0.0);
                 return;
             }
         }
@@ -884,27 +1707,220 @@ mod tests {
     }
 
     #[test]
-    fn given_save_if_new_control_was_plugged_since_it_gets_to_the_queque() {
-        let store = Store::new();
+    fn when_configuration_snapshot_is_applied_it_round_trips_through_save() {
+        let mut store = Store::new();
+        let mut configuration = store.configuration();
+        configuration.rewind_speed[2] = (0, 3);
+        configuration.tap_interval_denominator = 8;
+        configuration.capture_mapping = Some(2);
+        configuration.output_level = 3;
+        configuration.spread = 4;
+        configuration.tap_mode = TapMode::Rhythm;
+        configuration.position_crossfade = 0;
+
+        let save = store.apply_configuration(configuration).unwrap();
+        assert_eq!(store.configuration(), configuration);
+
+        let store = Store::from(save);
+        assert_eq!(store.configuration(), configuration);
+    }
 
-        let save = store.cache.save();
+    #[test]
+    fn when_invalid_configuration_is_applied_it_gets_rejected() {
+        let mut store = Store::new();
+        let original = store.configuration();
 
-        let mut input = InputSnapshot::default();
-        input.control[1] = Some(1.0);
+        let mut configuration = original;
+        configuration.gate_head = Some(4);
+        assert_eq!(
+            store.apply_configuration(configuration),
+            Err(InvalidConfiguration)
+        );
 
-        let mut store = Store::from(save);
-        store.apply_input_snapshot(input);
+        let mut configuration = original;
+        configuration.tap_interval_denominator = 3;
+        assert_eq!(
+            store.apply_configuration(configuration),
+            Err(InvalidConfiguration)
+        );
 
-        assert_eq!(store.state, State::Mapping(StateMapping { input: 1 }));
+        assert_eq!(store.configuration(), original);
     }
 
     #[test]
-    fn given_save_if_control_was_unplugged_since_it_gets_unmapped() {
-        let mut store = Store::new();
-        let mut input = InputSnapshot::default();
+    fn given_save_it_lists_all_mappings() {
+        let mut save = Store::new().cache.save();
+        save.mapping[0] = AttributeIdentifier::Drive;
+        save.mapping[2] = AttributeIdentifier::Position(3);
+        let store = Store::from(save);
 
-        input.control[1] = None;
-        store.apply_input_snapshot(input);
+        assert_eq!(
+            store.mappings(),
+            [
+                (0, AttributeIdentifier::Drive),
+                (1, AttributeIdentifier::None),
+                (2, AttributeIdentifier::Position(3)),
+                (3, AttributeIdentifier::None),
+            ]
+        );
+    }
+
+    fn input_with_heads() -> InputSnapshot {
+        let mut input = InputSnapshot::default();
+        for (i, head) in input.head.iter_mut().enumerate() {
+            head.position = 0.2 + i as f32 * 0.2;
+            head.volume = 0.9 - i as f32 * 0.2;
+            head.feedback = 0.1 + i as f32 * 0.15;
+            head.pan = 0.25 * i as f32;
+        }
+        input
+    }
+
+    #[test]
+    fn given_save_with_scene_it_recalls_head_attributes() {
+        let mut store = Store::new();
+        let input = input_with_heads();
+        for _ in 0..32 {
+            store.apply_input_snapshot(input);
+        }
+        let original = store.cache.attributes.head;
+
+        let save = store.save_scene();
+        let store = Store::from(save);
+
+        for (recalled, original) in store.cache.attributes.head.iter().zip(original) {
+            assert_relative_eq!(recalled.position, original.position, epsilon = 0.005);
+            assert_relative_eq!(recalled.volume, original.volume, epsilon = 0.001);
+            assert_relative_eq!(recalled.feedback, original.feedback, epsilon = 0.005);
+            assert_relative_eq!(recalled.pan, original.pan, epsilon = 0.005);
+        }
+    }
+
+    #[test]
+    fn when_scene_save_was_not_confirmed_it_is_produced_with_next_snapshot() {
+        let mut store = Store::new();
+        for _ in 0..32 {
+            if let Some(save) = store.apply_input_snapshot(input_with_heads()).save {
+                store.confirm_save(save);
+            }
+        }
+
+        let save = store.save_scene();
+
+        let result = store.apply_input_snapshot(input_with_heads());
+        assert_eq!(result.save, Some(save));
+
+        store.confirm_save(save);
+        let result = store.apply_input_snapshot(input_with_heads());
+        assert!(result.save.is_none());
+    }
+
+    #[test]
+    fn when_applied_configuration_was_not_confirmed_it_is_produced_with_next_snapshot() {
+        let mut store = Store::new();
+        let mut configuration = store.configuration();
+        configuration.tape_age = 3;
+
+        let save = store.apply_configuration(configuration).unwrap();
+
+        let result = store.apply_input_snapshot(InputSnapshot::default());
+        assert_eq!(result.save, Some(save));
+
+        store.confirm_save(save);
+        let result = store.apply_input_snapshot(InputSnapshot::default());
+        assert!(result.save.is_none());
+    }
+
+    #[test]
+    fn given_save_with_scene_its_dsp_attributes_are_available_before_any_input() {
+        let mut store = Store::new();
+        for _ in 0..32 {
+            store.apply_input_snapshot(input_with_heads());
+        }
+        let original = store.cache.attributes.head;
+
+        let save = store.save_scene();
+        let mut store = Store::from(save);
+
+        let attributes = store.dsp_attributes();
+        for (restored, original) in attributes.head.iter().zip(original) {
+            assert_relative_eq!(restored.volume, original.volume, epsilon = 0.001);
+        }
+    }
+
+    #[test]
+    fn given_recalled_scene_when_head_pot_moves_only_that_head_follows_pots() {
+        let mut store = Store::new();
+        for _ in 0..32 {
+            store.apply_input_snapshot(input_with_heads());
+        }
+        let save = store.save_scene();
+
+        let mut store = Store::from(save);
+        let mut input = InputSnapshot::default();
+        for _ in 0..32 {
+            store.warm_up(input);
+        }
+        for _ in 0..32 {
+            store.apply_input_snapshot(input);
+        }
+        let recalled = store.cache.attributes.head;
+        assert!(recalled[1].volume > 0.0);
+
+        input.head[0].volume = 0.5;
+        for _ in 0..32 {
+            store.apply_input_snapshot(input);
+        }
+
+        assert_relative_eq!(store.cache.attributes.head[0].position, 0.0);
+        assert_relative_eq!(store.cache.attributes.head[1].volume, recalled[1].volume);
+        assert_relative_eq!(
+            store.cache.attributes.head[1].position,
+            recalled[1].position
+        );
+    }
+
+    #[test]
+    fn given_save_it_finds_control_mapped_to_attribute() {
+        let mut save = Store::new().cache.save();
+        save.mapping[1] = AttributeIdentifier::Volume(2);
+        save.mapping[3] = AttributeIdentifier::Speed;
+        let store = Store::from(save);
+
+        assert_eq!(store.control_for(AttributeIdentifier::Volume(2)), Some(1));
+        assert_eq!(store.control_for(AttributeIdentifier::Speed), Some(3));
+        assert_eq!(store.control_for(AttributeIdentifier::Volume(1)), None);
+        assert_eq!(store.control_for(AttributeIdentifier::Feedback(2)), None);
+    }
+
+    #[test]
+    fn given_unmapped_controls_it_does_not_report_them_as_mapped_to_none() {
+        let store = Store::new();
+        assert_eq!(store.control_for(AttributeIdentifier::None), None);
+    }
+
+    #[test]
+    fn given_save_if_new_control_was_plugged_since_it_gets_to_the_queque() {
+        let store = Store::new();
+
+        let save = store.cache.save();
+
+        let mut input = InputSnapshot::default();
+        input.control[1] = Some(1.0);
+
+        let mut store = Store::from(save);
+        store.apply_input_snapshot(input);
+
+        assert_eq!(store.state, State::Mapping(StateMapping { input: 1 }));
+    }
+
+    #[test]
+    fn given_save_if_control_was_unplugged_since_it_gets_unmapped() {
+        let mut store = Store::new();
+        let mut input = InputSnapshot::default();
+
+        input.control[1] = None;
+        store.apply_input_snapshot(input);
 
         input.control[1] = Some(1.0);
         store.apply_input_snapshot(input);
@@ -925,6 +1941,46 @@ mod tests {
         assert_eq!(store.state, State::Normal);
     }
 
+    #[test]
+    fn given_save_with_out_of_range_indices_they_are_dropped() {
+        let mut save = Store::new().cache.save();
+        save.mapping[0] = AttributeIdentifier::Volume(2);
+        save.mapping[1] = AttributeIdentifier::Position(7);
+        save.configuration.position_reset_mapping = Some(3);
+        save.configuration.pause_resume_mapping = Some(9);
+
+        let mut store = Store::from(save);
+
+        assert_eq!(store.cache.mapping[0], AttributeIdentifier::Volume(2));
+        assert!(store.cache.mapping[1].is_none());
+        assert_eq!(store.cache.configuration.position_reset_mapping, Some(3));
+        assert_eq!(store.cache.configuration.pause_resume_mapping, None);
+
+        let mut input = InputSnapshot::default();
+        input.control = [Some(1.0); 4];
+        store.apply_input_snapshot(input);
+        store.tick();
+    }
+
+    #[test]
+    fn when_control_is_calibrated_its_calibrated_value_is_exposed() {
+        let mut store = Store::new();
+        let mut input = InputSnapshot::default();
+        store.cache.calibrations[2] = Calibration {
+            offset: 0.1,
+            scaling: 1.2,
+        };
+
+        assert_eq!(store.calibrated_control_value(2), None);
+
+        for (raw, calibrated) in [(0.0, 0.1), (1.0, 1.3), (2.5, 3.1)] {
+            input.control[2] = Some(raw);
+            store.apply_input_snapshot(input);
+            let value = store.calibrated_control_value(2).unwrap();
+            assert_relative_eq!(value, calibrated, epsilon = 0.0001);
+        }
+    }
+
     #[test]
     fn given_save_it_recovers_previously_set_calibration_and_mapping() {
         let mut store = Store::new();
@@ -1044,6 +2100,7 @@ mod tests {
     #[cfg(test)]
     mod given_normal_mode {
         use super::*;
+        use crate::cache::{DelayRange, MasterFeedback};
 
         fn init_store() -> Store {
             Store::new()
@@ -1063,105 +2120,543 @@ mod tests {
         }
 
         #[test]
-        fn when_button_is_clicked_in_rough_interval_within_toleration_it_detects_tempo() {
-            let mut store = Store::new();
-            let input = InputSnapshot::default();
-
-            tap_button(&mut store, input, 1990);
-            tap_button(&mut store, input, 2050);
-            tap_button(&mut store, input, 2000);
-            tap_button(&mut store, input, 2);
+        fn when_pan_is_turned_while_holding_button_it_sets_fine_offset() {
+            let mut store = init_store();
+            let mut input = InputSnapshot::default();
+            input.head[2].pan = 0.5;
+            for _ in 0..32 {
+                store.apply_input_snapshot(input);
+            }
 
+            input.button = true;
+            input.head[2].pan = 1.0;
+            for _ in 0..32 {
+                store.apply_input_snapshot(input);
+            }
+            input.button = false;
             let attributes = store.apply_input_snapshot(input).dsp_attributes;
-            assert_relative_eq!(attributes.speed, 2.0);
+
+            assert_relative_eq!(attributes.head[2].fine_offset_ms, 10.0, epsilon = 1.0);
+            assert_relative_eq!(attributes.head[0].fine_offset_ms, 0.0);
         }
 
         #[test]
-        fn when_button_is_clicked_too_fast_it_does_not_detect_tempo() {
-            let mut store = Store::new();
-            let input = InputSnapshot::default();
+        fn when_position_is_turned_while_holding_button_it_sets_tap_mapping() {
+            let mut store = init_store();
+            let mut input = InputSnapshot::default();
+            for _ in 0..32 {
+                store.apply_input_snapshot(input);
+            }
 
-            tap_button(&mut store, input, 20);
-            tap_button(&mut store, input, 20);
-            tap_button(&mut store, input, 20);
-            tap_button(&mut store, input, 20);
+            input.button = true;
+            input.head[0].position = 1.0;
+            for _ in 0..32 {
+                store.apply_input_snapshot(input);
+            }
+            assert_eq!(store.cache.configuration.tap_mapping, Some(3));
 
-            assert!(store.cache.tapped_tempo.is_none());
+            input.head[0].position = 0.0;
+            for _ in 0..32 {
+                store.apply_input_snapshot(input);
+            }
+            assert_eq!(store.cache.configuration.tap_mapping, None);
         }
 
         #[test]
-        fn when_button_is_clicked_in_unequal_interval_it_does_not_detect_tempo() {
-            let mut store = Store::new();
-            let input = InputSnapshot::default();
+        fn when_control_mapped_to_tap_receives_evenly_spaced_edges_it_detects_tempo() {
+            let mut store = init_store();
+            store.cache.configuration.tap_mapping = Some(1);
+            let mut input = InputSnapshot::default();
+            input.control[1] = Some(0.5);
+            for _ in 0..32 {
+                store.apply_input_snapshot(input);
+                store.tick();
+            }
+            assert!(store.cache.mapping[1].is_none());
 
-            tap_button(&mut store, input, 2000);
-            tap_button(&mut store, input, 1000);
-            tap_button(&mut store, input, 2000);
-            tap_button(&mut store, input, 2000);
+            for _ in 0..4 {
+                clock_trigger(&mut store, 1, input, 2000);
+            }
 
-            assert!(store.cache.tapped_tempo.is_none());
+            let attributes = store.apply_input_snapshot(input).dsp_attributes;
+            assert_relative_eq!(attributes.speed, 2.0);
+            assert_eq!(store.cache.tapped_tempo, Some(2.0));
         }
 
         #[test]
-        fn when_tempo_is_tapped_in_it_is_overwritten_only_after_speed_pot_turning() {
+        fn when_button_and_tap_control_alternate_their_taps_merge() {
             let mut store = init_store();
+            store.cache.configuration.tap_mapping = Some(1);
             let mut input = InputSnapshot::default();
+            input.control[1] = Some(0.5);
+            for _ in 0..32 {
+                store.apply_input_snapshot(input);
+                store.tick();
+            }
 
-            tap_button(&mut store, input, 2000);
-            tap_button(&mut store, input, 2000);
-            tap_button(&mut store, input, 2000);
-            tap_button(&mut store, input, 2000);
+            for _ in 0..2 {
+                tap_button(&mut store, input, 2000);
+                clock_trigger(&mut store, 1, input, 2000);
+            }
 
-            assert_relative_eq!(store.cache.attributes.speed, 2.0);
+            assert_eq!(store.cache.tapped_tempo, Some(2.0));
+        }
 
-            input.tone = 1.0;
-            store.apply_input_snapshot(input);
-            store.tick();
-            assert_relative_eq!(store.cache.attributes.speed, 2.0);
+        #[test]
+        fn when_speed_is_turned_while_holding_button_it_nudges_tapped_tempo() {
+            let mut store = init_store();
+            let mut input = InputSnapshot::default();
+            input.speed = 0.5;
+            for _ in 0..32 {
+                store.apply_input_snapshot(input);
+                store.tick();
+            }
+            for _ in 0..4 {
+                tap_button(&mut store, input, 2000);
+            }
+            assert_eq!(store.cache.tapped_tempo, Some(2.0));
 
-            input.speed = 0.1;
+            input.button = true;
+            input.speed = 0.6;
             for _ in 0..32 {
                 store.apply_input_snapshot(input);
                 store.tick();
             }
-            assert!(store.cache.attributes.speed > 20.0);
+            input.button = false;
+            for _ in 0..32 {
+                store.apply_input_snapshot(input);
+                store.tick();
+            }
+            let nudged_tempo = store.cache.tapped_tempo.unwrap();
+            assert_relative_eq!(nudged_tempo, 2.0 * (1.0 - 5.0 * 0.005));
+            assert_eq!(store.cache.options.delay_range, DelayRange::Long);
+
+            for _ in 0..2000 {
+                store.apply_input_snapshot(input);
+                store.tick();
+            }
+            assert_eq!(store.cache.tapped_tempo, Some(nudged_tempo));
+            let attributes = store.apply_input_snapshot(input).dsp_attributes;
+            assert_relative_eq!(attributes.speed, nudged_tempo);
         }
 
         #[test]
-        fn when_control_is_plugged_then_state_changes_to_mapping() {
+        fn when_feedback_is_turned_while_holding_button_it_sets_polarity() {
             let mut store = init_store();
             let mut input = InputSnapshot::default();
+            for _ in 0..32 {
+                store.apply_input_snapshot(input);
+            }
 
-            input.control[1] = None;
-            store.apply_input_snapshot(input);
+            input.button = true;
+            input.head[1].feedback = 1.0;
+            for _ in 0..32 {
+                store.apply_input_snapshot(input);
+            }
+            assert!(store.cache.options.bipolar_feedback);
 
-            input.control[1] = Some(1.0);
-            let save = store.apply_input_snapshot(input).save;
+            input.head[1].feedback = 0.0;
+            for _ in 0..32 {
+                store.apply_input_snapshot(input);
+            }
+            assert!(!store.cache.options.bipolar_feedback);
+        }
 
-            assert!(save.is_none());
-            assert!(matches!(
-                store.state,
-                State::Mapping(StateMapping { input: 1 })
-            ));
-            assert_animation(
-                &mut store,
-                &[9600, 0800, 0690, 0609, 9600, 0800, 0690, 0609],
-            );
+        fn map_control_to_speed(store: &mut Store, mut input: InputSnapshot) -> InputSnapshot {
+            input.control[0] = None;
+            store.apply_input_snapshot(input);
+            input.control[0] = Some(0.5);
+            store.apply_input_snapshot(input);
+            input.speed = 0.1;
+            for _ in 0..32 {
+                store.apply_input_snapshot(input);
+                store.tick();
+            }
+            assert_eq!(store.cache.mapping[0], AttributeIdentifier::Speed);
+            assert_eq!(store.state, State::Normal);
+            input
         }
 
         #[test]
-        fn when_control_is_plugged_before_start_then_state_changes_to_mapping_after_startup() {
+        fn when_tempo_is_tapped_and_speed_control_is_set_it_divides_the_tempo() {
             let mut store = init_store();
-            let mut input = InputSnapshot::default();
+            let mut input = map_control_to_speed(&mut store, InputSnapshot::default());
 
-            input.control[1] = Some(1.0);
-            for _ in 0..10 {
-                store.warm_up(input);
+            for _ in 0..4 {
+                tap_button(&mut store, input, 2000);
             }
+            let attributes = store.apply_input_snapshot(input).dsp_attributes;
+            assert_relative_eq!(attributes.speed, 2.0);
 
-            store.apply_input_snapshot(input);
+            for (voltage, speed) in [
+                (1.5, 1.0),
+                (2.5, 0.5),
+                (3.5, 0.25),
+                (4.5, 0.125),
+                (0.5, 2.0),
+            ] {
+                input.control[0] = Some(voltage);
+                let attributes = store.apply_input_snapshot(input).dsp_attributes;
+                assert_relative_eq!(attributes.speed, speed);
+                assert_eq!(store.cache.tapped_tempo, Some(2.0));
+            }
+        }
 
-            assert!(matches!(
+        #[test]
+        fn when_feedback_is_bipolar_turning_pot_below_middle_inverts_it() {
+            let mut store = init_store();
+            store.cache.options.bipolar_feedback = true;
+            let mut input = InputSnapshot::default();
+
+            for (pot, feedback) in [(0.0, -1.2), (0.25, -0.6), (0.5, 0.0), (1.0, 1.2)] {
+                input.head[0].feedback = pot;
+                for _ in 0..32 {
+                    store.apply_input_snapshot(input);
+                    store.tick();
+                }
+                assert_relative_eq!(
+                    store.cache.attributes.head[0].feedback,
+                    feedback,
+                    epsilon = 0.02
+                );
+            }
+        }
+
+        #[test]
+        fn when_range_is_unlimited_head_volume_exceeds_unity() {
+            let mut store = init_store();
+            let mut input = InputSnapshot::default();
+            input.head[0].volume = 1.0;
+
+            for _ in 0..32 {
+                store.apply_input_snapshot(input);
+            }
+            assert_relative_eq!(store.cache.attributes.head[0].volume, 0.25);
+
+            store.cache.options.unlimited = true;
+            store.apply_input_snapshot(input);
+            assert_relative_eq!(store.cache.attributes.head[0].volume, 2.0);
+        }
+
+        #[test]
+        fn when_oversampled_read_is_enabled_it_applies_only_in_audio_range() {
+            let mut store = init_store();
+            store.cache.configuration.oversampled_read = true;
+            let input = InputSnapshot::default();
+
+            let attributes = store.apply_input_snapshot(input).dsp_attributes;
+            assert!(!attributes.oversampled_read);
+
+            store.cache.options.delay_range = DelayRange::Audio;
+            let attributes = store.apply_input_snapshot(input).dsp_attributes;
+            assert!(attributes.oversampled_read);
+        }
+
+        #[test]
+        fn when_oversampled_read_is_disabled_audio_range_is_read_plainly() {
+            let mut store = init_store();
+            store.cache.options.delay_range = DelayRange::Audio;
+            let input = InputSnapshot::default();
+
+            let attributes = store.apply_input_snapshot(input).dsp_attributes;
+            assert!(!attributes.oversampled_read);
+        }
+
+        #[test]
+        fn when_volume_is_turned_while_holding_button_it_sets_master_feedback() {
+            let mut store = init_store();
+            let mut input = InputSnapshot::default();
+            for _ in 0..32 {
+                store.apply_input_snapshot(input);
+            }
+
+            input.button = true;
+            input.head[2].volume = 0.5;
+            for _ in 0..32 {
+                store.apply_input_snapshot(input);
+            }
+
+            assert_relative_eq!(store.cache.options.master_feedback.0, 0.5);
+        }
+
+        #[test]
+        fn when_master_feedback_is_set_feedback_of_all_heads_scales_keeping_ratios() {
+            let mut store = init_store();
+            let mut input = InputSnapshot::default();
+            input.head[0].feedback = 1.0;
+            input.head[1].feedback = 0.5;
+            input.head[3].feedback = 0.25;
+            for _ in 0..32 {
+                store.apply_input_snapshot(input);
+                store.tick();
+            }
+            let full = store.apply_input_snapshot(input).dsp_attributes;
+
+            store.cache.options.master_feedback = MasterFeedback(0.5);
+            let scaled = store.apply_input_snapshot(input).dsp_attributes;
+
+            for i in 0..4 {
+                assert_relative_eq!(scaled.head[i].feedback, full.head[i].feedback * 0.5);
+            }
+            assert!(full.head[1].feedback > 0.0);
+            assert_relative_eq!(
+                scaled.head[0].feedback / scaled.head[1].feedback,
+                full.head[0].feedback / full.head[1].feedback
+            );
+        }
+
+        fn map_control_to_pre_amp(store: &mut Store, mut input: InputSnapshot) -> InputSnapshot {
+            input.control[0] = None;
+            store.apply_input_snapshot(input);
+            input.control[0] = Some(0.5);
+            store.apply_input_snapshot(input);
+            input.pre_amp = 0.6;
+            for _ in 0..32 {
+                store.apply_input_snapshot(input);
+                store.tick();
+            }
+            assert_eq!(store.cache.mapping[0], AttributeIdentifier::PreAmp);
+            assert_eq!(store.state, State::Normal);
+            input
+        }
+
+        #[test]
+        fn when_control_is_unplugged_in_hold_mode_attribute_keeps_its_last_value() {
+            let mut store = init_store();
+            store.cache.configuration.unplugged_hold = true;
+            let mut input = map_control_to_pre_amp(&mut store, InputSnapshot::default());
+            let plugged = store.cache.attributes.pre_amp;
+
+            input.control[0] = None;
+            for _ in 0..32 {
+                store.apply_input_snapshot(input);
+                store.tick();
+            }
+
+            assert_eq!(store.cache.mapping[0], AttributeIdentifier::PreAmp);
+            assert_relative_eq!(store.cache.attributes.pre_amp, plugged);
+        }
+
+        #[test]
+        fn when_cv_interpolation_is_enabled_oscillator_glides_between_snapshots() {
+            let mut store = init_store();
+            store.cache.options.enable_oscillator = true;
+            store.cache.configuration.cv_interpolation = true;
+            let mut input = map_control_to_pre_amp(&mut store, InputSnapshot::default());
+
+            for i in 0..10 {
+                let previous = store.cache.attributes.oscillator;
+                input.control[0] = Some(1.0 + i as f32 * 0.01);
+                let attributes = store.apply_input_snapshot(input).dsp_attributes;
+                assert!(attributes.oscillator > previous);
+                assert_relative_eq!(
+                    attributes.oscillator_slope,
+                    (attributes.oscillator - previous) * 1000.0,
+                    max_relative = 0.001
+                );
+            }
+        }
+
+        #[test]
+        fn when_cv_interpolation_is_disabled_oscillator_steps_to_new_value() {
+            let mut store = init_store();
+            store.cache.options.enable_oscillator = true;
+            let mut input = map_control_to_pre_amp(&mut store, InputSnapshot::default());
+
+            input.control[0] = Some(1.5);
+            let attributes = store.apply_input_snapshot(input).dsp_attributes;
+            assert_relative_eq!(attributes.oscillator_slope, 0.0);
+        }
+
+        #[test]
+        fn when_tempo_is_tapped_and_speed_control_is_unplugged_it_uses_the_tempo_undivided() {
+            let mut store = init_store();
+            let mut input = map_control_to_speed(&mut store, InputSnapshot::default());
+
+            for _ in 0..4 {
+                tap_button(&mut store, input, 2000);
+            }
+            input.control[0] = Some(4.5);
+            store.apply_input_snapshot(input);
+
+            input.control[0] = None;
+            let attributes = store.apply_input_snapshot(input).dsp_attributes;
+            assert_relative_eq!(attributes.speed, 2.0);
+        }
+
+        #[test]
+        fn when_tempo_is_tapped_it_is_passed_to_dsp_as_beat() {
+            let mut store = init_store();
+            let input = map_control_to_speed(&mut store, InputSnapshot::default());
+            let attributes = store.apply_input_snapshot(input).dsp_attributes;
+            assert!(attributes.beat.is_none());
+
+            for _ in 0..4 {
+                tap_button(&mut store, input, 2000);
+            }
+            let attributes = store.apply_input_snapshot(input).dsp_attributes;
+            assert_relative_eq!(attributes.beat.unwrap(), 2.0);
+        }
+
+        #[test]
+        fn when_rewind_beat_lock_is_disabled_tapped_tempo_does_not_reach_rewind_beat() {
+            let mut store = init_store();
+            let input = map_control_to_speed(&mut store, InputSnapshot::default());
+
+            for _ in 0..4 {
+                tap_button(&mut store, input, 2000);
+            }
+            let attributes = store.apply_input_snapshot(input).dsp_attributes;
+            assert!(attributes.rewind_beat.is_none());
+        }
+
+        #[test]
+        fn when_rewind_beat_lock_is_enabled_tapped_tempo_is_passed_to_dsp_as_rewind_beat() {
+            let mut store = init_store();
+            store.cache.configuration.rewind_beat_lock = true;
+            let input = map_control_to_speed(&mut store, InputSnapshot::default());
+            let attributes = store.apply_input_snapshot(input).dsp_attributes;
+            assert!(attributes.rewind_beat.is_none());
+
+            for _ in 0..4 {
+                tap_button(&mut store, input, 2000);
+            }
+            let attributes = store.apply_input_snapshot(input).dsp_attributes;
+            assert_relative_eq!(attributes.rewind_beat.unwrap(), 2.0);
+        }
+
+        #[test]
+        fn when_tempo_is_tapped_and_clock_is_detected_on_speed_control_the_clock_wins() {
+            let mut store = init_store();
+            let input = map_control_to_speed(&mut store, InputSnapshot::default());
+
+            for _ in 0..4 {
+                tap_button(&mut store, input, 2000);
+            }
+            for _ in 0..3 {
+                clock_trigger(&mut store, 0, input, 1000);
+            }
+            clock_trigger(&mut store, 0, input, 1);
+
+            let attributes = store.apply_input_snapshot(input).dsp_attributes;
+            assert_relative_eq!(attributes.speed, 1.0);
+            assert_eq!(store.cache.tapped_tempo, Some(2.0));
+        }
+
+        #[test]
+        fn when_button_is_clicked_in_rough_interval_within_toleration_it_detects_tempo() {
+            let mut store = Store::new();
+            let input = InputSnapshot::default();
+
+            tap_button(&mut store, input, 1990);
+            tap_button(&mut store, input, 2050);
+            tap_button(&mut store, input, 2000);
+            tap_button(&mut store, input, 2);
+
+            let attributes = store.apply_input_snapshot(input).dsp_attributes;
+            assert_relative_eq!(attributes.speed, 2.0);
+        }
+
+        #[test]
+        fn when_button_is_clicked_too_fast_it_does_not_detect_tempo() {
+            let mut store = Store::new();
+            let input = InputSnapshot::default();
+
+            tap_button(&mut store, input, 20);
+            tap_button(&mut store, input, 20);
+            tap_button(&mut store, input, 20);
+            tap_button(&mut store, input, 20);
+
+            assert!(store.cache.tapped_tempo.is_none());
+        }
+
+        #[test]
+        fn when_button_is_clicked_in_unequal_interval_it_does_not_detect_tempo() {
+            let mut store = Store::new();
+            let input = InputSnapshot::default();
+
+            tap_button(&mut store, input, 2000);
+            tap_button(&mut store, input, 1000);
+            tap_button(&mut store, input, 2000);
+            tap_button(&mut store, input, 2000);
+
+            assert!(store.cache.tapped_tempo.is_none());
+        }
+
+        #[test]
+        fn when_first_tap_is_off_and_following_are_consistent_it_detects_tempo() {
+            let mut store = Store::new();
+            let input = InputSnapshot::default();
+
+            tap_button(&mut store, input, 700);
+            tap_button(&mut store, input, 2000);
+            tap_button(&mut store, input, 2000);
+            tap_button(&mut store, input, 2);
+
+            let attributes = store.apply_input_snapshot(input).dsp_attributes;
+            assert_relative_eq!(attributes.speed, 2.0);
+        }
+
+        #[test]
+        fn when_tempo_is_tapped_in_it_is_overwritten_only_after_speed_pot_turning() {
+            let mut store = init_store();
+            let mut input = InputSnapshot::default();
+
+            tap_button(&mut store, input, 2000);
+            tap_button(&mut store, input, 2000);
+            tap_button(&mut store, input, 2000);
+            tap_button(&mut store, input, 2000);
+
+            assert_relative_eq!(store.cache.attributes.speed, 2.0);
+
+            input.tone = 1.0;
+            store.apply_input_snapshot(input);
+            store.tick();
+            assert_relative_eq!(store.cache.attributes.speed, 2.0);
+
+            input.speed = 0.1;
+            for _ in 0..32 {
+                store.apply_input_snapshot(input);
+                store.tick();
+            }
+            assert!(store.cache.attributes.speed > 20.0);
+        }
+
+        #[test]
+        fn when_control_is_plugged_then_state_changes_to_mapping() {
+            let mut store = init_store();
+            let mut input = InputSnapshot::default();
+
+            input.control[1] = None;
+            store.apply_input_snapshot(input);
+
+            input.control[1] = Some(1.0);
+            let save = store.apply_input_snapshot(input).save;
+
+            assert!(save.is_none());
+            assert!(matches!(
+                store.state,
+                State::Mapping(StateMapping { input: 1 })
+            ));
+            assert_animation(
+                &mut store,
+                &[9600, 0800, 0690, 0609, 9600, 0800, 0690, 0609],
+            );
+        }
+
+        #[test]
+        fn when_control_is_plugged_before_start_then_state_changes_to_mapping_after_startup() {
+            let mut store = init_store();
+            let mut input = InputSnapshot::default();
+
+            input.control[1] = Some(1.0);
+            for _ in 0..10 {
+                store.warm_up(input);
+            }
+
+            store.apply_input_snapshot(input);
+
+            assert!(matches!(
                 store.state,
                 State::Mapping(StateMapping { input: 1 })
             ));
@@ -1209,161 +2704,1179 @@ mod tests {
             input.control[2] = Some(1.0);
             store.apply_input_snapshot(input);
 
-            assert!(matches!(
-                store.state,
-                State::Mapping(StateMapping { input: 1 })
-            ));
-            assert_eq!(store.queue.len(), 1);
+            assert!(matches!(
+                store.state,
+                State::Mapping(StateMapping { input: 1 })
+            ));
+            assert_eq!(store.queue.len(), 1);
+        }
+
+        #[test]
+        fn when_control_is_unplugged_it_is_removed_from_queue() {
+            let mut store = init_store();
+            let mut input = InputSnapshot::default();
+
+            input.control[1] = None;
+            input.control[2] = None;
+            input.control[3] = None;
+            store.apply_input_snapshot(input);
+
+            input.control[1] = Some(1.0);
+            input.control[2] = Some(1.0);
+            input.control[3] = Some(1.0);
+            store.apply_input_snapshot(input);
+
+            assert!(matches!(
+                store.state,
+                State::Mapping(StateMapping { input: 1 })
+            ));
+            assert_eq!(store.queue.len(), 2);
+
+            input.control[2] = None;
+            store.apply_input_snapshot(input);
+
+            assert!(matches!(
+                store.state,
+                State::Mapping(StateMapping { input: 1 })
+            ));
+            assert_eq!(store.queue.len(), 1);
+        }
+
+        #[test]
+        fn when_button_is_held_for_60_seconds_it_enters_configuration_mode() {
+            let mut store = init_store();
+            let input = InputSnapshot::default();
+
+            hold_button(&mut store, input);
+
+            assert!(matches!(store.state, State::Configuring(_)));
+        }
+
+        #[test]
+        fn when_button_is_held_shortly_it_freezes_delay_until_released() {
+            let mut store = init_store();
+            let mut input = InputSnapshot::default();
+            for _ in 0..1000 {
+                store.apply_input_snapshot(input);
+                store.tick();
+            }
+
+            input.button = true;
+            for _ in 0..FREEZE_HOLD - 1 {
+                let attributes = store.apply_input_snapshot(input).dsp_attributes;
+                store.tick();
+                assert!(!attributes.frozen_delay);
+            }
+            for _ in 0..500 {
+                let attributes = store.apply_input_snapshot(input).dsp_attributes;
+                store.tick();
+                assert!(attributes.frozen_delay);
+            }
+
+            input.button = false;
+            let attributes = store.apply_input_snapshot(input).dsp_attributes;
+            assert!(!attributes.frozen_delay);
+        }
+
+        #[test]
+        fn when_pot_is_turned_while_holding_button_it_releases_freeze() {
+            let mut store = init_store();
+            let mut input = InputSnapshot::default();
+
+            input.button = true;
+            for _ in 0..500 {
+                store.apply_input_snapshot(input);
+                store.tick();
+            }
+            input.head[0].pan = 1.0;
+            let attributes = store.apply_input_snapshot(input).dsp_attributes;
+
+            assert!(!attributes.frozen_delay);
+        }
+
+        #[test]
+        fn when_button_is_tapped_it_detects_tempo_without_freezing() {
+            let mut store = init_store();
+            let input = InputSnapshot::default();
+
+            for _ in 0..32 {
+                tap_button(&mut store, input, 2000);
+                assert!(!store.cache.build_dsp_attributes().frozen_delay);
+            }
+
+            let attributes = store.apply_input_snapshot(input).dsp_attributes;
+            assert_relative_eq!(attributes.speed, 2.0);
+        }
+
+        #[test]
+        fn when_tapping_is_interrupted_by_freeze_hold_it_does_not_detect_tempo() {
+            let mut store = init_store();
+            let mut input = InputSnapshot::default();
+
+            for _ in 0..2 {
+                tap_button(&mut store, input, 2000);
+            }
+            input.button = true;
+            for _ in 0..500 {
+                store.apply_input_snapshot(input);
+                store.tick();
+            }
+            input.button = false;
+            for _ in 0..1500 {
+                store.apply_input_snapshot(input);
+                store.tick();
+            }
+            tap_button(&mut store, input, 2000);
+            tap_button(&mut store, input, 2000);
+
+            assert!(store.cache.tapped_tempo.is_none());
+        }
+
+        fn settle_idle(store: &mut Store, input: InputSnapshot) {
+            for _ in 0..1000 {
+                store.apply_input_snapshot(input);
+                store.tick();
+            }
+        }
+
+        #[test]
+        fn when_button_is_held_for_2_seconds_freeze_gives_way_to_bypass() {
+            let mut store = init_store();
+            let mut input = InputSnapshot::default();
+            settle_idle(&mut store, input);
+
+            input.button = true;
+            let mut frozen = [false; 2_000];
+            for frozen in &mut frozen {
+                *frozen = store
+                    .apply_input_snapshot(input)
+                    .dsp_attributes
+                    .frozen_delay;
+                store.tick();
+            }
+            let freeze_start = FREEZE_HOLD as usize - 1;
+            let freeze_end = BYPASS_HOLD as usize - 1;
+            assert!(frozen[..freeze_start].iter().all(|f| !f));
+            assert!(frozen[freeze_start..freeze_end].iter().all(|f| *f));
+            assert!(frozen[freeze_end..].iter().all(|f| !f));
+
+            input.button = false;
+            let attributes = store.apply_input_snapshot(input).dsp_attributes;
+            assert!(attributes.bypass);
+            assert!(!attributes.frozen_delay);
+        }
+
+        #[test]
+        fn when_button_is_held_for_6_seconds_it_only_resets_the_buffer() {
+            let mut store = init_store();
+            let mut input = InputSnapshot::default();
+            settle_idle(&mut store, input);
+
+            input.button = true;
+            let mut cleared = false;
+            for _ in 0..6_000 {
+                let attributes = store.apply_input_snapshot(input).dsp_attributes;
+                cleared |= attributes.clear_buffer;
+                store.tick();
+            }
+            assert!(cleared);
+            assert!(!store.cache.build_dsp_attributes().frozen_delay);
+
+            input.button = false;
+            let attributes = store.apply_input_snapshot(input).dsp_attributes;
+            assert!(!attributes.bypass);
+            assert!(!attributes.frozen_delay);
+        }
+
+        #[test]
+        fn when_pot_is_turned_while_freezing_it_only_applies_the_alt_gesture() {
+            let mut store = init_store();
+            let mut input = InputSnapshot::default();
+            input.head[2].pan = 0.5;
+            settle_idle(&mut store, input);
+
+            input.button = true;
+            for _ in 0..500 {
+                store.apply_input_snapshot(input);
+                store.tick();
+            }
+            assert!(store.cache.build_dsp_attributes().frozen_delay);
+
+            input.head[2].pan = 1.0;
+            for _ in 0..1500 {
+                let attributes = store.apply_input_snapshot(input).dsp_attributes;
+                store.tick();
+                assert!(!attributes.frozen_delay);
+            }
+            input.button = false;
+            let attributes = store.apply_input_snapshot(input).dsp_attributes;
+
+            assert_relative_eq!(attributes.head[2].fine_offset_ms, 10.0, epsilon = 1.0);
+            assert!(!attributes.bypass);
+            assert!(!attributes.clear_buffer);
+        }
+
+        #[test]
+        fn when_rhythm_is_tapped_freeze_hold_does_not_place_a_head() {
+            let mut store = init_store();
+            let input = InputSnapshot::default();
+            store.cache.configuration.tap_mode = TapMode::Rhythm;
+            settle_idle(&mut store, input);
+
+            tap_button(&mut store, input, 250);
+            hold_button_for(&mut store, input, 500);
+            tap_button(&mut store, input, 250);
+
+            assert!(store.cache.tapped_positions[0].is_some());
+            assert!(store.cache.tapped_positions[1].is_none());
+        }
+
+        #[test]
+        fn when_speed_gear_is_toggled_speed_jumps_by_octaves() {
+            let mut store = init_store();
+            let mut input = InputSnapshot::default();
+            input.speed = 0.3;
+            for _ in 0..100 {
+                store.apply_input_snapshot(input);
+                store.tick();
+            }
+            let base = store.apply_input_snapshot(input).dsp_attributes.speed;
+
+            let mut speed_in_gear = |store: &mut Store, bias: f32| {
+                input.button = true;
+                input.bias = bias;
+                for _ in 0..50 {
+                    store.apply_input_snapshot(input);
+                    store.tick();
+                }
+                input.button = false;
+                store.apply_input_snapshot(input);
+                store.tick();
+                store.apply_input_snapshot(input).dsp_attributes.speed
+            };
+
+            assert_relative_eq!(speed_in_gear(&mut store, 1.0), base / 2.0);
+            assert_relative_eq!(speed_in_gear(&mut store, 0.0), base * 2.0);
+            assert_relative_eq!(speed_in_gear(&mut store, 0.5), base);
+        }
+
+        fn hold_button_for(store: &mut Store, mut input: InputSnapshot, cycles: usize) {
+            input.button = true;
+            for _ in 0..cycles {
+                store.apply_input_snapshot(input);
+                store.tick();
+            }
+            input.button = false;
+            store.apply_input_snapshot(input);
+            store.tick();
+        }
+
+        #[test]
+        fn when_button_is_held_for_2_seconds_it_toggles_bypass() {
+            let mut store = init_store();
+            let input = InputSnapshot::default();
+
+            hold_button_for(&mut store, input, 2_000);
+            assert!(store.cache.build_dsp_attributes().bypass);
+            assert!(matches!(
+                store.cache.display.prioritized[6],
+                Some(Screen::Bypassed(_))
+            ));
+
+            hold_button_for(&mut store, input, 2_000);
+            assert!(!store.cache.build_dsp_attributes().bypass);
+            assert!(store.cache.display.prioritized[6].is_none());
+        }
+
+        #[test]
+        fn when_button_is_clicked_or_held_for_too_long_it_does_not_toggle_bypass() {
+            let mut store = init_store();
+            let input = InputSnapshot::default();
+
+            click_button(&mut store, input);
+            assert!(!store.cache.build_dsp_attributes().bypass);
+
+            hold_button_for(&mut store, input, 6_000);
+            assert!(!store.cache.build_dsp_attributes().bypass);
+        }
+
+        #[test]
+        fn when_button_is_held_but_pots_are_active_it_does_not_enter_configuration_mode() {
+            let mut store = init_store();
+            let mut input = InputSnapshot::default();
+
+            input.button = true;
+            for _ in 0..1000 {
+                store.apply_input_snapshot(input);
+                store.tick();
+            }
+            input.pre_amp = 1.0;
+            for _ in 0..6 * 1000 {
+                store.apply_input_snapshot(input);
+                store.tick();
+            }
+            input.button = false;
+            store.apply_input_snapshot(input);
+            store.tick();
+
+            assert!(matches!(store.state, State::Normal));
+        }
+
+        #[test]
+        fn when_mapped_control_in_unplugged_it_is_unmapped() {
+            let mut store = init_store();
+            let mut input = InputSnapshot::default();
+
+            input.control[0] = None;
+            store.apply_input_snapshot(input);
+
+            input.control[0] = Some(1.0);
+            store.apply_input_snapshot(input);
+
+            input.drive = 0.4;
+            store.apply_input_snapshot(input);
+
+            assert_eq!(store.cache.mapping[0], AttributeIdentifier::Drive);
+
+            input.control[0] = None;
+            store.apply_input_snapshot(input);
+
+            assert_eq!(store.cache.mapping[0], AttributeIdentifier::None);
+        }
+    }
+
+    #[cfg(test)]
+    mod given_configuration_mode {
+        use super::*;
+        use crate::cache::DelayRange;
+
+        fn init_store() -> (Store, InputSnapshot) {
+            let mut store = Store::new();
+            let input = InputSnapshot::default();
+
+            hold_button(&mut store, input);
+
+            (store, input)
+        }
+
+        fn apply_input_snapshot(store: &mut Store, input: InputSnapshot) {
+            // NOTE: Applying it 32 times makes sure that pot smoothening is
+            // not affecting following asserts.
+            for _ in 0..32 {
+                store.apply_input_snapshot(input);
+                store.tick();
+            }
+        }
+
+        #[test]
+        fn when_clicks_button_it_saves_configuration_and_enters_normal_mode() {
+            let (mut store, mut input) = init_store();
+
+            input.speed = 1.0;
+            apply_input_snapshot(&mut store, input);
+
+            click_button(&mut store, input);
+
+            assert_eq!(
+                store.cache.configuration.default_display_page,
+                DisplayPage::Heads
+            );
+        }
+
+        #[test]
+        fn when_pot_jitters_within_the_set_value_it_does_not_produce_save() {
+            let (mut store, mut input) = init_store();
+
+            input.bias = 0.5;
+            apply_input_snapshot(&mut store, input);
+            let save = click_button(&mut store, input).unwrap();
+            store.confirm_save(save);
+            assert_eq!(store.cache.configuration.spread, 2);
+
+            hold_button(&mut store, input);
+            for bias in [0.46, 0.54, 0.47, 0.53, 0.5] {
+                input.bias = bias;
+                apply_input_snapshot(&mut store, input);
+            }
+            let save = click_button(&mut store, input);
+
+            assert_eq!(store.cache.configuration.spread, 2);
+            assert!(save.is_none());
+        }
+
+        #[test]
+        fn when_save_was_not_confirmed_it_is_produced_again_with_next_change() {
+            let (mut store, mut input) = init_store();
+
+            input.bias = 0.5;
+            apply_input_snapshot(&mut store, input);
+            assert!(click_button(&mut store, input).is_some());
+
+            hold_button(&mut store, input);
+            for bias in [0.46, 0.54, 0.47, 0.53, 0.5] {
+                input.bias = bias;
+                apply_input_snapshot(&mut store, input);
+            }
+            let save = click_button(&mut store, input);
+
+            assert_eq!(save.unwrap().configuration.spread, 2);
+        }
+
+        #[test]
+        fn when_save_was_not_confirmed_it_is_produced_again_with_next_snapshot() {
+            let (mut store, mut input) = init_store();
+
+            input.bias = 0.5;
+            apply_input_snapshot(&mut store, input);
+            let save = click_button(&mut store, input).unwrap();
+
+            assert_eq!(store.apply_input_snapshot(input).save, Some(save));
+
+            store.confirm_save(save);
+            assert!(store.apply_input_snapshot(input).save.is_none());
+        }
+
+        #[test]
+        fn when_does_not_change_attribute_it_keeps_the_previously_set_value() {
+            let (mut store, mut input) = init_store();
+
+            input.speed = 1.0;
+            apply_input_snapshot(&mut store, input);
+            click_button(&mut store, input);
+
+            assert_eq!(
+                store.cache.configuration.default_display_page,
+                DisplayPage::Heads
+            );
+
+            input.speed = 0.0;
+            apply_input_snapshot(&mut store, input);
+
+            hold_button(&mut store, input);
+
+            apply_input_snapshot(&mut store, input);
+            click_button(&mut store, input);
+
+            assert_eq!(
+                store.cache.configuration.default_display_page,
+                DisplayPage::Heads
+            );
+        }
+
+        fn select_default_page_and_return_to_normal_mode(
+            store: &mut Store,
+            mut input: InputSnapshot,
+            speed_pot: f32,
+        ) -> InputSnapshot {
+            input.speed = speed_pot;
+            apply_input_snapshot(store, input);
+            click_button(store, input);
+            apply_input_snapshot(store, input);
+            store.apply_dsp_reaction(DSPReaction {
+                new_position: 3,
+                ..DSPReaction::default()
+            });
+            input
+        }
+
+        #[test]
+        fn when_position_page_is_selected_it_becomes_the_fallback_screen() {
+            let (mut store, input) = init_store();
+
+            select_default_page_and_return_to_normal_mode(&mut store, input, 0.1);
+
+            assert_eq!(
+                store.cache.configuration.default_display_page,
+                DisplayPage::Position
+            );
+            assert!(matches!(
+                store.cache.display.prioritized[10],
+                Some(Screen::Attribute(_, AttributeScreen::Position(3)))
+            ));
+        }
+
+        #[test]
+        fn when_speed_page_is_selected_it_becomes_the_fallback_screen() {
+            let (mut store, input) = init_store();
+
+            select_default_page_and_return_to_normal_mode(&mut store, input, 0.25);
+
+            assert_eq!(
+                store.cache.configuration.default_display_page,
+                DisplayPage::Speed
+            );
+            assert!(matches!(
+                store.cache.display.prioritized[10],
+                Some(Screen::Attribute(_, AttributeScreen::Speed(_)))
+            ));
+        }
+
+        #[test]
+        fn when_speed_oscillates_around_led_boundary_shown_speed_stays_steady() {
+            let (mut store, input) = init_store();
+            let mut input = select_default_page_and_return_to_normal_mode(&mut store, input, 0.25);
+
+            // NOTE: Another LED lights up once the shown phase crosses 4/7.9.
+            // The first swings let the shown value settle within the band.
+            let boundary = 1.0 - 4.0 / 7.9;
+            let swings = [boundary - 0.006, boundary + 0.006].into_iter().cycle();
+            let mut shown = None;
+            for (i, speed) in swings.take(16).enumerate() {
+                input.speed = speed;
+                apply_input_snapshot(&mut store, input);
+                let leds = store.cache.display.active_screen().leds();
+                if i < 4 {
+                    continue;
+                }
+                let shown = *shown.get_or_insert(leds);
+                assert_eq!(leds, shown);
+            }
+        }
+
+        #[test]
+        fn when_feedback_page_is_selected_it_becomes_the_fallback_screen() {
+            let (mut store, mut input) = init_store();
+            input.head[1].feedback = 1.0;
+
+            select_default_page_and_return_to_normal_mode(&mut store, input, 0.45);
+
+            assert_eq!(
+                store.cache.configuration.default_display_page,
+                DisplayPage::Feedback
+            );
+            assert!(matches!(
+                store.cache.display.prioritized[10],
+                Some(Screen::Attribute(_, AttributeScreen::FeedbackOverview(_)))
+            ));
+            assert_eq!(
+                store.cache.display.prioritized[10].unwrap().leds(),
+                [false, true, false, false, false, true, false, false]
+            );
+        }
+
+        #[test]
+        fn when_heads_page_is_selected_in_audio_range_it_becomes_the_fallback_screen() {
+            let (mut store, input) = init_store();
+            store.cache.options.delay_range = DelayRange::Audio;
+
+            select_default_page_and_return_to_normal_mode(&mut store, input, 0.9);
+
+            assert_eq!(
+                store.cache.configuration.default_display_page,
+                DisplayPage::Heads
+            );
+            assert!(matches!(
+                store.cache.display.prioritized[10],
+                Some(Screen::Attribute(_, AttributeScreen::HeadsOverview(_)))
+            ));
+        }
+
+        #[test]
+        fn when_heads_page_is_selected_outside_audio_range_reported_position_is_shown() {
+            let (mut store, input) = init_store();
+            store.cache.options.delay_range = DelayRange::Long;
+
+            select_default_page_and_return_to_normal_mode(&mut store, input, 0.9);
+
+            assert!(matches!(
+                store.cache.display.prioritized[10],
+                Some(Screen::Attribute(_, AttributeScreen::Position(3)))
+            ));
+        }
+
+        #[test]
+        fn when_position_page_is_selected_in_audio_range_reported_position_is_shown() {
+            let (mut store, input) = init_store();
+            store.cache.options.delay_range = DelayRange::Audio;
+
+            select_default_page_and_return_to_normal_mode(&mut store, input, 0.1);
+
+            assert!(matches!(
+                store.cache.display.prioritized[10],
+                Some(Screen::Attribute(_, AttributeScreen::Position(3)))
+            ));
+        }
+
+        #[test]
+        fn when_spread_page_is_selected_it_shows_positions_of_heads() {
+            let (mut store, mut input) = init_store();
+            for (i, position) in [0.05, 0.3, 0.55, 0.8].iter().enumerate() {
+                input.head[i].position = *position;
+                input.head[i].volume = 1.0;
+            }
+
+            select_default_page_and_return_to_normal_mode(&mut store, input, 0.6);
+
+            assert_eq!(
+                store.cache.configuration.default_display_page,
+                DisplayPage::Spread
+            );
+            assert_eq!(
+                store.cache.display.prioritized[10].unwrap().leds(),
+                [true, false, true, false, false, true, false, true]
+            );
+        }
+
+        #[test]
+        fn when_input_level_page_is_selected_it_shows_held_peaks_decaying_over_time() {
+            let (mut store, input) = init_store();
+
+            select_default_page_and_return_to_normal_mode(&mut store, input, 0.75);
+            assert_eq!(
+                store.cache.configuration.default_display_page,
+                DisplayPage::InputLevel
+            );
+
+            store.apply_dsp_reaction(DSPReaction {
+                input_peak: [0.6, 0.1],
+                ..DSPReaction::default()
+            });
+            store.tick();
+            assert_eq!(
+                store.cache.display.prioritized[10].unwrap().leds(),
+                [true, true, true, false, true, false, false, false]
+            );
+
+            for _ in 0..400 {
+                store.tick();
+            }
+            assert_eq!(
+                store.cache.display.prioritized[10].unwrap().leds(),
+                [true, true, true, false, true, false, false, false]
+            );
+
+            for _ in 0..1000 {
+                store.tick();
+            }
+            assert_eq!(
+                store.cache.display.prioritized[10].unwrap().leds(),
+                [false; 8]
+            );
+        }
+
+        #[test]
+        fn when_spread_page_is_selected_it_follows_moving_heads() {
+            let (mut store, mut input) = init_store();
+            input.head[0].volume = 1.0;
+            input.head[0].position = 0.05;
+
+            let mut input = select_default_page_and_return_to_normal_mode(&mut store, input, 0.6);
+            input.head[0].position = 0.3;
+            apply_input_snapshot(&mut store, input);
+
+            assert_eq!(
+                store.cache.display.prioritized[10].unwrap().leds(),
+                [false, false, true, false, false, false, false, false]
+            );
+        }
+
+        fn select_multi_tap_pattern_and_return_to_normal_mode(
+            store: &mut Store,
+            mut input: InputSnapshot,
+            volume_pot: f32,
+        ) -> InputSnapshot {
+            input.head[0].volume = volume_pot;
+            apply_input_snapshot(store, input);
+            click_button(store, input);
+            for head in &mut input.head {
+                head.position = 0.5;
+            }
+            apply_input_snapshot(store, input);
+            input
+        }
+
+        fn assert_head_positions(store: &Store, expected: [f32; 4]) {
+            for (head, expected) in store.cache.attributes.head.iter().zip(expected) {
+                assert_relative_eq!(head.position, expected, epsilon = 0.001);
+            }
+        }
+
+        #[test]
+        fn when_triplet_spread_pattern_is_selected_heads_follow_it() {
+            let (mut store, input) = init_store();
+
+            select_multi_tap_pattern_and_return_to_normal_mode(&mut store, input, 0.4);
+
+            assert_eq!(store.cache.configuration.multi_tap_pattern, 1);
+            assert_head_positions(&store, [1.0 / 6.0, 1.0 / 3.0, 2.0 / 3.0, 1.0]);
+        }
+
+        #[test]
+        fn when_golden_ratio_pattern_is_selected_heads_follow_it() {
+            let (mut store, input) = init_store();
+
+            select_multi_tap_pattern_and_return_to_normal_mode(&mut store, input, 0.6);
+
+            assert_eq!(store.cache.configuration.multi_tap_pattern, 2);
+            assert_head_positions(&store, [0.236, 0.382, 0.618, 1.0]);
+        }
+
+        #[test]
+        fn when_dotted_cascade_pattern_is_selected_heads_follow_it() {
+            let (mut store, input) = init_store();
+
+            select_multi_tap_pattern_and_return_to_normal_mode(&mut store, input, 0.9);
+
+            assert_eq!(store.cache.configuration.multi_tap_pattern, 3);
+            assert_head_positions(&store, [0.1875, 0.375, 0.5625, 0.75]);
+        }
+
+        #[test]
+        fn when_no_pattern_is_selected_heads_follow_their_pots() {
+            let (mut store, input) = init_store();
+
+            select_multi_tap_pattern_and_return_to_normal_mode(&mut store, input, 0.1);
+
+            assert_eq!(store.cache.configuration.multi_tap_pattern, 0);
+            assert_head_positions(&store, [0.5; 4]);
+        }
+
+        #[test]
+        fn when_pattern_is_active_position_pots_offset_heads() {
+            let (mut store, input) = init_store();
+
+            let mut input =
+                select_multi_tap_pattern_and_return_to_normal_mode(&mut store, input, 0.9);
+            input.head[0].position = 0.7;
+            input.head[3].position = 0.0;
+            apply_input_snapshot(&mut store, input);
+
+            assert_head_positions(&store, [0.2915, 0.375, 0.5625, 0.5]);
+        }
+
+        #[test]
+        fn when_output_level_is_not_configured_it_stays_at_unity() {
+            let mut store = Store::new();
+            let input = InputSnapshot::default();
+
+            let attributes = store.apply_input_snapshot(input).dsp_attributes;
+
+            assert_relative_eq!(attributes.output_level, 1.0);
+        }
+
+        #[test]
+        fn when_output_level_is_configured_it_trims_the_output() {
+            let (mut store, mut input) = init_store();
+
+            input.head[1].volume = 0.45;
+            apply_input_snapshot(&mut store, input);
+            click_button(&mut store, input);
+            let attributes = store.apply_input_snapshot(input).dsp_attributes;
+
+            assert_eq!(store.cache.configuration.output_level, 3);
+            assert_relative_eq!(attributes.output_level, 0.5);
+        }
+
+        #[test]
+        fn when_single_pass_feedback_topology_is_configured_it_is_passed_to_dsp() {
+            let (mut store, mut input) = init_store();
+
+            input.head[2].volume = 0.9;
+            apply_input_snapshot(&mut store, input);
+            click_button(&mut store, input);
+            let attributes = store.apply_input_snapshot(input).dsp_attributes;
+
+            assert_eq!(
+                store.cache.configuration.feedback_topology,
+                FeedbackTopology::SinglePass
+            );
+            assert_eq!(attributes.feedback_topology, 1);
+        }
+
+        #[test]
+        fn when_trails_are_configured_they_are_passed_to_dsp() {
+            let (mut store, mut input) = init_store();
+
+            input.dry_wet = 0.9;
+            apply_input_snapshot(&mut store, input);
+            click_button(&mut store, input);
+            let attributes = store.apply_input_snapshot(input).dsp_attributes;
+
+            assert!(store.cache.configuration.trails);
+            assert!(attributes.trails);
+        }
+
+        #[test]
+        fn when_impulse_threshold_is_configured_it_is_passed_to_dsp() {
+            let (mut store, mut input) = init_store();
+
+            input.head[2].position = 0.6;
+            apply_input_snapshot(&mut store, input);
+            click_button(&mut store, input);
+            let attributes = store.apply_input_snapshot(input).dsp_attributes;
+
+            assert_eq!(store.cache.configuration.impulse_threshold, 4);
+            assert_relative_eq!(attributes.impulse_threshold, 0.2);
+        }
+
+        #[test]
+        fn when_cv_interpolation_is_configured_it_is_enabled() {
+            let (mut store, mut input) = init_store();
+
+            input.head[3].position = 0.9;
+            apply_input_snapshot(&mut store, input);
+            click_button(&mut store, input);
+
+            assert!(store.cache.configuration.cv_interpolation);
+        }
+
+        #[test]
+        fn when_rewind_beat_lock_is_configured_it_is_enabled() {
+            let (mut store, mut input) = init_store();
+
+            input.button = true;
+            input.head[1].position = 1.0;
+            apply_input_snapshot(&mut store, input);
+            apply_input_snapshot(&mut store, input);
+            input.button = false;
+            apply_input_snapshot(&mut store, input);
+            assert!(matches!(store.state, State::Configuring(_)));
+
+            click_button(&mut store, input);
+
+            assert_eq!(store.state, State::Normal);
+            assert!(store.cache.configuration.rewind_beat_lock);
+            assert_eq!(store.cache.configuration.tap_mode, TapMode::Tempo);
+        }
+
+        #[test]
+        fn when_oversampled_read_is_configured_it_is_enabled() {
+            let (mut store, mut input) = init_store();
+
+            input.button = true;
+            input.head[3].position = 1.0;
+            apply_input_snapshot(&mut store, input);
+            apply_input_snapshot(&mut store, input);
+            input.button = false;
+            apply_input_snapshot(&mut store, input);
+            click_button(&mut store, input);
+
+            assert!(store.cache.configuration.oversampled_read);
+            assert!(!store.cache.configuration.cv_interpolation);
+        }
+
+        #[test]
+        fn when_unplugged_hold_is_configured_it_is_enabled() {
+            let (mut store, mut input) = init_store();
+
+            input.button = true;
+            input.tone = 1.0;
+            apply_input_snapshot(&mut store, input);
+            apply_input_snapshot(&mut store, input);
+            input.button = false;
+            apply_input_snapshot(&mut store, input);
+            click_button(&mut store, input);
+
+            assert!(store.cache.configuration.unplugged_hold);
+            assert_eq!(store.cache.configuration.position_reset_mapping, None);
         }
 
         #[test]
-        fn when_control_is_unplugged_it_is_removed_from_queue() {
-            let mut store = init_store();
-            let mut input = InputSnapshot::default();
-
-            input.control[1] = None;
-            input.control[2] = None;
-            input.control[3] = None;
-            store.apply_input_snapshot(input);
-
-            input.control[1] = Some(1.0);
-            input.control[2] = Some(1.0);
-            input.control[3] = Some(1.0);
-            store.apply_input_snapshot(input);
-
-            assert!(matches!(
-                store.state,
-                State::Mapping(StateMapping { input: 1 })
-            ));
-            assert_eq!(store.queue.len(), 2);
+        fn when_speed_smoothing_is_configured_shown_speed_uses_its_dead_band() {
+            let (mut store, mut input) = init_store();
+            let default_display = store.cache.configuration.default_display_page;
 
-            input.control[2] = None;
-            store.apply_input_snapshot(input);
+            input.button = true;
+            input.speed = 1.0;
+            apply_input_snapshot(&mut store, input);
+            apply_input_snapshot(&mut store, input);
+            input.button = false;
+            apply_input_snapshot(&mut store, input);
+            click_button(&mut store, input);
+            apply_input_snapshot(&mut store, input);
 
-            assert!(matches!(
-                store.state,
-                State::Mapping(StateMapping { input: 1 })
-            ));
-            assert_eq!(store.queue.len(), 1);
+            assert_eq!(store.cache.configuration.speed_smoothing, 3);
+            assert_relative_eq!(store.cache.display.speed_dead_band, 0.08);
+            assert_eq!(
+                store.cache.configuration.default_display_page,
+                default_display
+            );
         }
 
         #[test]
-        fn when_button_is_held_for_60_seconds_it_enters_configuration_mode() {
-            let mut store = init_store();
-            let input = InputSnapshot::default();
+        fn when_position_crossfade_is_configured_quantized_heads_use_it() {
+            let (mut store, mut input) = init_store();
 
-            hold_button(&mut store, input);
+            input.button = true;
+            input.head[0].position = 1.0;
+            apply_input_snapshot(&mut store, input);
+            apply_input_snapshot(&mut store, input);
+            input.button = false;
+            apply_input_snapshot(&mut store, input);
+            click_button(&mut store, input);
+            assert_eq!(store.cache.configuration.position_crossfade, 4);
+            assert_eq!(store.cache.configuration.quantization_grid, 0);
 
-            assert!(matches!(store.state, State::Configuring(_)));
+            store.cache.configuration.quantization_grid = 1;
+            let attributes = store.apply_input_snapshot(input).dsp_attributes;
+
+            assert_relative_eq!(attributes.position_crossfade.unwrap(), 0.3);
         }
 
         #[test]
-        fn when_button_is_held_but_pots_are_active_it_does_not_enter_configuration_mode() {
-            let mut store = init_store();
-            let mut input = InputSnapshot::default();
+        fn when_tap_mapping_is_configured_it_is_kept_after_leaving_the_menu() {
+            let (mut store, mut input) = init_store();
 
             input.button = true;
-            for _ in 0..1000 {
-                store.apply_input_snapshot(input);
-                store.tick();
-            }
-            input.pre_amp = 1.0;
-            for _ in 0..6 * 1000 {
-                store.apply_input_snapshot(input);
-                store.tick();
-            }
+            input.head[2].position = 1.0;
+            apply_input_snapshot(&mut store, input);
+            apply_input_snapshot(&mut store, input);
             input.button = false;
-            store.apply_input_snapshot(input);
-            store.tick();
+            apply_input_snapshot(&mut store, input);
+            click_button(&mut store, input);
 
-            assert!(matches!(store.state, State::Normal));
+            assert_eq!(store.state, State::Normal);
+            assert_eq!(store.cache.configuration.tap_mapping, Some(3));
         }
 
         #[test]
-        fn when_mapped_control_in_unplugged_it_is_unmapped() {
-            let mut store = init_store();
-            let mut input = InputSnapshot::default();
-
-            input.control[0] = None;
-            store.apply_input_snapshot(input);
+        fn when_other_position_is_turned_with_button_tap_mapping_is_not_changed() {
+            let (mut store, mut input) = init_store();
 
-            input.control[0] = Some(1.0);
-            store.apply_input_snapshot(input);
+            input.button = true;
+            input.head[1].position = 1.0;
+            apply_input_snapshot(&mut store, input);
+            apply_input_snapshot(&mut store, input);
 
-            input.drive = 0.4;
-            store.apply_input_snapshot(input);
+            assert_eq!(store.cache.configuration.tap_mapping, None);
+            let State::Configuring(configuring) = &store.state else {
+                panic!("Expected configuring state");
+            };
+            assert_eq!(configuring.draft.tap_mapping, None);
+        }
 
-            assert_eq!(store.cache.mapping[0], AttributeIdentifier::Drive);
+        #[test]
+        fn when_click_level_is_configured_it_is_passed_to_dsp() {
+            let (mut store, mut input) = init_store();
 
-            input.control[0] = None;
-            store.apply_input_snapshot(input);
+            input.button = true;
+            input.dry_wet = 0.6;
+            apply_input_snapshot(&mut store, input);
+            apply_input_snapshot(&mut store, input);
+            input.button = false;
+            apply_input_snapshot(&mut store, input);
+            click_button(&mut store, input);
+            let attributes = store.apply_input_snapshot(input).dsp_attributes;
 
-            assert_eq!(store.cache.mapping[0], AttributeIdentifier::None);
+            assert_eq!(store.cache.configuration.click_level, 2);
+            assert_relative_eq!(attributes.click_level, 0.25);
+            assert!(!store.cache.configuration.trails);
         }
-    }
 
-    #[cfg(test)]
-    mod given_configuration_mode {
-        use super::*;
+        #[test]
+        fn when_rhythm_is_tapped_heads_land_on_its_onsets() {
+            let (mut store, mut input) = init_store();
 
-        fn init_store() -> (Store, InputSnapshot) {
-            let mut store = Store::new();
-            let input = InputSnapshot::default();
+            input.head[1].position = 0.9;
+            apply_input_snapshot(&mut store, input);
+            click_button(&mut store, input);
+            assert_eq!(store.cache.configuration.tap_mode, TapMode::Rhythm);
 
-            hold_button(&mut store, input);
+            input.speed = 0.875;
+            apply_input_snapshot(&mut store, input);
+            let length = store.cache.attributes.speed;
 
-            (store, input)
-        }
+            for interval in [250, 125, 375, 250] {
+                tap_button(&mut store, input, interval);
+            }
+            click_button(&mut store, input);
+            apply_input_snapshot(&mut store, input);
 
-        fn apply_input_snapshot(store: &mut Store, input: InputSnapshot) {
-            // NOTE: Applying it 32 times makes sure that pot smoothening is
-            // not affecting following asserts.
-            for _ in 0..32 {
-                store.apply_input_snapshot(input);
-                store.tick();
+            for (head, onset) in store
+                .cache
+                .attributes
+                .head
+                .iter()
+                .zip([0.25, 0.375, 0.75, 1.0])
+            {
+                assert_relative_eq!(head.position, onset / length, epsilon = 0.002);
             }
+            assert!(store.cache.tapped_tempo.is_none());
         }
 
         #[test]
-        fn when_clicks_button_it_saves_configuration_and_enters_normal_mode() {
+        fn when_gate_head_is_configured_impulse_output_follows_its_gate() {
             let (mut store, mut input) = init_store();
 
-            input.speed = 1.0;
+            input.pre_amp = 0.3;
             apply_input_snapshot(&mut store, input);
-
+            assert!(matches!(
+                store.cache.display.active_screen(),
+                Screen::Dialog(DialogScreen::Configuration(ConfigurationScreen::GateHead(
+                    Some(0)
+                )))
+            ));
             click_button(&mut store, input);
+            assert_eq!(store.cache.configuration.gate_head, Some(0));
+
+            store.apply_dsp_reaction(DSPReaction {
+                head_gates: [true, false, false, false],
+                ..DSPReaction::default()
+            });
+            for _ in 0..200 {
+                assert!(store.tick().impulse_trigger);
+            }
 
-            assert_eq!(
-                store.cache.configuration.default_display_page,
-                DisplayPage::Heads
-            );
+            store.apply_dsp_reaction(DSPReaction {
+                delay_impulse: true,
+                head_gates: [false, true, true, true],
+                ..DSPReaction::default()
+            });
+            assert!(!store.tick().impulse_trigger);
         }
 
         #[test]
-        fn when_does_not_change_attribute_it_keeps_the_previously_set_value() {
+        fn when_tape_age_is_configured_it_degrades_the_tape() {
             let (mut store, mut input) = init_store();
 
-            input.speed = 1.0;
+            input.wow_flut = 1.0;
             apply_input_snapshot(&mut store, input);
             click_button(&mut store, input);
+            input.tone = 0.5;
+            input.wow_flut = 0.5;
+            apply_input_snapshot(&mut store, input);
+            let worn = store.apply_input_snapshot(input).dsp_attributes;
+
+            let mut pristine_store = Store::new();
+            apply_input_snapshot(&mut pristine_store, input);
+            let pristine = pristine_store.apply_input_snapshot(input).dsp_attributes;
+
+            assert_eq!(store.cache.configuration.tape_age, 7);
+            assert!(worn.wow > pristine.wow);
+            assert!(worn.flutter_depth > pristine.flutter_depth);
+            assert!(worn.flutter_chance > pristine.flutter_chance);
+            assert!(worn.tone < 0.4);
+            assert_relative_eq!(pristine.tone, 0.5);
+        }
 
-            assert_eq!(
-                store.cache.configuration.default_display_page,
-                DisplayPage::Heads
-            );
-
-            input.speed = 0.0;
+        #[test]
+        fn when_spread_is_configured_heads_fan_out_symmetrically() {
+            let (mut store, mut input) = init_store();
+            for (i, pan) in [0.3, 0.4, 0.2, 0.9].iter().enumerate() {
+                input.head[i].pan = *pan;
+            }
+            click_button(&mut store, input);
             apply_input_snapshot(&mut store, input);
+            let individual = store.apply_input_snapshot(input).dsp_attributes;
+            assert_relative_eq!(individual.head[3].pan, 0.9, epsilon = 0.01);
+
+            let mut previous_width = 0.0;
+            for bias in [0.25, 0.5, 0.75, 1.0] {
+                hold_button(&mut store, input);
+                input.bias = bias;
+                apply_input_snapshot(&mut store, input);
+                click_button(&mut store, input);
+                let pans = store
+                    .apply_input_snapshot(input)
+                    .dsp_attributes
+                    .head
+                    .map(|head| head.pan);
+
+                assert_relative_eq!(pans[0] + pans[3], 1.0);
+                assert_relative_eq!(pans[1] + pans[2], 1.0);
+                assert!(pans[0] < pans[1] && pans[1] < pans[2] && pans[2] < pans[3]);
+                let width = pans[3] - pans[0];
+                assert!(width > previous_width);
+                previous_width = width;
+            }
+            assert_relative_eq!(previous_width, 1.0);
+        }
 
-            hold_button(&mut store, input);
+        #[test]
+        fn when_quantization_grid_is_configured_heads_snap_to_it() {
+            let (mut store, mut input) = init_store();
 
+            input.head[0].position = 0.2;
             apply_input_snapshot(&mut store, input);
             click_button(&mut store, input);
+            assert_eq!(store.cache.configuration.quantization_grid, 1);
 
-            assert_eq!(
-                store.cache.configuration.default_display_page,
-                DisplayPage::Heads
-            );
+            input.head[1].position = 0.5;
+            input.head[2].position = 0.7;
+            input.head[3].position = 1.0;
+            apply_input_snapshot(&mut store, input);
+
+            assert_head_positions(&store, [0.0, 1.0 / 3.0, 2.0 / 3.0, 2.0 / 3.0]);
+        }
+
+        #[test]
+        fn when_heads_are_quantized_configured_crossfade_is_passed_to_dsp() {
+            let (mut store, input) = init_store();
+            store.cache.configuration.quantization_grid = 1;
+            store.cache.configuration.position_crossfade = 0;
+
+            let attributes = store.apply_input_snapshot(input).dsp_attributes;
+
+            assert_relative_eq!(attributes.position_crossfade.unwrap(), 0.005);
+        }
+
+        #[test]
+        fn when_heads_are_not_quantized_default_crossfade_is_kept() {
+            let (mut store, input) = init_store();
+            store.cache.configuration.position_crossfade = 0;
+
+            let attributes = store.apply_input_snapshot(input).dsp_attributes;
+
+            assert!(attributes.position_crossfade.is_none());
+        }
+
+        #[test]
+        fn when_click_is_configured_its_level_is_passed_to_dsp() {
+            let (mut store, input) = init_store();
+            let attributes = store.apply_input_snapshot(input).dsp_attributes;
+            assert_relative_eq!(attributes.click_level, 0.0);
+
+            store.cache.configuration.click_level = 2;
+            let attributes = store.apply_input_snapshot(input).dsp_attributes;
+            assert_relative_eq!(attributes.click_level, 0.25);
+        }
+
+        #[test]
+        fn when_cv_attenuversion_is_configured_it_scales_control_input() {
+            for (pot, expected) in [
+                (1.0, 2.0),
+                (0.7, 1.0),
+                (0.5, 0.0),
+                (0.3, -1.0),
+                (0.05, -2.0),
+            ] {
+                let mut save = Store::new().cache.save();
+                save.mapping[0] = AttributeIdentifier::Drive;
+                let mut store = Store::from(save);
+                let mut input = InputSnapshot::default();
+                input.control[0] = Some(2.0);
+                for _ in 0..40 {
+                    store.warm_up(input);
+                }
+                hold_button(&mut store, input);
+
+                input.head[0].feedback = pot;
+                apply_input_snapshot(&mut store, input);
+                click_button(&mut store, input);
+
+                let value = store
+                    .control_value_for_attribute(AttributeIdentifier::Drive)
+                    .unwrap();
+                assert_relative_eq!(value, expected);
+            }
+        }
+
+        #[test]
+        fn when_cv_attenuversion_is_not_configured_it_keeps_control_input_intact() {
+            let mut save = Store::new().cache.save();
+            save.mapping[2] = AttributeIdentifier::Drive;
+            let mut store = Store::from(save);
+            let mut input = InputSnapshot::default();
+            input.control[2] = Some(2.0);
+            for _ in 0..40 {
+                store.warm_up(input);
+            }
+            store.apply_input_snapshot(input);
+
+            let value = store
+                .control_value_for_attribute(AttributeIdentifier::Drive)
+                .unwrap();
+            assert_relative_eq!(value, 2.0);
         }
 
         #[test]
@@ -1722,6 +4235,55 @@ mod tests {
             assert_animation(&mut store, &[8888, 0000, 8888, 0000]);
         }
 
+        #[test]
+        fn when_left_idle_for_too_long_it_cancels_calibration_and_returns_to_normal() {
+            let (mut store, input) = init_store(1);
+            assert_eq!(
+                store.state,
+                State::Calibrating(StateCalibrating {
+                    input: 0,
+                    phase: CalibrationPhase::Octave1
+                })
+            );
+
+            for _ in 0..CALIBRATION_TIMEOUT - 10 {
+                store.apply_input_snapshot(input);
+            }
+            assert!(matches!(store.state, State::Calibrating(_)));
+
+            for _ in 0..20 {
+                store.apply_input_snapshot(input);
+            }
+            assert_eq!(store.state, State::Normal);
+            assert_eq!(store.queue.len(), 0);
+            assert_animation(&mut store, &[8888, 0000, 8888, 0000]);
+
+            store.apply_input_snapshot(input);
+            assert_eq!(store.state, State::Normal);
+        }
+
+        #[test]
+        fn when_button_is_clicked_it_restarts_calibration_timeout() {
+            let (mut store, mut input) = init_store(1);
+
+            for _ in 0..CALIBRATION_TIMEOUT - 10 {
+                store.apply_input_snapshot(input);
+            }
+            input.control[0] = Some(1.3);
+            click_button(&mut store, input);
+            for _ in 0..CALIBRATION_TIMEOUT - 10 {
+                store.apply_input_snapshot(input);
+            }
+
+            assert!(matches!(
+                store.state,
+                State::Calibrating(StateCalibrating {
+                    input: 0,
+                    phase: CalibrationPhase::Octave2(_)
+                })
+            ));
+        }
+
         #[test]
         fn when_calibrated_control_is_unplugged_it_retains_calibration() {
             let (mut store, mut input) = init_store(1);
@@ -1760,6 +4322,30 @@ mod tests {
         );
     }
 
+    #[test]
+    fn given_clock_mapped_to_speed_it_reports_phase_within_beat() {
+        let mut save = Store::new().cache.save();
+        save.mapping[1] = AttributeIdentifier::Speed;
+        let mut store = Store::from(save);
+        let mut input = InputSnapshot::default();
+
+        clock_trigger(&mut store, 1, input, 2000);
+        clock_trigger(&mut store, 1, input, 2000);
+        assert!(store.clock_phase().is_none());
+
+        clock_trigger(&mut store, 1, input, 2000);
+        clock_trigger(&mut store, 1, input, 2000);
+        clock_trigger(&mut store, 1, input, 500);
+
+        input.control[1] = Some(0.5);
+        assert_relative_eq!(store.clock_phase().unwrap(), 0.25, epsilon = 0.001);
+        for _ in 0..1000 {
+            store.apply_input_snapshot(input);
+            store.tick();
+        }
+        assert_relative_eq!(store.clock_phase().unwrap(), 0.75, epsilon = 0.001);
+    }
+
     #[test]
     fn when_clock_within_toleration_passes_it_detects_tempo() {
         let mut store = Store::new();
@@ -0,0 +1,66 @@
+//! Driving the control store from a host program, the way a simulator would.
+
+use kaseta_control::{ApplyInputSnapshotResult, InputSnapshot, Store, StoreStatus};
+use kaseta_dsp::processor::Reaction as DSPReaction;
+
+fn warmed_up_store(input: InputSnapshot) -> Store {
+    let mut store = Store::new();
+    for _ in 0..100 {
+        store.warm_up(input);
+    }
+    store
+}
+
+// NOTE: Pots are smoothened, applying the snapshot repeatedly lets them
+// settle.
+fn settle(store: &mut Store, input: InputSnapshot) -> ApplyInputSnapshotResult {
+    for _ in 0..31 {
+        store.apply_input_snapshot(input);
+        store.tick();
+    }
+    let result = store.apply_input_snapshot(input);
+    store.tick();
+    result
+}
+
+#[test]
+fn when_pot_is_turned_dsp_attributes_follow() {
+    let mut input = InputSnapshot::default();
+    let mut store = warmed_up_store(input);
+    assert_eq!(store.status(), StoreStatus::Normal);
+
+    let dark = settle(&mut store, input).dsp_attributes.tone;
+    input.tone = 1.0;
+    let bright = settle(&mut store, input).dsp_attributes.tone;
+
+    assert!(bright > dark);
+}
+
+#[test]
+fn when_dsp_reports_impulse_it_is_sent_to_the_trigger_output() {
+    let input = InputSnapshot::default();
+    let mut store = warmed_up_store(input);
+    settle(&mut store, input);
+
+    store.apply_dsp_reaction(DSPReaction {
+        delay_impulse: true,
+        ..DSPReaction::default()
+    });
+    let output = store.tick();
+
+    assert!(output.impulse_trigger);
+}
+
+#[test]
+fn when_control_is_plugged_status_reports_its_mapping() {
+    let mut input = InputSnapshot::default();
+    let mut store = warmed_up_store(input);
+
+    input.control[1] = Some(1.0);
+    settle(&mut store, input);
+    assert_eq!(store.status(), StoreStatus::Mapping(1));
+
+    input.drive = 1.0;
+    settle(&mut store, input);
+    assert_eq!(store.status(), StoreStatus::Normal);
+}
@@ -6,6 +6,7 @@ use sirena::memory_manager::MemoryManager;
 
 use kaseta_dsp::processor::{Attributes, AttributesHead, Processor};
 use kaseta_dsp::random::Random;
+use kaseta_dsp::BLOCK_SIZE;
 
 struct KasetaRandom;
 
@@ -24,9 +25,9 @@ fn criterion_benchmark(c: &mut Criterion) {
     let mut sdram_manager = MemoryManager::from(unsafe { &mut MEMORY[..] });
     let mut rng = rand::thread_rng();
 
-    let mut buffer = [(0.0, 0.0); 32];
+    let mut buffer = [(0.0, 0.0); BLOCK_SIZE];
     #[allow(clippy::cast_precision_loss)]
-    let mut processor = Processor::new(FS as f32, &mut stack_manager, &mut sdram_manager);
+    let mut processor = Processor::new(FS as f32, &mut stack_manager, &mut sdram_manager).unwrap();
 
     c.bench_function("Bench", |b| {
         b.iter(|| {
@@ -36,17 +37,27 @@ fn criterion_benchmark(c: &mut Criterion) {
                 saturation: 0.5,
                 bias: 0.5,
                 dry_wet: 0.5,
+                auto_makeup: false,
+                tape_bypass: false,
                 wow: 1.0,
                 flutter_depth: 1.0,
                 flutter_chance: 1.0,
                 speed: 0.5,
                 tone: 0.5,
+                tone_filter_type: 0,
+                tone_slew: None,
+                tone_loudness_compensation: false,
+                tone_cutoff: None,
                 head: [AttributesHead {
                     position: 0.1,
                     volume: 1.0,
                     feedback: 1.0,
                     pan: 0.4,
+                    fine_offset_ms: 0.0,
+                    feedback_invert: false,
+                    feedback_highpass: 0.0,
                 }; 4],
+                output_level: 1.0,
                 ..Attributes::default()
             });
 
@@ -0,0 +1,108 @@
+//! Level meter of low, mid and high band of the output.
+//!
+//! Bands are split by one-pole filters. Their slopes are shallow, so the
+//! bands overlap, but that is good enough for a coarse display while
+//! costing only a couple of multiplications per sample.
+
+use crate::one_pole_filter::OnePoleFilter;
+
+// Boundaries between the low and mid, and the mid and high band.
+const LOW_CUTOFF: f32 = 250.0;
+const HIGH_CUTOFF: f32 = 4000.0;
+
+// Slow enough to ignore single cycles of the lowest band, fast enough to
+// follow the rhythm.
+const AVERAGE_CUTOFF: f32 = 10.0;
+
+#[derive(Debug)]
+#[cfg_attr(feature = "defmt", derive(defmt::Format))]
+pub struct BandMeter {
+    low_split: OnePoleFilter,
+    high_split: OnePoleFilter,
+    power: [OnePoleFilter; 3],
+}
+
+#[derive(Default, Clone, Copy, Debug)]
+#[cfg_attr(feature = "defmt", derive(defmt::Format))]
+pub struct Reaction {
+    /// Mean power of the low, mid and high band.
+    pub band_power: [f32; 3],
+}
+
+impl BandMeter {
+    pub fn new(sample_rate: f32) -> Self {
+        Self {
+            low_split: OnePoleFilter::new(sample_rate, LOW_CUTOFF),
+            high_split: OnePoleFilter::new(sample_rate, HIGH_CUTOFF),
+            power: [
+                OnePoleFilter::new(sample_rate, AVERAGE_CUTOFF),
+                OnePoleFilter::new(sample_rate, AVERAGE_CUTOFF),
+                OnePoleFilter::new(sample_rate, AVERAGE_CUTOFF),
+            ],
+        }
+    }
+
+    pub fn process(&mut self, buffer_left: &[f32], buffer_right: &[f32]) -> Reaction {
+        let mut band_power = [0.0; 3];
+        for (l, r) in buffer_left.iter().zip(buffer_right) {
+            let x = (l + r) * 0.5;
+            let low = self.low_split.tick(x);
+            let below_high = self.high_split.tick(x);
+            let bands = [low, below_high - low, x - below_high];
+            for ((power, filter), band) in band_power.iter_mut().zip(&mut self.power).zip(bands) {
+                *power = filter.tick(band * band);
+            }
+        }
+        Reaction { band_power }
+    }
+
+    pub fn reset(&mut self) {
+        self.low_split.reset();
+        self.high_split.reset();
+        for power in &mut self.power {
+            power.reset();
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    use core::f32::consts::PI;
+
+    use libm::sinf;
+
+    const SAMPLE_RATE: f32 = 48_000.0;
+
+    fn settled_band_power(meter: &mut BandMeter, frequency: f32) -> [f32; 3] {
+        let mut reaction = Reaction::default();
+        for block in 0..500 {
+            let buffer: [f32; 32] = core::array::from_fn(|i| {
+                sinf(2.0 * PI * frequency * (block * 32 + i) as f32 / SAMPLE_RATE)
+            });
+            reaction = meter.process(&buffer, &buffer);
+        }
+        reaction.band_power
+    }
+
+    #[test]
+    fn given_low_tone_low_band_is_the_loudest() {
+        let mut meter = BandMeter::new(SAMPLE_RATE);
+
+        let [low, mid, high] = settled_band_power(&mut meter, 60.0);
+
+        assert!(low > mid * 2.0, "Low {low}, mid {mid}");
+        assert!(low > high * 2.0, "Low {low}, high {high}");
+    }
+
+    #[test]
+    fn given_high_tone_high_band_is_the_loudest() {
+        let mut meter = BandMeter::new(SAMPLE_RATE);
+
+        let [low, mid, high] = settled_band_power(&mut meter, 12_000.0);
+
+        assert!(high > mid * 2.0, "High {high}, mid {mid}");
+        assert!(high > low * 2.0, "High {high}, low {low}");
+    }
+}
@@ -0,0 +1,304 @@
+//! Capture a short window of input and loop it as a one-shot stutter.
+//!
+//! The input is continuously recorded into a ring buffer, independent of the
+//! main delay. When triggered, the last captured window is looped given number
+//! of times before the live input takes over again. Each pass of the loop is
+//! crossfaded with the live input to avoid clicks on its boundaries.
+
+use sirena::memory_manager::MemoryManager;
+
+use crate::math;
+use crate::ring_buffer::RingBuffer;
+
+// Limited by the space left in the main memory after the wow and flutter
+// buffer gets allocated there. The buffer cannot be moved to SDRAM: the tape
+// of the delay is rounded up to a power of two, which at 48 kHz takes all of
+// its 64 MB.
+const MAX_LENGTH_IN_SECONDS: f32 = 0.25;
+
+const FADE_IN_SECONDS: f32 = 0.002;
+
+#[derive(Debug)]
+#[cfg_attr(feature = "defmt", derive(defmt::Format))]
+pub struct Capture {
+    sample_rate: f32,
+    buffer: RingBuffer,
+    fade: usize,
+    window: usize,
+    repeats: usize,
+    voice: Option<Voice>,
+    fading_voice: Option<(Voice, usize)>,
+}
+
+#[derive(Default, Clone, Copy, Debug)]
+#[cfg_attr(feature = "defmt", derive(defmt::Format))]
+pub struct Attributes {
+    pub trigger: bool,
+    pub length: f32,
+    pub repeats: usize,
+}
+
+#[derive(Clone, Copy, Debug)]
+#[cfg_attr(feature = "defmt", derive(defmt::Format))]
+struct Voice {
+    window: usize,
+    duration: usize,
+    elapsed: usize,
+    recorded: usize,
+}
+
+impl Capture {
+    /// Returns `None` if there is not enough space in the memory manager to
+    /// allocate a buffer of `MAX_LENGTH_IN_SECONDS`.
+    pub fn new(sample_rate: f32, memory_manager: &mut MemoryManager) -> Option<Self> {
+        let buffer_size = math::upper_power_of_two((sample_rate * MAX_LENGTH_IN_SECONDS) as usize);
+        Some(Self {
+            sample_rate,
+            buffer: RingBuffer::from(memory_manager.allocate(buffer_size)?),
+            fade: (sample_rate * FADE_IN_SECONDS) as usize,
+            window: 0,
+            repeats: 1,
+            voice: None,
+            fading_voice: None,
+        })
+    }
+
+    pub fn process(&mut self, buffer: &mut [f32]) {
+        for x in buffer.iter_mut() {
+            let input = *x;
+
+            let mut wet = 0.0;
+            let mut amount = 0.0;
+
+            if let Some(voice) = &mut self.voice {
+                let envelope = voice.envelope(self.fade);
+                wet += self.buffer.peek(voice.age()) * envelope;
+                amount += envelope;
+                voice.elapsed += 1;
+                if voice.is_finished() {
+                    self.voice = None;
+                }
+            }
+
+            if let Some((voice, remaining)) = &mut self.fading_voice {
+                let envelope = voice.envelope(self.fade) * (*remaining as f32 / self.fade as f32);
+                wet += self.buffer.peek(voice.age()) * envelope;
+                amount += envelope;
+                voice.elapsed += 1;
+                *remaining -= 1;
+                if *remaining == 0 || voice.is_finished() {
+                    self.fading_voice = None;
+                }
+            }
+
+            self.record(input);
+
+            *x = wet + input * (1.0 - amount).max(0.0);
+        }
+    }
+
+    /// Stop any playback and forget the captured input.
+    pub fn reset(&mut self) {
+        self.voice = None;
+        self.fading_voice = None;
+        self.buffer.clear();
+    }
+
+    // NOTE: Recording is paused when it would overwrite a window that is still
+    // being played back. Retriggering in such a case captures the last window
+    // recorded before the pause.
+    fn record(&mut self, value: f32) {
+        let capacity = self.buffer.len() - 1;
+        let voices = [
+            self.voice.as_ref(),
+            self.fading_voice.as_ref().map(|(v, _)| v),
+        ];
+        if voices
+            .iter()
+            .flatten()
+            .any(|voice| voice.window + voice.recorded >= capacity)
+        {
+            return;
+        }
+
+        self.buffer.write(value);
+
+        if let Some(voice) = &mut self.voice {
+            voice.recorded += 1;
+        }
+        if let Some((voice, _)) = &mut self.fading_voice {
+            voice.recorded += 1;
+        }
+    }
+
+    pub fn set_attributes(&mut self, attributes: Attributes) {
+        self.window = ((attributes.length * self.sample_rate) as usize).min(self.buffer.len() / 2);
+        self.repeats = attributes.repeats.max(1);
+
+        if attributes.trigger {
+            self.trigger();
+        }
+    }
+
+    fn trigger(&mut self) {
+        // NOTE: A window shorter than both fades would never reach full volume.
+        if self.window <= self.fade * 2 {
+            return;
+        }
+
+        if let Some(voice) = self.voice.take() {
+            if self.fade > 0 {
+                self.fading_voice = Some((voice, self.fade));
+            }
+        }
+
+        self.voice = Some(Voice {
+            window: self.window,
+            duration: self.window * self.repeats,
+            elapsed: 0,
+            recorded: 0,
+        });
+    }
+}
+
+impl Voice {
+    // NOTE: The buffer keeps recording while the voice plays, so the captured
+    // window moves further back with every recorded sample.
+    fn age(&self) -> usize {
+        let position = self.elapsed % self.window;
+        self.window - 1 - position + self.recorded
+    }
+
+    fn envelope(&self, fade: usize) -> f32 {
+        if fade == 0 {
+            return 1.0;
+        }
+        let position = self.elapsed % self.window;
+        let distance_from_edge = position.min(self.window - 1 - position);
+        (distance_from_edge as f32 / fade as f32).min(1.0)
+    }
+
+    fn is_finished(&self) -> bool {
+        self.elapsed >= self.duration
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::BLOCK_SIZE;
+
+    const SAMPLE_RATE: f32 = 2000.0;
+
+    fn ramp(capture: &mut Capture, samples: usize) {
+        let mut buffer = [0.0; BLOCK_SIZE];
+        let mut value = 0.0;
+        for _ in 0..samples / buffer.len() {
+            for x in buffer.iter_mut() {
+                value += 1.0;
+                *x = value;
+            }
+            capture.process(&mut buffer);
+        }
+    }
+
+    fn process(capture: &mut Capture, buffer: &mut [f32]) {
+        for chunk in buffer.chunks_mut(BLOCK_SIZE) {
+            capture.process(chunk);
+        }
+    }
+
+    #[test]
+    fn when_triggered_it_plays_back_captured_window() {
+        let mut memory_manager = memory_manager!(512);
+        let mut capture = Capture::new(SAMPLE_RATE, &mut memory_manager).unwrap();
+        ramp(&mut capture, 128);
+
+        capture.set_attributes(Attributes {
+            trigger: true,
+            length: 0.032,
+            repeats: 1,
+        });
+        let mut buffer = [0.0; 64];
+        process(&mut capture, &mut buffer);
+
+        // NOTE: Samples around the boundaries are faded with the live input.
+        for (i, x) in buffer.iter().enumerate().skip(4).take(56) {
+            assert_relative_eq!(*x, 65.0 + i as f32);
+        }
+    }
+
+    #[test]
+    fn when_repeating_it_plays_the_window_again() {
+        let mut memory_manager = memory_manager!(512);
+        let mut capture = Capture::new(SAMPLE_RATE, &mut memory_manager).unwrap();
+        ramp(&mut capture, 128);
+
+        capture.set_attributes(Attributes {
+            trigger: true,
+            length: 0.016,
+            repeats: 2,
+        });
+        // NOTE: The window is 32 samples long.
+        let mut first_pass = [0.0; 32];
+        process(&mut capture, &mut first_pass);
+        let mut second_pass = [0.0; 32];
+        process(&mut capture, &mut second_pass);
+
+        for i in 4..28 {
+            assert_relative_eq!(first_pass[i], 97.0 + i as f32);
+            assert_relative_eq!(second_pass[i], 97.0 + i as f32);
+        }
+    }
+
+    #[test]
+    fn when_playback_finishes_it_releases_to_live_input() {
+        let mut memory_manager = memory_manager!(512);
+        let mut capture = Capture::new(SAMPLE_RATE, &mut memory_manager).unwrap();
+        ramp(&mut capture, 128);
+
+        let mut attributes = Attributes {
+            trigger: true,
+            length: 0.016,
+            repeats: 1,
+        };
+        capture.set_attributes(attributes);
+        let mut buffer = [0.0; 32];
+        process(&mut capture, &mut buffer);
+
+        attributes.trigger = false;
+        capture.set_attributes(attributes);
+        let mut buffer = [-1.0; 32];
+        process(&mut capture, &mut buffer);
+
+        for x in &buffer {
+            assert_relative_eq!(*x, -1.0);
+        }
+    }
+
+    #[test]
+    fn when_retriggered_during_playback_it_restarts_with_a_fresh_window() {
+        let mut memory_manager = memory_manager!(512);
+        let mut capture = Capture::new(SAMPLE_RATE, &mut memory_manager).unwrap();
+        ramp(&mut capture, 256);
+
+        let attributes = Attributes {
+            trigger: true,
+            length: 0.032,
+            repeats: 1,
+        };
+        capture.set_attributes(attributes);
+        let mut buffer = [0.0; 32];
+        process(&mut capture, &mut buffer);
+        let last_before_retrigger = buffer[31];
+
+        capture.set_attributes(attributes);
+        let mut buffer = [0.0; 32];
+        process(&mut capture, &mut buffer);
+
+        assert!((buffer[0] - last_before_retrigger).abs() < 2.0);
+        for (i, x) in buffer.iter().enumerate().skip(4) {
+            assert_relative_eq!(*x, 225.0 + i as f32);
+        }
+    }
+}
@@ -1,8 +1,20 @@
 //! Simple hard-clipper.
 
-#[derive(Default, Debug)]
+// Full scale of the codec.
+const DEFAULT_THRESHOLD: f32 = 1.0;
+
+#[derive(Debug)]
+#[cfg_attr(feature = "defmt", derive(defmt::Format))]
+pub struct Clipper {
+    threshold: f32,
+}
+
+#[derive(Default, Clone, Copy, Debug)]
 #[cfg_attr(feature = "defmt", derive(defmt::Format))]
-pub struct Clipper;
+pub struct Attributes {
+    /// Ceiling of the output. When `None`, the full scale is used.
+    pub threshold: Option<f32>,
+}
 
 #[derive(Default, Clone, Copy, Debug)]
 #[cfg_attr(feature = "defmt", derive(defmt::Format))]
@@ -10,20 +22,64 @@ pub struct Reaction {
     pub clipping: bool,
 }
 
+impl Default for Clipper {
+    fn default() -> Self {
+        Self {
+            threshold: DEFAULT_THRESHOLD,
+        }
+    }
+}
+
 impl Clipper {
-    pub fn process(buffer: &mut [f32]) -> Reaction {
+    pub fn process(&self, buffer: &mut [f32]) -> Reaction {
         let mut reaction = Reaction::default();
 
         for x in buffer.iter_mut() {
-            if *x < -1.0 {
-                *x = -1.0;
+            if *x < -self.threshold {
+                *x = -self.threshold;
                 reaction.clipping = true;
-            } else if *x > 1.0 {
-                *x = 1.0;
+            } else if *x > self.threshold {
+                *x = self.threshold;
                 reaction.clipping = true;
             }
         }
 
         reaction
     }
+
+    pub fn set_attributes(&mut self, attributes: Attributes) {
+        self.threshold = attributes
+            .threshold
+            .map_or(DEFAULT_THRESHOLD, |t| t.clamp(0.0, DEFAULT_THRESHOLD));
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn when_threshold_is_not_set_it_clips_at_full_scale() {
+        let clipper = Clipper::default();
+        let mut buffer = [2.0, -2.0, 0.9];
+
+        let reaction = clipper.process(&mut buffer);
+
+        assert_eq!(buffer, [1.0, -1.0, 0.9]);
+        assert!(reaction.clipping);
+    }
+
+    #[test]
+    fn when_threshold_is_set_it_clips_below_full_scale() {
+        let mut clipper = Clipper::default();
+        clipper.set_attributes(Attributes {
+            threshold: Some(0.5),
+        });
+        let mut buffer = [0.9, -0.9, 0.4];
+
+        let reaction = clipper.process(&mut buffer);
+
+        assert_eq!(buffer, [0.5, -0.5, 0.4]);
+        assert!(reaction.clipping);
+    }
 }
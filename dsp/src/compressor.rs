@@ -2,12 +2,13 @@
 //!
 //! Based on the README of <https://github.com/p-hlp/CTAGDRC>.
 
-use libm::{expf, fabsf};
+use libm::{expf, fabsf, powf, sqrtf};
 
 use crate::decibels;
 
 const ATTACK_IN_SECONDS: f32 = 0.01;
 const RELEASE_IN_SECONDS: f32 = 0.14;
+const RMS_WINDOW_IN_SECONDS: f32 = 0.01;
 const TRESHOLD: f32 = -6.0; // This is 0.5 amplitude
 const RATIO: f32 = 16.0;
 const SLOPE: f32 = 1.0 / RATIO - 1.0;
@@ -17,27 +18,82 @@ const KNEE_HALF: f32 = KNEE / 2.0;
 #[derive(Default, Debug)]
 #[cfg_attr(feature = "defmt", derive(defmt::Format))]
 pub struct Compressor {
+    sample_rate: f32,
     n1: f32,
     alpha_attack: f32,
     alpha_release: f32,
+    detection: Detection,
+    alpha_rms: f32,
+    mean_square: f32,
+}
+
+#[derive(Default, Clone, Copy, Debug)]
+#[cfg_attr(feature = "defmt", derive(defmt::Format))]
+pub struct Attributes {
+    /// Attack in seconds. When `None`, the default smooth attack is used.
+    pub attack: Option<f32>,
+    /// Release in seconds. When `None`, the default release is used.
+    pub release: Option<f32>,
+    pub detection: Detection,
+}
+
+/// Peak detection reacts to every transient, while RMS follows the average
+/// energy over `RMS_WINDOW_IN_SECONDS`, reacting slower but more musically.
+#[derive(Default, Clone, Copy, Debug, PartialEq)]
+#[cfg_attr(feature = "defmt", derive(defmt::Format))]
+pub enum Detection {
+    #[default]
+    Peak,
+    Rms,
 }
 
 impl Compressor {
     #[must_use]
     pub fn new(sample_rate: f32) -> Self {
         Self {
+            sample_rate,
             n1: 0.0,
-            alpha_attack: expf(-1.0 / (sample_rate * ATTACK_IN_SECONDS)),
-            alpha_release: expf(-1.0 / (sample_rate * RELEASE_IN_SECONDS)),
+            alpha_attack: alpha(sample_rate, ATTACK_IN_SECONDS),
+            alpha_release: alpha(sample_rate, RELEASE_IN_SECONDS),
+            detection: Detection::default(),
+            alpha_rms: alpha(sample_rate, RMS_WINDOW_IN_SECONDS),
+            mean_square: 0.0,
         }
     }
 
+    pub fn reset(&mut self) {
+        self.n1 = 0.0;
+        self.mean_square = 0.0;
+    }
+
+    /// Current reduction of the gain in decibels. It is positive while the
+    /// compressor works, zero when it is idle.
+    pub fn gain_reduction(&self) -> f32 {
+        -self.n1
+    }
+
+    pub fn set_attributes(&mut self, attributes: Attributes) {
+        let attack = attributes.attack.unwrap_or(ATTACK_IN_SECONDS);
+        let release = attributes.release.unwrap_or(RELEASE_IN_SECONDS);
+        self.alpha_attack = alpha(self.sample_rate, attack);
+        self.alpha_release = alpha(self.sample_rate, release);
+        self.detection = attributes.detection;
+    }
+
     pub fn process(&mut self, buffer_left: &mut [f32], buffer_right: &mut [f32]) {
         for (l, r) in buffer_left.iter_mut().zip(buffer_right) {
             let l_abs = fabsf(*l);
             let r_abs = fabsf(*r);
             let max = if l_abs > r_abs { l_abs } else { r_abs };
-            let level = if max > 0.2 { max } else { 0.2 };
+            let detected = match self.detection {
+                Detection::Peak => max,
+                Detection::Rms => {
+                    self.mean_square =
+                        self.alpha_rms * self.mean_square + (1.0 - self.alpha_rms) * max * max;
+                    sqrtf(self.mean_square)
+                }
+            };
+            let level = if detected > 0.2 { detected } else { 0.2 };
             // let level_in_decibels = 20.0 * log10f(level);
             let level_in_decibels = decibels::linear_to_db(level);
 
@@ -63,3 +119,289 @@ impl Compressor {
         }
     }
 }
+
+/// Downward expander reducing hiss during quiet passages.
+///
+/// It works like the compressor in reverse, emulating the decoding side of
+/// a tape noise reduction. Once the program level falls below
+/// `EXPANDER_THRESHOLD`, the gain follows it down with ratio 1:2, so the
+/// noise floor drops while nothing is playing. The gain opens quickly with
+/// the signal and closes slowly after it.
+#[derive(Default, Debug)]
+#[cfg_attr(feature = "defmt", derive(defmt::Format))]
+pub struct Expander {
+    gain: f32,
+    floor: f32,
+    alpha_open: f32,
+    alpha_close: f32,
+    alpha_rms: f32,
+    mean_square: f32,
+}
+
+const EXPANDER_THRESHOLD: f32 = 0.01; // This is -40 dB
+const EXPANDER_MAX_REDUCTION: f32 = 30.0;
+const EXPANDER_OPEN_IN_SECONDS: f32 = 0.002;
+const EXPANDER_CLOSE_IN_SECONDS: f32 = 0.1;
+
+impl Expander {
+    #[must_use]
+    pub fn new(sample_rate: f32) -> Self {
+        Self {
+            gain: 1.0,
+            floor: 1.0,
+            alpha_open: alpha(sample_rate, EXPANDER_OPEN_IN_SECONDS),
+            alpha_close: alpha(sample_rate, EXPANDER_CLOSE_IN_SECONDS),
+            alpha_rms: alpha(sample_rate, RMS_WINDOW_IN_SECONDS),
+            mean_square: 0.0,
+        }
+    }
+
+    pub fn reset(&mut self) {
+        self.gain = 1.0;
+        self.mean_square = 0.0;
+    }
+
+    /// Set the depth of the reduction between 0.0 (off) and 1.0 (up to
+    /// `EXPANDER_MAX_REDUCTION` decibels).
+    pub fn set_amount(&mut self, amount: f32) {
+        self.floor = powf(
+            10.0,
+            -amount.clamp(0.0, 1.0) * EXPANDER_MAX_REDUCTION / 20.0,
+        );
+    }
+
+    pub fn process(&mut self, buffer_left: &mut [f32], buffer_right: &mut [f32]) {
+        // NOTE: When turned off, the gain is still let to open up smoothly
+        // before the expander gets skipped.
+        if self.floor >= 1.0 && self.gain >= 1.0 {
+            return;
+        }
+
+        for (l, r) in buffer_left.iter_mut().zip(buffer_right) {
+            let l_abs = fabsf(*l);
+            let r_abs = fabsf(*r);
+            let max = if l_abs > r_abs { l_abs } else { r_abs };
+            self.mean_square =
+                self.alpha_rms * self.mean_square + (1.0 - self.alpha_rms) * max * max;
+
+            // NOTE: With ratio 1:2, the gain is linearly proportional to the
+            // level below the threshold.
+            let target = (sqrtf(self.mean_square) / EXPANDER_THRESHOLD).clamp(self.floor, 1.0);
+            let alpha = if target > self.gain {
+                self.alpha_open
+            } else {
+                self.alpha_close
+            };
+            self.gain = alpha * self.gain + (1.0 - alpha) * target;
+
+            *l *= self.gain;
+            *r *= self.gain;
+        }
+    }
+}
+
+// NOTE: Time constant shorter than a sample would make the filter unstable.
+fn alpha(sample_rate: f32, time_in_seconds: f32) -> f32 {
+    expf(-1.0 / (sample_rate * time_in_seconds).max(1.0))
+}
+
+#[cfg(test)]
+mod tests {
+    use core::f32::consts::PI;
+
+    use libm::sinf;
+
+    use super::*;
+    use crate::BLOCK_SIZE;
+
+    const SAMPLE_RATE: f32 = 48_000.0;
+
+    // NOTE: Returns number of samples it takes for the gain reduction to reach
+    // 63 % of its final value, matching the time constant of the envelope.
+    fn samples_to_settle(compressor: &mut Compressor, from: f32, to: f32) -> usize {
+        let gain_for = |compressor: &mut Compressor, x: f32| {
+            let mut left = [x];
+            let mut right = [x];
+            compressor.process(&mut left, &mut right);
+            decibels::linear_to_db(left[0] / x)
+        };
+
+        for _ in 0..SAMPLE_RATE as usize {
+            gain_for(compressor, from);
+        }
+        let initial = gain_for(compressor, from);
+
+        let mut gains = [0.0; 48_000];
+        for gain in gains.iter_mut() {
+            *gain = gain_for(compressor, to);
+        }
+        let last = gains[gains.len() - 1];
+
+        let threshold = initial + (last - initial) * (1.0 - expf(-1.0));
+        let falling = last < initial;
+        gains
+            .iter()
+            .position(|gain| {
+                if falling {
+                    *gain <= threshold
+                } else {
+                    *gain >= threshold
+                }
+            })
+            .unwrap()
+            + 1
+    }
+
+    fn compressor_with(attack: f32, release: f32, detection: Detection) -> Compressor {
+        let mut compressor = Compressor::new(SAMPLE_RATE);
+        compressor.set_attributes(Attributes {
+            attack: Some(attack),
+            release: Some(release),
+            detection,
+        });
+        compressor
+    }
+
+    #[test]
+    fn when_peak_detection_is_used_attack_matches_configured_time() {
+        let mut compressor = compressor_with(0.001, 0.1, Detection::Peak);
+
+        let samples = samples_to_settle(&mut compressor, 0.1, 1.0);
+
+        assert!(
+            (47..=49).contains(&samples),
+            "Attack took {samples} samples"
+        );
+    }
+
+    #[test]
+    fn when_peak_detection_is_used_release_matches_configured_time() {
+        let mut compressor = compressor_with(0.001, 0.05, Detection::Peak);
+
+        let samples = samples_to_settle(&mut compressor, 1.0, 0.1);
+
+        assert!(
+            (2390..=2410).contains(&samples),
+            "Release took {samples} samples"
+        );
+    }
+
+    #[test]
+    fn when_rms_detection_is_used_attack_is_slower_than_with_peak() {
+        let mut peak = compressor_with(0.001, 0.1, Detection::Peak);
+        let mut rms = compressor_with(0.001, 0.1, Detection::Rms);
+
+        let peak_samples = samples_to_settle(&mut peak, 0.1, 1.0);
+        let rms_samples = samples_to_settle(&mut rms, 0.1, 1.0);
+
+        let window = (RMS_WINDOW_IN_SECONDS * SAMPLE_RATE) as usize;
+        assert!(
+            rms_samples > peak_samples && rms_samples < peak_samples + window,
+            "Peak took {peak_samples} samples, RMS {rms_samples}"
+        );
+    }
+
+    #[test]
+    fn when_signal_is_quiet_there_is_no_gain_reduction() {
+        let mut compressor = Compressor::new(SAMPLE_RATE);
+
+        for _ in 0..SAMPLE_RATE as usize / BLOCK_SIZE {
+            compressor.process(&mut [0.1; BLOCK_SIZE], &mut [0.1; BLOCK_SIZE]);
+        }
+
+        assert_relative_eq!(compressor.gain_reduction(), 0.0);
+    }
+
+    #[test]
+    fn when_input_level_increases_gain_reduction_increases() {
+        let mut previous = 0.0;
+        for level in [0.6, 1.0, 2.0, 4.0] {
+            let mut compressor = Compressor::new(SAMPLE_RATE);
+
+            for _ in 0..SAMPLE_RATE as usize / BLOCK_SIZE {
+                compressor.process(&mut [level; BLOCK_SIZE], &mut [level; BLOCK_SIZE]);
+            }
+
+            let gain_reduction = compressor.gain_reduction();
+            assert!(
+                gain_reduction > previous,
+                "Level {level} reduced by {gain_reduction} dB, previous by {previous} dB"
+            );
+            previous = gain_reduction;
+        }
+    }
+
+    // NOTE: Returns RMS of the signal and hiss in sections alternating each
+    // half a second, measured once the expander settles in each of them.
+    fn expanded_signal_and_gap_rms(amount: f32) -> (f32, f32) {
+        let mut expander = Expander::new(SAMPLE_RATE);
+        expander.set_amount(amount);
+
+        let mut seed: u32 = 0x1234_5678;
+        let mut hiss = || {
+            seed ^= seed << 13;
+            seed ^= seed >> 17;
+            seed ^= seed << 5;
+            (seed as f32 / u32::MAX as f32 * 2.0 - 1.0) * 0.003
+        };
+
+        let section = SAMPLE_RATE as usize / 2;
+        let settled = section / 2;
+        let mut signal_square = 0.0;
+        let mut gap_square = 0.0;
+        for i in 0..section * 4 {
+            let in_signal = (i / section).is_multiple_of(2);
+            let signal = if in_signal {
+                0.3 * sinf(i as f32 * 2.0 * PI * 440.0 / SAMPLE_RATE)
+            } else {
+                0.0
+            };
+            let x = signal + hiss();
+            let mut left = [x];
+            let mut right = [x];
+            expander.process(&mut left, &mut right);
+            if i % section >= settled {
+                if in_signal {
+                    signal_square += left[0] * left[0];
+                } else {
+                    gap_square += left[0] * left[0];
+                }
+            }
+        }
+
+        let measured = (2 * (section - settled)) as f32;
+        (
+            sqrtf(signal_square / measured),
+            sqrtf(gap_square / measured),
+        )
+    }
+
+    #[test]
+    fn when_expander_is_set_hiss_in_gaps_is_reduced_while_signal_passes() {
+        let (dry_signal, dry_gap) = expanded_signal_and_gap_rms(0.0);
+        let (wet_signal, wet_gap) = expanded_signal_and_gap_rms(1.0);
+
+        assert!(
+            fabsf(wet_signal / dry_signal - 1.0) < 0.01,
+            "Signal changed from {dry_signal} to {wet_signal}"
+        );
+        assert!(
+            wet_gap < dry_gap / 4.0,
+            "Noise floor only dropped from {dry_gap} to {wet_gap}"
+        );
+    }
+
+    #[test]
+    fn when_attributes_are_not_set_default_timing_is_kept() {
+        let mut configured = Compressor::new(SAMPLE_RATE);
+        configured.set_attributes(Attributes::default());
+
+        let samples = samples_to_settle(&mut configured, 0.1, 1.0);
+
+        let expected = (ATTACK_IN_SECONDS * SAMPLE_RATE) as usize;
+        assert!(
+            samples.abs_diff(expected) <= 1,
+            "Attack took {samples} samples"
+        );
+    }
+}
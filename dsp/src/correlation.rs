@@ -0,0 +1,122 @@
+//! Correlation meter of the stereo output.
+//!
+//! Correlation of +1 means both channels carry the same signal, 0 that they
+//! are unrelated and -1 that they are inverted and would cancel out when
+//! summed to mono.
+
+use libm::sqrtf;
+
+use crate::one_pole_filter::OnePoleFilter;
+
+// Slow enough to ignore momentary phase differences of a single cycle.
+const AVERAGE_CUTOFF: f32 = 2.0;
+
+// Correlation below this means that summing to mono would cause a
+// significant loss of level.
+const MONO_INCOMPATIBLE_THRESHOLD: f32 = -0.5;
+
+// Power of the signal, below which the correlation is not evaluated. This
+// avoids false alarms from noise on silent outputs.
+const SILENCE_THRESHOLD: f32 = 0.000_1;
+
+#[derive(Debug)]
+#[cfg_attr(feature = "defmt", derive(defmt::Format))]
+pub struct Correlation {
+    product: OnePoleFilter,
+    power_left: OnePoleFilter,
+    power_right: OnePoleFilter,
+}
+
+#[derive(Default, Clone, Copy, Debug)]
+#[cfg_attr(feature = "defmt", derive(defmt::Format))]
+pub struct Reaction {
+    pub mono_incompatible: bool,
+}
+
+impl Correlation {
+    pub fn new(sample_rate: f32) -> Self {
+        Self {
+            product: OnePoleFilter::new(sample_rate, AVERAGE_CUTOFF),
+            power_left: OnePoleFilter::new(sample_rate, AVERAGE_CUTOFF),
+            power_right: OnePoleFilter::new(sample_rate, AVERAGE_CUTOFF),
+        }
+    }
+
+    pub fn process(&mut self, buffer_left: &[f32], buffer_right: &[f32]) -> Reaction {
+        let mut product = 0.0;
+        let mut power_left = 0.0;
+        let mut power_right = 0.0;
+        for (l, r) in buffer_left.iter().zip(buffer_right) {
+            product = self.product.tick(l * r);
+            power_left = self.power_left.tick(l * l);
+            power_right = self.power_right.tick(r * r);
+        }
+
+        let power = power_left * power_right;
+        let correlation = if power > SILENCE_THRESHOLD * SILENCE_THRESHOLD {
+            product / sqrtf(power)
+        } else {
+            0.0
+        };
+
+        Reaction {
+            mono_incompatible: correlation < MONO_INCOMPATIBLE_THRESHOLD,
+        }
+    }
+
+    pub fn reset(&mut self) {
+        self.product.reset();
+        self.power_left.reset();
+        self.power_right.reset();
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    use core::f32::consts::PI;
+
+    use libm::sinf;
+
+    const SAMPLE_RATE: f32 = 1000.0;
+
+    fn settled_correlation(correlation: &mut Correlation, right_gain: f32) -> Reaction {
+        let mut reaction = Reaction::default();
+        for block in 0..200 {
+            let left: [f32; 32] = core::array::from_fn(|i| {
+                sinf(2.0 * PI * 50.0 * (block * 32 + i) as f32 / SAMPLE_RATE)
+            });
+            let right = left.map(|x| x * right_gain);
+            reaction = correlation.process(&left, &right);
+        }
+        reaction
+    }
+
+    #[test]
+    fn given_identical_channels_it_does_not_report_mono_incompatibility() {
+        let mut correlation = Correlation::new(SAMPLE_RATE);
+
+        let reaction = settled_correlation(&mut correlation, 1.0);
+
+        assert!(!reaction.mono_incompatible);
+    }
+
+    #[test]
+    fn given_inverted_channels_it_reports_mono_incompatibility() {
+        let mut correlation = Correlation::new(SAMPLE_RATE);
+
+        let reaction = settled_correlation(&mut correlation, -1.0);
+
+        assert!(reaction.mono_incompatible);
+    }
+
+    #[test]
+    fn given_quiet_inverted_channels_it_does_not_report_mono_incompatibility() {
+        let mut correlation = Correlation::new(SAMPLE_RATE);
+
+        let reaction = settled_correlation(&mut correlation, -0.000_01);
+
+        assert!(!reaction.mono_incompatible);
+    }
+}
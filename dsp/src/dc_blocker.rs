@@ -19,6 +19,11 @@ impl DCBlocker {
         }
     }
 
+    pub fn reset(&mut self) {
+        self.x_m1 = 0.0;
+        self.y_m1 = 0.0;
+    }
+
     pub fn tick(&mut self, x: f32) -> f32 {
         let y = x - self.x_m1 + POLE * self.y_m1;
         self.x_m1 = x;
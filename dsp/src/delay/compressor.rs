@@ -27,6 +27,10 @@ impl Compressor {
         }
     }
 
+    pub fn reset(&mut self) {
+        self.n1 = 0.0;
+    }
+
     pub fn process(&mut self, x: f32) -> f32 {
         let abs = fabsf(x);
         let level = if abs > 1.0e-6 { abs } else { 1.0e-6 };
@@ -0,0 +1,107 @@
+//! Estimate of how much of the loop holds audible content.
+//!
+//! The loop may span minutes of audio, so it is never scanned whole. The loop
+//! is split into segments and only a few samples of a single segment are
+//! probed on each call. The estimate is refreshed once all the segments are
+//! visited.
+
+use crate::ring_buffer::RingBuffer;
+
+const SEGMENTS: usize = 32;
+const PROBES_PER_SEGMENT: usize = 16;
+
+// Content quieter than this is considered silent.
+const SILENCE_THRESHOLD: f32 = 0.001;
+
+#[derive(Debug)]
+#[cfg_attr(feature = "defmt", derive(defmt::Format))]
+pub struct FillMeter {
+    filled: [bool; SEGMENTS],
+    segment: usize,
+}
+
+impl Default for FillMeter {
+    fn default() -> Self {
+        Self {
+            filled: [false; SEGMENTS],
+            segment: 0,
+        }
+    }
+}
+
+impl FillMeter {
+    /// Probe the next segment of the loop of given length in samples and
+    /// return the portion of segments found filled, from 0.0 to 1.0.
+    pub fn scan(&mut self, buffer: &RingBuffer, length: usize) -> f32 {
+        let segment_length = length / SEGMENTS;
+        let stride = (segment_length / PROBES_PER_SEGMENT).max(1);
+        let start = self.segment * segment_length;
+        self.filled[self.segment] = (0..PROBES_PER_SEGMENT.min(segment_length))
+            .any(|i| buffer.peek(start + i * stride).abs() > SILENCE_THRESHOLD);
+        self.segment = (self.segment + 1) % SEGMENTS;
+
+        self.filled.iter().filter(|filled| **filled).count() as f32 / SEGMENTS as f32
+    }
+
+    pub fn reset(&mut self) {
+        self.filled = [false; SEGMENTS];
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    const LENGTH: usize = 1024;
+
+    fn scan_all_segments(meter: &mut FillMeter, buffer: &RingBuffer) -> f32 {
+        let mut fill = 0.0;
+        for _ in 0..SEGMENTS {
+            fill = meter.scan(buffer, LENGTH);
+        }
+        fill
+    }
+
+    #[test]
+    fn when_content_is_written_into_part_of_the_loop_fill_rises() {
+        let mut memory_manager = memory_manager!(LENGTH * 2);
+        let mut buffer = RingBuffer::from(memory_manager.allocate(LENGTH * 2).unwrap());
+        let mut meter = FillMeter::default();
+
+        for _ in 0..LENGTH {
+            buffer.write(0.0);
+        }
+        let silent = scan_all_segments(&mut meter, &buffer);
+
+        for i in 0..LENGTH / 4 {
+            buffer.write(if i % 2 == 0 { 0.5 } else { -0.5 });
+        }
+        let quarter = scan_all_segments(&mut meter, &buffer);
+
+        for i in 0..LENGTH / 4 {
+            buffer.write(if i % 2 == 0 { 0.5 } else { -0.5 });
+        }
+        let half = scan_all_segments(&mut meter, &buffer);
+
+        assert_relative_eq!(silent, 0.0);
+        assert_relative_eq!(quarter, 0.25);
+        assert_relative_eq!(half, 0.5);
+    }
+
+    #[test]
+    fn when_only_a_few_segments_are_scanned_fill_is_updated_incrementally() {
+        let mut memory_manager = memory_manager!(LENGTH * 2);
+        let mut buffer = RingBuffer::from(memory_manager.allocate(LENGTH * 2).unwrap());
+        let mut meter = FillMeter::default();
+
+        for _ in 0..LENGTH {
+            buffer.write(0.5);
+        }
+
+        let first = meter.scan(&buffer, LENGTH);
+        let second = meter.scan(&buffer, LENGTH);
+
+        assert_relative_eq!(first, 1.0 / SEGMENTS as f32);
+        assert_relative_eq!(second, 2.0 / SEGMENTS as f32);
+    }
+}
@@ -37,6 +37,7 @@ struct StateRewinding {
     pub relative_speed: f32,
     pub target_position: f32,
     pub rewind_speed: f32,
+    pub remaining_reads: Option<f32>,
 }
 
 #[derive(Clone, Copy, Debug)]
@@ -55,6 +56,9 @@ pub struct FractionalDelayAttributes {
     pub position: f32,
     pub rewind_forward: Option<f32>,
     pub rewind_backward: Option<f32>,
+    /// When set, rewind travels at a steady pace so it lands on the target
+    /// after the given number of reads.
+    pub rewind_beat: Option<f32>,
     pub blend_steps: usize,
 }
 
@@ -62,21 +66,25 @@ pub struct FractionalDelayAttributes {
 // instantly, fading between the current and the destination.
 impl FractionalDelay {
     pub fn read(&mut self, buffer: &RingBuffer, offset: f32) -> f32 {
-        let x = {
-            let a = buffer.peek((self.pointer + offset) as usize);
-            let b = buffer.peek((self.pointer + offset) as usize + 1);
-            a + (b - a) * (self.pointer + offset).fract()
-        };
+        let out = self.peek(buffer, offset);
         match &mut self.state {
-            State::Stable => x,
+            State::Stable => (),
             State::Rewinding(StateRewinding {
                 ref mut relative_speed,
                 target_position,
                 rewind_speed,
+                remaining_reads,
             }) => {
                 self.pointer += *relative_speed;
 
-                if has_crossed_target(self.pointer, *target_position, *rewind_speed) {
+                if let Some(remaining_reads) = remaining_reads {
+                    *remaining_reads -= 1.0;
+                    if *remaining_reads < 0.5
+                        || has_crossed_target(self.pointer, *target_position, *relative_speed)
+                    {
+                        self.pointer = *target_position;
+                    }
+                } else if has_crossed_target(self.pointer, *target_position, *rewind_speed) {
                     self.pointer = *target_position;
                 } else {
                     reflect_inertia_on_relative_speed(
@@ -86,8 +94,6 @@ impl FractionalDelay {
                         *rewind_speed,
                     );
                 }
-
-                x
             }
             State::Blending(StateBlending {
                 target,
@@ -96,13 +102,6 @@ impl FractionalDelay {
                 step,
                 done,
             }) => {
-                let y = {
-                    let a = buffer.peek((*target + offset) as usize);
-                    let b = buffer.peek((*target + offset) as usize + 1);
-                    a + (b - a) * (self.pointer + offset).fract()
-                };
-                let out = x * *current_volume + y * *target_volume;
-
                 if target_volume.relative_eq(1.0, 0.0001) {
                     self.pointer = *target;
                     *done = true;
@@ -114,10 +113,35 @@ impl FractionalDelay {
                     *current_volume -= *step;
                     *target_volume += *step;
                 }
-
-                out
             }
         }
+        out
+    }
+
+    /// Get the value the next read would return, without moving the head.
+    #[must_use]
+    pub fn peek(&self, buffer: &RingBuffer, offset: f32) -> f32 {
+        let x = {
+            let a = buffer.peek_across_seam((self.pointer + offset) as usize);
+            let b = buffer.peek_across_seam((self.pointer + offset) as usize + 1);
+            a + (b - a) * (self.pointer + offset).fract()
+        };
+        if let State::Blending(StateBlending {
+            target,
+            current_volume,
+            target_volume,
+            ..
+        }) = &self.state
+        {
+            let y = {
+                let a = buffer.peek_across_seam((*target + offset) as usize);
+                let b = buffer.peek_across_seam((*target + offset) as usize + 1);
+                a + (b - a) * (self.pointer + offset).fract()
+            };
+            x * *current_volume + y * *target_volume
+        } else {
+            x
+        }
     }
 
     // NOTE: This must be called every 32 or so reads, to assure that the right
@@ -137,11 +161,31 @@ impl FractionalDelay {
         } else {
             attributes.rewind_backward
         };
-        if let Some(rewind_speed) = rewind_config {
+        if let (Some(rewind_speed), Some(beat)) = (rewind_config, attributes.rewind_beat) {
+            // NOTE: The pace is calculated only once per target, otherwise
+            // the landing would keep being postponed.
+            let same_target = matches!(
+                self.state,
+                State::Rewinding(StateRewinding {
+                    target_position,
+                    remaining_reads: Some(_),
+                    ..
+                }) if target_position.relative_eq(attributes.position, 0.0001)
+            );
+            if !same_target {
+                self.state = State::Rewinding(StateRewinding {
+                    relative_speed: (attributes.position - self.pointer) / beat.max(1.0),
+                    target_position: attributes.position,
+                    rewind_speed,
+                    remaining_reads: Some(beat),
+                });
+            }
+        } else if let Some(rewind_speed) = rewind_config {
             self.state = if let State::Rewinding(state) = self.state {
                 State::Rewinding(StateRewinding {
                     target_position: attributes.position,
                     rewind_speed,
+                    remaining_reads: None,
                     ..state
                 })
             } else {
@@ -149,6 +193,7 @@ impl FractionalDelay {
                     relative_speed: 0.0,
                     target_position: attributes.position,
                     rewind_speed,
+                    remaining_reads: None,
                 })
             };
         } else {
@@ -220,3 +265,31 @@ impl F32Ext for f32 {
         (self - other).abs() < epsilon
     }
 }
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn when_head_reads_across_wrap_of_full_buffer_output_has_no_step() {
+        let mut memory_manager = memory_manager!(1024);
+        let mut buffer = RingBuffer::from(memory_manager.allocate(1024).unwrap());
+        for i in 0..buffer.len() {
+            buffer.write(i as f32 / buffer.len() as f32);
+        }
+
+        let delay = FractionalDelay::default();
+        let mut previous = delay.peek(&buffer, 800.0);
+        for i in 1..1000 {
+            let current = delay.peek(&buffer, 800.0 + i as f32 * 0.5);
+            assert!(
+                (current - previous).abs() < 0.02,
+                "Step at offset {}: {} -> {}",
+                800.0 + i as f32 * 0.5,
+                previous,
+                current
+            );
+            previous = current;
+        }
+    }
+}
@@ -1,5 +1,7 @@
 mod compressor;
+mod fill;
 mod fractional;
+mod splice;
 
 #[allow(unused_imports)]
 use micromath::F32Ext as _;
@@ -8,19 +10,65 @@ use sirena::memory_manager::MemoryManager;
 
 use crate::dc_blocker::DCBlocker;
 use crate::math;
+use crate::oversampling::{Downsampler4, FACTOR as OVERSAMPLING_FACTOR};
+use crate::processor::InsufficientMemory;
 use crate::random::Random;
 use crate::ring_buffer::RingBuffer;
+use crate::state_variable_filter::StateVariableFilter;
 use crate::tone::Tone2;
-use crate::wow_flutter::WowFlutter;
+use crate::trigonometry;
+use crate::wow_flutter::{ComponentDelays, WowFlutter};
+use crate::BLOCK_SIZE;
 
 use self::compressor::Compressor;
+use self::fill::FillMeter;
 use self::fractional::{FractionalDelay, FractionalDelayAttributes};
+use self::splice::Splice;
 
 // Assuming sample rate of 48 kHz, 64 MB memory and f32 samples of 4 bytes,
 // the module should hold up to 349 seconds of audio. Rounding down to whole
 // minutes and adding some overhead for wow and flutter.
 const MAX_LENGTH: f32 = 5.0 * 60.0 + 5.0;
 
+// Heads closer than this are nudged apart when `separate_heads` is enabled,
+// so their echoes do not fuse into a comb filter. Moving by this much is
+// handled by a crossfade of the reader, so it is click-free.
+const MIN_HEAD_DISTANCE: f32 = 0.01;
+
+// In the beat-relative mode, the full range of head position covers a bar
+// of this many beats.
+const BEATS_PER_BAR: f32 = 4.0;
+
+// Duration of the crossfade between recording and the frozen loop.
+const FREEZE_FADE_DURATION: f32 = 0.01;
+
+// Duration of the glide of wow and flutter depth between the input and the
+// read heads after the placement changes. Jumping would shift the read
+// position abruptly, causing a click.
+const WOW_FLUTTER_PLACEMENT_GLIDE: f32 = 0.5;
+
+// Highest cutoff of the high-pass filter in the feedback of each head, in Hz.
+const MAX_FEEDBACK_HIGHPASS: f32 = 1000.0;
+
+// Heads quieter than this are considered muted and send no impulses.
+const DEFAULT_IMPULSE_THRESHOLD: f32 = 0.01;
+
+// Clearing of the tape is spread over this many blocks.
+const RESET_STEPS: usize = 2 << 10;
+
+// Each head is read twice per sample, before and after the feedback is
+// written.
+const READS_PER_SAMPLE: f32 = 2.0;
+
+// Longest offset of the right channel behind the left one, in seconds.
+// Beyond it, the channels stop fusing into a single wide echo.
+const MAX_STEREO_OFFSET: f32 = 0.03;
+
+// Number of reads a head takes to crossfade from its old position to a new
+// one, unless set in attributes. Each head is read `READS_PER_SAMPLE` times
+// per sample, and the blend must span whole blocks.
+const DEFAULT_BLEND_STEPS: usize = 3200;
+
 #[derive(Debug)]
 #[cfg_attr(feature = "defmt", derive(defmt::Format))]
 pub struct Delay {
@@ -30,12 +78,50 @@ pub struct Delay {
     length: f32,
     cursor: f32,
     random_impulse: bool,
+    impulse_accent: f32,
+    impulse_threshold: f32,
+    impulse_subdivisions: usize,
+    beat: Option<f32>,
+    gate_width: f32,
     filter_placement: FilterPlacement,
+    // Samples by which the tone filter on the input delays the recording,
+    // taken off the head positions.
+    input_latency: usize,
     wow_flutter_placement: WowFlutterPlacement,
+    flutter_placement: WowFlutterPlacement,
+    // Portions of wow and flutter depth applied on the input and on the read
+    // heads, gliding towards those of the current placement.
+    wow_flutter_shares: (f32, f32),
+    flutter_shares: (f32, f32),
+    pan_law: PanLaw,
+    feedback_topology: FeedbackTopology,
+    feedback_character: f32,
+    // Delay of the right read behind the left one, in seconds.
+    stereo_offset: f32,
     buffer_reset: BufferReset,
+    splice_buffer: bool,
+    // Shuffle planned for the ongoing buffer reset, if it splices the loop
+    // instead of clearing it.
+    splice: Option<Splice>,
     compressor: [Compressor; 4],
     dc_blocker: [DCBlocker; 4],
+    feedback_highpass: [StateVariableFilter; 4],
     play_state: PlayState,
+    fill_meter: FillMeter,
+    freeze: bool,
+    // Portion of the frozen loop in the recording, 0.0 when recording the
+    // input and feedback and 1.0 when fully frozen.
+    freeze_amount: f32,
+    // Portion of the feedback of all heads, pulled back by the processor to
+    // stop it from running away.
+    feedback_scale: f32,
+    oversampled_read: bool,
+    // Filters of the left and right oversampled output. They are kept out
+    // of the tape memory and allocated only on request.
+    read_downsamplers: Option<[Downsampler4; 2]>,
+    // Wow and flutter delays of the last read, so the oversampled reads can
+    // be placed between it and the current one.
+    previous_read_delays: (f32, f32),
 }
 
 #[derive(Default, Debug)]
@@ -44,6 +130,8 @@ struct Head {
     reader: FractionalDelay,
     position: f32,
     feedback: f32,
+    feedback_invert: bool,
+    feedback_highpass: f32,
     volume: f32,
     pan: f32,
 }
@@ -55,21 +143,84 @@ pub struct Attributes {
     pub heads: [HeadAttributes; 4],
     pub reset_impulse: bool,
     pub random_impulse: bool,
+    /// How much random impulses favor heads placed on a beat. With 0.0, all
+    /// heads have equal chance. With 1.0, heads in the middle between beats
+    /// never fire.
+    pub impulse_accent: f32,
+    /// Heads quieter than this volume do not send impulses.
+    pub impulse_threshold: f32,
+    /// Number of evenly spaced impulses each head sends per cycle of the
+    /// delay, the first of them on its crossing.
+    pub impulse_subdivisions: usize,
     pub filter_placement: FilterPlacement,
     pub wow_flutter_placement: WowFlutterPlacement,
+    /// Place flutter apart from wow, e.g. to warble the recording while wow
+    /// bends pitch of the echoes. When `None`, flutter follows
+    /// `wow_flutter_placement`.
+    pub flutter_placement: Option<WowFlutterPlacement>,
+    pub pan_law: PanLaw,
+    pub feedback_topology: FeedbackTopology,
+    /// Hardness of the saturation of the summed feedback, letting heads
+    /// self-oscillate into a stable tone instead of growing until clipped.
+    /// Zero keeps the feedback linear.
+    pub feedback_character: f32,
+    /// Delay of the right read of each head behind the left one, widening
+    /// the echo. Limited to a few tens of milliseconds.
+    pub stereo_offset_ms: f32,
+    pub position_mode: PositionMode,
+    /// Length of a beat in seconds, of the detected or tapped tempo. It
+    /// serves beat-relative head positions and accented random impulses.
+    pub beat: Option<f32>,
+    /// Length of a beat in seconds. When set, rewinding heads land on their
+    /// target exactly one beat after they start moving.
+    pub rewind_beat: Option<f32>,
+    /// Portion of the loop following a head crossing, during which the gate
+    /// of that head is high.
+    pub gate_width: f32,
+    /// Keep audible heads at least `MIN_HEAD_DISTANCE` seconds apart.
+    pub separate_heads: bool,
+    /// Bounce echoes of the first head between left and right.
+    pub ping_pong: bool,
     pub reset_buffer: bool,
+    /// Instead of clearing the tape, the buffer reset shuffles chunks of the
+    /// loop, fading their edges.
+    pub splice_buffer: bool,
     pub paused: bool,
+    /// Stop recording and keep looping the last delay length on the tape.
+    pub freeze: bool,
+    /// Duration of the crossfade in seconds when a head jumps to a new
+    /// position, e.g. between quantized slots. It gets rounded to whole
+    /// blocks. When `None`, the default crossfade is used.
+    pub position_crossfade: Option<f32>,
+    /// Read the output at four times the sample rate and filter it down,
+    /// reducing aliasing of fast modulated reads at the cost of CPU. This
+    /// delays the output by a few samples. It has no effect until
+    /// `allocate_read_oversampling` is called.
+    pub oversampled_read: bool,
 }
 
 #[derive(Clone, Copy, Debug)]
 #[cfg_attr(feature = "defmt", derive(defmt::Format))]
 pub struct HeadAttributes {
     pub position: f32,
+    /// Gain of the head in the feedback. Negative values feed the signal
+    /// back with flipped polarity, same as `feedback_invert`.
     pub feedback: f32,
     pub volume: f32,
     pub pan: f32,
+    /// Nudge of the head from its position in milliseconds, in both
+    /// directions. The resulting position is kept between the write cursor
+    /// and `MAX_LENGTH`.
+    pub fine_offset_ms: f32,
     pub rewind_forward: Option<f32>,
     pub rewind_backward: Option<f32>,
+    /// Flip polarity of the feedback, moving the resonances of the comb
+    /// filter between the original ones.
+    pub feedback_invert: bool,
+    /// Cutoff of the high-pass filter on the feedback of the head in Hz,
+    /// keeping low end from building up in long feedback loops. Disabled
+    /// when set to 0.0.
+    pub feedback_highpass: f32,
 }
 
 #[derive(Debug, Clone, Copy)]
@@ -88,6 +239,49 @@ pub enum WowFlutterPlacement {
     Both,
 }
 
+#[derive(Debug, Clone, Copy)]
+#[cfg_attr(feature = "defmt", derive(defmt::Format))]
+pub enum PanLaw {
+    Linear,
+    EqualPower,
+}
+
+/// Where the feedback tone filter sits relative to the feedback sum.
+///
+/// With `Cumulative`, the filter is inside the loop and every repetition
+/// darkens the echo further. With `SinglePass`, it filters the output, so all
+/// echoes keep the same tone.
+#[derive(Debug, Clone, Copy)]
+#[cfg_attr(feature = "defmt", derive(defmt::Format))]
+pub enum FeedbackTopology {
+    Cumulative,
+    SinglePass,
+}
+
+/// How head position translates to the distance from the write cursor.
+///
+/// With `Proportional`, the position is a portion of the delay length, so
+/// all heads scale together when the length changes. With `BeatRelative`,
+/// the position is a portion of a bar of `BEATS_PER_BAR` beats, so heads stay
+/// rhythmically locked while the length changes. Without a known beat, the
+/// length is taken as the bar.
+#[derive(Debug, Clone, Copy)]
+#[cfg_attr(feature = "defmt", derive(defmt::Format))]
+pub enum PositionMode {
+    Proportional,
+    BeatRelative,
+}
+
+/// Content the tape holds before anything gets recorded on it.
+#[derive(Debug, Clone, Copy, PartialEq)]
+#[cfg_attr(feature = "defmt", derive(defmt::Format))]
+pub enum Priming {
+    Silence,
+    /// White hiss of the given peak amplitude, so feedback has something to
+    /// chew on right after boot, like a worn tape would.
+    Noise(f32),
+}
+
 #[derive(Debug, Clone, Copy)]
 #[cfg_attr(feature = "defmt", derive(defmt::Format))]
 pub enum BufferReset {
@@ -109,8 +303,14 @@ struct ResetSelector {
 #[cfg_attr(feature = "defmt", derive(defmt::Format))]
 pub struct Reaction {
     pub impulse: bool,
+    pub head_gates: [bool; 4],
     pub new_position: usize,
     pub buffer_reset_progress: Option<u8>,
+    /// Portion of the loop holding audible content, from 0.0 to 1.0.
+    pub fill: f32,
+    /// Current read pointer of each head relative to the loop length,
+    /// following the head through rewinds and blends.
+    pub head_positions: [f32; 4],
 }
 
 #[derive(Clone, Copy, Debug)]
@@ -123,20 +323,18 @@ enum PlayState {
 }
 
 impl Delay {
-    /// # Panics
-    ///
-    /// Panics if there is not enough space in the memory manager to allocate a
-    /// buffer of `MAX_LENGTH`.
-    pub fn new(sample_rate: f32, memory_manager: &mut MemoryManager) -> Self {
-        Self {
+    /// Returns `None` if there is not enough space in the memory manager to
+    /// allocate a buffer of `MAX_LENGTH`.
+    pub fn new(sample_rate: f32, memory_manager: &mut MemoryManager) -> Option<Self> {
+        let mut buffer = RingBuffer::from(memory_manager.allocate(math::upper_power_of_two(
+            (sample_rate * MAX_LENGTH) as usize,
+        ))?);
+        // NOTE: The memory may keep its content over a warm reset. Reading it
+        // before it gets overwritten would play back a burst of garbage.
+        buffer.clear();
+        Some(Self {
             sample_rate,
-            buffer: RingBuffer::from(
-                memory_manager
-                    .allocate(math::upper_power_of_two(
-                        (sample_rate * MAX_LENGTH) as usize,
-                    ))
-                    .unwrap(),
-            ),
+            buffer,
             heads: [
                 Head::default(),
                 Head::default(),
@@ -146,9 +344,24 @@ impl Delay {
             length: 0.0,
             cursor: 0.0,
             random_impulse: false,
+            impulse_accent: 0.0,
+            impulse_threshold: DEFAULT_IMPULSE_THRESHOLD,
+            impulse_subdivisions: 1,
+            beat: None,
+            gate_width: 0.0,
             filter_placement: FilterPlacement::default(),
+            input_latency: 0,
             wow_flutter_placement: WowFlutterPlacement::default(),
+            flutter_placement: WowFlutterPlacement::default(),
+            wow_flutter_shares: WowFlutterPlacement::default().shares(),
+            flutter_shares: WowFlutterPlacement::default().shares(),
+            pan_law: PanLaw::default(),
+            feedback_topology: FeedbackTopology::default(),
+            feedback_character: 0.0,
+            stereo_offset: 0.0,
             buffer_reset: BufferReset::Disarmed,
+            splice_buffer: false,
+            splice: None,
             compressor: [
                 Compressor::new(sample_rate),
                 Compressor::new(sample_rate),
@@ -161,8 +374,38 @@ impl Delay {
                 DCBlocker::default(),
                 DCBlocker::default(),
             ],
+            feedback_highpass: [
+                StateVariableFilter::new(sample_rate as u32),
+                StateVariableFilter::new(sample_rate as u32),
+                StateVariableFilter::new(sample_rate as u32),
+                StateVariableFilter::new(sample_rate as u32),
+            ],
             play_state: PlayState::default(),
-        }
+            fill_meter: FillMeter::default(),
+            freeze: false,
+            freeze_amount: 0.0,
+            feedback_scale: 1.0,
+            oversampled_read: false,
+            read_downsamplers: None,
+            previous_read_delays: (0.0, 0.0),
+        })
+    }
+
+    /// Allocate filters needed by `oversampled_read`.
+    ///
+    /// # Errors
+    ///
+    /// This fails with `InsufficientMemory` when the filters cannot be
+    /// allocated in the given memory manager.
+    pub fn allocate_read_oversampling(
+        &mut self,
+        memory_manager: &mut MemoryManager,
+    ) -> Result<(), InsufficientMemory> {
+        self.read_downsamplers = Some([
+            Downsampler4::new_4(memory_manager).ok_or(InsufficientMemory)?,
+            Downsampler4::new_4(memory_manager).ok_or(InsufficientMemory)?,
+        ]);
+        Ok(())
     }
 
     // IN                     (1) write samples from the input
@@ -195,27 +438,55 @@ impl Delay {
             *x *= amp;
         }
 
+        self.input_latency = 0;
         if self.filter_placement.is_input() {
+            self.input_latency = tone.tone_1.latency();
             tone.tone_1.process(input_buffer);
-        }
-
-        let mut wow_flutter_delays = [0.0; 32];
-        wow_flutter.populate_delays(&mut wow_flutter_delays[..], random);
-        if self.wow_flutter_placement.is_both() {
-            for x in &mut wow_flutter_delays {
-                *x /= 2.0;
+            let compensation = tone.tone_1.compensation();
+            for x in input_buffer.iter_mut() {
+                *x *= compensation;
             }
         }
 
-        if self.wow_flutter_placement.is_input() {
-            wow_flutter.process(input_buffer, &wow_flutter_delays);
-        } else {
-            wow_flutter.dry_process(input_buffer);
-        }
+        let mut component_delays_left = [ComponentDelays::default(); BLOCK_SIZE];
+        let mut component_delays_right = [ComponentDelays::default(); BLOCK_SIZE];
+        wow_flutter.populate_stereo_component_delays(
+            &mut component_delays_left,
+            &mut component_delays_right,
+            random,
+        );
+        let decorrelated = wow_flutter.is_decorrelated();
+        let (input_wow_flutter_delays, read_wow_flutter_delays, read_wow_flutter_delays_right) =
+            self.place_wow_flutter_delays(&component_delays_left, &component_delays_right);
+        // NOTE: The right channel is read on its own only when it differs
+        // from the left one.
+        let stereo_offset = self.stereo_offset * self.sample_rate;
+        let split_channels = decorrelated || stereo_offset > 0.0;
+        let read_wow_flutter_delays_right: [f32; BLOCK_SIZE] = core::array::from_fn(|i| {
+            let delay = if decorrelated {
+                read_wow_flutter_delays_right[i]
+            } else {
+                read_wow_flutter_delays[i]
+            };
+            delay + stereo_offset
+        });
 
-        if self.play_state.is_playing() {
-            for x in input_buffer.iter() {
-                self.buffer.write(*x);
+        wow_flutter.process(input_buffer, &input_wow_flutter_delays);
+
+        let filter_feedback = self.filter_placement.is_feedback();
+        let filter_loop = filter_feedback && !self.feedback_topology.is_single_pass();
+        let filter_output = filter_feedback && self.feedback_topology.is_single_pass();
+
+        // NOTE: Recording stops while the loop is spliced, so the chunks stay
+        // in place until they are all swapped.
+        if self.play_state.is_playing() && !self.is_splicing() {
+            let freeze_amounts = self.calculate_freeze_amounts();
+            // NOTE: The loop is never shorter than a sample, so it does not
+            // copy the sample onto itself.
+            let loop_length = self.length_samples().max(1);
+
+            for (x, freeze_amount) in input_buffer.iter().zip(freeze_amounts) {
+                self.buffer.write(*x * (1.0 - freeze_amount));
             }
 
             for (i, (l, r)) in output_buffer_left
@@ -227,67 +498,175 @@ impl Delay {
                 let age = buffer_len - i;
                 let offset = age as f32;
 
+                let feedback_scale = self.feedback_scale;
                 let mut feedback: f32 = self
                     .heads
                     .iter_mut()
-                    .map(|head| {
+                    .zip(&mut self.feedback_highpass)
+                    .map(|(head, highpass)| {
                         // NOTE: Wow and flutter on a very short loop cause
                         // beeps and wobbles.
-                        head.reader.read(&self.buffer, {
-                            if self.wow_flutter_placement.is_read() && head.position > 0.01 {
-                                offset + wow_flutter_delays[i]
+                        let x = head.reader.read(&self.buffer, {
+                            if head.position > 0.01 {
+                                offset + read_wow_flutter_delays[i]
                             } else {
                                 offset
                             }
                         }) * head.feedback
+                            * feedback_scale
+                            * if head.feedback_invert { -1.0 } else { 1.0 };
+                        if head.feedback_highpass > 0.0 {
+                            highpass.tick(x).high_pass
+                        } else {
+                            x
+                        }
                     })
                     .enumerate()
                     .map(|(i, x)| self.compressor[i].process(self.dc_blocker[i].tick(x)))
                     .sum();
-                if self.filter_placement.is_feedback() {
+                // NOTE: Linear-phase filter delays the feedback. Writing it to
+                // an older sample compensates for it, so the loop keeps its
+                // length.
+                let mut latency = 0;
+                if filter_loop {
                     feedback = tone.tone_2.tick(feedback);
+                    latency = tone.tone_2.latency();
+                }
+                feedback = saturate(feedback, self.feedback_character);
+                // NOTE: Feedback is faded in together with the input after
+                // the buffer reset, so the emptied tape does not swell.
+                feedback *= self
+                    .buffer_reset
+                    .calculate_feedback_amplitude(i, buffer_len);
+                // NOTE: While frozen, the feedback is replaced by a copy of
+                // the tape one loop length back, so the loop repeats without
+                // decaying or darkening.
+                feedback *= 1.0 - freeze_amounts[i];
+                *self.buffer.peek_mut(age + latency) += feedback;
+                if freeze_amounts[i] > 0.0 {
+                    let looped = self.buffer.peek(age + loop_length);
+                    *self.buffer.peek_mut(age) += looped * freeze_amounts[i];
                 }
-                *self.buffer.peek_mut(age) += feedback;
 
                 // NOTE: Must read again now when feedback was written back.
                 let mut left = 0.0;
                 let mut right = 0.0;
+                // NOTE: When oversampled, additional reads are placed between
+                // the previous sample and the current one.
+                let oversampled = self.oversampled_read && self.read_downsamplers.is_some();
+                let mut between_left = [0.0; OVERSAMPLING_FACTOR - 1];
+                let mut between_right = [0.0; OVERSAMPLING_FACTOR - 1];
+                let read_delays = (read_wow_flutter_delays[i], read_wow_flutter_delays_right[i]);
                 for head in &mut self.heads {
-                    let value = head.reader.read(&self.buffer, {
-                        if self.wow_flutter_placement.is_read() {
-                            offset + wow_flutter_delays[i]
-                        } else {
-                            offset
+                    if oversampled {
+                        let (left_gain, right_gain) = self.pan_law.gains(head.pan);
+                        for (j, (between_l, between_r)) in
+                            between_left.iter_mut().zip(&mut between_right).enumerate()
+                        {
+                            let phase = (j + 1) as f32 / OVERSAMPLING_FACTOR as f32;
+                            let (delay_left, delay_right) =
+                                interpolate_delays(self.previous_read_delays, read_delays, phase);
+                            let offset = offset + 1.0 - phase;
+                            let value_left = head.reader.peek(&self.buffer, offset + delay_left);
+                            let value_right = if split_channels {
+                                head.reader.peek(&self.buffer, offset + delay_right)
+                            } else {
+                                value_left
+                            };
+                            *between_l += value_left * head.volume * left_gain;
+                            *between_r += value_right * head.volume * right_gain;
                         }
-                    });
-                    let amplified = value * head.volume;
-                    left += amplified * (1.0 - head.pan);
-                    right += amplified * head.pan;
+                    }
+                    // NOTE: The right channel is peeked first, so the read
+                    // moving the head happens only once per pass.
+                    let (value_left, value_right) = if split_channels {
+                        let value_right = head
+                            .reader
+                            .peek(&self.buffer, offset + read_wow_flutter_delays_right[i]);
+                        let value_left = head
+                            .reader
+                            .read(&self.buffer, offset + read_wow_flutter_delays[i]);
+                        (value_left, value_right)
+                    } else {
+                        let value = head
+                            .reader
+                            .read(&self.buffer, offset + read_wow_flutter_delays[i]);
+                        (value, value)
+                    };
+                    let (left_gain, right_gain) = self.pan_law.gains(head.pan);
+                    left += value_left * head.volume * left_gain;
+                    right += value_right * head.volume * right_gain;
+                }
+                self.previous_read_delays = read_delays;
+                if let (true, Some([downsampler_left, downsampler_right])) =
+                    (oversampled, &mut self.read_downsamplers)
+                {
+                    left = downsample(downsampler_left, between_left, left);
+                    right = downsample(downsampler_right, between_right, right);
+                }
+
+                // NOTE: Loudness compensation is skipped inside the loop,
+                // where it would boost the feedback.
+                if filter_output {
+                    left = tone.tone_2.tick(left) * tone.tone_2.compensation();
+                    right = tone.tone_3.tick(right) * tone.tone_3.compensation();
                 }
 
-                let amp = self.buffer_reset.calculate_output_amplitude(i, buffer_len);
+                let amp = self.buffer_reset.calculate_output_amplitude(
+                    i,
+                    buffer_len,
+                    self.splice.is_some(),
+                );
 
                 *l = left * amp;
                 *r = right * amp;
             }
         }
 
-        if let Some(ResetSelector { index, block_size }) = self.buffer_reset.tick() {
-            let delay_chunk = self.buffer.len() / block_size;
-            self.buffer.reset(index * delay_chunk, delay_chunk);
-            let wow_flutter_chunk = wow_flutter.buffer_len() / block_size;
-            wow_flutter.buffer_reset(index * wow_flutter_chunk, wow_flutter_chunk);
+        if matches!(self.buffer_reset, BufferReset::Armed) {
+            // NOTE: Swapping reads and writes two samples, it moves a quarter
+            // of what clearing zeroes in a single step.
+            self.splice = self.splice_buffer.then(|| {
+                Splice::plan(
+                    self.length_samples(),
+                    self.buffer.len() / RESET_STEPS / 4,
+                    self.sample_rate,
+                    random,
+                )
+            });
+        }
+        let steps = self.splice.as_ref().map_or(RESET_STEPS, Splice::steps);
+        if let Some(ResetSelector { index, block_size }) = self.buffer_reset.tick(steps) {
+            if let Some(splice) = &self.splice {
+                splice.step(&mut self.buffer, index);
+            } else {
+                let delay_chunk = self.buffer.len() / block_size;
+                self.buffer.reset(index * delay_chunk, delay_chunk);
+                let wow_flutter_chunk = wow_flutter.buffer_len() / block_size;
+                wow_flutter.buffer_reset(index * wow_flutter_chunk, wow_flutter_chunk);
+            }
         }
 
         self.play_state.tick();
 
-        let impulse = if self.play_state.is_playing() {
-            self.consider_impulse(input_buffer.len(), random)
+        let (impulse, head_gates) = if self.play_state.is_playing() {
+            let impulse = self.consider_impulse(input_buffer.len(), random);
+            (impulse, self.calculate_head_gates())
         } else {
-            false
+            (false, [false; 4])
         };
         let new_position = self.calculate_position_index();
 
+        let fill = self.fill_meter.scan(&self.buffer, self.length_samples());
+
+        // NOTE: In case the length gets set to 0, report all heads at the start.
+        let head_positions = if self.length < f32::EPSILON {
+            [0.0; 4]
+        } else {
+            let length_samples = self.length * self.sample_rate;
+            core::array::from_fn(|i| self.heads[i].reader.impulse_position() / length_samples)
+        };
+
         let buffer_reset_progress = if let BufferReset::Resetting(i, n) = self.buffer_reset {
             Some(((i as f32 / n as f32) * 8.99) as u8)
         } else {
@@ -296,9 +675,87 @@ impl Delay {
 
         Reaction {
             impulse,
+            head_gates,
             new_position,
             buffer_reset_progress,
+            fill,
+            head_positions,
+        }
+    }
+
+    /// Zero states of filters and compressors on the feedback path.
+    ///
+    /// Clearing the tape is optional, since it would wipe everything the user
+    /// recorded.
+    pub fn reset(&mut self, clear_buffer: bool) {
+        for compressor in &mut self.compressor {
+            compressor.reset();
         }
+        for dc_blocker in &mut self.dc_blocker {
+            dc_blocker.reset();
+        }
+        for highpass in &mut self.feedback_highpass {
+            highpass.reset();
+        }
+        if let Some(downsamplers) = &mut self.read_downsamplers {
+            for downsampler in downsamplers {
+                downsampler.reset();
+            }
+        }
+        self.previous_read_delays = (0.0, 0.0);
+        if clear_buffer {
+            self.buffer.clear();
+            self.fill_meter.reset();
+            self.buffer_reset = BufferReset::Disarmed;
+            self.splice = None;
+        }
+    }
+
+    /// Fill the whole tape, overwriting anything recorded on it.
+    ///
+    /// The tape is cleared to silence on construction already, this is to
+    /// be called when it should start with noise instead.
+    pub fn prime(&mut self, priming: Priming, random: &mut impl Random) {
+        match priming {
+            Priming::Silence => self.buffer.clear(),
+            Priming::Noise(amplitude) => {
+                // NOTE: Drawing from the hardware generator for every sample
+                // would stall the boot, so it only seeds a cheap xorshift.
+                let mut state = ((random.normal() * u32::MAX as f32) as u32).max(1);
+                for i in 0..self.buffer.len() {
+                    state ^= state << 13;
+                    state ^= state >> 17;
+                    state ^= state << 5;
+                    let normal = (state >> 8) as f32 / (1 << 24) as f32;
+                    *self.buffer.peek_mut(i) = (normal * 2.0 - 1.0) * amplitude;
+                }
+            }
+        }
+        self.fill_meter.reset();
+    }
+
+    /// Scale feedback of all heads on top of the one set in attributes.
+    pub fn set_feedback_scale(&mut self, scale: f32) {
+        self.feedback_scale = scale.clamp(0.0, 1.0);
+    }
+
+    /// Active length of the delay in seconds, clamped to the capacity of the
+    /// tape.
+    #[must_use]
+    pub fn length_seconds(&self) -> f32 {
+        self.length
+    }
+
+    /// Active length of the delay in samples, clamped to the capacity of the
+    /// tape.
+    #[must_use]
+    pub fn length_samples(&self) -> usize {
+        (self.length * self.sample_rate) as usize
+    }
+
+    /// Decimated peaks of the whole tape, from the oldest to the newest.
+    pub fn snapshot<const M: usize>(&self, out: &mut [f32; M]) {
+        self.buffer.snapshot(out);
     }
 
     fn consider_impulse(&mut self, traversed_samples: usize, random: &mut impl Random) -> bool {
@@ -315,17 +772,29 @@ impl Delay {
 
         let mut impulse = false;
         for head in &self.heads {
-            if head.volume < 0.01 {
+            if head.volume < self.impulse_threshold {
                 continue;
             }
             let head_position = head.reader.impulse_position() / self.sample_rate;
-            let crossed_head = if initial_cursor > self.cursor {
-                head_position >= initial_cursor || head_position < self.cursor
-            } else {
-                initial_cursor <= head_position && head_position < self.cursor
-            };
+            let subdivision = self.length / self.impulse_subdivisions as f32;
+            let crossed_head = (0..self.impulse_subdivisions).any(|i| {
+                let position = head_position + i as f32 * subdivision;
+                let position = if position >= self.length {
+                    position - self.length
+                } else {
+                    position
+                };
+                if initial_cursor > self.cursor {
+                    position >= initial_cursor || position < self.cursor
+                } else {
+                    initial_cursor <= position && position < self.cursor
+                }
+            });
             let chance = if self.random_impulse {
-                dice_to_bool(random.normal(), head.volume)
+                dice_to_bool(
+                    random.normal(),
+                    head.volume * self.accent_weight(head_position),
+                )
             } else {
                 true
             };
@@ -335,6 +804,141 @@ impl Delay {
         impulse
     }
 
+    // NOTE: There is only a single tape, so the bounce is formed by reading it
+    // twice. The first head sets the interval, volume and feedback. It plays
+    // hard left at the interval and the second head plays hard right at twice
+    // the interval, from where it feeds back to bounce again. Each bounce is
+    // attenuated by the feedback. The remaining heads are muted.
+    fn arrange_ping_pong(&mut self) {
+        let interval = self.heads[0].position;
+        let volume = self.heads[0].volume;
+        let feedback = self.heads[0].feedback;
+        let feedback_invert = self.heads[0].feedback_invert;
+        let feedback_highpass = self.heads[0].feedback_highpass;
+
+        self.heads[0].feedback = 0.0;
+        self.heads[0].pan = 0.0;
+
+        self.heads[1].position = (interval * 2.0).min(MAX_LENGTH);
+        self.heads[1].volume = volume * feedback;
+        self.heads[1].feedback = feedback * feedback;
+        self.heads[1].feedback_invert = feedback_invert;
+        self.heads[1].feedback_highpass = feedback_highpass;
+        self.heads[1].pan = 1.0;
+
+        for head in &mut self.heads[2..] {
+            head.volume = 0.0;
+            head.feedback = 0.0;
+        }
+    }
+
+    // NOTE: Heads crossed right on a beat keep their full chance, the ones in
+    // the middle between beats lose the most. Without a known tempo, the start
+    // of the loop is taken as the only downbeat.
+    fn accent_weight(&self, head_position: f32) -> f32 {
+        let beat = self.beat.unwrap_or(self.length);
+        if beat < f32::EPSILON {
+            return 1.0;
+        }
+        let phase = head_position / beat - (head_position / beat).floor();
+        let distance_from_beat = phase.min(1.0 - phase) * 2.0;
+        1.0 - self.impulse_accent * distance_from_beat
+    }
+
+    // NOTE: Each head is compared only with the ones before it, so the first
+    // of overlapping heads stays in place and the rest make space.
+    fn separate_heads(&mut self) {
+        for j in 1..self.heads.len() {
+            if !self.heads[j].is_audible() {
+                continue;
+            }
+            // NOTE: Moving a head may make it collide with another one checked
+            // before, so it is checked again for each of the preceding heads.
+            // The tolerance keeps heads that were just nudged to the minimal
+            // distance from being detected again due to rounding.
+            for _ in 0..j {
+                let collision = self.heads[..j].iter().find(|head| {
+                    head.is_audible()
+                        && (head.position - self.heads[j].position).abs() < MIN_HEAD_DISTANCE * 0.9
+                });
+                let Some(other) = collision else {
+                    break;
+                };
+                self.heads[j].position = if other.position + MIN_HEAD_DISTANCE <= MAX_LENGTH {
+                    other.position + MIN_HEAD_DISTANCE
+                } else {
+                    other.position - MIN_HEAD_DISTANCE
+                };
+            }
+        }
+    }
+
+    fn is_splicing(&self) -> bool {
+        self.splice.is_some() && matches!(self.buffer_reset, BufferReset::Resetting(_, _))
+    }
+
+    fn calculate_head_gates(&self) -> [bool; 4] {
+        let mut gates = [false; 4];
+        if self.length < f32::EPSILON {
+            return gates;
+        }
+
+        for (gate, head) in gates.iter_mut().zip(&self.heads) {
+            let head_position = head.reader.impulse_position() / self.sample_rate;
+            let since_crossing = self.cursor - head_position;
+            let since_crossing =
+                since_crossing - (since_crossing / self.length).floor() * self.length;
+            *gate = since_crossing < self.gate_width * self.length;
+        }
+
+        gates
+    }
+
+    fn calculate_freeze_amounts(&mut self) -> [f32; BLOCK_SIZE] {
+        let step = 1.0 / (self.sample_rate * FREEZE_FADE_DURATION);
+        let step = if self.freeze { step } else { -step };
+        core::array::from_fn(|_| {
+            self.freeze_amount = (self.freeze_amount + step).clamp(0.0, 1.0);
+            self.freeze_amount
+        })
+    }
+
+    // NOTE: Each component of the modulation is split between the input and
+    // the read heads, so it is never applied with more than its full depth.
+    // The input and the feedback are mono, so they follow the left channel.
+    fn place_wow_flutter_delays(
+        &mut self,
+        delays_left: &[ComponentDelays; BLOCK_SIZE],
+        delays_right: &[ComponentDelays; BLOCK_SIZE],
+    ) -> ([f32; BLOCK_SIZE], [f32; BLOCK_SIZE], [f32; BLOCK_SIZE]) {
+        let mut input = [0.0; BLOCK_SIZE];
+        let mut read_left = [0.0; BLOCK_SIZE];
+        let mut read_right = [0.0; BLOCK_SIZE];
+        let wow_shares = self.calculate_wow_flutter_shares();
+        let flutter_shares = self.calculate_flutter_shares();
+        for i in 0..BLOCK_SIZE {
+            let (wow_input, wow_read) = wow_shares[i];
+            let (flutter_input, flutter_read) = flutter_shares[i];
+            let (left, right) = (delays_left[i], delays_right[i]);
+            input[i] = left.wow * wow_input + left.flutter * flutter_input;
+            read_left[i] = left.wow * wow_read + left.flutter * flutter_read;
+            read_right[i] = right.wow * wow_read + right.flutter * flutter_read;
+        }
+        (input, read_left, read_right)
+    }
+
+    fn calculate_wow_flutter_shares(&mut self) -> [(f32, f32); BLOCK_SIZE] {
+        let step = 1.0 / (self.sample_rate * WOW_FLUTTER_PLACEMENT_GLIDE);
+        let target = self.wow_flutter_placement.shares();
+        core::array::from_fn(|_| glide_shares(&mut self.wow_flutter_shares, target, step))
+    }
+
+    fn calculate_flutter_shares(&mut self) -> [(f32, f32); BLOCK_SIZE] {
+        let step = 1.0 / (self.sample_rate * WOW_FLUTTER_PLACEMENT_GLIDE);
+        let target = self.flutter_placement.shares();
+        core::array::from_fn(|_| glide_shares(&mut self.flutter_shares, target, step))
+    }
+
     fn calculate_position_index(&self) -> usize {
         ((self.cursor / self.length) * 7.9999) as usize
     }
@@ -344,27 +948,94 @@ impl Delay {
             self.cursor = 0.0;
         }
         self.random_impulse = attributes.random_impulse;
+        self.impulse_accent = attributes.impulse_accent.clamp(0.0, 1.0);
+        self.impulse_threshold = attributes.impulse_threshold.max(0.0);
+        self.impulse_subdivisions = attributes.impulse_subdivisions.max(1);
+        self.beat = attributes.beat;
+        self.oversampled_read = attributes.oversampled_read;
+        self.gate_width = attributes.gate_width;
         self.filter_placement = attributes.filter_placement;
         self.wow_flutter_placement = attributes.wow_flutter_placement;
+        self.flutter_placement = attributes
+            .flutter_placement
+            .unwrap_or(attributes.wow_flutter_placement);
+        self.pan_law = attributes.pan_law;
+        self.feedback_topology = attributes.feedback_topology;
+        self.feedback_character = attributes.feedback_character.max(0.0);
+        self.stereo_offset = (attributes.stereo_offset_ms / 1000.0).clamp(0.0, MAX_STEREO_OFFSET);
 
-        self.length = attributes.length;
+        // NOTE: The tape is allocated to fit `MAX_LENGTH`. Longer loops
+        // would wrap over the buffer.
+        self.length = attributes.length.clamp(0.0, MAX_LENGTH);
+        let span = match (attributes.position_mode, attributes.beat) {
+            (PositionMode::BeatRelative, Some(beat)) => beat * BEATS_PER_BAR,
+            _ => self.length,
+        };
         for (i, head) in self.heads.iter_mut().enumerate() {
-            head.position = self.length * attributes.heads[i].position;
+            // NOTE: Fine offset must not push the head over the write cursor
+            // nor beyond the end of the buffer.
+            let fine_offset = attributes.heads[i].fine_offset_ms / 1000.0;
+            head.position =
+                (span * attributes.heads[i].position + fine_offset).clamp(0.0, MAX_LENGTH);
             head.feedback = attributes.heads[i].feedback;
+            head.feedback_invert = attributes.heads[i].feedback_invert;
+            head.feedback_highpass = attributes.heads[i]
+                .feedback_highpass
+                .clamp(0.0, MAX_FEEDBACK_HIGHPASS);
             head.volume = attributes.heads[i].volume;
             head.pan = attributes.heads[i].pan;
+        }
+
+        if attributes.ping_pong {
+            self.arrange_ping_pong();
+        }
+
+        if attributes.separate_heads {
+            self.separate_heads();
+        }
+
+        // NOTE: A disabled filter would otherwise keep its stale state until
+        // it gets enabled again.
+        for (head, highpass) in self.heads.iter().zip(&mut self.feedback_highpass) {
+            if head.feedback_highpass > 0.0 {
+                highpass.set_frequency(head.feedback_highpass);
+            } else {
+                highpass.reset();
+            }
+        }
+
+        // NOTE: The blend must finish exactly at the end of a block, so
+        // its length is kept in whole blocks.
+        let blend_steps = attributes
+            .position_crossfade
+            .map_or(DEFAULT_BLEND_STEPS, |time| {
+                let blocks =
+                    ((time * self.sample_rate / BLOCK_SIZE as f32).round() as usize).max(1);
+                blocks * BLOCK_SIZE * READS_PER_SAMPLE as usize
+            });
+        for (i, head) in self.heads.iter_mut().enumerate() {
+            // NOTE: Linear-phase filter on the input records the signal
+            // late. Reading it sooner compensates for it, unless the head is
+            // closer to the write than that.
+            let position = (head.position * self.sample_rate - self.input_latency as f32).max(0.0);
             head.reader.set_attributes(&FractionalDelayAttributes {
-                position: self.length * attributes.heads[i].position * self.sample_rate,
+                position,
                 rewind_forward: attributes.heads[i].rewind_forward,
                 rewind_backward: attributes.heads[i].rewind_backward,
-                blend_steps: 3200, // XXX: It must be also dividable by buffer size
+                rewind_beat: attributes
+                    .rewind_beat
+                    .map(|beat| beat * self.sample_rate * READS_PER_SAMPLE),
+                blend_steps,
             });
         }
 
         if attributes.reset_buffer {
             self.buffer_reset = BufferReset::Armed;
+            self.splice_buffer = attributes.splice_buffer;
         }
 
+        self.freeze = attributes.freeze;
+
         if attributes.paused {
             self.play_state.pause();
         } else {
@@ -373,10 +1044,42 @@ impl Delay {
     }
 }
 
+fn glide_shares(shares: &mut (f32, f32), target: (f32, f32), step: f32) -> (f32, f32) {
+    shares.0 += (target.0 - shares.0).clamp(-step, step);
+    shares.1 += (target.1 - shares.1).clamp(-step, step);
+    *shares
+}
+
 fn dice_to_bool(random: f32, chance: f32) -> bool {
     random + chance > 0.99
 }
 
+// NOTE: Quiet signal passes unchanged, while the loud one cannot exceed
+// `1.0 / hardness`. A loop with gain above unity then settles where the
+// saturation brings its gain back to unity.
+fn saturate(x: f32, hardness: f32) -> f32 {
+    x / (1.0 + hardness * x.abs())
+}
+
+fn interpolate_delays(previous: (f32, f32), current: (f32, f32), phase: f32) -> (f32, f32) {
+    (
+        previous.0 + (current.0 - previous.0) * phase,
+        previous.1 + (current.1 - previous.1) * phase,
+    )
+}
+
+fn downsample(
+    downsampler: &mut Downsampler4,
+    between: [f32; OVERSAMPLING_FACTOR - 1],
+    current: f32,
+) -> f32 {
+    let mut oversampled = [current; OVERSAMPLING_FACTOR];
+    oversampled[..OVERSAMPLING_FACTOR - 1].copy_from_slice(&between);
+    let mut output = [0.0];
+    downsampler.process(&oversampled, &mut output);
+    output[0]
+}
+
 impl Default for FilterPlacement {
     fn default() -> Self {
         Self::Both
@@ -393,23 +1096,66 @@ impl FilterPlacement {
     }
 }
 
-impl Default for WowFlutterPlacement {
+impl Default for PanLaw {
     fn default() -> Self {
-        Self::Both
+        Self::Linear
     }
 }
 
-impl WowFlutterPlacement {
-    fn is_input(self) -> bool {
-        matches!(self, Self::Input) || matches!(self, Self::Both)
+impl PanLaw {
+    fn gains(self, pan: f32) -> (f32, f32) {
+        match self {
+            Self::Linear => (1.0 - pan, pan),
+            // NOTE: Trigonometry takes phase of the whole cycle, pan is mapped
+            // to its first quarter.
+            Self::EqualPower => (
+                trigonometry::cos(pan * 0.25),
+                trigonometry::cos(0.75 + pan * 0.25),
+            ),
+        }
+    }
+}
+
+impl Head {
+    fn is_audible(&self) -> bool {
+        // NOTE: Negative feedback is audible too, fed back inverted.
+        self.volume > 0.0 || self.feedback != 0.0
+    }
+}
+
+impl Default for FeedbackTopology {
+    fn default() -> Self {
+        Self::Cumulative
+    }
+}
+
+impl FeedbackTopology {
+    fn is_single_pass(self) -> bool {
+        matches!(self, Self::SinglePass)
+    }
+}
+
+impl Default for PositionMode {
+    fn default() -> Self {
+        Self::Proportional
     }
+}
 
-    fn is_read(self) -> bool {
-        matches!(self, Self::Read) || matches!(self, Self::Both)
+impl Default for WowFlutterPlacement {
+    fn default() -> Self {
+        Self::Both
     }
+}
 
-    fn is_both(self) -> bool {
-        matches!(self, Self::Both)
+impl WowFlutterPlacement {
+    // NOTE: Portions of the depth applied on the input and on the read heads.
+    // They always sum up to the full depth.
+    fn shares(self) -> (f32, f32) {
+        match self {
+            Self::Input => (1.0, 0.0),
+            Self::Read => (0.0, 1.0),
+            Self::Both => (0.5, 0.5),
+        }
     }
 }
 
@@ -433,8 +1179,11 @@ impl BufferReset {
         }
     }
 
-    fn calculate_output_amplitude(&mut self, i: usize, buffer_len: usize) -> f32 {
+    // NOTE: A spliced loop keeps its content, so the output fades in together
+    // with the input, avoiding a jump from silence.
+    fn calculate_output_amplitude(&mut self, i: usize, buffer_len: usize, splice: bool) -> f32 {
         match self {
+            BufferReset::FadingIn(_, _) if splice => self.calculate_input_amplitude(i, buffer_len),
             BufferReset::FadingOut(j, n) => {
                 let part = 1.0 / *n as f32;
                 let start = *j as f32 / *n as f32;
@@ -446,14 +1195,22 @@ impl BufferReset {
         }
     }
 
-    fn tick(&mut self) -> Option<ResetSelector> {
+    fn calculate_feedback_amplitude(&mut self, i: usize, buffer_len: usize) -> f32 {
+        match self {
+            BufferReset::Resetting(_, _) | BufferReset::FadingIn(_, _) => {
+                self.calculate_input_amplitude(i, buffer_len)
+            }
+            _ => 1.0,
+        }
+    }
+
+    fn tick(&mut self, steps: usize) -> Option<ResetSelector> {
         let mut reset_request = None;
         *self = match self {
             BufferReset::Armed => BufferReset::FadingOut(0, 50),
             BufferReset::FadingOut(j, n) => {
                 if j == n {
-                    let chunks = 2 << 10;
-                    BufferReset::Resetting(0, chunks)
+                    BufferReset::Resetting(0, steps)
                 } else {
                     BufferReset::FadingOut(*j + 1, *n)
                 }
@@ -555,3 +1312,1827 @@ impl PlayState {
         };
     }
 }
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::tone::{Attributes as ToneAttributes, FilterType};
+    use crate::wow_flutter::Attributes as WowFlutterAttributes;
+
+    const SAMPLE_RATE: f32 = 1000.0;
+
+    struct TestRandom;
+
+    impl Random for TestRandom {
+        fn normal(&mut self) -> f32 {
+            0.0
+        }
+    }
+
+    // NOTE: Xorshift, so the long-run statistics are reproducible.
+    struct SeededRandom(u32);
+
+    impl Random for SeededRandom {
+        fn normal(&mut self) -> f32 {
+            self.0 ^= self.0 << 13;
+            self.0 ^= self.0 >> 17;
+            self.0 ^= self.0 << 5;
+            (self.0 >> 8) as f32 / (1 << 24) as f32
+        }
+    }
+
+    fn attributes_with_head(position: f32, fine_offset_ms: f32) -> Attributes {
+        let head = HeadAttributes {
+            position,
+            feedback: 0.0,
+            volume: 1.0,
+            pan: 0.5,
+            fine_offset_ms,
+            rewind_forward: None,
+            rewind_backward: None,
+            feedback_invert: false,
+            feedback_highpass: 0.0,
+        };
+        Attributes {
+            length: 1.0,
+            heads: [head; 4],
+            reset_impulse: false,
+            random_impulse: false,
+            impulse_accent: 0.0,
+            impulse_threshold: 0.01,
+            impulse_subdivisions: 1,
+            filter_placement: FilterPlacement::Input,
+            wow_flutter_placement: WowFlutterPlacement::Input,
+            flutter_placement: None,
+            pan_law: PanLaw::Linear,
+            feedback_topology: FeedbackTopology::Cumulative,
+            feedback_character: 0.0,
+            stereo_offset_ms: 0.0,
+            position_mode: PositionMode::Proportional,
+            beat: None,
+            rewind_beat: None,
+            gate_width: 0.0,
+            separate_heads: false,
+            ping_pong: false,
+            reset_buffer: false,
+            splice_buffer: false,
+            paused: false,
+            freeze: false,
+            position_crossfade: None,
+            oversampled_read: false,
+        }
+    }
+
+    fn settle(
+        delay: &mut Delay,
+        attributes: Attributes,
+        tone: &mut Tone2,
+        wow_flutter: &mut WowFlutter,
+    ) {
+        // NOTE: Heads blend to their new position through multiple buffers.
+        for _ in 0..200 {
+            delay.set_attributes(attributes);
+            let mut input = [0.0; BLOCK_SIZE];
+            let mut left = [0.0; BLOCK_SIZE];
+            let mut right = [0.0; BLOCK_SIZE];
+            delay.process(
+                &mut input,
+                &mut left,
+                &mut right,
+                tone,
+                wow_flutter,
+                &mut TestRandom,
+            );
+        }
+    }
+
+    #[test]
+    fn when_length_exceeds_capacity_it_is_clamped_and_reported() {
+        let mut delay_manager = memory_manager!(512 * 1024);
+        let mut delay = Delay::new(SAMPLE_RATE, &mut delay_manager).unwrap();
+
+        let mut attributes = attributes_with_head(0.5, 0.0);
+        attributes.length = 2.5;
+        delay.set_attributes(attributes);
+        assert_relative_eq!(delay.length_seconds(), 2.5);
+        assert_eq!(delay.length_samples(), 2500);
+
+        attributes.length = MAX_LENGTH * 2.0;
+        delay.set_attributes(attributes);
+        assert_relative_eq!(delay.length_seconds(), MAX_LENGTH);
+        assert_eq!(delay.length_samples(), (MAX_LENGTH * SAMPLE_RATE) as usize);
+        assert!(delay.length_samples() < delay.buffer.len());
+    }
+
+    #[test]
+    fn when_memory_holds_garbage_tape_starts_silent() {
+        let mut delay_manager = memory_manager!(512 * 1024, 0.5_f32.to_bits());
+        let delay = Delay::new(SAMPLE_RATE, &mut delay_manager).unwrap();
+
+        for i in 0..delay.buffer.len() {
+            assert_relative_eq!(delay.buffer.peek(i), 0.0);
+        }
+    }
+
+    #[test]
+    fn when_primed_with_noise_tape_holds_low_level_hiss() {
+        let mut delay_manager = memory_manager!(512 * 1024);
+        let mut delay = Delay::new(SAMPLE_RATE, &mut delay_manager).unwrap();
+
+        delay.prime(Priming::Noise(0.01), &mut SeededRandom(1));
+        let mut sum = 0.0;
+        for i in 0..delay.buffer.len() {
+            let sample = delay.buffer.peek(i);
+            assert!(sample.abs() <= 0.01);
+            sum += sample * sample;
+        }
+        let rms = (sum / delay.buffer.len() as f32).sqrt();
+        assert!(rms > 0.004, "rms={rms}");
+
+        delay.prime(Priming::Silence, &mut SeededRandom(1));
+        for i in 0..delay.buffer.len() {
+            assert_relative_eq!(delay.buffer.peek(i), 0.0);
+        }
+    }
+
+    #[test]
+    fn when_fine_offset_is_set_it_shifts_head_by_exact_amount() {
+        let mut delay_manager = memory_manager!(512 * 1024);
+        let mut wow_flutter_manager = memory_manager!(1024);
+        let mut delay = Delay::new(SAMPLE_RATE, &mut delay_manager).unwrap();
+        let mut tone = Tone2::new(SAMPLE_RATE);
+        let mut wow_flutter =
+            WowFlutter::new(SAMPLE_RATE as u32, &mut wow_flutter_manager).unwrap();
+
+        settle(
+            &mut delay,
+            attributes_with_head(0.5, 0.0),
+            &mut tone,
+            &mut wow_flutter,
+        );
+        let coarse = delay.heads[0].reader.impulse_position();
+
+        settle(
+            &mut delay,
+            attributes_with_head(0.5, 3.0),
+            &mut tone,
+            &mut wow_flutter,
+        );
+        let fine = delay.heads[0].reader.impulse_position();
+
+        assert_relative_eq!(coarse, 500.0);
+        assert_relative_eq!(fine - coarse, 3.0, epsilon = 0.001);
+    }
+
+    fn impulse_arrival(
+        delay_manager: &mut MemoryManager,
+        wow_flutter_manager: &mut MemoryManager,
+        filter_placement: FilterPlacement,
+    ) -> usize {
+        let mut delay = Delay::new(SAMPLE_RATE, delay_manager).unwrap();
+        let mut tone = Tone2::new(SAMPLE_RATE);
+        tone.set_attributes(ToneAttributes {
+            tone: 0.5,
+            filter_type: FilterType::Fir,
+            slew: None,
+            loudness_compensation: false,
+            cutoff: None,
+        });
+        let mut wow_flutter = WowFlutter::new(SAMPLE_RATE as u32, wow_flutter_manager).unwrap();
+        let mut attributes = attributes_with_head(0.1, 0.0);
+        attributes.filter_placement = filter_placement;
+        settle(&mut delay, attributes, &mut tone, &mut wow_flutter);
+
+        let mut output = [0.0; 8 * BLOCK_SIZE];
+        for (i, chunk) in output.chunks_mut(BLOCK_SIZE).enumerate() {
+            delay.set_attributes(attributes);
+            let mut input = [0.0; BLOCK_SIZE];
+            if i == 0 {
+                input[0] = 1.0;
+            }
+            let mut right = [0.0; BLOCK_SIZE];
+            delay.process(
+                &mut input,
+                chunk,
+                &mut right,
+                &mut tone,
+                &mut wow_flutter,
+                &mut TestRandom,
+            );
+        }
+
+        output
+            .iter()
+            .enumerate()
+            .fold((0, 0.0), |(index, peak), (i, x)| {
+                if x.abs() > peak {
+                    (i, x.abs())
+                } else {
+                    (index, peak)
+                }
+            })
+            .0
+    }
+
+    #[test]
+    fn when_linear_phase_filter_is_on_input_heads_keep_their_timing() {
+        let mut feedback_delay_manager = memory_manager!(512 * 1024);
+        let mut feedback_wow_flutter_manager = memory_manager!(1024);
+        let mut input_delay_manager = memory_manager!(512 * 1024);
+        let mut input_wow_flutter_manager = memory_manager!(1024);
+
+        // NOTE: Without feedback, the filter in the feedback loop is not
+        // heard, so the timing is not affected by it.
+        let unfiltered = impulse_arrival(
+            &mut feedback_delay_manager,
+            &mut feedback_wow_flutter_manager,
+            FilterPlacement::Feedback,
+        );
+        let filtered = impulse_arrival(
+            &mut input_delay_manager,
+            &mut input_wow_flutter_manager,
+            FilterPlacement::Input,
+        );
+
+        assert!(unfiltered > 0);
+        assert_eq!(filtered, unfiltered);
+    }
+
+    #[test]
+    fn when_position_crossfade_is_set_head_blends_over_given_samples() {
+        let mut delay_manager = memory_manager!(512 * 1024);
+        let mut wow_flutter_manager = memory_manager!(1024);
+        let mut delay = Delay::new(SAMPLE_RATE, &mut delay_manager).unwrap();
+        let mut tone = Tone2::new(SAMPLE_RATE);
+        tone.set_attributes(ToneAttributes {
+            tone: 0.5,
+            filter_type: FilterType::Iir,
+            slew: None,
+            loudness_compensation: false,
+            cutoff: None,
+        });
+        let mut wow_flutter =
+            WowFlutter::new(SAMPLE_RATE as u32, &mut wow_flutter_manager).unwrap();
+
+        let mut process = |delay: &mut Delay, attributes: Attributes, x: f32| {
+            delay.set_attributes(attributes);
+            let mut input = [x; BLOCK_SIZE];
+            let mut left = [0.0; BLOCK_SIZE];
+            let mut right = [0.0; BLOCK_SIZE];
+            delay.process(
+                &mut input,
+                &mut left,
+                &mut right,
+                &mut tone,
+                &mut wow_flutter,
+                &mut TestRandom,
+            );
+            left
+        };
+
+        // NOTE: 64 ms at 1 kHz make 64 samples, a whole number of blocks.
+        let mut attributes = attributes_with_head(0.1, 0.0);
+        attributes.position_crossfade = Some(0.064);
+
+        // Record a long stretch of signal followed by a short silence, so the
+        // head sits in the silence and the new position lands in the signal.
+        for _ in 0..1280 / BLOCK_SIZE {
+            process(&mut delay, attributes, 1.0);
+        }
+        for _ in 0..256 / BLOCK_SIZE {
+            process(&mut delay, attributes, 0.0);
+        }
+        assert_relative_eq!(
+            process(&mut delay, attributes, 0.0)[BLOCK_SIZE - 1],
+            0.0,
+            epsilon = 0.000_01
+        );
+
+        attributes.heads[0].position = 0.5;
+        attributes.heads[1].position = 0.5;
+        attributes.heads[2].position = 0.5;
+        attributes.heads[3].position = 0.5;
+        let mut output = [0.0; 128];
+        for chunk in output.chunks_mut(BLOCK_SIZE) {
+            chunk.copy_from_slice(&process(&mut delay, attributes, 0.0));
+        }
+        let full = output[127];
+
+        assert!(full > 0.1);
+        assert_relative_eq!(output[31], full / 2.0, max_relative = 0.02);
+        assert!(output[63] < full * 0.999);
+        assert_relative_eq!(output[64], full, max_relative = 0.001);
+    }
+
+    #[test]
+    fn when_flutter_is_placed_apart_wow_modulates_read_and_flutter_input() {
+        let mut delay_manager = memory_manager!(512 * 1024);
+        let mut delay = Delay::new(SAMPLE_RATE, &mut delay_manager).unwrap();
+
+        let mut attributes = attributes_with_head(0.5, 0.0);
+        attributes.wow_flutter_placement = WowFlutterPlacement::Read;
+        attributes.flutter_placement = Some(WowFlutterPlacement::Input);
+        delay.set_attributes(attributes);
+
+        let left = [ComponentDelays {
+            wow: 3.0,
+            flutter: 0.5,
+        }; BLOCK_SIZE];
+        let right = [ComponentDelays {
+            wow: 2.0,
+            flutter: 0.25,
+        }; BLOCK_SIZE];
+        // NOTE: Let the shares glide from the default placement.
+        for _ in 0..1024 / BLOCK_SIZE {
+            delay.place_wow_flutter_delays(&left, &right);
+        }
+        let (input, read_left, read_right) = delay.place_wow_flutter_delays(&left, &right);
+
+        for i in 0..BLOCK_SIZE {
+            assert_relative_eq!(input[i], 0.5);
+            assert_relative_eq!(read_left[i], 3.0);
+            assert_relative_eq!(read_right[i], 2.0);
+        }
+    }
+
+    #[test]
+    fn when_flutter_placement_is_not_set_it_follows_wow() {
+        let mut delay_manager = memory_manager!(512 * 1024);
+        let mut delay = Delay::new(SAMPLE_RATE, &mut delay_manager).unwrap();
+
+        let mut attributes = attributes_with_head(0.5, 0.0);
+        attributes.wow_flutter_placement = WowFlutterPlacement::Read;
+        delay.set_attributes(attributes);
+
+        let delays = [ComponentDelays {
+            wow: 3.0,
+            flutter: 0.5,
+        }; BLOCK_SIZE];
+        for _ in 0..1024 / BLOCK_SIZE {
+            delay.place_wow_flutter_delays(&delays, &delays);
+        }
+        let (input, read_left, _) = delay.place_wow_flutter_delays(&delays, &delays);
+
+        for i in 0..BLOCK_SIZE {
+            assert_relative_eq!(input[i], 0.0);
+            assert_relative_eq!(read_left[i], 3.5);
+        }
+    }
+
+    #[test]
+    fn when_fine_offset_points_before_write_cursor_it_gets_clamped() {
+        let mut delay_manager = memory_manager!(512 * 1024);
+        let mut wow_flutter_manager = memory_manager!(1024);
+        let mut delay = Delay::new(SAMPLE_RATE, &mut delay_manager).unwrap();
+        let mut tone = Tone2::new(SAMPLE_RATE);
+        let mut wow_flutter =
+            WowFlutter::new(SAMPLE_RATE as u32, &mut wow_flutter_manager).unwrap();
+
+        settle(
+            &mut delay,
+            attributes_with_head(0.5, 0.0),
+            &mut tone,
+            &mut wow_flutter,
+        );
+        settle(
+            &mut delay,
+            attributes_with_head(0.005, -10.0),
+            &mut tone,
+            &mut wow_flutter,
+        );
+
+        assert_relative_eq!(delay.heads[0].reader.impulse_position(), 0.0);
+    }
+
+    // NOTE: Ratio of the energy of the first difference to the energy of the
+    // signal. The lower it is, the darker the echo.
+    fn echo_brightness(
+        delay_manager: &mut MemoryManager,
+        wow_flutter_manager: &mut MemoryManager,
+        topology: FeedbackTopology,
+    ) -> [f32; 4] {
+        let mut delay = Delay::new(SAMPLE_RATE, delay_manager).unwrap();
+        let mut tone = Tone2::new(SAMPLE_RATE);
+        tone.set_attributes(ToneAttributes {
+            tone: 0.15,
+            filter_type: FilterType::Iir,
+            slew: None,
+            loudness_compensation: false,
+            cutoff: None,
+        });
+        let mut wow_flutter = WowFlutter::new(SAMPLE_RATE as u32, wow_flutter_manager).unwrap();
+
+        let mut attributes = attributes_with_head(0.1, 0.0);
+        attributes.heads[0].feedback = 0.9;
+        attributes.filter_placement = FilterPlacement::Feedback;
+        attributes.feedback_topology = topology;
+        settle(&mut delay, attributes, &mut tone, &mut wow_flutter);
+
+        let mut output = [0.0; 640];
+        for (i, chunk) in output.chunks_mut(BLOCK_SIZE).enumerate() {
+            let mut input = [0.0; BLOCK_SIZE];
+            if i == 0 {
+                input[0] = 0.5;
+            }
+            let mut right = [0.0; BLOCK_SIZE];
+            delay.process(
+                &mut input,
+                chunk,
+                &mut right,
+                &mut tone,
+                &mut wow_flutter,
+                &mut TestRandom,
+            );
+        }
+
+        core::array::from_fn(|i| {
+            let start = (i + 1) * 100 - 20;
+            let echo = &output[start..start + 80];
+            let energy: f32 = echo.iter().map(|x| x * x).sum();
+            let difference: f32 = echo.windows(2).map(|w| (w[1] - w[0]) * (w[1] - w[0])).sum();
+            difference / energy
+        })
+    }
+
+    // NOTE: Returns the summed magnitude of the impulse response on the
+    // harmonics of the loop and in between them.
+    fn comb_response(
+        delay_manager: &mut MemoryManager,
+        wow_flutter_manager: &mut MemoryManager,
+        feedback: f32,
+        feedback_invert: bool,
+    ) -> (f32, f32) {
+        let mut delay = Delay::new(SAMPLE_RATE, delay_manager).unwrap();
+        let mut tone = Tone2::new(SAMPLE_RATE);
+        tone.set_attributes(ToneAttributes {
+            tone: 0.5,
+            filter_type: FilterType::Iir,
+            slew: None,
+            loudness_compensation: false,
+            cutoff: None,
+        });
+        let mut wow_flutter = WowFlutter::new(SAMPLE_RATE as u32, wow_flutter_manager).unwrap();
+
+        // NOTE: The loop is 100 samples long.
+        let mut attributes = attributes_with_head(0.1, 0.0);
+        attributes.heads[0].feedback = feedback;
+        attributes.heads[0].feedback_invert = feedback_invert;
+        settle(&mut delay, attributes, &mut tone, &mut wow_flutter);
+
+        let mut output = [0.0; BLOCK_SIZE * 64];
+        for (i, chunk) in output.chunks_mut(BLOCK_SIZE).enumerate() {
+            let mut input = [0.0; BLOCK_SIZE];
+            if i == 0 {
+                input[0] = 0.5;
+            }
+            let mut right = [0.0; BLOCK_SIZE];
+            delay.process(
+                &mut input,
+                chunk,
+                &mut right,
+                &mut tone,
+                &mut wow_flutter,
+                &mut TestRandom,
+            );
+        }
+
+        let magnitude = |frequency: f32| {
+            let (mut in_phase, mut quadrature) = (0.0, 0.0);
+            for (i, x) in output.iter().enumerate() {
+                let phase = 2.0 * core::f32::consts::PI * frequency * i as f32;
+                in_phase += x * libm::cosf(phase);
+                quadrature += x * libm::sinf(phase);
+            }
+            libm::sqrtf(in_phase * in_phase + quadrature * quadrature)
+        };
+        let harmonics = (2..6).map(|k| magnitude(k as f32 / 100.0)).sum();
+        let in_between = (2..6).map(|k| magnitude((k as f32 - 0.5) / 100.0)).sum();
+        (harmonics, in_between)
+    }
+
+    #[test]
+    fn when_feedback_is_inverted_resonances_move_between_the_harmonics() {
+        let mut delay_manager = memory_manager!(2 * 512 * 1024);
+        let mut wow_flutter_manager = memory_manager!(2048);
+
+        let (harmonics, in_between) =
+            comb_response(&mut delay_manager, &mut wow_flutter_manager, 0.9, false);
+        assert!(
+            harmonics > in_between * 4.0,
+            "Harmonics {harmonics}, in between {in_between}"
+        );
+
+        let (harmonics, in_between) =
+            comb_response(&mut delay_manager, &mut wow_flutter_manager, 0.9, true);
+        assert!(
+            in_between > harmonics * 4.0,
+            "Harmonics {harmonics}, in between {in_between}"
+        );
+    }
+
+    #[test]
+    fn when_feedback_is_negative_resonances_differ_from_positive_of_same_magnitude() {
+        let mut delay_manager = memory_manager!(2 * 512 * 1024);
+        let mut wow_flutter_manager = memory_manager!(2048);
+
+        let (positive_harmonics, positive_in_between) =
+            comb_response(&mut delay_manager, &mut wow_flutter_manager, 0.9, false);
+        let (negative_harmonics, negative_in_between) =
+            comb_response(&mut delay_manager, &mut wow_flutter_manager, -0.9, false);
+
+        assert!(
+            positive_harmonics > negative_harmonics * 4.0,
+            "Positive {positive_harmonics}, negative {negative_harmonics}"
+        );
+        assert!(
+            negative_in_between > positive_in_between * 4.0,
+            "Positive {positive_in_between}, negative {negative_in_between}"
+        );
+    }
+
+    fn tail_low_end(
+        delay_manager: &mut MemoryManager,
+        wow_flutter_manager: &mut MemoryManager,
+        feedback_highpass: f32,
+    ) -> f32 {
+        let mut delay = Delay::new(SAMPLE_RATE, delay_manager).unwrap();
+        let mut tone = Tone2::new(SAMPLE_RATE);
+        tone.set_attributes(ToneAttributes {
+            tone: 0.5,
+            filter_type: FilterType::Iir,
+            slew: None,
+            loudness_compensation: false,
+            cutoff: None,
+        });
+        let mut wow_flutter = WowFlutter::new(SAMPLE_RATE as u32, wow_flutter_manager).unwrap();
+
+        // NOTE: The loop is 100 samples long, so 10 Hz resonates in it.
+        let mut attributes = attributes_with_head(0.1, 0.0);
+        attributes.heads[0].feedback = 0.9;
+        attributes.heads[0].feedback_highpass = feedback_highpass;
+        settle(&mut delay, attributes, &mut tone, &mut wow_flutter);
+
+        let mut output = [0.0; BLOCK_SIZE * 64];
+        for (i, chunk) in output.chunks_mut(BLOCK_SIZE).enumerate() {
+            let mut input: [f32; BLOCK_SIZE] = core::array::from_fn(|j| {
+                let n = i * BLOCK_SIZE + j;
+                if n < 100 {
+                    0.5 * libm::sinf(2.0 * core::f32::consts::PI * 10.0 * n as f32 / SAMPLE_RATE)
+                } else {
+                    0.0
+                }
+            });
+            let mut right = [0.0; BLOCK_SIZE];
+            delay.process(
+                &mut input,
+                chunk,
+                &mut right,
+                &mut tone,
+                &mut wow_flutter,
+                &mut TestRandom,
+            );
+        }
+
+        let tail = &output[BLOCK_SIZE * 16..];
+        let (mut in_phase, mut quadrature) = (0.0, 0.0);
+        for (i, x) in tail.iter().enumerate() {
+            let phase = 2.0 * core::f32::consts::PI * 10.0 * i as f32 / SAMPLE_RATE;
+            in_phase += x * libm::cosf(phase);
+            quadrature += x * libm::sinf(phase);
+        }
+        libm::sqrtf(in_phase * in_phase + quadrature * quadrature)
+    }
+
+    #[test]
+    fn when_feedback_highpass_is_raised_low_end_in_the_tail_drops() {
+        let mut delay_manager = memory_manager!(2 * 512 * 1024);
+        let mut wow_flutter_manager = memory_manager!(2048);
+
+        let unfiltered = tail_low_end(&mut delay_manager, &mut wow_flutter_manager, 0.0);
+        let filtered = tail_low_end(&mut delay_manager, &mut wow_flutter_manager, 50.0);
+
+        assert!(unfiltered > 1.0, "Unfiltered {unfiltered}");
+        assert!(
+            unfiltered > filtered * 10.0,
+            "Unfiltered {unfiltered}, filtered {filtered}"
+        );
+    }
+
+    #[test]
+    fn when_feedback_tone_is_cumulative_each_echo_gets_darker() {
+        let mut delay_manager = memory_manager!(512 * 1024);
+        let mut wow_flutter_manager = memory_manager!(1024);
+        let brightness = echo_brightness(
+            &mut delay_manager,
+            &mut wow_flutter_manager,
+            FeedbackTopology::Cumulative,
+        );
+
+        for i in 1..brightness.len() {
+            assert!(brightness[i] < brightness[i - 1] * 0.9);
+        }
+    }
+
+    #[test]
+    fn when_feedback_tone_is_single_pass_all_echoes_keep_the_same_tone() {
+        let mut delay_manager = memory_manager!(512 * 1024);
+        let mut wow_flutter_manager = memory_manager!(1024);
+        let brightness = echo_brightness(
+            &mut delay_manager,
+            &mut wow_flutter_manager,
+            FeedbackTopology::SinglePass,
+        );
+
+        for x in &brightness[1..] {
+            assert_relative_eq!(*x, brightness[0], max_relative = 0.05);
+        }
+    }
+
+    #[test]
+    fn when_rewind_is_locked_to_beat_it_lands_on_target_within_one_beat() {
+        let mut delay_manager = memory_manager!(512 * 1024);
+        let mut wow_flutter_manager = memory_manager!(1024);
+        let mut delay = Delay::new(SAMPLE_RATE, &mut delay_manager).unwrap();
+        let mut tone = Tone2::new(SAMPLE_RATE);
+        let mut wow_flutter =
+            WowFlutter::new(SAMPLE_RATE as u32, &mut wow_flutter_manager).unwrap();
+
+        settle(
+            &mut delay,
+            attributes_with_head(0.2, 0.0),
+            &mut tone,
+            &mut wow_flutter,
+        );
+
+        const BEAT: f32 = 0.25;
+        let mut attributes = attributes_with_head(0.7, 0.0);
+        attributes.heads[0].rewind_backward = Some(0.5);
+        attributes.rewind_beat = Some(BEAT);
+
+        let beat_in_samples = (BEAT * SAMPLE_RATE) as usize;
+        let mut landed_after = None;
+        for block in 0..20 {
+            delay.set_attributes(attributes);
+            let mut input = [0.0; BLOCK_SIZE];
+            let mut left = [0.0; BLOCK_SIZE];
+            let mut right = [0.0; BLOCK_SIZE];
+            delay.process(
+                &mut input,
+                &mut left,
+                &mut right,
+                &mut tone,
+                &mut wow_flutter,
+                &mut TestRandom,
+            );
+            if landed_after.is_none() && delay.heads[0].reader.impulse_position() == 700.0 {
+                landed_after = Some((block + 1) * BLOCK_SIZE);
+            }
+        }
+
+        let landed_after = landed_after.expect("The head never reached the target");
+        assert!(landed_after >= beat_in_samples);
+        assert!(landed_after < beat_in_samples + BLOCK_SIZE);
+    }
+
+    #[test]
+    fn when_head_rewinds_its_reported_position_tracks_the_target() {
+        let mut delay_manager = memory_manager!(512 * 1024);
+        let mut wow_flutter_manager = memory_manager!(1024);
+        let mut delay = Delay::new(SAMPLE_RATE, &mut delay_manager).unwrap();
+        let mut tone = Tone2::new(SAMPLE_RATE);
+        let mut wow_flutter =
+            WowFlutter::new(SAMPLE_RATE as u32, &mut wow_flutter_manager).unwrap();
+
+        settle(
+            &mut delay,
+            attributes_with_head(0.2, 0.0),
+            &mut tone,
+            &mut wow_flutter,
+        );
+
+        // NOTE: Without a beat, the inertia of the rewind is tuned to the
+        // sample rate of the hardware, taking ages in the test.
+        let mut attributes = attributes_with_head(0.7, 0.0);
+        attributes.heads[0].rewind_backward = Some(0.5);
+        attributes.rewind_beat = Some(0.25);
+
+        let mut last_position = 0.2;
+        let mut passed_between = false;
+        for _ in 0..20 {
+            delay.set_attributes(attributes);
+            let mut input = [0.0; BLOCK_SIZE];
+            let mut left = [0.0; BLOCK_SIZE];
+            let mut right = [0.0; BLOCK_SIZE];
+            let reaction = delay.process(
+                &mut input,
+                &mut left,
+                &mut right,
+                &mut tone,
+                &mut wow_flutter,
+                &mut TestRandom,
+            );
+            let position = reaction.head_positions[0];
+            assert!(position >= last_position);
+            assert!(position <= 0.7);
+            passed_between |= position > 0.3 && position < 0.6;
+            last_position = position;
+        }
+
+        assert!(passed_between);
+        assert_relative_eq!(last_position, 0.7);
+    }
+
+    #[test]
+    fn when_length_is_zero_reported_head_positions_stay_finite() {
+        let mut delay_manager = memory_manager!(512 * 1024);
+        let mut wow_flutter_manager = memory_manager!(1024);
+        let mut delay = Delay::new(SAMPLE_RATE, &mut delay_manager).unwrap();
+        let mut tone = Tone2::new(SAMPLE_RATE);
+        let mut wow_flutter =
+            WowFlutter::new(SAMPLE_RATE as u32, &mut wow_flutter_manager).unwrap();
+
+        let mut attributes = attributes_with_head(0.5, 0.0);
+        attributes.length = 0.0;
+        delay.set_attributes(attributes);
+        let mut input = [0.0; BLOCK_SIZE];
+        let mut left = [0.0; BLOCK_SIZE];
+        let mut right = [0.0; BLOCK_SIZE];
+        let reaction = delay.process(
+            &mut input,
+            &mut left,
+            &mut right,
+            &mut tone,
+            &mut wow_flutter,
+            &mut TestRandom,
+        );
+
+        for position in reaction.head_positions {
+            assert!(position.is_finite());
+            assert_relative_eq!(position, 0.0);
+        }
+    }
+
+    // NOTE: Blocks covering all but the last few samples of a 1000 samples
+    // long loop.
+    const LOOP_BLOCKS: usize = 992 / BLOCK_SIZE;
+
+    #[test]
+    fn when_cursor_passes_head_its_gate_is_high_for_the_given_width() {
+        let mut delay_manager = memory_manager!(512 * 1024);
+        let mut wow_flutter_manager = memory_manager!(1024);
+        let mut delay = Delay::new(SAMPLE_RATE, &mut delay_manager).unwrap();
+        let mut tone = Tone2::new(SAMPLE_RATE);
+        let mut wow_flutter =
+            WowFlutter::new(SAMPLE_RATE as u32, &mut wow_flutter_manager).unwrap();
+
+        let mut attributes = attributes_with_head(0.5, 0.0);
+        attributes.gate_width = 0.1;
+        settle(&mut delay, attributes, &mut tone, &mut wow_flutter);
+
+        // NOTE: The loop is 1000 samples long. With the cursor reset, the
+        // head at its half is crossed on the 500th sample.
+        let mut gates = [false; LOOP_BLOCKS];
+        for (i, gate) in gates.iter_mut().enumerate() {
+            attributes.reset_impulse = i == 0;
+            delay.set_attributes(attributes);
+            let mut input = [0.0; BLOCK_SIZE];
+            let mut left = [0.0; BLOCK_SIZE];
+            let mut right = [0.0; BLOCK_SIZE];
+            let reaction = delay.process(
+                &mut input,
+                &mut left,
+                &mut right,
+                &mut tone,
+                &mut wow_flutter,
+                &mut TestRandom,
+            );
+            *gate = reaction.head_gates[0];
+        }
+
+        for (i, gate) in gates.iter().enumerate() {
+            let cursor = (i + 1) * BLOCK_SIZE;
+            let expected = (500..600).contains(&cursor);
+            assert_eq!(*gate, expected, "Gate after {cursor} samples");
+        }
+    }
+
+    fn settled_head_positions(
+        delay_manager: &mut MemoryManager,
+        wow_flutter_manager: &mut MemoryManager,
+        separate_heads: bool,
+    ) -> [f32; 4] {
+        let mut delay = Delay::new(SAMPLE_RATE, delay_manager).unwrap();
+        let mut tone = Tone2::new(SAMPLE_RATE);
+        let mut wow_flutter = WowFlutter::new(SAMPLE_RATE as u32, wow_flutter_manager).unwrap();
+
+        let mut attributes = attributes_with_head(0.5, 0.0);
+        attributes.separate_heads = separate_heads;
+        settle(&mut delay, attributes, &mut tone, &mut wow_flutter);
+
+        core::array::from_fn(|i| delay.heads[i].reader.impulse_position())
+    }
+
+    // NOTE: Returns positions of the first two heads in samples, settled
+    // before and after the length doubles.
+    fn head_positions_before_and_after_length_change(
+        delay_manager: &mut MemoryManager,
+        wow_flutter_manager: &mut MemoryManager,
+        position_mode: PositionMode,
+    ) -> ([f32; 2], [f32; 2]) {
+        let mut delay = Delay::new(SAMPLE_RATE, delay_manager).unwrap();
+        let mut wow_flutter = WowFlutter::new(SAMPLE_RATE as u32, wow_flutter_manager).unwrap();
+        let mut tone = Tone2::new(SAMPLE_RATE);
+
+        let mut attributes = attributes_with_head(0.0, 0.0);
+        attributes.heads[0].position = 0.25;
+        attributes.heads[1].position = 0.5;
+        attributes.beat = Some(0.1);
+        attributes.position_mode = position_mode;
+
+        settle(&mut delay, attributes, &mut tone, &mut wow_flutter);
+        let before = core::array::from_fn(|i| delay.heads[i].reader.impulse_position());
+
+        attributes.length = 2.0;
+        settle(&mut delay, attributes, &mut tone, &mut wow_flutter);
+        let after = core::array::from_fn(|i| delay.heads[i].reader.impulse_position());
+
+        (before, after)
+    }
+
+    #[test]
+    fn when_position_is_proportional_heads_scale_with_length() {
+        let mut delay_manager = memory_manager!(512 * 1024);
+        let mut wow_flutter_manager = memory_manager!(1024);
+
+        let (before, after) = head_positions_before_and_after_length_change(
+            &mut delay_manager,
+            &mut wow_flutter_manager,
+            PositionMode::Proportional,
+        );
+
+        assert_relative_eq!(before[0], 250.0);
+        assert_relative_eq!(before[1], 500.0);
+        assert_relative_eq!(after[0], 500.0);
+        assert_relative_eq!(after[1], 1000.0);
+    }
+
+    #[test]
+    fn when_position_is_beat_relative_heads_keep_their_spacing_when_length_changes() {
+        let mut delay_manager = memory_manager!(512 * 1024);
+        let mut wow_flutter_manager = memory_manager!(1024);
+
+        let (before, after) = head_positions_before_and_after_length_change(
+            &mut delay_manager,
+            &mut wow_flutter_manager,
+            PositionMode::BeatRelative,
+        );
+
+        // NOTE: A bar of 4 beats of 0.1 seconds, heads on its second and
+        // third beat.
+        assert_relative_eq!(before[0], 100.0);
+        assert_relative_eq!(before[1], 200.0);
+        assert_relative_eq!(after[0], 100.0);
+        assert_relative_eq!(after[1], 200.0);
+    }
+
+    #[test]
+    fn when_heads_overlap_and_separation_is_enabled_they_are_nudged_apart() {
+        let mut delay_manager = memory_manager!(512 * 1024);
+        let mut wow_flutter_manager = memory_manager!(1024);
+
+        let positions = settled_head_positions(&mut delay_manager, &mut wow_flutter_manager, true);
+
+        let min_distance = MIN_HEAD_DISTANCE * SAMPLE_RATE;
+        for i in 0..positions.len() {
+            for j in 0..i {
+                let distance = (positions[i] - positions[j]).abs();
+                assert!(
+                    distance >= min_distance * 0.999,
+                    "Heads {j} and {i} are {distance} samples apart"
+                );
+            }
+        }
+    }
+
+    #[test]
+    fn when_heads_overlap_and_separation_is_disabled_they_stay_in_place() {
+        let mut delay_manager = memory_manager!(512 * 1024);
+        let mut wow_flutter_manager = memory_manager!(1024);
+
+        let positions = settled_head_positions(&mut delay_manager, &mut wow_flutter_manager, false);
+
+        for position in positions {
+            assert_relative_eq!(position, 500.0);
+        }
+    }
+
+    #[test]
+    fn when_muted_head_feeds_back_inverted_it_is_still_separated() {
+        let mut delay_manager = memory_manager!(512 * 1024);
+        let mut wow_flutter_manager = memory_manager!(1024);
+        let mut delay = Delay::new(SAMPLE_RATE, &mut delay_manager).unwrap();
+        let mut tone = Tone2::new(SAMPLE_RATE);
+        let mut wow_flutter =
+            WowFlutter::new(SAMPLE_RATE as u32, &mut wow_flutter_manager).unwrap();
+
+        let mut attributes = attributes_with_head(0.5, 0.0);
+        attributes.separate_heads = true;
+        attributes.heads[1].volume = 0.0;
+        attributes.heads[1].feedback = -0.5;
+        for head in &mut attributes.heads[2..] {
+            head.volume = 0.0;
+        }
+        settle(&mut delay, attributes, &mut tone, &mut wow_flutter);
+
+        let distance = (delay.heads[1].reader.impulse_position()
+            - delay.heads[0].reader.impulse_position())
+        .abs();
+        assert!(distance >= MIN_HEAD_DISTANCE * SAMPLE_RATE * 0.999);
+    }
+
+    #[test]
+    fn when_ping_pong_is_enabled_echoes_alternate_between_sides_and_decay() {
+        let mut delay_manager = memory_manager!(512 * 1024);
+        let mut wow_flutter_manager = memory_manager!(1024);
+        let mut delay = Delay::new(SAMPLE_RATE, &mut delay_manager).unwrap();
+        let mut tone = Tone2::new(SAMPLE_RATE);
+        tone.set_attributes(ToneAttributes {
+            tone: 0.5,
+            filter_type: FilterType::Iir,
+            slew: None,
+            loudness_compensation: false,
+            cutoff: None,
+        });
+        let mut wow_flutter =
+            WowFlutter::new(SAMPLE_RATE as u32, &mut wow_flutter_manager).unwrap();
+
+        let mut attributes = attributes_with_head(0.1, 0.0);
+        attributes.heads[0].feedback = 0.5;
+        attributes.ping_pong = true;
+        settle(&mut delay, attributes, &mut tone, &mut wow_flutter);
+
+        let mut left = [0.0; 512];
+        let mut right = [0.0; 512];
+        for (i, (l, r)) in left
+            .chunks_mut(BLOCK_SIZE)
+            .zip(right.chunks_mut(BLOCK_SIZE))
+            .enumerate()
+        {
+            let mut input = [0.0; BLOCK_SIZE];
+            if i == 0 {
+                input[0] = 1.0;
+            }
+            delay.process(
+                &mut input,
+                l,
+                r,
+                &mut tone,
+                &mut wow_flutter,
+                &mut TestRandom,
+            );
+        }
+
+        // NOTE: The loop is 1000 samples long, so the interval is 100 samples.
+        let peak = |channel: &[f32], echo: usize| {
+            channel[echo * 100 - 5..echo * 100 + 5]
+                .iter()
+                .fold(0.0_f32, |max, x| max.max(x.abs()))
+        };
+        let mut previous = f32::MAX;
+        for echo in 1..=4 {
+            let (near, far) = if echo % 2 == 1 {
+                (peak(&left, echo), peak(&right, echo))
+            } else {
+                (peak(&right, echo), peak(&left, echo))
+            };
+            assert!(near > 0.05, "Echo {echo} is missing");
+            assert!(far < near * 0.05, "Echo {echo} leaks to the other side");
+            assert!(near < previous * 0.75, "Echo {echo} does not decay");
+            previous = near;
+        }
+    }
+
+    // NOTE: Returns number of impulses sent by a single head placed on the
+    // given offset from a beat, in beats, over 200 loops.
+    fn random_impulses_of_head(
+        delay: &mut Delay,
+        tone: &mut Tone2,
+        wow_flutter: &mut WowFlutter,
+        offset_from_beat: f32,
+        impulse_accent: f32,
+    ) -> usize {
+        // NOTE: The loop is 256 samples long and split into four beats.
+        const LENGTH: f32 = 0.256;
+        const BEAT: f32 = LENGTH / 4.0;
+
+        let mut attributes = attributes_with_head((1.0 + offset_from_beat) * BEAT / LENGTH, 0.0);
+        attributes.length = LENGTH;
+        attributes.beat = Some(BEAT);
+        attributes.random_impulse = true;
+        attributes.impulse_accent = impulse_accent;
+        attributes.heads[0].volume = 0.5;
+        for head in &mut attributes.heads[1..] {
+            head.volume = 0.0;
+        }
+        settle(delay, attributes, tone, wow_flutter);
+
+        let mut random = SeededRandom(0x1234_5678);
+        let mut impulses = 0;
+        for _ in 0..200 * 256 / BLOCK_SIZE {
+            let mut input = [0.0; BLOCK_SIZE];
+            let mut left = [0.0; BLOCK_SIZE];
+            let mut right = [0.0; BLOCK_SIZE];
+            let reaction = delay.process(
+                &mut input,
+                &mut left,
+                &mut right,
+                tone,
+                wow_flutter,
+                &mut random,
+            );
+            impulses += usize::from(reaction.impulse);
+        }
+        impulses
+    }
+
+    fn blocks_with_impulse(
+        delay: &mut Delay,
+        tone: &mut Tone2,
+        wow_flutter: &mut WowFlutter,
+        impulse_threshold: f32,
+    ) -> [bool; LOOP_BLOCKS] {
+        // NOTE: The loop is 1000 samples long. Heads get louder the later
+        // they are placed.
+        let mut attributes = attributes_with_head(0.0, 0.0);
+        attributes.impulse_threshold = impulse_threshold;
+        for (head, (position, volume)) in
+            attributes
+                .heads
+                .iter_mut()
+                .zip([(0.1, 0.05), (0.35, 0.15), (0.6, 0.3), (0.85, 0.6)])
+        {
+            head.position = position;
+            head.volume = volume;
+        }
+        settle(delay, attributes, tone, wow_flutter);
+
+        let mut impulses = [false; LOOP_BLOCKS];
+        for (i, impulse) in impulses.iter_mut().enumerate() {
+            attributes.reset_impulse = i == 0;
+            delay.set_attributes(attributes);
+            let mut input = [0.0; BLOCK_SIZE];
+            let mut left = [0.0; BLOCK_SIZE];
+            let mut right = [0.0; BLOCK_SIZE];
+            let reaction = delay.process(
+                &mut input,
+                &mut left,
+                &mut right,
+                tone,
+                wow_flutter,
+                &mut TestRandom,
+            );
+            *impulse = reaction.impulse;
+        }
+        impulses
+    }
+
+    #[test]
+    fn when_impulse_threshold_is_raised_only_louder_heads_send_impulses() {
+        let mut delay_manager = memory_manager!(512 * 1024);
+        let mut wow_flutter_manager = memory_manager!(1024);
+        let mut delay = Delay::new(SAMPLE_RATE, &mut delay_manager).unwrap();
+        let mut tone = Tone2::new(SAMPLE_RATE);
+        let mut wow_flutter =
+            WowFlutter::new(SAMPLE_RATE as u32, &mut wow_flutter_manager).unwrap();
+
+        let impulse_blocks = |impulses: [bool; LOOP_BLOCKS]| {
+            let mut blocks = impulses
+                .iter()
+                .enumerate()
+                .filter_map(|(i, impulse)| impulse.then_some(i));
+            [blocks.next(), blocks.next(), blocks.next(), blocks.next()]
+        };
+
+        // NOTE: With the cursor reset, heads are crossed on the 100th, 350th,
+        // 600th and 850th sample.
+        let [first, second, third, fourth] = [100, 350, 600, 850].map(|x| Some(x / BLOCK_SIZE));
+        let impulses = blocks_with_impulse(&mut delay, &mut tone, &mut wow_flutter, 0.01);
+        assert_eq!(impulse_blocks(impulses), [first, second, third, fourth]);
+
+        let impulses = blocks_with_impulse(&mut delay, &mut tone, &mut wow_flutter, 0.2);
+        assert_eq!(impulse_blocks(impulses), [third, fourth, None, None]);
+    }
+
+    #[test]
+    fn when_impulse_is_subdivided_head_sends_given_number_of_impulses_per_cycle() {
+        let mut delay_manager = memory_manager!(512 * 1024);
+        let mut wow_flutter_manager = memory_manager!(1024);
+        let mut delay = Delay::new(SAMPLE_RATE, &mut delay_manager).unwrap();
+        let mut tone = Tone2::new(SAMPLE_RATE);
+        let mut wow_flutter =
+            WowFlutter::new(SAMPLE_RATE as u32, &mut wow_flutter_manager).unwrap();
+
+        for subdivisions in 1..=4 {
+            // NOTE: The loop is 1000 samples long and the only audible head
+            // is placed at its 100th sample.
+            let mut attributes = attributes_with_head(0.1, 0.0);
+            attributes.impulse_subdivisions = subdivisions;
+            for head in &mut attributes.heads[1..] {
+                head.volume = 0.0;
+            }
+            settle(&mut delay, attributes, &mut tone, &mut wow_flutter);
+
+            let mut impulses = 0;
+            for i in 0..LOOP_BLOCKS {
+                attributes.reset_impulse = i == 0;
+                delay.set_attributes(attributes);
+                let mut input = [0.0; BLOCK_SIZE];
+                let mut left = [0.0; BLOCK_SIZE];
+                let mut right = [0.0; BLOCK_SIZE];
+                let reaction = delay.process(
+                    &mut input,
+                    &mut left,
+                    &mut right,
+                    &mut tone,
+                    &mut wow_flutter,
+                    &mut TestRandom,
+                );
+                impulses += usize::from(reaction.impulse);
+            }
+
+            assert_eq!(impulses, subdivisions);
+        }
+    }
+
+    #[test]
+    fn when_impulse_accent_is_high_random_impulses_concentrate_on_beats() {
+        let mut delay_manager = memory_manager!(512 * 1024);
+        let mut wow_flutter_manager = memory_manager!(1024);
+        let mut delay = Delay::new(SAMPLE_RATE, &mut delay_manager).unwrap();
+        let mut tone = Tone2::new(SAMPLE_RATE);
+        let mut wow_flutter =
+            WowFlutter::new(SAMPLE_RATE as u32, &mut wow_flutter_manager).unwrap();
+
+        let [on_beat, quarter_off, half_off] = [0.0, 0.25, 0.5].map(|offset| {
+            random_impulses_of_head(&mut delay, &mut tone, &mut wow_flutter, offset, 1.0)
+        });
+
+        assert!(on_beat > 70, "On beat {on_beat}");
+        assert!(quarter_off < on_beat * 3 / 4, "Quarter off {quarter_off}");
+        assert!(half_off < 10, "Half off {half_off}");
+    }
+
+    #[test]
+    fn when_impulse_accent_is_zero_random_impulses_are_spread_evenly() {
+        let mut delay_manager = memory_manager!(512 * 1024);
+        let mut wow_flutter_manager = memory_manager!(1024);
+        let mut delay = Delay::new(SAMPLE_RATE, &mut delay_manager).unwrap();
+        let mut tone = Tone2::new(SAMPLE_RATE);
+        let mut wow_flutter =
+            WowFlutter::new(SAMPLE_RATE as u32, &mut wow_flutter_manager).unwrap();
+
+        let [on_beat, half_off] = [0.0, 0.5].map(|offset| {
+            random_impulses_of_head(&mut delay, &mut tone, &mut wow_flutter, offset, 0.0)
+        });
+
+        assert!(on_beat > 70, "On beat {on_beat}");
+        assert!(half_off > 70, "Half off {half_off}");
+    }
+
+    // NOTE: The tape is filled so the heads have something to feed back. It
+    // alternates, so it passes through DC blockers on the feedback path. The
+    // input is silent, so everything written in the block is the feedback.
+    fn feedback_contribution(
+        delay: &mut Delay,
+        tone: &mut Tone2,
+        wow_flutter: &mut WowFlutter,
+    ) -> f32 {
+        for i in 0..delay.buffer.len() {
+            delay.buffer.write(if i % 2 == 0 { 1.0 } else { -1.0 });
+        }
+
+        let mut input = [0.0; BLOCK_SIZE];
+        let mut left = [0.0; BLOCK_SIZE];
+        let mut right = [0.0; BLOCK_SIZE];
+        delay.process(
+            &mut input,
+            &mut left,
+            &mut right,
+            tone,
+            wow_flutter,
+            &mut TestRandom,
+        );
+
+        (0..BLOCK_SIZE).map(|i| delay.buffer.peek(i).abs()).sum()
+    }
+
+    // NOTE: Returns the peak of the output during each of the last loops of
+    // a head feeding back with gain above unity.
+    fn self_oscillation_peaks(
+        delay: &mut Delay,
+        tone: &mut Tone2,
+        wow_flutter: &mut WowFlutter,
+        feedback_character: f32,
+    ) -> [f32; 4] {
+        // NOTE: The loop is 96 samples long, three blocks.
+        let mut attributes = attributes_with_head(0.096, 0.0);
+        attributes.heads[0].feedback = 1.5;
+        attributes.feedback_character = feedback_character;
+        for head in &mut attributes.heads[1..] {
+            head.volume = 0.0;
+        }
+        settle(delay, attributes, tone, wow_flutter);
+
+        let mut peaks = [0.0_f32; 4];
+        for i in 0..300 {
+            let mut input = [0.0; BLOCK_SIZE];
+            if i == 0 {
+                input[0] = 0.1;
+            }
+            let mut left = [0.0; BLOCK_SIZE];
+            let mut right = [0.0; BLOCK_SIZE];
+            delay.process(
+                &mut input,
+                &mut left,
+                &mut right,
+                tone,
+                wow_flutter,
+                &mut TestRandom,
+            );
+            let loop_index = i / 3;
+            if loop_index >= 96 {
+                let peak = &mut peaks[loop_index - 96];
+                *peak = left.iter().fold(*peak, |peak, x| peak.max(x.abs()));
+            }
+        }
+        peaks
+    }
+
+    #[test]
+    fn when_feedback_character_is_set_self_oscillation_settles_below_clipping() {
+        let mut delay_manager = memory_manager!(512 * 1024);
+        let mut wow_flutter_manager = memory_manager!(1024);
+        let mut delay = Delay::new(SAMPLE_RATE, &mut delay_manager).unwrap();
+        let mut tone = Tone2::new(SAMPLE_RATE);
+        tone.set_attributes(ToneAttributes {
+            tone: 0.5,
+            filter_type: FilterType::Iir,
+            slew: None,
+            loudness_compensation: false,
+            cutoff: None,
+        });
+        let mut wow_flutter =
+            WowFlutter::new(SAMPLE_RATE as u32, &mut wow_flutter_manager).unwrap();
+
+        let peaks = self_oscillation_peaks(&mut delay, &mut tone, &mut wow_flutter, 1.0);
+
+        for peak in peaks {
+            assert!((0.05..1.0).contains(&peak), "Peak {peak}");
+            assert!(
+                (peak / peaks[0] - 1.0).abs() < 0.05,
+                "Peaks did not settle {peaks:?}"
+            );
+        }
+    }
+
+    #[test]
+    fn when_stereo_offset_is_set_right_echo_follows_the_left_one() {
+        let mut delay_manager = memory_manager!(512 * 1024);
+        let mut wow_flutter_manager = memory_manager!(1024);
+        let mut delay = Delay::new(SAMPLE_RATE, &mut delay_manager).unwrap();
+        let mut tone = Tone2::new(SAMPLE_RATE);
+        tone.set_attributes(ToneAttributes {
+            tone: 0.5,
+            filter_type: FilterType::Iir,
+            slew: None,
+            loudness_compensation: false,
+            cutoff: None,
+        });
+        let mut wow_flutter =
+            WowFlutter::new(SAMPLE_RATE as u32, &mut wow_flutter_manager).unwrap();
+
+        // NOTE: With the sample rate of 1 kHz, a millisecond is a sample.
+        let mut attributes = attributes_with_head(0.1, 0.0);
+        attributes.stereo_offset_ms = 5.0;
+        settle(&mut delay, attributes, &mut tone, &mut wow_flutter);
+
+        let mut left = [0.0; BLOCK_SIZE * 8];
+        let mut right = [0.0; BLOCK_SIZE * 8];
+        for (i, (chunk_left, chunk_right)) in left
+            .chunks_mut(BLOCK_SIZE)
+            .zip(right.chunks_mut(BLOCK_SIZE))
+            .enumerate()
+        {
+            let mut input = [0.0; BLOCK_SIZE];
+            if i == 0 {
+                input[0] = 1.0;
+            }
+            delay.process(
+                &mut input,
+                chunk_left,
+                chunk_right,
+                &mut tone,
+                &mut wow_flutter,
+                &mut TestRandom,
+            );
+        }
+
+        let peak_index = |buffer: &[f32]| {
+            (0..buffer.len())
+                .max_by(|a, b| buffer[*a].abs().total_cmp(&buffer[*b].abs()))
+                .unwrap()
+        };
+        // NOTE: The head is 100 samples behind the write cursor.
+        let left_peak = peak_index(&left);
+        assert!((100..=101).contains(&left_peak), "Left echo at {left_peak}");
+        assert_eq!(peak_index(&right), left_peak + 5);
+    }
+
+    #[test]
+    fn when_buffer_reset_finishes_feedback_ramps_up_with_the_input() {
+        let mut delay_manager = memory_manager!(512 * 1024);
+        let mut wow_flutter_manager = memory_manager!(1024);
+        let mut delay = Delay::new(SAMPLE_RATE, &mut delay_manager).unwrap();
+        let mut tone = Tone2::new(SAMPLE_RATE);
+        let mut wow_flutter =
+            WowFlutter::new(SAMPLE_RATE as u32, &mut wow_flutter_manager).unwrap();
+
+        let mut attributes = attributes_with_head(0.5, 0.0);
+        attributes.heads[0].feedback = 0.9;
+        settle(&mut delay, attributes, &mut tone, &mut wow_flutter);
+        let full = feedback_contribution(&mut delay, &mut tone, &mut wow_flutter);
+
+        attributes.reset_buffer = true;
+        delay.set_attributes(attributes);
+        attributes.reset_buffer = false;
+        delay.set_attributes(attributes);
+        while !matches!(delay.buffer_reset, BufferReset::FadingIn(0, _)) {
+            let mut input = [0.0; BLOCK_SIZE];
+            let mut left = [0.0; BLOCK_SIZE];
+            let mut right = [0.0; BLOCK_SIZE];
+            delay.process(
+                &mut input,
+                &mut left,
+                &mut right,
+                &mut tone,
+                &mut wow_flutter,
+                &mut TestRandom,
+            );
+        }
+        let first_block = feedback_contribution(&mut delay, &mut tone, &mut wow_flutter);
+
+        while !matches!(delay.buffer_reset, BufferReset::FadingIn(500, _)) {
+            feedback_contribution(&mut delay, &mut tone, &mut wow_flutter);
+        }
+        let halfway = feedback_contribution(&mut delay, &mut tone, &mut wow_flutter);
+
+        assert!(full > 1.0, "Full feedback {full}");
+        assert!(first_block < full * 0.01, "First block {first_block}");
+        assert!(
+            (0.4..0.6).contains(&(halfway / full)),
+            "Halfway {halfway}, full {full}"
+        );
+    }
+
+    #[test]
+    fn when_buffer_is_spliced_loop_holds_a_permutation_of_its_chunks() {
+        let mut delay_manager = memory_manager!(512 * 1024);
+        let mut wow_flutter_manager = memory_manager!(1024);
+        let mut delay = Delay::new(SAMPLE_RATE, &mut delay_manager).unwrap();
+        let mut tone = Tone2::new(SAMPLE_RATE);
+        let mut wow_flutter =
+            WowFlutter::new(SAMPLE_RATE as u32, &mut wow_flutter_manager).unwrap();
+        let mut random = SeededRandom(0x1234_5678);
+
+        let mut attributes = attributes_with_head(1.0, 0.0);
+        settle(&mut delay, attributes, &mut tone, &mut wow_flutter);
+        attributes.reset_buffer = true;
+        attributes.splice_buffer = true;
+        delay.set_attributes(attributes);
+        attributes.reset_buffer = false;
+        delay.set_attributes(attributes);
+
+        let mut process = |delay: &mut Delay, random: &mut SeededRandom| {
+            let mut input = [0.0; BLOCK_SIZE];
+            let mut left = [0.0; BLOCK_SIZE];
+            let mut right = [0.0; BLOCK_SIZE];
+            delay.process(
+                &mut input,
+                &mut left,
+                &mut right,
+                &mut tone,
+                &mut wow_flutter,
+                random,
+            );
+        };
+
+        // NOTE: Each chunk of the loop is filled with its own number, so it
+        // can be recognized after the shuffle.
+        while !matches!(delay.buffer_reset, BufferReset::Resetting(0, _)) {
+            process(&mut delay, &mut random);
+        }
+        let loop_length = delay.length_samples();
+        let chunk_length = loop_length / splice::CHUNKS;
+        for age in 0..loop_length {
+            *delay.buffer.peek_mut(age) = (age / chunk_length + 1) as f32;
+        }
+        while !matches!(delay.buffer_reset, BufferReset::FadingIn(0, _)) {
+            process(&mut delay, &mut random);
+        }
+
+        let mut chunks = [0.0; splice::CHUNKS];
+        for (i, chunk) in chunks.iter_mut().enumerate() {
+            let middle = delay.buffer.peek(i * chunk_length + chunk_length / 2);
+            for age in i * chunk_length + 10..(i + 1) * chunk_length - 10 {
+                assert_relative_eq!(delay.buffer.peek(age), middle);
+            }
+            *chunk = middle;
+        }
+        let mut sorted = chunks;
+        sorted.sort_by(f32::total_cmp);
+        assert_eq!(sorted, [1.0, 2.0, 3.0, 4.0, 5.0, 6.0, 7.0, 8.0]);
+        assert_ne!(chunks, sorted);
+
+        // NOTE: Neighbouring chunks differ by up to 7, joins are smoothed
+        // by the fades.
+        let largest_step = (1..loop_length).fold(0.0, |max: f32, age| {
+            max.max((delay.buffer.peek(age) - delay.buffer.peek(age - 1)).abs())
+        });
+        assert!(largest_step < 2.0, "Largest step {largest_step}");
+    }
+
+    #[test]
+    fn when_frozen_it_keeps_looping_the_tape_and_ignores_the_input() {
+        let mut delay_manager = memory_manager!(512 * 1024);
+        let mut wow_flutter_manager = memory_manager!(1024);
+        let mut delay = Delay::new(SAMPLE_RATE, &mut delay_manager).unwrap();
+        let mut tone = Tone2::new(SAMPLE_RATE);
+        tone.set_attributes(ToneAttributes {
+            tone: 0.5,
+            filter_type: FilterType::Iir,
+            slew: None,
+            loudness_compensation: false,
+            cutoff: None,
+        });
+        let mut wow_flutter =
+            WowFlutter::new(SAMPLE_RATE as u32, &mut wow_flutter_manager).unwrap();
+
+        // NOTE: The loop spans 128 samples.
+        const LOOP_LENGTH: usize = 128;
+        let mut attributes = attributes_with_head(0.5, 0.0);
+        attributes.length = LOOP_LENGTH as f32 / SAMPLE_RATE;
+        settle(&mut delay, attributes, &mut tone, &mut wow_flutter);
+
+        let mut process = |delay: &mut Delay, i: usize| {
+            let mut input: [f32; BLOCK_SIZE] =
+                core::array::from_fn(|j| libm::sinf((i * BLOCK_SIZE + j) as f32 * 0.37) * 0.5);
+            let mut left = [0.0; BLOCK_SIZE];
+            let mut right = [0.0; BLOCK_SIZE];
+            delay.process(
+                &mut input,
+                &mut left,
+                &mut right,
+                &mut tone,
+                &mut wow_flutter,
+                &mut TestRandom,
+            );
+            left
+        };
+
+        const WARM_UP_BLOCKS: usize = 640 / BLOCK_SIZE;
+        for i in 0..WARM_UP_BLOCKS {
+            process(&mut delay, i);
+        }
+        attributes.freeze = true;
+        delay.set_attributes(attributes);
+
+        // NOTE: Skip the fade and let the loop get recorded over once.
+        let mut output = [0.0; 640];
+        for (i, chunk) in output.chunks_mut(BLOCK_SIZE).enumerate() {
+            chunk.copy_from_slice(&process(&mut delay, WARM_UP_BLOCKS + i));
+        }
+        let settled = &output[LOOP_LENGTH * 2..];
+
+        let peak = settled.iter().fold(0.0, |max: f32, x| max.max(x.abs()));
+        assert!(peak > 0.5, "Peak {peak}");
+        for (x, looped) in settled.iter().zip(&settled[LOOP_LENGTH..]) {
+            assert_relative_eq!(*x, *looped, epsilon = 0.000_1);
+        }
+    }
+
+    #[test]
+    fn when_content_is_recorded_into_the_loop_fill_estimate_rises() {
+        let mut delay_manager = memory_manager!(512 * 1024);
+        let mut wow_flutter_manager = memory_manager!(1024);
+        let mut delay = Delay::new(SAMPLE_RATE, &mut delay_manager).unwrap();
+        let mut tone = Tone2::new(SAMPLE_RATE);
+        tone.set_attributes(ToneAttributes {
+            tone: 0.5,
+            filter_type: FilterType::Iir,
+            slew: None,
+            loudness_compensation: false,
+            cutoff: None,
+        });
+        let mut wow_flutter =
+            WowFlutter::new(SAMPLE_RATE as u32, &mut wow_flutter_manager).unwrap();
+
+        // NOTE: The loop spans 64 blocks.
+        const LOOP_LENGTH: usize = 64 * BLOCK_SIZE;
+        let mut attributes = attributes_with_head(0.5, 0.0);
+        attributes.length = LOOP_LENGTH as f32 / SAMPLE_RATE;
+        settle(&mut delay, attributes, &mut tone, &mut wow_flutter);
+
+        let mut process = |delay: &mut Delay, amplitude: f32, i: usize| {
+            let mut input: [f32; BLOCK_SIZE] = core::array::from_fn(|j| {
+                libm::sinf((i * BLOCK_SIZE + j) as f32 * 0.37) * amplitude
+            });
+            let mut left = [0.0; BLOCK_SIZE];
+            let mut right = [0.0; BLOCK_SIZE];
+            delay
+                .process(
+                    &mut input,
+                    &mut left,
+                    &mut right,
+                    &mut tone,
+                    &mut wow_flutter,
+                    &mut TestRandom,
+                )
+                .fill
+        };
+
+        let mut silent = 0.0;
+        for i in 0..64 {
+            silent = process(&mut delay, 0.0, i);
+        }
+
+        // NOTE: Record into half of the loop, then give the estimate time to
+        // visit all the segments while the recording stays within the loop.
+        for i in 0..LOOP_LENGTH / BLOCK_SIZE / 2 {
+            process(&mut delay, 0.5, i);
+        }
+        let mut half = 0.0;
+        for i in 0..LOOP_LENGTH / BLOCK_SIZE / 2 {
+            half = process(&mut delay, 0.0, i);
+        }
+
+        assert_relative_eq!(silent, 0.0);
+        assert!(half > 0.4 && half < 0.6, "Fill {half}");
+    }
+
+    // NOTE: Returns magnitudes of the tone and of its modulation sidebands
+    // folded back from above Nyquist. The tone is 310 Hz and the scrape 40 Hz,
+    // so the folded sidebands land between the genuine ones.
+    fn tone_and_aliasing_of_modulated_read(oversampled_read: bool) -> (f32, f32) {
+        let mut delay_manager = memory_manager!(512 * 1024);
+        let mut wow_flutter_manager = memory_manager!(1024);
+        let mut oversampling_manager = memory_manager!(512);
+        let mut delay = Delay::new(SAMPLE_RATE, &mut delay_manager).unwrap();
+        delay
+            .allocate_read_oversampling(&mut oversampling_manager)
+            .unwrap();
+        let mut tone = Tone2::new(SAMPLE_RATE);
+        let mut wow_flutter =
+            WowFlutter::new(SAMPLE_RATE as u32, &mut wow_flutter_manager).unwrap();
+
+        let mut attributes = attributes_with_head(0.5, 0.0);
+        attributes.wow_flutter_placement = WowFlutterPlacement::Read;
+        attributes.oversampled_read = oversampled_read;
+        for head in &mut attributes.heads[1..] {
+            head.volume = 0.0;
+        }
+        // NOTE: Without feedback, the tone filter is left out of the path.
+        attributes.filter_placement = FilterPlacement::Feedback;
+        delay.set_attributes(attributes);
+        // NOTE: Depth of the scrape is smoothed, it takes a while to settle.
+        for _ in 0..5000 {
+            wow_flutter.set_attributes(WowFlutterAttributes {
+                scrape_depth: 0.004,
+                ..WowFlutterAttributes::default()
+            });
+        }
+
+        let mut output = [0.0; 2048];
+        let skipped_blocks = output.len() / BLOCK_SIZE;
+        for i in 0..2 * skipped_blocks {
+            let mut input: [f32; BLOCK_SIZE] = core::array::from_fn(|j| {
+                let phase = (i * BLOCK_SIZE + j) as f32 * 310.0 / SAMPLE_RATE;
+                libm::sinf(2.0 * core::f32::consts::PI * phase) * 0.5
+            });
+            let mut left = [0.0; BLOCK_SIZE];
+            let mut right = [0.0; BLOCK_SIZE];
+            delay.process(
+                &mut input,
+                &mut left,
+                &mut right,
+                &mut tone,
+                &mut wow_flutter,
+                &mut TestRandom,
+            );
+            if i >= skipped_blocks {
+                let start = (i - skipped_blocks) * BLOCK_SIZE;
+                output[start..start + BLOCK_SIZE].copy_from_slice(&left);
+            }
+        }
+
+        let magnitude = |frequency: f32| {
+            let mut in_phase = 0.0;
+            let mut quadrature = 0.0;
+            // NOTE: Hann window keeps the tone from leaking into its
+            // neighbourhood.
+            for (i, x) in output.iter().enumerate() {
+                let window = 0.5
+                    - 0.5
+                        * libm::cosf(2.0 * core::f32::consts::PI * i as f32 / output.len() as f32);
+                let phase = 2.0 * core::f32::consts::PI * frequency * i as f32 / SAMPLE_RATE;
+                in_phase += x * window * libm::sinf(phase);
+                quadrature += x * window * libm::cosf(phase);
+            }
+            libm::sqrtf(in_phase * in_phase + quadrature * quadrature) * 4.0 / output.len() as f32
+        };
+        let aliasing = [490.0, 450.0, 410.0, 370.0]
+            .iter()
+            .map(|f| magnitude(*f))
+            .sum();
+        (magnitude(310.0), aliasing)
+    }
+
+    #[test]
+    fn when_read_is_oversampled_aliasing_of_modulated_tone_is_reduced() {
+        let (plain_tone, plain_aliasing) = tone_and_aliasing_of_modulated_read(false);
+        let (oversampled_tone, oversampled_aliasing) = tone_and_aliasing_of_modulated_read(true);
+
+        assert!(
+            oversampled_tone > plain_tone * 0.5,
+            "Tone dropped from {plain_tone} to {oversampled_tone}"
+        );
+        assert!(
+            oversampled_aliasing < plain_aliasing / 10.0,
+            "Aliasing only dropped from {plain_aliasing} to {oversampled_aliasing}"
+        );
+    }
+
+    #[test]
+    fn when_reset_with_oversampled_read_it_does_not_replay_stale_audio() {
+        let mut delay_manager = memory_manager!(512 * 1024);
+        let mut wow_flutter_manager = memory_manager!(1024);
+        let mut oversampling_manager = memory_manager!(512);
+        let mut delay = Delay::new(SAMPLE_RATE, &mut delay_manager).unwrap();
+        delay
+            .allocate_read_oversampling(&mut oversampling_manager)
+            .unwrap();
+        let mut tone = Tone2::new(SAMPLE_RATE);
+        let mut wow_flutter =
+            WowFlutter::new(SAMPLE_RATE as u32, &mut wow_flutter_manager).unwrap();
+
+        let mut attributes = attributes_with_head(0.01, 0.0);
+        attributes.oversampled_read = true;
+        settle(&mut delay, attributes, &mut tone, &mut wow_flutter);
+        for _ in 0..4 {
+            let mut input = [1.0; BLOCK_SIZE];
+            let mut left = [0.0; BLOCK_SIZE];
+            let mut right = [0.0; BLOCK_SIZE];
+            delay.process(
+                &mut input,
+                &mut left,
+                &mut right,
+                &mut tone,
+                &mut wow_flutter,
+                &mut TestRandom,
+            );
+        }
+
+        delay.reset(true);
+        tone.reset();
+        wow_flutter.reset();
+        let mut input = [0.0; BLOCK_SIZE];
+        let mut left = [0.0; BLOCK_SIZE];
+        let mut right = [0.0; BLOCK_SIZE];
+        delay.process(
+            &mut input,
+            &mut left,
+            &mut right,
+            &mut tone,
+            &mut wow_flutter,
+            &mut TestRandom,
+        );
+
+        for x in left.iter().chain(&right) {
+            assert_relative_eq!(*x, 0.0);
+        }
+    }
+
+    #[test]
+    fn when_wow_flutter_placement_changes_depth_glides_between_stages_keeping_its_sum() {
+        let mut delay_manager = memory_manager!(512 * 1024);
+        let mut delay = Delay::new(SAMPLE_RATE, &mut delay_manager).unwrap();
+        let step = 1.0 / (SAMPLE_RATE * WOW_FLUTTER_PLACEMENT_GLIDE);
+
+        for (placement, target) in [
+            (WowFlutterPlacement::Input, (1.0, 0.0)),
+            (WowFlutterPlacement::Read, (0.0, 1.0)),
+            (WowFlutterPlacement::Both, (0.5, 0.5)),
+            (WowFlutterPlacement::Input, (1.0, 0.0)),
+        ] {
+            delay.wow_flutter_placement = placement;
+            let mut previous = delay.wow_flutter_shares;
+            for _ in 0..100 {
+                for shares in delay.calculate_wow_flutter_shares() {
+                    assert_relative_eq!(shares.0 + shares.1, 1.0, epsilon = 0.000_1);
+                    assert!((shares.0 - previous.0).abs() <= step + f32::EPSILON);
+                    previous = shares;
+                }
+            }
+            assert_relative_eq!(delay.wow_flutter_shares.0, target.0, epsilon = 0.000_1);
+            assert_relative_eq!(delay.wow_flutter_shares.1, target.1, epsilon = 0.000_1);
+        }
+    }
+
+    #[test]
+    fn when_wow_flutter_placement_changes_live_output_does_not_click() {
+        let mut delay_manager = memory_manager!(512 * 1024);
+        let mut wow_flutter_manager = memory_manager!(1024);
+        let mut delay = Delay::new(SAMPLE_RATE, &mut delay_manager).unwrap();
+        let mut tone = Tone2::new(SAMPLE_RATE);
+        tone.set_attributes(ToneAttributes {
+            tone: 0.5,
+            filter_type: FilterType::Iir,
+            slew: None,
+            loudness_compensation: false,
+            cutoff: None,
+        });
+        let mut wow_flutter =
+            WowFlutter::new(SAMPLE_RATE as u32, &mut wow_flutter_manager).unwrap();
+        // NOTE: Depth is filtered, let it reach the destination.
+        for _ in 0..10_000 {
+            wow_flutter.set_attributes(WowFlutterAttributes {
+                wow_depth: 0.05,
+                ..WowFlutterAttributes::default()
+            });
+        }
+
+        let mut attributes = attributes_with_head(0.5, 0.0);
+        attributes.length = 0.1;
+        settle(&mut delay, attributes, &mut tone, &mut wow_flutter);
+
+        let mut process = |delay: &mut Delay, i: usize| {
+            let mut input: [f32; BLOCK_SIZE] =
+                core::array::from_fn(|j| libm::sinf((i * BLOCK_SIZE + j) as f32 * 0.05) * 0.5);
+            let mut left = [0.0; BLOCK_SIZE];
+            let mut right = [0.0; BLOCK_SIZE];
+            delay.process(
+                &mut input,
+                &mut left,
+                &mut right,
+                &mut tone,
+                &mut wow_flutter,
+                &mut TestRandom,
+            );
+            left
+        };
+        let max_step = |output: &[f32]| {
+            output
+                .windows(2)
+                .fold(0.0, |max: f32, x| max.max((x[1] - x[0]).abs()))
+        };
+
+        // NOTE: Let the wow swing to its full depth.
+        let mut block = 0;
+        let mut output = [0.0; BLOCK_SIZE * 50];
+        for _ in 0..200 {
+            process(&mut delay, block);
+            block += 1;
+        }
+        for chunk in output.chunks_mut(BLOCK_SIZE) {
+            chunk.copy_from_slice(&process(&mut delay, block));
+            block += 1;
+        }
+        let step_before = max_step(&output);
+
+        for placement in [
+            WowFlutterPlacement::Read,
+            WowFlutterPlacement::Both,
+            WowFlutterPlacement::Input,
+        ] {
+            attributes.wow_flutter_placement = placement;
+            delay.set_attributes(attributes);
+            for chunk in output.chunks_mut(BLOCK_SIZE) {
+                chunk.copy_from_slice(&process(&mut delay, block));
+                block += 1;
+            }
+            let step_after = max_step(&output);
+            assert!(
+                step_after < step_before * 1.5,
+                "Step before {step_before}, after {step_after}"
+            );
+        }
+    }
+
+    #[test]
+    fn when_equal_power_pan_law_is_used_total_power_stays_constant() {
+        for i in 0..=100 {
+            let pan = i as f32 / 100.0;
+            let (left, right) = PanLaw::EqualPower.gains(pan);
+            assert_relative_eq!(left * left + right * right, 1.0, epsilon = 0.001);
+        }
+    }
+}
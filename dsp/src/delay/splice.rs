@@ -0,0 +1,95 @@
+//! Reordering of chunks of the loop, making glitchy tape-splice textures.
+//!
+//! The loop may span minutes of audio, so its chunks are never swapped at
+//! once. The shuffle is planned up front and carried out in short steps, each
+//! moving only a part of a chunk. In the last step, edges of all the chunks
+//! are faded, so the new joins do not click.
+
+use crate::random::Random;
+use crate::ring_buffer::RingBuffer;
+
+pub const CHUNKS: usize = 8;
+
+// Duration of the fade on both sides of each join.
+const FADE_DURATION: f32 = 0.005;
+
+#[derive(Debug)]
+#[cfg_attr(feature = "defmt", derive(defmt::Format))]
+pub struct Splice {
+    // Chunk swapped with the chunk of the given index. Following
+    // Fisher-Yates, chunks are visited from the last one to the second.
+    swaps: [usize; CHUNKS],
+    chunk_length: usize,
+    step_length: usize,
+    fade_length: usize,
+}
+
+impl Splice {
+    /// Plan a shuffle of the newest `length` samples of the tape, moving at
+    /// most `step_length` samples in each step.
+    pub fn plan(
+        length: usize,
+        step_length: usize,
+        sample_rate: f32,
+        random: &mut impl Random,
+    ) -> Self {
+        let mut swaps = [0; CHUNKS];
+        for (i, swap) in swaps.iter_mut().enumerate().skip(1) {
+            *swap = ((random.normal() * (i + 1) as f32) as usize).min(i);
+        }
+        let chunk_length = length / CHUNKS;
+        Self {
+            swaps,
+            chunk_length,
+            step_length: step_length.max(1),
+            fade_length: ((sample_rate * FADE_DURATION) as usize).min(chunk_length / 2),
+        }
+    }
+
+    /// Number of steps needed to finish the splice.
+    pub fn steps(&self) -> usize {
+        (CHUNKS - 1) * self.steps_per_chunk() + 1
+    }
+
+    pub fn step(&self, buffer: &mut RingBuffer, index: usize) {
+        let steps_per_chunk = self.steps_per_chunk();
+        if index < (CHUNKS - 1) * steps_per_chunk {
+            let chunk = CHUNKS - 1 - index / steps_per_chunk;
+            let start = (index % steps_per_chunk) * self.step_length;
+            let end = (start + self.step_length).min(self.chunk_length);
+            self.swap_part(buffer, chunk, self.swaps[chunk], start, end);
+        } else {
+            self.fade_joins(buffer);
+        }
+    }
+
+    fn steps_per_chunk(&self) -> usize {
+        self.chunk_length.div_ceil(self.step_length).max(1)
+    }
+
+    fn swap_part(&self, buffer: &mut RingBuffer, a: usize, b: usize, start: usize, end: usize) {
+        if a == b {
+            return;
+        }
+        for offset in start..end {
+            let age_a = a * self.chunk_length + offset;
+            let age_b = b * self.chunk_length + offset;
+            let value_a = buffer.peek(age_a);
+            *buffer.peek_mut(age_a) = buffer.peek(age_b);
+            *buffer.peek_mut(age_b) = value_a;
+        }
+    }
+
+    // NOTE: The age grows towards the past, so the chunk with the higher
+    // index ends at the join and the one with the lower index starts there.
+    fn fade_joins(&self, buffer: &mut RingBuffer) {
+        for chunk in 1..CHUNKS {
+            let join = chunk * self.chunk_length;
+            for i in 0..self.fade_length {
+                let gain = i as f32 / self.fade_length as f32;
+                *buffer.peek_mut(join + i) *= gain;
+                *buffer.peek_mut(join - 1 - i) *= gain;
+            }
+        }
+    }
+}
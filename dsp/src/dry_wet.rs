@@ -0,0 +1,145 @@
+//! Blend of the signal before and after the tape saturation.
+
+use crate::trigonometry;
+
+#[derive(Debug)]
+#[cfg_attr(feature = "defmt", derive(defmt::Format))]
+pub struct DryWet {
+    dry_gain: f32,
+    wet_gain: f32,
+}
+
+#[derive(Default, Clone, Copy, Debug)]
+#[cfg_attr(feature = "defmt", derive(defmt::Format))]
+pub struct Attributes {
+    pub mix: f32,
+    pub curve: Curve,
+}
+
+/// Shape of gains of both signals across the mix range.
+#[derive(Default, Clone, Copy, Debug, PartialEq, Eq)]
+#[cfg_attr(feature = "defmt", derive(defmt::Format))]
+pub enum Curve {
+    /// Gains sum to unity. Uncorrelated signals dip by 3 dB in the middle.
+    #[default]
+    Linear,
+    /// Powers sum to unity, keeping the level of uncorrelated signals.
+    EqualPower,
+    /// The dominant signal stays at unity while the other one fades in.
+    ConstantGain,
+}
+
+impl DryWet {
+    #[must_use]
+    pub fn new() -> Self {
+        let mut dry_wet = Self {
+            dry_gain: 1.0,
+            wet_gain: 0.0,
+        };
+        dry_wet.set_attributes(Attributes::default());
+        dry_wet
+    }
+
+    pub fn process(&self, dry_buffer: &[f32], wet_buffer: &mut [f32]) {
+        for (dry, wet) in dry_buffer.iter().zip(wet_buffer.iter_mut()) {
+            *wet = dry * self.dry_gain + *wet * self.wet_gain;
+        }
+    }
+
+    pub fn set_attributes(&mut self, attributes: Attributes) {
+        let mix = attributes.mix.clamp(0.0, 1.0);
+        (self.dry_gain, self.wet_gain) = match attributes.curve {
+            Curve::Linear => (1.0 - mix, mix),
+            Curve::EqualPower => (
+                trigonometry::cos(mix * 0.25),
+                trigonometry::cos(0.75 + mix * 0.25),
+            ),
+            Curve::ConstantGain => (((1.0 - mix) * 2.0).min(1.0), (mix * 2.0).min(1.0)),
+        };
+    }
+}
+
+impl Default for DryWet {
+    fn default() -> Self {
+        Self::new()
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use core::f32::consts::PI;
+
+    const LENGTH: usize = 1000;
+
+    // NOTE: Two sines of different frequency are uncorrelated, so their
+    // powers add up. That is how the ear perceives the blend too.
+    fn mixed_level(curve: Curve, mix: f32) -> f32 {
+        let dry: [f32; LENGTH] =
+            core::array::from_fn(|i| libm::sinf(i as f32 * 2.0 * PI * 10.0 / LENGTH as f32));
+        let mut wet: [f32; LENGTH] =
+            core::array::from_fn(|i| libm::sinf(i as f32 * 2.0 * PI * 30.0 / LENGTH as f32));
+
+        let mut dry_wet = DryWet::new();
+        dry_wet.set_attributes(Attributes { mix, curve });
+        dry_wet.process(&dry, &mut wet);
+
+        libm::sqrtf(wet.iter().map(|x| x * x).sum::<f32>() / LENGTH as f32)
+    }
+
+    #[test]
+    fn when_mixed_with_equal_power_level_holds_across_the_range() {
+        let full = mixed_level(Curve::EqualPower, 0.0);
+        for mix in [0.25, 0.5, 0.75, 1.0] {
+            let level = mixed_level(Curve::EqualPower, mix);
+            assert_relative_eq!(level, full, max_relative = 0.01);
+        }
+    }
+
+    #[test]
+    fn when_mixed_linearly_level_dips_in_the_middle() {
+        let full = mixed_level(Curve::Linear, 0.0);
+        let middle = mixed_level(Curve::Linear, 0.5);
+
+        assert_relative_eq!(mixed_level(Curve::Linear, 1.0), full, max_relative = 0.01);
+        assert_relative_eq!(
+            middle,
+            full * core::f32::consts::FRAC_1_SQRT_2,
+            max_relative = 0.01
+        );
+    }
+
+    #[test]
+    fn when_mixed_with_constant_gain_level_rises_in_the_middle() {
+        let full = mixed_level(Curve::ConstantGain, 0.0);
+        let middle = mixed_level(Curve::ConstantGain, 0.5);
+
+        assert_relative_eq!(
+            mixed_level(Curve::ConstantGain, 1.0),
+            full,
+            max_relative = 0.01
+        );
+        assert_relative_eq!(
+            middle,
+            full * core::f32::consts::SQRT_2,
+            max_relative = 0.01
+        );
+    }
+
+    #[test]
+    fn when_mix_is_at_its_ends_only_one_signal_passes() {
+        for curve in [Curve::Linear, Curve::EqualPower, Curve::ConstantGain] {
+            let mut dry_wet = DryWet::new();
+
+            dry_wet.set_attributes(Attributes { mix: 0.0, curve });
+            let mut wet = [0.5];
+            dry_wet.process(&[1.0], &mut wet);
+            assert_relative_eq!(wet[0], 1.0, epsilon = 0.001);
+
+            dry_wet.set_attributes(Attributes { mix: 1.0, curve });
+            let mut wet = [0.5];
+            dry_wet.process(&[1.0], &mut wet);
+            assert_relative_eq!(wet[0], 0.5, epsilon = 0.001);
+        }
+    }
+}
@@ -0,0 +1,137 @@
+//! Safety net against runaway feedback.
+//!
+//! Feedback above unity makes the delay oscillate, building up until the
+//! output compressor pins it at its ceiling. The guard follows the level of
+//! the compressed output and once it stays there for a while, it slowly pulls
+//! back feedback of all the heads. After the output calms down, the feedback
+//! is gradually restored. Loud passages of the input do not last long enough
+//! to engage it.
+
+use crate::one_pole_filter::OnePoleFilter;
+
+// RMS level of the output held by the compressor close to its ceiling.
+// Even dense mixes sit lower, thanks to their crest factor.
+const THRESHOLD: f32 = 0.25;
+
+// Slow enough to ride over single cycles of low tones.
+const LEVEL_CUTOFF: f32 = 5.0;
+
+// Time the level must stay above the threshold before the guard engages.
+const ENGAGE_TIME: f32 = 3.0;
+
+// Portion of the feedback pulled back per second while engaged.
+const ATTENUATION_RATE: f32 = 0.25;
+
+// Portion of the feedback restored per second once the output calms down.
+const RECOVERY_RATE: f32 = 0.05;
+
+#[derive(Debug)]
+#[cfg_attr(feature = "defmt", derive(defmt::Format))]
+pub struct FeedbackGuard {
+    mean_square: OnePoleFilter,
+    engage_samples: u32,
+    loud_samples: u32,
+    attenuation_step: f32,
+    recovery_step: f32,
+    scale: f32,
+}
+
+#[derive(Default, Clone, Copy, Debug)]
+#[cfg_attr(feature = "defmt", derive(defmt::Format))]
+pub struct Reaction {
+    pub attenuating: bool,
+}
+
+impl FeedbackGuard {
+    pub fn new(sample_rate: f32) -> Self {
+        Self {
+            mean_square: OnePoleFilter::new(sample_rate, LEVEL_CUTOFF),
+            engage_samples: (sample_rate * ENGAGE_TIME) as u32,
+            loud_samples: 0,
+            attenuation_step: ATTENUATION_RATE / sample_rate,
+            recovery_step: RECOVERY_RATE / sample_rate,
+            scale: 1.0,
+        }
+    }
+
+    pub fn process(&mut self, buffer_left: &[f32], buffer_right: &[f32]) -> Reaction {
+        for (l, r) in buffer_left.iter().zip(buffer_right) {
+            let mean_square = self.mean_square.tick((l * l).max(r * r));
+            if mean_square > THRESHOLD * THRESHOLD {
+                self.loud_samples = (self.loud_samples + 1).min(self.engage_samples);
+            } else {
+                self.loud_samples = 0;
+            }
+
+            self.scale = if self.loud_samples >= self.engage_samples {
+                (self.scale - self.attenuation_step).max(0.0)
+            } else {
+                (self.scale + self.recovery_step).min(1.0)
+            };
+        }
+
+        Reaction {
+            attenuating: self.scale < 1.0,
+        }
+    }
+
+    /// Portion of the feedback of all heads left by the guard.
+    pub fn scale(&self) -> f32 {
+        self.scale
+    }
+
+    pub fn reset(&mut self) {
+        self.mean_square.reset();
+        self.loud_samples = 0;
+        self.scale = 1.0;
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::BLOCK_SIZE;
+
+    use core::f32::consts::PI;
+
+    use libm::sinf;
+
+    const SAMPLE_RATE: f32 = 1000.0;
+
+    // NOTE: Returns whether the guard engaged while processing a sine of
+    // given amplitude and duration.
+    fn engaged_by_sine(feedback_guard: &mut FeedbackGuard, amplitude: f32, seconds: f32) -> bool {
+        let mut attenuating = false;
+        let blocks = (SAMPLE_RATE * seconds) as usize / BLOCK_SIZE;
+        for i in 0..blocks {
+            let block: [f32; BLOCK_SIZE] = core::array::from_fn(|j| {
+                amplitude * sinf(2.0 * PI * 50.0 * (i * BLOCK_SIZE + j) as f32 / SAMPLE_RATE)
+            });
+            attenuating |= feedback_guard.process(&block, &block).attenuating;
+        }
+        attenuating
+    }
+
+    #[test]
+    fn when_output_is_loud_only_briefly_feedback_is_kept() {
+        let mut feedback_guard = FeedbackGuard::new(SAMPLE_RATE);
+
+        assert!(!engaged_by_sine(&mut feedback_guard, 1.0, 1.0));
+        assert!(!engaged_by_sine(&mut feedback_guard, 0.0, 1.0));
+        assert!(!engaged_by_sine(&mut feedback_guard, 1.0, 1.0));
+
+        assert_relative_eq!(feedback_guard.scale(), 1.0);
+    }
+
+    #[test]
+    fn when_output_stays_loud_feedback_is_pulled_back_and_later_restored() {
+        let mut feedback_guard = FeedbackGuard::new(SAMPLE_RATE);
+
+        assert!(engaged_by_sine(&mut feedback_guard, 1.0, 4.0));
+        let attenuated = feedback_guard.scale();
+        assert!(attenuated < 0.9, "Scale {attenuated}");
+
+        engaged_by_sine(&mut feedback_guard, 0.0, 1.0);
+        assert!(feedback_guard.scale() > attenuated);
+    }
+}
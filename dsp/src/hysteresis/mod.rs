@@ -3,7 +3,10 @@
 mod makeup;
 pub mod processor;
 mod simulation;
+mod stereo;
 
 pub use processor::Attributes;
 pub use processor::Reaction;
 pub use processor::State as Hysteresis;
+pub use simulation::Recovery;
+pub use stereo::Stereo as StereoHysteresis;
@@ -1,23 +1,48 @@
+use core::f32::consts::PI;
+
+use libm::{expf as exp, sqrtf as sqrt};
+
 use super::makeup;
-use super::simulation::Simulation;
+use super::simulation::{self, Recovery, Simulation};
 
 const AMPLITUDE_LIMIT: f32 = 2.0;
 
+/// Cutoff of followers estimating RMS of the input and the output.
+///
+/// It is low enough not to follow the waveform, but it still adapts to a
+/// change of drive within a fraction of a second.
+const RMS_CUTOFF: f32 = 5.0;
+
+/// The automatic makeup would otherwise blow up near-silent signal.
+const AUTO_MAKEUP_LIMIT: f32 = 8.0;
+
+/// Keeps the RMS ratio defined when both signals are silent.
+const RMS_FLOOR: f32 = 1.0e-6;
+
 #[derive(Debug)]
 #[cfg_attr(feature = "defmt", derive(defmt::Format))]
 pub struct State {
-    dry_wet: f32,
     simulation: Simulation,
     makeup: f32,
+    makeup_target: f32,
+    smoothing: f32,
+    auto_makeup: bool,
+    auto_makeup_gain: f32,
+    input_power: f32,
+    output_power: f32,
+    rms_smoothing: f32,
 }
 
 #[derive(Default, Clone, Copy, Debug)]
 #[cfg_attr(feature = "defmt", derive(defmt::Format))]
 pub struct Attributes {
-    pub dry_wet: f32,
     pub drive: f32,
     pub saturation: f32,
     pub width: f32,
+    pub auto_makeup: bool,
+    /// Magnetisation considered unstable. When `None`, the default is used.
+    pub limit: Option<f32>,
+    pub recovery: Recovery,
 }
 
 #[derive(Default, Clone, Copy, Debug)]
@@ -34,11 +59,18 @@ impl State {
 
         let state = {
             let mut state = Self {
-                dry_wet: 0.0,
                 simulation,
                 makeup: 0.0,
+                makeup_target: 0.0,
+                smoothing: simulation::smoothing_coefficient(sample_rate),
+                auto_makeup: false,
+                auto_makeup_gain: 1.0,
+                input_power: 0.0,
+                output_power: 0.0,
+                rms_smoothing: 1.0 - exp(-2.0 * PI * RMS_CUTOFF / sample_rate),
             };
             state.set_attributes(Attributes::default());
+            state.makeup = state.makeup_target;
             state
         };
 
@@ -46,25 +78,56 @@ impl State {
     }
 
     pub fn set_attributes(&mut self, attributes: Attributes) {
-        self.dry_wet = attributes.dry_wet;
-        self.simulation.set_drive(attributes.drive);
-        self.simulation.set_saturation(attributes.saturation);
-        self.simulation.set_width(attributes.width);
-        self.makeup = makeup::calculate(attributes.drive, attributes.saturation, attributes.width);
+        self.auto_makeup = attributes.auto_makeup;
+        self.simulation
+            .glide(attributes.drive, attributes.saturation, attributes.width);
+        self.simulation.set_stability(
+            attributes.limit.unwrap_or(simulation::DEFAULT_LIMIT),
+            attributes.recovery,
+        );
+        self.makeup_target =
+            makeup::calculate(attributes.drive, attributes.saturation, attributes.width);
+    }
+
+    pub fn reset(&mut self) {
+        self.simulation.reset();
+        self.makeup = self.makeup_target;
+        self.auto_makeup_gain = 1.0;
+        self.input_power = 0.0;
+        self.output_power = 0.0;
     }
 
     pub fn process(&mut self, buffer: &mut [f32]) -> Reaction {
         let mut reaction = Reaction::default();
+        let auto_makeup_target = if self.auto_makeup {
+            self.calculate_auto_makeup()
+        } else {
+            1.0
+        };
         for x in buffer.iter_mut() {
             let (clamped, clipped) = clamp(*x);
             reaction.clipping |= clipped;
             *x = clamped;
-            let dry = *x * (1.0 - self.dry_wet);
-            let wet = self.simulation.process(*x) * self.makeup * self.dry_wet;
-            *x = dry + wet * 0.5;
+            // NOTE: Makeup must follow the coefficients smoothed inside the
+            // simulation, otherwise the loudness would jump ahead of them.
+            self.makeup += (self.makeup_target - self.makeup) * self.smoothing;
+            let output = self.simulation.process(*x) * self.makeup * 0.5;
+            if self.auto_makeup {
+                self.input_power += (*x * *x - self.input_power) * self.rms_smoothing;
+                self.output_power += (output * output - self.output_power) * self.rms_smoothing;
+            }
+            self.auto_makeup_gain += (auto_makeup_target - self.auto_makeup_gain) * self.smoothing;
+            *x = output * self.auto_makeup_gain;
         }
         reaction
     }
+
+    // NOTE: The gain is calculated once per buffer and then smoothed, so the
+    // square root does not need to be taken on every sample.
+    fn calculate_auto_makeup(&self) -> f32 {
+        let ratio = (self.input_power + RMS_FLOOR) / (self.output_power + RMS_FLOOR);
+        sqrt(ratio).min(AUTO_MAKEUP_LIMIT)
+    }
 }
 
 fn clamp(x: f32) -> (f32, bool) {
@@ -76,3 +139,108 @@ fn clamp(x: f32) -> (f32, bool) {
         (x, false)
     }
 }
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::BLOCK_SIZE;
+
+    const FS: f32 = 48_000.0;
+    const FREQUENCY: f32 = 100.0;
+
+    fn sine(phase: &mut f32, buffer: &mut [f32]) {
+        for x in buffer.iter_mut() {
+            *x = libm::sinf(*phase * 2.0 * PI);
+            *phase = (*phase + FREQUENCY / FS) % 1.0;
+        }
+    }
+
+    fn max_step(mut previous: f32, buffer: &[f32]) -> f32 {
+        let mut max_step: f32 = 0.0;
+        for x in buffer {
+            max_step = max_step.max((x - previous).abs());
+            previous = *x;
+        }
+        max_step
+    }
+
+    #[test]
+    fn when_drive_changes_abruptly_output_does_not_jump() {
+        let mut hysteresis = State::new(FS);
+        let mut attributes = Attributes {
+            drive: 0.1,
+            saturation: 0.5,
+            width: 0.5,
+            auto_makeup: false,
+            ..Attributes::default()
+        };
+        hysteresis.set_attributes(attributes);
+
+        // NOTE: Start on the peak of the sine, so the change of drive lands
+        // ten periods later where the signal is flat and any jump of the
+        // envelope stands out. Steps are compared over a short window on
+        // both sides of the change.
+        const CHANGE: usize = 4800;
+        const WINDOW: usize = 32;
+        let mut phase = 0.25;
+        let mut output = [0.0; 2 * CHANGE];
+        for (i, chunk) in output.chunks_mut(BLOCK_SIZE).enumerate() {
+            if i * BLOCK_SIZE == CHANGE {
+                attributes.drive = 1.0;
+                hysteresis.set_attributes(attributes);
+            }
+            sine(&mut phase, chunk);
+            hysteresis.process(chunk);
+        }
+        let steady_step = max_step(
+            output[CHANGE - WINDOW - 1],
+            &output[CHANGE - WINDOW..CHANGE],
+        );
+        let transition_step = max_step(output[CHANGE - 1], &output[CHANGE..CHANGE + WINDOW]);
+
+        assert!(
+            transition_step < steady_step * 2.0,
+            "Output jumped by {transition_step}, steady step is {steady_step}"
+        );
+    }
+
+    fn rms(buffer: &[f32]) -> f32 {
+        sqrt(buffer.iter().map(|x| x * x).sum::<f32>() / buffer.len() as f32)
+    }
+
+    #[test]
+    fn when_auto_makeup_is_enabled_drive_does_not_change_loudness() {
+        let input_rms = core::f32::consts::FRAC_1_SQRT_2;
+        let mut buffer = [0.0; BLOCK_SIZE];
+
+        for drive in [0.1, 0.4, 0.7, 1.0] {
+            let mut hysteresis = State::new(FS);
+            hysteresis.set_attributes(Attributes {
+                drive,
+                saturation: 0.5,
+                width: 0.5,
+                auto_makeup: true,
+                ..Attributes::default()
+            });
+
+            let mut phase = 0.0;
+            for _ in 0..48_000 / BLOCK_SIZE {
+                sine(&mut phase, &mut buffer);
+                hysteresis.process(&mut buffer);
+            }
+
+            // NOTE: Measure over multiple whole periods of the sine.
+            let mut output = [0.0; 1920];
+            for chunk in output.chunks_mut(BLOCK_SIZE) {
+                sine(&mut phase, chunk);
+                hysteresis.process(chunk);
+            }
+            let output_rms = rms(&output);
+
+            assert!(
+                (output_rms / input_rms - 1.0).abs() < 0.05,
+                "Output RMS {output_rms} with drive {drive}, input RMS {input_rms}"
+            );
+        }
+    }
+}
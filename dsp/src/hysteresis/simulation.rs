@@ -7,7 +7,20 @@
 //! * <https://ccrma.stanford.edu/~jatin/papers/Complex_NLs.pdf>
 //! * <https://github.com/jatinchowdhury18/audio_dspy>
 
-use libm::{fabsf as fabs, sqrtf as sqrt};
+use core::f32::consts::PI;
+
+use libm::{expf as exp, fabsf as fabs, sqrtf as sqrt};
+
+/// Cutoff of the one-pole smoothing applied on coefficients when they change.
+///
+/// This keeps abrupt changes of attributes from thumping.
+const SMOOTHING_CUTOFF: f32 = 200.0;
+
+/// Portion of the remaining distance to the target a smoothed value moves
+/// with every sample.
+pub fn smoothing_coefficient(fs: f32) -> f32 {
+    1.0 - exp(-2.0 * PI * SMOOTHING_CUTOFF / fs)
+}
 
 /// Time domain differentiation using the trapezoidal rule.
 #[derive(Debug)]
@@ -30,6 +43,11 @@ impl Differentiator {
         }
     }
 
+    pub fn reset(&mut self) {
+        self.x_n1 = 0.0;
+        self.x_d_n1 = 0.0;
+    }
+
     pub fn differentiate(&mut self, x: f32) -> f32 {
         const D_ALPHA: f32 = 0.75;
         let x_d = (((1.0 + D_ALPHA) / self.t) * (x - self.x_n1)) - D_ALPHA * self.x_d_n1;
@@ -63,6 +81,22 @@ fn langevin_deriv(x: f32) -> f32 {
     }
 }
 
+/// Magnetisation beyond which the simulation is considered unstable.
+pub const DEFAULT_LIMIT: f32 = 20.0;
+
+/// Handling of magnetisation running beyond the limit.
+#[derive(Default, Clone, Copy, Debug, PartialEq)]
+#[cfg_attr(feature = "defmt", derive(defmt::Format))]
+pub enum Recovery {
+    /// Demagnetize the tape at once. This is the most stable, but it causes
+    /// an audible dropout.
+    #[default]
+    Reset,
+    /// Hold the magnetisation on the boundary, so the signal breaks up
+    /// smoothly. Only non-finite values lead to the reset.
+    SoftLimit,
+}
+
 /// Applying hysteresis on input signal.
 #[derive(Debug)]
 #[cfg_attr(feature = "defmt", derive(defmt::Format))]
@@ -84,12 +118,26 @@ pub struct Simulation {
     /// Initial susceptibilities
     c: f32,
 
+    /// Magnetisation saturation the smoothed value converges to
+    m_s_target: f32,
+    /// Anhysteric magnetisation shape the smoothed value converges to
+    a_target: f32,
+    /// Initial susceptibilities the smoothed value converges to
+    c_target: f32,
+    /// Smoothing coefficient of the one-pole filter
+    smoothing: f32,
+
     /// Previous magnetisation
     m_n1: f32,
     /// Previous magnetic field
     h_n1: f32,
     /// Time derivative of the previous magnetic field
     h_d_n1: f32,
+
+    /// Magnetisation limit, beyond which the recovery kicks in
+    limit: f32,
+    /// Handling of magnetisation running beyond the limit
+    recovery: Recovery,
 }
 
 impl Simulation {
@@ -112,9 +160,17 @@ impl Simulation {
             a: 0.0,
             c: 0.0,
 
+            m_s_target: 0.0,
+            a_target: 0.0,
+            c_target: 0.0,
+            smoothing: smoothing_coefficient(fs),
+
             m_n1: 0.0,
             h_n1: 0.0,
             h_d_n1: 0.0,
+
+            limit: DEFAULT_LIMIT,
+            recovery: Recovery::default(),
         };
         hysteresis.set_drive(0.0);
         hysteresis.set_saturation(0.0);
@@ -123,19 +179,69 @@ impl Simulation {
     }
 
     pub fn set_drive(&mut self, drive: f32) {
-        self.drive = drive;
-        self.a = self.m_s / (0.01 + 6.0 * drive);
+        self.set_drive_target(drive);
+        self.snap_coefficients();
     }
 
     pub fn set_saturation(&mut self, saturation: f32) {
-        self.saturation = saturation;
-        self.m_s = 0.5 + 1.5 * (1.0 - saturation);
-        self.set_drive(self.drive);
+        self.set_saturation_target(saturation);
+        self.snap_coefficients();
     }
 
     pub fn set_width(&mut self, width: f32) {
+        self.set_width_target(width);
+        self.snap_coefficients();
+    }
+
+    pub fn set_stability(&mut self, limit: f32, recovery: Recovery) {
+        self.limit = limit;
+        self.recovery = recovery;
+    }
+
+    /// Change all the parameters at once, letting the coefficients glide to
+    /// their new values over the following samples.
+    pub fn glide(&mut self, drive: f32, saturation: f32, width: f32) {
+        self.set_saturation_target(saturation);
+        self.set_drive_target(drive);
+        self.set_width_target(width);
+    }
+
+    /// Demagnetize the simulated tape and settle coefficients on their
+    /// targets.
+    pub fn reset(&mut self) {
+        self.differentiator.reset();
+        self.snap_coefficients();
+        self.m_n1 = 0.0;
+        self.h_n1 = 0.0;
+        self.h_d_n1 = 0.0;
+    }
+
+    fn set_drive_target(&mut self, drive: f32) {
+        self.drive = drive;
+        self.a_target = self.m_s_target / (0.01 + 6.0 * drive);
+    }
+
+    fn set_saturation_target(&mut self, saturation: f32) {
+        self.saturation = saturation;
+        self.m_s_target = 0.5 + 1.5 * (1.0 - saturation);
+        self.set_drive_target(self.drive);
+    }
+
+    fn set_width_target(&mut self, width: f32) {
         self.width = width;
-        self.c = sqrt(1.0 - width) - 0.01;
+        self.c_target = sqrt(1.0 - width) - 0.01;
+    }
+
+    fn snap_coefficients(&mut self) {
+        self.m_s = self.m_s_target;
+        self.a = self.a_target;
+        self.c = self.c_target;
+    }
+
+    fn smooth_coefficients(&mut self) {
+        self.m_s += (self.m_s_target - self.m_s) * self.smoothing;
+        self.a += (self.a_target - self.a) * self.smoothing;
+        self.c += (self.c_target - self.c) * self.smoothing;
     }
 
     /// Jiles-Atherton differential equation.
@@ -218,13 +324,16 @@ impl Simulation {
 
     #[must_use]
     pub fn process(&mut self, h: f32) -> f32 {
+        self.smooth_coefficients();
+
         let (h_d, m) = {
             let h_d = self.differentiator.differentiate(h);
             let m = self.rk2(self.m_n1, h, self.h_n1, h_d, self.h_d_n1);
 
-            const UPPER_LIMIT: f32 = 20.0;
-            if (-UPPER_LIMIT..=UPPER_LIMIT).contains(&m) {
+            if (-self.limit..=self.limit).contains(&m) {
                 (h_d, m)
+            } else if self.recovery == Recovery::SoftLimit && m.is_finite() {
+                (h_d, m.clamp(-self.limit, self.limit))
             } else {
                 (0.0, 0.0)
             }
@@ -316,4 +425,65 @@ mod tests {
             );
         }
     }
+
+    #[test]
+    fn when_input_is_above_nyquist_given_soft_limit_when_given_noise_it_remains_stable() {
+        const PRE_AMP: f32 = 20.0;
+        const FS: f32 = 1024.0;
+        let mut hysteresis = Simulation::new(FS);
+        hysteresis.set_drive(1.0);
+        hysteresis.set_saturation(1.0);
+        hysteresis.set_width(0.0);
+        hysteresis.set_stability(DEFAULT_LIMIT, Recovery::SoftLimit);
+
+        use rand::Rng;
+        let mut rng = rand::thread_rng();
+        for _ in 0..100 {
+            let input = rng.gen_range(-PRE_AMP..PRE_AMP);
+            let output = hysteresis.process(input);
+            assert!(
+                (-DEFAULT_LIMIT..=DEFAULT_LIMIT).contains(&output),
+                "Hysteresis output is unstable: {output}"
+            );
+        }
+    }
+
+    // NOTE: The limit is set low, so a loud sine reaches it on every peak.
+    fn peaks_hitting_low_limit(recovery: Recovery) -> [f32; 64] {
+        const FS: f32 = 1024.0;
+        const LIMIT: f32 = 0.5;
+        let mut hysteresis = Simulation::new(FS);
+        hysteresis.set_drive(1.0);
+        hysteresis.set_saturation(0.0);
+        hysteresis.set_width(0.5);
+        hysteresis.set_stability(LIMIT, recovery);
+
+        let mut output = [0.0; 64];
+        for (i, x) in output.iter_mut().enumerate() {
+            let phase = i as f32 / 32.0;
+            *x = hysteresis.process(libm::sinf(2.0 * core::f32::consts::PI * phase) * 10.0);
+        }
+        output
+    }
+
+    #[test]
+    fn when_limit_is_exceeded_with_reset_recovery_magnetisation_drops_to_zero() {
+        let output = peaks_hitting_low_limit(Recovery::Reset);
+
+        assert!(output[40..56].contains(&0.0), "Expected a dropout");
+    }
+
+    #[test]
+    fn when_limit_is_exceeded_with_soft_limit_recovery_magnetisation_holds_the_boundary() {
+        let output = peaks_hitting_low_limit(Recovery::SoftLimit);
+
+        for x in &output[40..56] {
+            assert!(x.abs() <= 0.5, "Output {x} is beyond the limit");
+        }
+        assert!(output[40..56].iter().any(|x| x.abs() == 0.5));
+        assert!(
+            !output[40..56].contains(&0.0),
+            "Magnetisation dropped to zero"
+        );
+    }
 }
@@ -0,0 +1,137 @@
+//! Independent tape saturation of the left and right channel.
+//!
+//! Each channel has its own magnetisation, so sound panned to one side does
+//! not drive the other, keeping the stereo image through the tape stage.
+//! This costs twice as much as the mono `State`, so it is meant to be used
+//! only when the signal in front of the tape is stereo.
+
+use super::processor::{Attributes, Reaction, State};
+
+#[derive(Debug)]
+#[cfg_attr(feature = "defmt", derive(defmt::Format))]
+pub struct Stereo {
+    left: State,
+    right: State,
+}
+
+impl Stereo {
+    #[must_use]
+    pub fn new(sample_rate: f32) -> Self {
+        Self {
+            left: State::new(sample_rate),
+            right: State::new(sample_rate),
+        }
+    }
+
+    pub fn set_attributes(&mut self, attributes: Attributes) {
+        self.left.set_attributes(attributes);
+        self.right.set_attributes(attributes);
+    }
+
+    pub fn reset(&mut self) {
+        self.left.reset();
+        self.right.reset();
+    }
+
+    pub fn process(&mut self, buffer_left: &mut [f32], buffer_right: &mut [f32]) -> Reaction {
+        let reaction_left = self.left.process(buffer_left);
+        let reaction_right = self.right.process(buffer_right);
+        Reaction {
+            clipping: reaction_left.clipping || reaction_right.clipping,
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    use libm::sqrtf as sqrt;
+    use rand::rngs::StdRng;
+    use rand::{Rng, SeedableRng};
+
+    use crate::BLOCK_SIZE;
+
+    const FS: f32 = 48_000.0;
+
+    fn attributes() -> Attributes {
+        Attributes {
+            drive: 0.8,
+            saturation: 0.5,
+            width: 0.5,
+            ..Attributes::default()
+        }
+    }
+
+    fn correlation(left: &[f32], right: &[f32]) -> f32 {
+        let product: f32 = left.iter().zip(right).map(|(l, r)| l * r).sum();
+        let power_left: f32 = left.iter().map(|l| l * l).sum();
+        let power_right: f32 = right.iter().map(|r| r * r).sum();
+        product / sqrt(power_left * power_right)
+    }
+
+    fn decorrelated_noise() -> ([f32; BLOCK_SIZE * 300], [f32; BLOCK_SIZE * 300]) {
+        let mut rng = StdRng::seed_from_u64(0);
+        let mut left = [0.0; BLOCK_SIZE * 300];
+        let mut right = [0.0; BLOCK_SIZE * 300];
+        for (l, r) in left.iter_mut().zip(right.iter_mut()) {
+            *l = rng.gen_range(-1.0..1.0);
+            *r = rng.gen_range(-1.0..1.0);
+        }
+        (left, right)
+    }
+
+    #[test]
+    fn given_decorrelated_channels_they_stay_decorrelated() {
+        let mut stereo = Stereo::new(FS);
+        stereo.set_attributes(attributes());
+
+        let (mut left, mut right) = decorrelated_noise();
+        for (chunk_left, chunk_right) in left
+            .chunks_mut(BLOCK_SIZE)
+            .zip(right.chunks_mut(BLOCK_SIZE))
+        {
+            stereo.process(chunk_left, chunk_right);
+        }
+
+        let correlation = correlation(&left, &right);
+        assert!(
+            correlation.abs() < 0.1,
+            "Channels got correlated: {correlation}"
+        );
+    }
+
+    #[test]
+    fn given_decorrelated_channels_it_differs_from_saturated_mono_sum() {
+        let mut stereo = Stereo::new(FS);
+        stereo.set_attributes(attributes());
+        let mut mono = State::new(FS);
+        mono.set_attributes(attributes());
+
+        let (mut left, mut right) = decorrelated_noise();
+        let mut sum = [0.0; BLOCK_SIZE * 300];
+        for (s, (l, r)) in sum.iter_mut().zip(left.iter().zip(&right)) {
+            *s = l + r;
+        }
+        for ((chunk_left, chunk_right), chunk_sum) in left
+            .chunks_mut(BLOCK_SIZE)
+            .zip(right.chunks_mut(BLOCK_SIZE))
+            .zip(sum.chunks_mut(BLOCK_SIZE))
+        {
+            stereo.process(chunk_left, chunk_right);
+            mono.process(chunk_sum);
+        }
+
+        // NOTE: In the mono sum, each channel drives the saturation of the
+        // other, so the result is not a mix of separately saturated channels.
+        let mut stereo_sum = [0.0; BLOCK_SIZE * 300];
+        for (s, (l, r)) in stereo_sum.iter_mut().zip(left.iter().zip(&right)) {
+            *s = l + r;
+        }
+        let correlation = correlation(&stereo_sum, &sum);
+        assert!(
+            correlation < 0.9,
+            "Stereo is too close to mono: {correlation}"
+        );
+    }
+}
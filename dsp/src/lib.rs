@@ -15,6 +15,43 @@
 #[macro_use]
 extern crate approx;
 
+/// Number of samples processed in a single call of the processor.
+///
+/// Shorter blocks lower the latency, longer ones spend less time on work done
+/// once per block. All the internal buffers are derived from it.
+pub const BLOCK_SIZE: usize = 32;
+
+// NOTE: Tests measure stretches of samples divisible by 64, so they hold for
+// any block size dividing it. Firmware checks that its audio interface
+// delivers blocks of this size.
+#[cfg(test)]
+const _: () = assert!(
+    64 % BLOCK_SIZE == 0,
+    "Tests expect the block size to divide 64 samples"
+);
+
+/// Declare a static memory of the given number of words and return a manager
+/// over it. Every expansion gets memory of its own, so tests running in
+/// parallel never share it. An optional word may be given to fill the memory
+/// with before it is handed over.
+#[cfg(test)]
+macro_rules! memory_manager {
+    ($words:expr) => {{
+        static mut MEMORY: [core::mem::MaybeUninit<u32>; $words] =
+            unsafe { core::mem::MaybeUninit::uninit().assume_init() };
+        sirena::memory_manager::MemoryManager::from(unsafe { &mut MEMORY[..] })
+    }};
+    ($words:expr, $fill:expr) => {{
+        static mut MEMORY: [core::mem::MaybeUninit<u32>; $words] =
+            unsafe { core::mem::MaybeUninit::uninit().assume_init() };
+        let memory = unsafe { &mut MEMORY[..] };
+        for word in memory.iter_mut() {
+            word.write($fill);
+        }
+        sirena::memory_manager::MemoryManager::from(memory)
+    }};
+}
+
 pub mod processor;
 pub mod random;
 
@@ -27,13 +64,23 @@ pub mod oversampling;
 pub mod tone;
 pub mod wow_flutter;
 
+mod band_meter;
+mod capture;
 mod clipper;
+mod correlation;
 mod dc_blocker;
 mod decibels;
+mod dry_wet;
+mod feedback_guard;
+mod limiter;
+mod linear_phase_filter;
 mod linkwitz_riley_filter;
 mod math;
+mod metronome;
 mod one_pole_filter;
+mod output_level;
 mod pre_amp;
 mod ring_buffer;
+mod saturation_knee;
 mod state_variable_filter;
 mod trigonometry;
@@ -0,0 +1,204 @@
+//! Brick-wall limiter with a short look-ahead.
+//!
+//! Unlike the clipper, it does not cut overshoots off. It delays the signal
+//! by a few samples and uses that time to turn the gain down before the
+//! transient arrives, keeping the shape of the waveform intact.
+
+use libm::expf;
+
+// Full scale of the codec.
+const DEFAULT_CEILING: f32 = 1.0;
+
+// Delay of the signal, giving the gain time to ramp down ahead of a peak.
+const LOOKAHEAD: usize = 16;
+
+// Time needed for the gain to recover after a peak.
+const RELEASE_TIME: f32 = 0.05;
+
+const WINDOW: usize = LOOKAHEAD + 1;
+
+#[derive(Debug)]
+#[cfg_attr(feature = "defmt", derive(defmt::Format))]
+pub struct Limiter {
+    enabled: bool,
+    ceiling: f32,
+    release: f32,
+    delay_left: [f32; WINDOW],
+    delay_right: [f32; WINDOW],
+    // Gain needed by each sample in the delay line to stay under the ceiling.
+    required_gain: [f32; WINDOW],
+    // Required gain held over the look-ahead window and released slowly.
+    held_gain: [f32; WINDOW],
+    held: f32,
+    index: usize,
+}
+
+#[derive(Default, Clone, Copy, Debug)]
+#[cfg_attr(feature = "defmt", derive(defmt::Format))]
+pub struct Attributes {
+    /// Enabling it delays the output by a few samples.
+    pub enabled: bool,
+    /// Ceiling of the output. When `None`, the full scale is used.
+    pub ceiling: Option<f32>,
+}
+
+#[derive(Default, Clone, Copy, Debug)]
+#[cfg_attr(feature = "defmt", derive(defmt::Format))]
+pub struct Reaction {
+    pub limiting: bool,
+}
+
+impl Limiter {
+    pub fn new(sample_rate: f32) -> Self {
+        Self {
+            enabled: false,
+            ceiling: DEFAULT_CEILING,
+            release: 1.0 - expf(-1.0 / (sample_rate * RELEASE_TIME)),
+            delay_left: [0.0; WINDOW],
+            delay_right: [0.0; WINDOW],
+            required_gain: [1.0; WINDOW],
+            held_gain: [1.0; WINDOW],
+            held: 1.0,
+            index: 0,
+        }
+    }
+
+    // NOTE: Each sample leaves the delay line with the average of the held
+    // gain over the look-ahead window. Every value in that average was
+    // held while the sample was still in the line, so none of them can be
+    // above the gain the sample needs. That guarantees the ceiling, while
+    // the averaging keeps the gain changes smooth.
+    pub fn process(&mut self, buffer_left: &mut [f32], buffer_right: &mut [f32]) -> Reaction {
+        let mut reaction = Reaction::default();
+
+        if !self.enabled {
+            return reaction;
+        }
+
+        for (l, r) in buffer_left.iter_mut().zip(buffer_right.iter_mut()) {
+            let peak = l.abs().max(r.abs());
+            let required = if peak > self.ceiling {
+                self.ceiling / peak
+            } else {
+                1.0
+            };
+
+            let oldest = (self.index + 1) % WINDOW;
+            let (delayed_left, delayed_right) = (self.delay_left[oldest], self.delay_right[oldest]);
+            self.delay_left[self.index] = *l;
+            self.delay_right[self.index] = *r;
+            self.required_gain[self.index] = required;
+
+            let window_minimum = self.required_gain.iter().fold(1.0, |a: f32, b| a.min(*b));
+            self.held = window_minimum.min(self.held + (1.0 - self.held) * self.release);
+            self.held_gain[self.index] = self.held;
+            let gain = self.held_gain.iter().sum::<f32>() / WINDOW as f32;
+
+            self.index = oldest;
+
+            // NOTE: The averaged gain may miss the required one by a rounding
+            // error. Clamping here only removes that, never the waveform.
+            *l = (delayed_left * gain).clamp(-self.ceiling, self.ceiling);
+            *r = (delayed_right * gain).clamp(-self.ceiling, self.ceiling);
+            reaction.limiting |= gain < 1.0;
+        }
+
+        reaction
+    }
+
+    pub fn set_attributes(&mut self, attributes: Attributes) {
+        if attributes.enabled && !self.enabled {
+            self.reset();
+        }
+        self.enabled = attributes.enabled;
+        self.ceiling = attributes
+            .ceiling
+            .map_or(DEFAULT_CEILING, |c| c.clamp(0.0, DEFAULT_CEILING));
+    }
+
+    pub(crate) fn reset(&mut self) {
+        self.delay_left = [0.0; WINDOW];
+        self.delay_right = [0.0; WINDOW];
+        self.required_gain = [1.0; WINDOW];
+        self.held_gain = [1.0; WINDOW];
+        self.held = 1.0;
+        self.index = 0;
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use core::f32::consts::PI;
+
+    const SAMPLE_RATE: f32 = 1000.0;
+
+    fn enabled_limiter() -> Limiter {
+        let mut limiter = Limiter::new(SAMPLE_RATE);
+        limiter.set_attributes(Attributes {
+            enabled: true,
+            ceiling: None,
+        });
+        limiter
+    }
+
+    #[test]
+    fn when_transient_exceeds_full_scale_it_is_turned_down_without_flat_topping() {
+        let mut limiter = enabled_limiter();
+
+        // NOTE: The burst jumps straight to three times the full scale.
+        let input: [f32; 256] = core::array::from_fn(|i| {
+            if (100..160).contains(&i) {
+                3.0 * libm::sinf(2.0 * PI * 50.0 * i as f32 / SAMPLE_RATE)
+            } else {
+                0.0
+            }
+        });
+        let mut left = input;
+        let mut right = [0.0; 256];
+        let reaction = limiter.process(&mut left, &mut right);
+
+        let peak = left.iter().fold(0.0, |a: f32, b| a.max(b.abs()));
+        assert!(reaction.limiting);
+        assert!(peak <= DEFAULT_CEILING + 0.0001, "Peak {peak}");
+        assert!(peak > 0.99, "Peak {peak}");
+
+        let samples_at_ceiling = left
+            .windows(2)
+            .filter(|w| w[0].abs() > 0.99 && w[1].abs() > 0.99);
+        assert_eq!(samples_at_ceiling.count(), 0);
+
+        // NOTE: Within the burst, the waveform is only scaled down.
+        for i in 120..150 {
+            assert_relative_eq!(left[i + LOOKAHEAD], input[i] / 3.0, epsilon = 0.001);
+        }
+    }
+
+    #[test]
+    fn when_signal_is_under_ceiling_it_passes_only_delayed() {
+        let mut limiter = enabled_limiter();
+
+        let input: [f32; 64] = core::array::from_fn(|i| 0.9 * libm::sinf(i as f32 * 0.3));
+        let mut left = input;
+        let mut right = input;
+        let reaction = limiter.process(&mut left, &mut right);
+
+        assert!(!reaction.limiting);
+        for i in 0..64 - LOOKAHEAD {
+            assert_relative_eq!(left[i + LOOKAHEAD], input[i]);
+            assert_relative_eq!(right[i + LOOKAHEAD], input[i]);
+        }
+    }
+
+    #[test]
+    fn when_disabled_signal_passes_untouched() {
+        let mut limiter = Limiter::new(SAMPLE_RATE);
+        let mut left = [2.0, -2.0, 0.5];
+        let mut right = [0.0; 3];
+
+        let reaction = limiter.process(&mut left, &mut right);
+
+        assert_eq!(left, [2.0, -2.0, 0.5]);
+        assert!(!reaction.limiting);
+    }
+}
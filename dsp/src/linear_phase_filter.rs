@@ -0,0 +1,172 @@
+//! Linear-phase FIR filter.
+//!
+//! Windowed-sinc design with a Hann window. All frequencies are delayed by the
+//! same amount, so transients are not smeared, at the cost of `LATENCY`
+//! samples. The length is fixed, so cutoffs below `MIN_RELATIVE_CUTOFF` cannot
+//! be resolved.
+
+use core::f32::consts::PI;
+
+use libm::{cosf, sinf};
+
+const LENGTH: usize = 63;
+
+/// Delay in samples introduced by the filter, equal for all frequencies.
+pub const LATENCY: usize = (LENGTH - 1) / 2;
+
+/// Lowest cutoff relative to the sample rate. The transition band of the
+/// window is about as wide, so lower cutoffs would barely filter anything.
+pub const MIN_RELATIVE_CUTOFF: f32 = 2.0 / LENGTH as f32;
+
+#[derive(Debug)]
+#[cfg_attr(feature = "defmt", derive(defmt::Format))]
+pub struct LinearPhaseFilter {
+    sample_rate: f32,
+    coefficients: [f32; LENGTH],
+    history: [f32; LENGTH],
+    write_index: usize,
+}
+
+impl LinearPhaseFilter {
+    pub fn new(sample_rate: f32) -> Self {
+        let mut filter = Self {
+            sample_rate,
+            coefficients: [0.0; LENGTH],
+            history: [0.0; LENGTH],
+            write_index: 0,
+        };
+        filter.set_all_pass();
+        filter
+    }
+
+    pub fn set_all_pass(&mut self) {
+        self.coefficients = [0.0; LENGTH];
+        self.coefficients[LATENCY] = 1.0;
+    }
+
+    pub fn set_low_pass(&mut self, frequency: f32) {
+        let cutoff = (frequency / self.sample_rate).clamp(MIN_RELATIVE_CUTOFF, 0.5);
+
+        let mut sum = 0.0;
+        for (i, coefficient) in self.coefficients.iter_mut().enumerate() {
+            let n = i as f32 - LATENCY as f32;
+            let sinc = if i == LATENCY {
+                2.0 * cutoff
+            } else {
+                sinf(2.0 * PI * cutoff * n) / (PI * n)
+            };
+            let window = 0.5 - 0.5 * cosf(2.0 * PI * (i + 1) as f32 / (LENGTH + 1) as f32);
+            *coefficient = sinc * window;
+            sum += *coefficient;
+        }
+
+        // NOTE: Normalize so the pass band stays at unity gain.
+        if sum > f32::EPSILON {
+            for coefficient in self.coefficients.iter_mut() {
+                *coefficient /= sum;
+            }
+        }
+    }
+
+    /// High-pass is calculated through spectral inversion of the low-pass.
+    ///
+    /// Below the lowest resolvable cutoff, only a part of the low-pass is
+    /// subtracted, so a sweep fades in from all-pass instead of jumping.
+    pub fn set_high_pass(&mut self, frequency: f32) {
+        let amount = (frequency / (self.sample_rate * MIN_RELATIVE_CUTOFF)).clamp(0.0, 1.0);
+        self.set_low_pass(frequency);
+        for coefficient in self.coefficients.iter_mut() {
+            *coefficient = -*coefficient * amount;
+        }
+        self.coefficients[LATENCY] += 1.0;
+    }
+
+    pub fn reset(&mut self) {
+        self.history = [0.0; LENGTH];
+    }
+
+    pub fn tick(&mut self, x: f32) -> f32 {
+        self.write_index = (self.write_index + 1) % LENGTH;
+        self.history[self.write_index] = x;
+
+        let mut y = 0.0;
+        let mut index = self.write_index;
+        for coefficient in &self.coefficients {
+            y += self.history[index] * coefficient;
+            index = if index == 0 { LENGTH - 1 } else { index - 1 };
+        }
+        y
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    const SAMPLE_RATE: f32 = 48_000.0;
+
+    #[test]
+    fn when_set_to_all_pass_it_delays_impulse_by_latency() {
+        let mut filter = LinearPhaseFilter::new(SAMPLE_RATE);
+        filter.set_all_pass();
+
+        let output: [f32; LENGTH] =
+            core::array::from_fn(|i| filter.tick(if i == 0 { 1.0 } else { 0.0 }));
+
+        for (i, x) in output.iter().enumerate() {
+            assert_relative_eq!(*x, if i == LATENCY { 1.0 } else { 0.0 });
+        }
+    }
+
+    #[test]
+    fn when_set_to_low_pass_its_impulse_response_is_symmetric() {
+        let mut filter = LinearPhaseFilter::new(SAMPLE_RATE);
+        filter.set_low_pass(2000.0);
+
+        let output: [f32; LENGTH] =
+            core::array::from_fn(|i| filter.tick(if i == 0 { 1.0 } else { 0.0 }));
+
+        for i in 0..LATENCY {
+            assert_relative_eq!(output[i], output[LENGTH - 1 - i]);
+        }
+        assert_relative_eq!(output.iter().sum::<f32>(), 1.0, epsilon = 0.0001);
+    }
+
+    #[test]
+    fn when_low_pass_cutoff_is_below_minimum_it_is_clamped() {
+        let mut clamped = LinearPhaseFilter::new(SAMPLE_RATE);
+        clamped.set_low_pass(20.0);
+        let mut minimum = LinearPhaseFilter::new(SAMPLE_RATE);
+        minimum.set_low_pass(SAMPLE_RATE * MIN_RELATIVE_CUTOFF);
+
+        for (a, b) in clamped.coefficients.iter().zip(&minimum.coefficients) {
+            assert_relative_eq!(a, b);
+        }
+    }
+
+    #[test]
+    fn when_high_pass_cutoff_is_below_minimum_dc_is_attenuated_partially() {
+        let mut filter = LinearPhaseFilter::new(SAMPLE_RATE);
+        filter.set_high_pass(SAMPLE_RATE * MIN_RELATIVE_CUTOFF / 4.0);
+
+        let mut y = 0.0;
+        for _ in 0..LENGTH * 2 {
+            y = filter.tick(1.0);
+        }
+
+        assert_relative_eq!(y, 0.75, epsilon = 0.0001);
+    }
+
+    #[test]
+    fn when_set_to_high_pass_it_blocks_dc() {
+        let mut filter = LinearPhaseFilter::new(SAMPLE_RATE);
+        filter.set_high_pass(2000.0);
+
+        let mut y = 0.0;
+        for _ in 0..LENGTH * 2 {
+            y = filter.tick(1.0);
+        }
+
+        assert_relative_eq!(y, 0.0, epsilon = 0.0001);
+    }
+}
@@ -42,6 +42,13 @@ impl LinkwitzRileyFilter {
         self
     }
 
+    pub fn reset(&mut self) {
+        self.s0 = 0.0;
+        self.s1 = 0.0;
+        self.s2 = 0.0;
+        self.s3 = 0.0;
+    }
+
     pub fn tick(&mut self, x: f32) -> Signal {
         let y_h = (x - (SQRT_2 + self.g) * self.s0 - self.s1) * self.h;
 
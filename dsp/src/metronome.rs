@@ -0,0 +1,156 @@
+//! Audible click on the beat, to practice along with the module.
+//!
+//! The click is a short decaying blip, played on every beat of the tapped or
+//! detected tempo. Between the beats it runs freely, the incoming clock only
+//! aligns its phase.
+
+use crate::trigonometry;
+
+const CLICK_FREQUENCY: f32 = 2000.0;
+const CLICK_DECAY_IN_SECONDS: f32 = 0.005;
+
+// Sync arriving while the click is still this loud was already played by
+// the free running counter and is not repeated.
+const RETRIGGER_ENVELOPE: f32 = 0.5;
+
+#[derive(Debug)]
+#[cfg_attr(feature = "defmt", derive(defmt::Format))]
+pub struct Metronome {
+    sample_rate: f32,
+    beat_in_samples: Option<f32>,
+    samples_since_beat: f32,
+    level: f32,
+    envelope: f32,
+    decay: f32,
+    phase: f32,
+}
+
+#[derive(Default, Clone, Copy, Debug)]
+#[cfg_attr(feature = "defmt", derive(defmt::Format))]
+pub struct Attributes {
+    /// Length of the beat in seconds. With `None`, no clicks are played.
+    pub beat: Option<f32>,
+    /// Level of the click mixed into the output. Zero turns it off.
+    pub level: f32,
+    /// Start the beat right now, aligning it with the clock.
+    pub sync: bool,
+}
+
+impl Metronome {
+    pub fn new(sample_rate: f32) -> Self {
+        Self {
+            sample_rate,
+            beat_in_samples: None,
+            samples_since_beat: 0.0,
+            level: 0.0,
+            envelope: 0.0,
+            decay: libm::expf(-1.0 / (sample_rate * CLICK_DECAY_IN_SECONDS)),
+            phase: 0.0,
+        }
+    }
+
+    pub fn set_attributes(&mut self, attributes: Attributes) {
+        self.beat_in_samples = attributes.beat.map(|beat| beat * self.sample_rate);
+        self.level = attributes.level;
+        if attributes.sync {
+            self.samples_since_beat = 0.0;
+            if self.envelope < RETRIGGER_ENVELOPE {
+                self.trigger();
+            }
+        }
+    }
+
+    pub fn process(&mut self, buffer_left: &mut [f32], buffer_right: &mut [f32]) {
+        if self.level <= 0.0 {
+            self.envelope = 0.0;
+            return;
+        }
+
+        for (l, r) in buffer_left.iter_mut().zip(buffer_right.iter_mut()) {
+            if let Some(beat_in_samples) = self.beat_in_samples {
+                if self.samples_since_beat >= beat_in_samples {
+                    self.samples_since_beat -= beat_in_samples;
+                    self.trigger();
+                }
+                self.samples_since_beat += 1.0;
+            }
+
+            let click = trigonometry::cos(self.phase) * self.envelope * self.level;
+            *l += click;
+            *r += click;
+
+            self.envelope *= self.decay;
+            self.phase = (self.phase + CLICK_FREQUENCY / self.sample_rate).fract();
+        }
+    }
+
+    fn trigger(&mut self) {
+        self.envelope = 1.0;
+        self.phase = 0.0;
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::BLOCK_SIZE;
+
+    const SAMPLE_RATE: f32 = 48_000.0;
+
+    // NOTE: The click oscillates, so loud samples closer than a millisecond
+    // to the previous one are considered a part of the same click.
+    fn onsets(metronome: &mut Metronome, samples: usize) -> [Option<usize>; 8] {
+        let mut onsets = [None; 8];
+        let mut found = 0;
+        let mut last_loud: Option<usize> = None;
+        for i in 0..samples / BLOCK_SIZE {
+            let mut left = [0.0; BLOCK_SIZE];
+            let mut right = [0.0; BLOCK_SIZE];
+            metronome.process(&mut left, &mut right);
+            for (j, x) in left.iter().enumerate() {
+                let index = i * BLOCK_SIZE + j;
+                if x.abs() > 0.2 {
+                    let new_click = last_loud.is_none_or(|last| index - last > 48);
+                    if new_click && found < onsets.len() {
+                        onsets[found] = Some(index);
+                        found += 1;
+                    }
+                    last_loud = Some(index);
+                }
+            }
+        }
+        onsets
+    }
+
+    #[test]
+    fn when_level_is_zero_nothing_is_played() {
+        let mut metronome = Metronome::new(SAMPLE_RATE);
+        metronome.set_attributes(Attributes {
+            beat: Some(0.1),
+            level: 0.0,
+            sync: true,
+        });
+
+        assert_eq!(onsets(&mut metronome, 32_000), [None; 8]);
+    }
+
+    #[test]
+    fn when_synced_beat_restarts_from_the_sync() {
+        let mut metronome = Metronome::new(SAMPLE_RATE);
+        metronome.set_attributes(Attributes {
+            beat: Some(0.1),
+            level: 0.5,
+            sync: false,
+        });
+        assert_eq!(onsets(&mut metronome, 6400)[0], Some(4800));
+
+        metronome.set_attributes(Attributes {
+            beat: Some(0.1),
+            level: 0.5,
+            sync: true,
+        });
+        let onsets = onsets(&mut metronome, 12_800);
+        assert_eq!(onsets[0], Some(0));
+        assert_eq!(onsets[1], Some(4800));
+    }
+}
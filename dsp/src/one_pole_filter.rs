@@ -24,8 +24,19 @@ impl OnePoleFilter {
         Self { y_m1: 0.0, a0, b1 }
     }
 
+    /// Start the filter settled on the given value instead of zero.
+    pub fn new_settled(sample_rate: f32, cutoff: f32, value: f32) -> Self {
+        let mut filter = Self::new(sample_rate, cutoff);
+        filter.y_m1 = value;
+        filter
+    }
+
     pub fn tick(&mut self, x: f32) -> f32 {
         self.y_m1 = x * self.a0 + self.y_m1 * self.b1;
         self.y_m1
     }
+
+    pub fn reset(&mut self) {
+        self.y_m1 = 0.0;
+    }
 }
@@ -5,19 +5,32 @@ use core::f32::consts::PI;
 
 const SUB_COEFFICIENT: f32 = 0.499;
 
+// Gain of the oscillator going into the shaper with the drive fully open.
+const MAX_DRIVE_GAIN: f32 = 10.0;
+
 #[derive(Debug)]
 #[cfg_attr(feature = "defmt", derive(defmt::Format))]
 pub struct Oscillator {
     sample_rate: f32,
     frequency: f32,
+    target_frequency: f32,
+    frequency_step: f32,
     phase_base: f32,
     phase_sub: f32,
+    drive: f32,
 }
 
 #[derive(Debug)]
 #[cfg_attr(feature = "defmt", derive(defmt::Format))]
 pub struct Attributes {
     pub frequency: f32,
+    /// Pace of the frequency change in Hz per second. When set, the
+    /// frequency moves to the new value sample by sample at this pace, so
+    /// control updates arriving less often than samples do not step.
+    pub slope: f32,
+    /// Saturation of the oscillator before it enters the chain, from 0.0
+    /// for a clean output to 1.0.
+    pub drive: f32,
 }
 
 impl Oscillator {
@@ -26,16 +39,28 @@ impl Oscillator {
         Self {
             sample_rate,
             frequency: 0.0,
+            target_frequency: 0.0,
+            frequency_step: 0.0,
             phase_base: 0.0,
             phase_sub: 0.0,
+            drive: 0.0,
         }
     }
 
     pub fn populate(&mut self, buffer: &mut [f32]) {
         for x in buffer.iter_mut() {
+            self.frequency = if self.frequency < self.target_frequency {
+                (self.frequency + self.frequency_step).min(self.target_frequency)
+            } else {
+                (self.frequency - self.frequency_step).max(self.target_frequency)
+            };
+
             let x_base = f32::sin(self.phase_base * 2.0 * PI);
             let x_sub = f32::sin(self.phase_sub * 2.0 * PI);
             *x = (x_base + x_sub) * 0.9;
+            if self.drive > 0.0 {
+                *x = saturate(*x, self.drive);
+            }
 
             let step = self.frequency / self.sample_rate;
             self.phase_base += step;
@@ -51,6 +76,96 @@ impl Oscillator {
     }
 
     pub fn set_attributes(&mut self, attributes: &Attributes) {
-        self.frequency = attributes.frequency;
+        self.target_frequency = attributes.frequency;
+        if attributes.slope == 0.0 {
+            self.frequency = attributes.frequency;
+        }
+        self.frequency_step = attributes.slope.abs() / self.sample_rate;
+        self.drive = attributes.drive.clamp(0.0, 1.0);
+    }
+}
+
+// NOTE: The shaper is normalized so a signal of unity amplitude keeps its
+// peak, only its shape changes.
+fn saturate(x: f32, drive: f32) -> f32 {
+    let gain = 1.0 + drive * (MAX_DRIVE_GAIN - 1.0);
+    libm::tanhf(x * gain) / libm::tanhf(gain)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    const SAMPLE_RATE: f32 = 1000.0;
+    const FREQUENCY: f32 = 10.0;
+
+    fn magnitude(buffer: &[f32], frequency: f32) -> f32 {
+        let mut in_phase = 0.0;
+        let mut quadrature = 0.0;
+        for (i, x) in buffer.iter().enumerate() {
+            let phase = 2.0 * PI * frequency * i as f32 / SAMPLE_RATE;
+            in_phase += x * libm::sinf(phase);
+            quadrature += x * libm::cosf(phase);
+        }
+        libm::sqrtf(in_phase * in_phase + quadrature * quadrature) * 2.0 / buffer.len() as f32
+    }
+
+    fn third_harmonic(drive: f32) -> f32 {
+        let mut oscillator = Oscillator::new(SAMPLE_RATE);
+        oscillator.set_attributes(&Attributes {
+            frequency: FREQUENCY,
+            slope: 0.0,
+            drive,
+        });
+        let mut buffer = [0.0; SAMPLE_RATE as usize];
+        oscillator.populate(&mut buffer);
+        magnitude(&buffer, FREQUENCY * 3.0)
+    }
+
+    #[test]
+    fn when_drive_increases_oscillator_gains_harmonics() {
+        let clean = third_harmonic(0.0);
+        let mild = third_harmonic(0.3);
+        let hot = third_harmonic(1.0);
+
+        assert!(clean < 0.01, "Clean {clean}");
+        assert!(mild > clean * 2.0, "Clean {clean}, mild {mild}");
+        assert!(hot > mild * 2.0, "Mild {mild}, hot {hot}");
+    }
+
+    #[test]
+    fn when_frequency_is_given_with_slope_it_glides_between_updates() {
+        // NOTE: Control updates arrive every 8 samples, each 8 Hz higher.
+        const UPDATE_INTERVAL: usize = 8;
+        const UPDATE_STEP: f32 = 8.0;
+
+        let mut oscillator = Oscillator::new(SAMPLE_RATE);
+        oscillator.set_attributes(&Attributes {
+            frequency: FREQUENCY,
+            slope: 0.0,
+            drive: 0.0,
+        });
+
+        let mut frequencies = [0.0; 8 * UPDATE_INTERVAL];
+        for (i, chunk) in frequencies.chunks_mut(UPDATE_INTERVAL).enumerate() {
+            oscillator.set_attributes(&Attributes {
+                frequency: FREQUENCY + (i + 1) as f32 * UPDATE_STEP,
+                slope: UPDATE_STEP * SAMPLE_RATE / UPDATE_INTERVAL as f32,
+                drive: 0.0,
+            });
+            for frequency in chunk.iter_mut() {
+                oscillator.populate(&mut [0.0]);
+                *frequency = oscillator.frequency;
+            }
+        }
+
+        let largest_step = frequencies
+            .windows(2)
+            .fold(0.0, |max: f32, x| max.max((x[1] - x[0]).abs()));
+        assert_relative_eq!(largest_step, 1.0, epsilon = 0.001);
+        assert_relative_eq!(
+            frequencies[frequencies.len() - 1],
+            FREQUENCY + 8.0 * UPDATE_STEP
+        );
     }
 }
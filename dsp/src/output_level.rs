@@ -0,0 +1,96 @@
+//! Master level of the output, to match the module with the rest of the rig.
+
+use crate::one_pole_filter::OnePoleFilter;
+
+// Smoothening of the level to avoid zipper noise while it changes.
+const LEVEL_CUTOFF: f32 = 20.0;
+
+#[derive(Debug)]
+#[cfg_attr(feature = "defmt", derive(defmt::Format))]
+pub struct OutputLevel {
+    level: f32,
+    level_filter: OnePoleFilter,
+}
+
+#[derive(Clone, Copy, Debug)]
+#[cfg_attr(feature = "defmt", derive(defmt::Format))]
+pub struct Attributes {
+    pub level: f32,
+}
+
+impl OutputLevel {
+    pub fn new(sample_rate: f32) -> Self {
+        Self {
+            level: 1.0,
+            level_filter: OnePoleFilter::new_settled(sample_rate, LEVEL_CUTOFF, 1.0),
+        }
+    }
+
+    pub fn process(&mut self, buffer_left: &mut [f32], buffer_right: &mut [f32]) {
+        for (l, r) in buffer_left.iter_mut().zip(buffer_right.iter_mut()) {
+            let level = self.level_filter.tick(self.level);
+            *l *= level;
+            *r *= level;
+        }
+    }
+
+    pub fn set_attributes(&mut self, attributes: Attributes) {
+        self.level = attributes.level;
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::BLOCK_SIZE;
+
+    const SAMPLE_RATE: f32 = 48_000.0;
+
+    fn settled_output(output_level: &mut OutputLevel, level: f32) -> (f32, f32) {
+        output_level.set_attributes(Attributes { level });
+        let mut left = [0.0; BLOCK_SIZE];
+        let mut right = [0.0; BLOCK_SIZE];
+        for _ in 0..6400 / BLOCK_SIZE {
+            left = [0.8; BLOCK_SIZE];
+            right = [-0.4; BLOCK_SIZE];
+            output_level.process(&mut left, &mut right);
+        }
+        (left[BLOCK_SIZE - 1], right[BLOCK_SIZE - 1])
+    }
+
+    #[test]
+    fn when_level_is_set_output_scales_linearly() {
+        let mut output_level = OutputLevel::new(SAMPLE_RATE);
+
+        for level in [1.0, 0.5, 0.25, 0.0] {
+            let (left, right) = settled_output(&mut output_level, level);
+            assert_relative_eq!(left, 0.8 * level, epsilon = 0.0001);
+            assert_relative_eq!(right, -0.4 * level, epsilon = 0.0001);
+        }
+    }
+
+    #[test]
+    fn when_level_is_not_set_output_passes_at_unity_from_start() {
+        let mut output_level = OutputLevel::new(SAMPLE_RATE);
+
+        let mut left = [0.8; BLOCK_SIZE];
+        let mut right = [-0.4; BLOCK_SIZE];
+        output_level.process(&mut left, &mut right);
+
+        assert_relative_eq!(left[0], 0.8);
+        assert_relative_eq!(right[0], -0.4);
+    }
+
+    #[test]
+    fn when_level_changes_it_does_not_jump() {
+        let mut output_level = OutputLevel::new(SAMPLE_RATE);
+        settled_output(&mut output_level, 1.0);
+
+        output_level.set_attributes(Attributes { level: 0.0 });
+        let mut left = [1.0; BLOCK_SIZE];
+        let mut right = [1.0; BLOCK_SIZE];
+        output_level.process(&mut left, &mut right);
+
+        assert!(left[0] > 0.9);
+    }
+}
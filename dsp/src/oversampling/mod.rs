@@ -4,20 +4,21 @@
 //!
 //! ```
 //! use sirena::signal::{self, Signal};
-//! use kaseta_dsp::oversampling::{Downsampler4, Upsampler4};
+//! use kaseta_dsp::oversampling::{Downsampler4, Upsampler4, FACTOR};
+//! use kaseta_dsp::BLOCK_SIZE;
 //! use sirena::memory_manager::MemoryManager;
 //! use core::mem::MaybeUninit;
 //!
 //! static mut MEMORY: [MaybeUninit<u32>; 512] = unsafe { MaybeUninit::uninit().assume_init() };
 //! let mut memory_manager = MemoryManager::from(unsafe { &mut MEMORY[..] });
-//! let mut upsampler = Upsampler4::new_4(&mut memory_manager);
-//! let mut downsampler = Downsampler4::new_4(&mut memory_manager);
+//! let mut upsampler = Upsampler4::new_4(&mut memory_manager).unwrap();
+//! let mut downsampler = Downsampler4::new_4(&mut memory_manager).unwrap();
 //!
-//! let input = [0.0; 32];
-//! let mut upsampled = [0.0; 32 * 4];
+//! let input = [0.0; BLOCK_SIZE];
+//! let mut upsampled = [0.0; BLOCK_SIZE * FACTOR];
 //! upsampler.process(&input, &mut upsampled);
 //! // nonlinear_processing(&mut upsampled);
-//! let mut downsampled = [0.0; 32];
+//! let mut downsampled = [0.0; BLOCK_SIZE];
 //! downsampler.process(&upsampled, &mut downsampled);
 //! ```
 
@@ -28,6 +29,9 @@ pub mod upsampling;
 pub use downsampling::Downsampler4;
 pub use upsampling::Upsampler4;
 
+/// Ratio between the oversampled and the original sample rate.
+pub const FACTOR: usize = 4;
+
 #[cfg(test)]
 mod tests {
     use super::*;
@@ -49,7 +53,7 @@ mod tests {
         const SAMPLES: usize = 1024;
         const OVERSAMPLING: usize = 4;
 
-        let mut downsampler = Downsampler4::new_4(&mut memory_manager);
+        let mut downsampler = Downsampler4::new_4(&mut memory_manager).unwrap();
 
         // Downsample oversampled signal with sine over original nyquist rate
         // and store it in a buffer.
@@ -78,8 +82,8 @@ mod tests {
         const NYQUIST: f32 = FS / 2.0 - 1.0;
         const SAMPLES: usize = 1024;
 
-        let mut upsampler = Upsampler4::new_4(&mut memory_manager);
-        let mut downsampler = Downsampler4::new_4(&mut memory_manager);
+        let mut upsampler = Upsampler4::new_4(&mut memory_manager).unwrap();
+        let mut downsampler = Downsampler4::new_4(&mut memory_manager).unwrap();
 
         let original_buffer: [f32; SAMPLES] = signal::sine(FS, NYQUIST / 2.0)
             .take(SAMPLES)
@@ -5,6 +5,7 @@ use core::fmt;
 use sirena::memory_manager::MemoryManager;
 
 use super::coefficients::COEFFICIENTS_4;
+use super::FACTOR;
 use crate::math;
 use crate::ring_buffer::RingBuffer;
 
@@ -33,21 +34,20 @@ impl<const N: usize, const M: usize> defmt::Format for Upsampler<N, M> {
 pub type Upsampler4 = Upsampler<{ COEFFICIENTS_4.len() }, { COEFFICIENTS_4.len() / 2 + 1 }>;
 
 impl Upsampler4 {
-    /// # Panics
-    ///
-    /// Panics if there is not enough space in the memory manager to allocate a
-    /// buffer.
-    #[must_use]
-    pub fn new_4(memory_manager: &mut MemoryManager) -> Self {
-        Self {
-            factor: 4,
+    /// Returns `None` if there is not enough space in the memory manager to
+    /// allocate a buffer.
+    pub fn new_4(memory_manager: &mut MemoryManager) -> Option<Self> {
+        Some(Self {
+            factor: FACTOR,
             coefficients: &COEFFICIENTS_4,
             buffer: RingBuffer::from(
-                memory_manager
-                    .allocate(math::upper_power_of_two(COEFFICIENTS_4.len()))
-                    .unwrap(),
+                memory_manager.allocate(math::upper_power_of_two(COEFFICIENTS_4.len()))?,
             ),
-        }
+        })
+    }
+
+    pub fn reset(&mut self) {
+        self.buffer.clear();
     }
 
     pub fn process(&mut self, input_buffer: &[f32], output_buffer: &mut [f32]) {
@@ -1,7 +1,13 @@
+use crate::one_pole_filter::OnePoleFilter;
+
+// Smoothening of the gain to avoid zipper noise while it changes.
+const GAIN_CUTOFF: f32 = 20.0;
+
 #[derive(Debug)]
 #[cfg_attr(feature = "defmt", derive(defmt::Format))]
 pub struct PreAmp {
     gain: f32,
+    gain_filter: OnePoleFilter,
 }
 
 #[derive(Clone, Copy, Debug)]
@@ -11,13 +17,25 @@ pub struct Attributes {
 }
 
 impl PreAmp {
-    pub fn new() -> Self {
-        Self { gain: 0.0 }
+    pub fn new(sample_rate: f32) -> Self {
+        Self {
+            gain: 0.0,
+            gain_filter: OnePoleFilter::new(sample_rate, GAIN_CUTOFF),
+        }
     }
 
     pub fn process(&mut self, buffer: &mut [f32]) {
         for x in buffer.iter_mut() {
-            *x *= self.gain;
+            *x *= self.gain_filter.tick(self.gain);
+        }
+    }
+
+    /// Amplify both channels with the same smoothened gain.
+    pub fn process_stereo(&mut self, buffer_left: &mut [f32], buffer_right: &mut [f32]) {
+        for (l, r) in buffer_left.iter_mut().zip(buffer_right.iter_mut()) {
+            let gain = self.gain_filter.tick(self.gain);
+            *l *= gain;
+            *r *= gain;
         }
     }
 
@@ -25,3 +43,53 @@ impl PreAmp {
         self.gain = attributes.gain;
     }
 }
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::BLOCK_SIZE;
+
+    const SAMPLE_RATE: f32 = 48_000.0;
+
+    fn settle(pre_amp: &mut PreAmp, gain: f32) {
+        pre_amp.set_attributes(Attributes { gain });
+        for _ in 0..6400 / BLOCK_SIZE {
+            let mut buffer = [1.0; BLOCK_SIZE];
+            pre_amp.process(&mut buffer);
+        }
+    }
+
+    #[test]
+    fn when_gain_is_settled_input_is_scaled_by_it() {
+        let mut pre_amp = PreAmp::new(SAMPLE_RATE);
+        settle(&mut pre_amp, 2.0);
+
+        let mut buffer = [0.5; BLOCK_SIZE];
+        pre_amp.process(&mut buffer);
+
+        for x in &buffer {
+            assert_relative_eq!(*x, 1.0, epsilon = 0.0001);
+        }
+    }
+
+    #[test]
+    fn when_gain_steps_it_ramps_across_samples_instead_of_jumping() {
+        let mut pre_amp = PreAmp::new(SAMPLE_RATE);
+        settle(&mut pre_amp, 0.0);
+
+        pre_amp.set_attributes(Attributes { gain: 1.0 });
+        let mut buffer = [1.0; BLOCK_SIZE];
+        pre_amp.process(&mut buffer);
+
+        assert!(buffer[0] < 0.01, "First sample {}", buffer[0]);
+        for pair in buffer.windows(2) {
+            assert!(pair[1] > pair[0]);
+            assert!(pair[1] - pair[0] < 0.01);
+        }
+        assert!(
+            buffer[BLOCK_SIZE - 1] < 0.5,
+            "Last sample {}",
+            buffer[BLOCK_SIZE - 1]
+        );
+    }
+}
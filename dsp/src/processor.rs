@@ -2,37 +2,94 @@
 
 use sirena::memory_manager::MemoryManager;
 
-use crate::clipper::{Clipper, Reaction as ClipperReaction};
-use crate::compressor::Compressor;
+use crate::band_meter::{BandMeter, Reaction as BandMeterReaction};
+use crate::capture::{Attributes as CaptureAttributes, Capture};
+use crate::clipper::{Attributes as ClipperAttributes, Clipper, Reaction as ClipperReaction};
+use crate::compressor::{
+    Attributes as CompressorAttributes, Compressor, Detection as CompressorDetection, Expander,
+};
+use crate::correlation::{Correlation, Reaction as CorrelationReaction};
 use crate::dc_blocker::DCBlocker;
 use crate::delay::{
-    Attributes as DelayAttributes, Delay, FilterPlacement, HeadAttributes as DelayHeadAttributes,
+    Attributes as DelayAttributes, Delay, FeedbackTopology, FilterPlacement,
+    HeadAttributes as DelayHeadAttributes, PanLaw, PositionMode, Priming as DelayPriming,
     Reaction as DelayReaction, WowFlutterPlacement,
 };
+use crate::dry_wet::{Attributes as DryWetAttributes, Curve as DryWetCurve, DryWet};
+use crate::feedback_guard::{FeedbackGuard, Reaction as FeedbackGuardReaction};
 use crate::hysteresis::{
     Attributes as HysteresisAttributes, Hysteresis, Reaction as HysteresisReaction,
+    Recovery as HysteresisRecovery, StereoHysteresis,
 };
+use crate::limiter::{Attributes as LimiterAttributes, Limiter, Reaction as LimiterReaction};
+use crate::metronome::{Attributes as MetronomeAttributes, Metronome};
 use crate::oscillator::{Attributes as OscillatorAttributes, Oscillator};
-use crate::oversampling::{Downsampler4, Upsampler4};
+use crate::output_level::{Attributes as OutputLevelAttributes, OutputLevel};
+use crate::oversampling::{Downsampler4, Upsampler4, FACTOR as OVERSAMPLING_FACTOR};
 use crate::pre_amp::{Attributes as PreAmpAttributes, PreAmp};
 use crate::random::Random;
-use crate::tone::{Attributes as ToneAttributes, Tone2};
+use crate::saturation_knee::SaturationKnee;
+use crate::tone::{Attributes as ToneAttributes, Cutoff, FilterType as ToneFilterType, Tone2};
 use crate::wow_flutter::{Attributes as WowFlutterAttributes, WowFlutter};
+use crate::BLOCK_SIZE;
+
+// Short enough to feel immediate, long enough to avoid a click.
+const BYPASS_CROSSFADE_DURATION: f32 = 0.01;
+
+// Peak of the trails below which they are considered silent, about -80 dB.
+const TRAILS_SILENCE_THRESHOLD: f32 = 0.0001;
 
 #[derive(Debug)]
 #[cfg_attr(feature = "defmt", derive(defmt::Format))]
 pub struct Processor {
     upsampler: Upsampler4,
     downsampler: Downsampler4,
+    upsampler_right: Upsampler4,
+    downsampler_right: Downsampler4,
     pre_amp: PreAmp,
     oscillator: Oscillator,
+    capture: Capture,
     hysteresis: Hysteresis,
+    stereo_hysteresis: StereoHysteresis,
+    dry_wet: DryWet,
+    saturation_knee: SaturationKnee,
+    saturation_knee_right: SaturationKnee,
     wow_flutter: WowFlutter,
     delay: Delay,
     tone: Tone2,
     compressor: Compressor,
+    expander: Expander,
+    feedback_guard: FeedbackGuard,
+    output_level: OutputLevel,
+    metronome: Metronome,
+    limiter: Limiter,
+    clipper: Clipper,
+    correlation: Correlation,
+    band_meter: BandMeter,
     dc_blocker: [DCBlocker; 3],
     first_stage: FirstStage,
+    oscillator_mix: f32,
+    tape_bypass: bool,
+    stereo_tape: bool,
+    dc_blocker_enabled: bool,
+    input_source: InputSource,
+    bypass: bool,
+    // Amount of the dry input in the output, 0.0 when processing and 1.0
+    // when fully bypassed.
+    bypass_mix: f32,
+    bypass_step: f32,
+    trails: bool,
+    // Amount of trails kept while bypassed, 1.0 when they ring out and 0.0
+    // when the delay is bypassed together with the input.
+    trails_mix: f32,
+    // Samples since the trails fell silent while bypassed.
+    trails_silence: usize,
+    swap_outputs: bool,
+    band_metering: bool,
+    // Gain of the output rising from 0.0 to 1.0 after `fade_in` is called.
+    fade_in_gain: f32,
+    fade_in_step: f32,
+    attributes: Attributes,
 }
 
 #[derive(Debug, Clone, Copy)]
@@ -42,31 +99,239 @@ enum FirstStage {
     Oscillator,
 }
 
-#[derive(Default, Clone, Copy, Debug)]
+/// Selection of input channels feeding the processing.
+#[derive(Default, Clone, Copy, Debug, PartialEq)]
+#[cfg_attr(feature = "defmt", derive(defmt::Format))]
+pub enum InputSource {
+    Left,
+    #[default]
+    Right,
+    Sum,
+    /// Crossfade between left (0.0) and right (1.0).
+    Blend(f32),
+}
+
+#[derive(Clone, Copy, Debug)]
 #[cfg_attr(feature = "defmt", derive(defmt::Format))]
 #[allow(clippy::struct_excessive_bools)]
 pub struct Attributes {
     pub pre_amp: f32,
     pub oscillator: f32,
+    /// Pace of the oscillator frequency change in Hz per second, to glide
+    /// between updates. With 0.0, the frequency jumps to the new value.
+    pub oscillator_slope: f32,
+    /// Saturation of the oscillator, from 0.0 for clean to 1.0.
+    pub oscillator_drive: f32,
     pub drive: f32,
     pub saturation: f32,
     pub bias: f32,
     pub dry_wet: f32,
+    /// Either 0 for a linear blend of the dry and the saturated signal, 1
+    /// for equal-power, or 2 for constant-gain.
+    pub dry_wet_curve: u8,
+    /// RMS level of the pre-amplified input above which the tape saturation
+    /// fades in, reaching its full amount at the double. Quieter passages
+    /// stay clean. When `None`, the saturation applies at all levels.
+    pub saturation_knee: Option<f32>,
+    pub auto_makeup: bool,
+    /// Magnetisation at which the hysteresis is considered unstable. When
+    /// `None`, the default is used.
+    pub hysteresis_limit: Option<f32>,
+    /// Either 0 to reset the magnetisation once it exceeds the limit, or 1
+    /// to hold it on the limit.
+    pub hysteresis_recovery: u8,
+    pub tape_bypass: bool,
+    /// Saturate the left and right input on tapes of their own before they
+    /// are mixed for the delay, so a loud channel does not drive the quiet
+    /// one. It doubles the cost of the tape and makes a difference only
+    /// when `input_source` mixes both channels.
+    pub stereo_tape: bool,
+    /// Pass the input straight to the output, skipping all the processing,
+    /// including the delay and output stages.
+    pub bypass: bool,
+    /// While bypassed, keep the delay running with its input muted, so the
+    /// echoes already on the tape ring out on top of the dry signal.
+    pub trails: bool,
+    /// Block sub-bass with the DC blockers around the delay. When disabled,
+    /// those inside the feedback loop stay, so it cannot run away.
+    pub dc_blocker_enabled: bool,
+    pub input_source: InputSource,
     pub wow: f32,
     pub flutter_depth: f32,
     pub flutter_chance: f32,
+    /// Depth of the high-rate scrape flutter in seconds.
+    pub scrape_depth: f32,
+    /// Modulate the left and right output with independent wow and flutter.
+    pub wow_flutter_decorrelated: bool,
+    /// Hold the current wow and flutter delay, resuming smoothly once
+    /// released.
+    pub wow_flutter_frozen: bool,
     pub speed: f32,
     pub tone: f32,
+    pub tone_filter_type: u8,
+    /// Time constant in seconds of the cutoff following the tone. When
+    /// `None`, the default fast slew is used.
+    pub tone_slew: Option<f32>,
+    /// Make up for the loudness removed by the tone filter, keeping the level
+    /// of broadband signals as the tone sweeps.
+    pub tone_loudness_compensation: bool,
+    /// Exact -3 dB point of the tone filter. When set, it wins over `tone`.
+    pub tone_cutoff: Option<Cutoff>,
     pub head: [AttributesHead; 4],
     pub enable_oscillator: bool,
+    /// Level of the oscillator summed with the input. Ignored when
+    /// the oscillator is enabled to replace the input.
+    pub oscillator_mix: f32,
     pub rewind: bool,
     pub reset_impulse: bool,
     pub random_impulse: bool,
+    pub impulse_accent: f32,
+    pub impulse_threshold: f32,
+    /// Number of evenly spaced impulses each head sends per cycle of the
+    /// delay. Zero is treated as one.
+    pub impulse_subdivisions: usize,
     pub paused_delay: bool,
+    /// Stop recording and keep looping the last delay length.
+    pub frozen_delay: bool,
     pub filter_placement: u8,
     pub wow_flutter_placement: u8,
+    /// Placement of flutter when it should differ from the one of wow,
+    /// encoded as `wow_flutter_placement`.
+    pub flutter_placement: Option<u8>,
+    pub pan_law: u8,
+    pub feedback_topology: u8,
+    /// Hardness of the saturation on the feedback loop, letting heads
+    /// self-oscillate into a stable tone. Zero keeps the feedback linear.
+    pub feedback_character: f32,
+    /// Delay of the right read of each head behind the left one, in
+    /// milliseconds, for a wider echo. Limited to 30 ms.
+    pub stereo_offset_ms: f32,
+    /// Either 0 for head positions proportional to the delay length, or 1
+    /// for positions relative to the beat.
+    pub position_mode: u8,
     pub clear_buffer: bool,
+    /// Shuffle chunks of the loop instead of clearing it with `clear_buffer`.
+    pub splice_buffer: bool,
     pub rewind_speed: [(f32, f32); 4],
+    /// Length of a beat in seconds, of the detected or tapped tempo. Used
+    /// by beat-relative positions, accented impulses and the click.
+    pub beat: Option<f32>,
+    /// Length of a beat in seconds to time rewinds to. Kept apart from
+    /// `beat`, so rewinds can keep their configured speed under a tempo.
+    pub rewind_beat: Option<f32>,
+    pub gate_width: f32,
+    /// Duration of the crossfade in seconds when a head jumps to a new
+    /// position. When `None`, the default crossfade is used.
+    pub position_crossfade: Option<f32>,
+    /// Read the delay output oversampled, to reduce aliasing of heads
+    /// modulated fast, e.g. in the audio range.
+    pub oversampled_read: bool,
+    pub separate_heads: bool,
+    pub ping_pong: bool,
+    pub capture: bool,
+    pub capture_length: f32,
+    pub capture_repeats: usize,
+    /// Linear gain of the final output, unity by default.
+    pub output_level: f32,
+    /// Level of a click played on every `beat`, aligned by
+    /// `reset_impulse`. Zero turns it off.
+    pub click_level: f32,
+    pub clip_threshold: Option<f32>,
+    /// Turn the output down ahead of overshoots instead of clipping them.
+    /// Delays the output by a few samples.
+    pub limiter: bool,
+    pub compressor_attack: Option<f32>,
+    pub compressor_release: Option<f32>,
+    pub compressor_detection: u8,
+    /// Depth of a downward expander turning down hiss while the output is
+    /// quiet, between 0.0 (off) and 1.0.
+    pub noise_reduction: f32,
+    /// Exchange the left and right output. This is applied last, after
+    /// panning and even on the bypassed signal, so it only changes which
+    /// jack carries which channel.
+    pub swap_outputs: bool,
+    /// Measure power of the low, mid and high band of the output, reported
+    /// in `Reaction::band_power`.
+    pub band_metering: bool,
+}
+impl Default for Attributes {
+    fn default() -> Self {
+        Self {
+            pre_amp: 0.0,
+            oscillator: 0.0,
+            oscillator_slope: 0.0,
+            oscillator_drive: 0.0,
+            drive: 0.0,
+            saturation: 0.0,
+            bias: 0.0,
+            dry_wet: 0.0,
+            dry_wet_curve: 0,
+            saturation_knee: None,
+            auto_makeup: false,
+            hysteresis_limit: None,
+            hysteresis_recovery: 0,
+            tape_bypass: false,
+            stereo_tape: false,
+            bypass: false,
+            trails: false,
+            dc_blocker_enabled: true,
+            input_source: InputSource::default(),
+            wow: 0.0,
+            flutter_depth: 0.0,
+            flutter_chance: 0.0,
+            scrape_depth: 0.0,
+            wow_flutter_decorrelated: false,
+            wow_flutter_frozen: false,
+            speed: 0.0,
+            tone: 0.0,
+            tone_filter_type: 0,
+            tone_slew: None,
+            tone_loudness_compensation: false,
+            tone_cutoff: None,
+            head: [AttributesHead::default(); 4],
+            enable_oscillator: false,
+            oscillator_mix: 0.0,
+            rewind: false,
+            reset_impulse: false,
+            random_impulse: false,
+            impulse_accent: 0.0,
+            impulse_threshold: 0.0,
+            impulse_subdivisions: 0,
+            paused_delay: false,
+            frozen_delay: false,
+            filter_placement: 0,
+            wow_flutter_placement: 0,
+            flutter_placement: None,
+            pan_law: 0,
+            feedback_topology: 0,
+            feedback_character: 0.0,
+            stereo_offset_ms: 0.0,
+            position_mode: 0,
+            clear_buffer: false,
+            splice_buffer: false,
+            rewind_speed: [(0.0, 0.0); 4],
+            beat: None,
+            rewind_beat: None,
+            gate_width: 0.0,
+            position_crossfade: None,
+            oversampled_read: false,
+            separate_heads: false,
+            ping_pong: false,
+            capture: false,
+            capture_length: 0.0,
+            capture_repeats: 0,
+            output_level: 1.0,
+            click_level: 0.0,
+            clip_threshold: None,
+            limiter: false,
+            compressor_attack: None,
+            compressor_release: None,
+            compressor_detection: 0,
+            noise_reduction: 0.0,
+            swap_outputs: false,
+            band_metering: false,
+        }
+    }
 }
 
 #[derive(Clone, Copy, Debug, Default)]
@@ -74,8 +339,14 @@ pub struct Attributes {
 pub struct AttributesHead {
     pub position: f32,
     pub volume: f32,
+    /// Negative values feed the signal back with flipped polarity.
     pub feedback: f32,
     pub pan: f32,
+    /// Nudge of the head from its position in milliseconds, in both
+    /// directions. It cannot push the head past the write cursor.
+    pub fine_offset_ms: f32,
+    pub feedback_invert: bool,
+    pub feedback_highpass: f32,
 }
 
 #[derive(Default, Clone, Copy, Debug)]
@@ -83,70 +354,189 @@ pub struct AttributesHead {
 pub struct Reaction {
     pub hysteresis_clipping: bool,
     pub delay_impulse: bool,
+    pub head_gates: [bool; 4],
     pub output_clipping: bool,
+    pub output_limiting: bool,
+    /// The output would largely cancel out when summed to mono.
+    pub mono_incompatible: bool,
+    /// Feedback is being pulled back, since the output stayed at its ceiling
+    /// for too long.
+    pub runaway_feedback: bool,
+    /// Gain reduction of the output compressor in decibels.
+    pub compressor_gain_reduction: f32,
     pub new_position: usize,
     pub buffer_reset_progress: Option<u8>,
+    /// Portion of the delay loop holding audible content, from 0.0 to 1.0.
+    pub buffer_fill: f32,
+    /// Peak amplitude of the left and right input before any processing.
+    pub input_peak: [f32; 2],
+    /// Read pointer of each delay head relative to the loop length.
+    pub head_positions: [f32; 4],
+    /// Mean power of the low, mid and high band of the output. Zero unless
+    /// `band_metering` is enabled.
+    pub band_power: [f32; 3],
 }
 
+/// The memory provided to the processor is not big enough to fit all its
+/// buffers.
+#[derive(Debug)]
+#[cfg_attr(feature = "defmt", derive(defmt::Format))]
+pub struct InsufficientMemory;
+
 impl Processor {
+    /// # Errors
+    ///
+    /// This fails with `InsufficientMemory` when any of the buffers cannot be
+    /// allocated in given memory managers.
     #[allow(clippy::let_and_return)]
-    #[must_use]
     pub fn new(
         fs: f32,
         stack_manager: &mut MemoryManager,
         sdram_manager: &mut MemoryManager,
-    ) -> Self {
+    ) -> Result<Self, InsufficientMemory> {
         let mut uninitialized_processor = Self {
-            upsampler: Upsampler4::new_4(stack_manager),
-            downsampler: Downsampler4::new_4(stack_manager),
-            pre_amp: PreAmp::new(),
+            upsampler: Upsampler4::new_4(stack_manager).ok_or(InsufficientMemory)?,
+            downsampler: Downsampler4::new_4(stack_manager).ok_or(InsufficientMemory)?,
+            upsampler_right: Upsampler4::new_4(stack_manager).ok_or(InsufficientMemory)?,
+            downsampler_right: Downsampler4::new_4(stack_manager).ok_or(InsufficientMemory)?,
+            pre_amp: PreAmp::new(fs),
             oscillator: Oscillator::new(fs),
+            capture: Capture::new(fs, stack_manager).ok_or(InsufficientMemory)?,
             hysteresis: Hysteresis::new(fs),
-            wow_flutter: WowFlutter::new(fs as u32, stack_manager),
-            delay: Delay::new(fs, sdram_manager),
+            stereo_hysteresis: StereoHysteresis::new(fs),
+            dry_wet: DryWet::new(),
+            saturation_knee: SaturationKnee::new(fs * OVERSAMPLING_FACTOR as f32),
+            saturation_knee_right: SaturationKnee::new(fs * OVERSAMPLING_FACTOR as f32),
+            wow_flutter: WowFlutter::new(fs as u32, stack_manager).ok_or(InsufficientMemory)?,
+            delay: Delay::new(fs, sdram_manager).ok_or(InsufficientMemory)?,
             tone: Tone2::new(fs),
             compressor: Compressor::new(fs),
+            expander: Expander::new(fs),
+            feedback_guard: FeedbackGuard::new(fs),
+            output_level: OutputLevel::new(fs),
+            metronome: Metronome::new(fs),
+            limiter: Limiter::new(fs),
+            clipper: Clipper::default(),
+            correlation: Correlation::new(fs),
+            band_meter: BandMeter::new(fs),
             dc_blocker: [
                 DCBlocker::default(),
                 DCBlocker::default(),
                 DCBlocker::default(),
             ],
             first_stage: FirstStage::PreAmp,
+            oscillator_mix: 0.0,
+            tape_bypass: false,
+            stereo_tape: false,
+            dc_blocker_enabled: true,
+            input_source: InputSource::default(),
+            bypass: false,
+            bypass_mix: 0.0,
+            bypass_step: 1.0 / (fs * BYPASS_CROSSFADE_DURATION),
+            trails: false,
+            trails_mix: 0.0,
+            trails_silence: 0,
+            swap_outputs: false,
+            band_metering: false,
+            fade_in_gain: 1.0,
+            fade_in_step: 0.0,
+            attributes: Attributes::default(),
         };
 
+        uninitialized_processor
+            .delay
+            .allocate_read_oversampling(stack_manager)?;
         uninitialized_processor.set_attributes(Attributes::default());
         let processor = uninitialized_processor;
 
-        processor
+        Ok(processor)
     }
 
-    pub fn process(&mut self, block: &mut [(f32, f32); 32], random: &mut impl Random) -> Reaction {
-        let mut reaction = Reaction::default();
+    pub fn process(
+        &mut self,
+        block: &mut [(f32, f32); BLOCK_SIZE],
+        random: &mut impl Random,
+    ) -> Reaction {
+        let mut reaction = Reaction {
+            input_peak: block.iter().fold([0.0, 0.0], |peak, (l, r)| {
+                [peak[0].max(l.abs()), peak[1].max(r.abs())]
+            }),
+            ..Reaction::default()
+        };
 
-        let mut buffer = [0.0; 32];
+        // NOTE: Once the crossfade is over, the input is left untouched and
+        // none of the stages run, unless they are kept for trails.
+        if self.is_stalled() {
+            self.fade_in_if_enabled(block);
+            self.swap_outputs_if_enabled(block);
+            return reaction;
+        }
+        let dry = *block;
+        let bypass_mixes = self.calculate_bypass_mixes();
+        let trails_mixes = self.calculate_trails_mixes();
+
+        let mut buffer = [0.0; BLOCK_SIZE];
+        // NOTE: With the stereo tape, the right input is carried separately
+        // until it passes the tape, while `buffer` holds only the left one.
+        let mut buffer_input_right = [0.0; BLOCK_SIZE];
         match self.first_stage {
             FirstStage::PreAmp => {
-                for (i, x) in block.iter().enumerate() {
-                    buffer[i] = x.1;
+                if self.stereo_tape {
+                    for (i, x) in block.iter().enumerate() {
+                        (buffer[i], buffer_input_right[i]) = self.input_source.split(*x);
+                    }
+                    self.pre_amp
+                        .process_stereo(&mut buffer, &mut buffer_input_right);
+                } else {
+                    for (i, x) in block.iter().enumerate() {
+                        buffer[i] = self.input_source.pick(*x);
+                    }
+                    self.pre_amp.process(&mut buffer);
+                }
+                if self.oscillator_mix > 0.0 {
+                    let mut oscillator_buffer = [0.0; BLOCK_SIZE];
+                    self.oscillator.populate(&mut oscillator_buffer);
+                    for (x, o) in buffer.iter_mut().zip(oscillator_buffer) {
+                        *x += o * self.oscillator_mix;
+                    }
                 }
-                self.pre_amp.process(&mut buffer);
             }
             FirstStage::Oscillator => {
                 self.oscillator.populate(&mut buffer);
             }
         }
+        // NOTE: With trails, the bypass mutes the input of the delay instead
+        // of its output.
+        for (i, (bypass_mix, trails_mix)) in bypass_mixes.iter().zip(trails_mixes).enumerate() {
+            buffer[i] *= 1.0 - bypass_mix * trails_mix;
+            buffer_input_right[i] *= 1.0 - bypass_mix * trails_mix;
+        }
 
-        let mut oversampled_block = [0.0; 32 * 4];
-        self.upsampler.process(&buffer, &mut oversampled_block);
-        self.hysteresis
-            .process(&mut oversampled_block)
-            .notify(&mut reaction);
-        self.downsampler
-            .process(&oversampled_block, &mut buffer[..]);
+        // NOTE: Oversampling and hysteresis are the most expensive part of
+        // the processing. When the tape is bypassed, they are skipped.
+        //
+        // The capture buffer is mono, so with the stereo tape it records the
+        // already saturated mix of both channels.
+        if self.stereo_tape {
+            if !self.tape_bypass {
+                self.process_stereo_tape(&mut buffer, &mut buffer_input_right, &mut reaction);
+            }
+            for (x, right) in buffer.iter_mut().zip(buffer_input_right) {
+                *x += right;
+            }
+            self.capture.process(&mut buffer);
+        } else {
+            self.capture.process(&mut buffer);
+            if !self.tape_bypass {
+                self.process_tape(&mut buffer, &mut reaction);
+            }
+        }
 
-        let mut buffer_left = [0.0; 32];
-        let mut buffer_right = [0.0; 32];
-        self.dc_blocker[0].process(&mut buffer[..]);
+        let mut buffer_left = [0.0; BLOCK_SIZE];
+        let mut buffer_right = [0.0; BLOCK_SIZE];
+        if self.dc_blocker_enabled {
+            self.dc_blocker[0].process(&mut buffer[..]);
+        }
         self.delay
             .process(
                 &mut buffer[..],
@@ -158,33 +548,319 @@ impl Processor {
             )
             .notify(&mut reaction);
 
-        self.dc_blocker[1].process(&mut buffer_left);
-        self.dc_blocker[2].process(&mut buffer_right);
+        if self.dc_blocker_enabled {
+            self.dc_blocker[1].process(&mut buffer_left);
+            self.dc_blocker[2].process(&mut buffer_right);
+        }
         self.compressor.process(&mut buffer_left, &mut buffer_right);
-        Clipper::process(&mut buffer_left).notify(&mut reaction);
-        Clipper::process(&mut buffer_right).notify(&mut reaction);
+        reaction.compressor_gain_reduction = self.compressor.gain_reduction();
+        self.expander.process(&mut buffer_left, &mut buffer_right);
+        // NOTE: The reduction is slow, it does not matter that it is applied
+        // to the delay only from the next block on.
+        self.feedback_guard
+            .process(&buffer_left, &buffer_right)
+            .notify(&mut reaction);
+        self.delay.set_feedback_scale(self.feedback_guard.scale());
+        // NOTE: The level is applied before the clipper, so even trim above
+        // unity cannot overload the codec.
+        self.output_level
+            .process(&mut buffer_left, &mut buffer_right);
+        self.metronome.process(&mut buffer_left, &mut buffer_right);
+        self.limiter
+            .process(&mut buffer_left, &mut buffer_right)
+            .notify(&mut reaction);
+        self.clipper.process(&mut buffer_left).notify(&mut reaction);
+        self.clipper
+            .process(&mut buffer_right)
+            .notify(&mut reaction);
+        self.correlation
+            .process(&buffer_left, &buffer_right)
+            .notify(&mut reaction);
+        if self.band_metering {
+            self.band_meter
+                .process(&buffer_left, &buffer_right)
+                .notify(&mut reaction);
+        }
 
         for (i, (l, r)) in block.iter_mut().enumerate() {
             *l = buffer_left[i];
             *r = buffer_right[i];
         }
 
+        self.detect_trails_silence(&buffer_left, &buffer_right);
+
+        for ((((l, r), (dry_l, dry_r)), bypass_mix), trails_mix) in block
+            .iter_mut()
+            .zip(&dry)
+            .zip(bypass_mixes)
+            .zip(trails_mixes)
+        {
+            let wet_mix = 1.0 - bypass_mix * (1.0 - trails_mix);
+            *l = *l * wet_mix + dry_l * bypass_mix;
+            *r = *r * wet_mix + dry_r * bypass_mix;
+        }
+
+        self.fade_in_if_enabled(block);
+        self.swap_outputs_if_enabled(block);
+
         reaction
     }
 
+    fn fade_in_if_enabled(&mut self, block: &mut [(f32, f32); BLOCK_SIZE]) {
+        if self.fade_in_gain >= 1.0 {
+            return;
+        }
+        for (l, r) in block.iter_mut() {
+            *l *= self.fade_in_gain;
+            *r *= self.fade_in_gain;
+            self.fade_in_gain = (self.fade_in_gain + self.fade_in_step).min(1.0);
+        }
+    }
+
+    fn swap_outputs_if_enabled(&self, block: &mut [(f32, f32); BLOCK_SIZE]) {
+        if self.swap_outputs {
+            for (l, r) in block.iter_mut() {
+                core::mem::swap(l, r);
+            }
+        }
+    }
+
+    // NOTE: Trails keep the stages running only until they fade out. The
+    // delay is given its whole length to bring back any remaining echo.
+    fn is_stalled(&self) -> bool {
+        let trails_over = if self.trails {
+            self.trails_silence > self.delay.length_samples()
+        } else {
+            self.trails_mix <= 0.0
+        };
+        self.bypass && self.bypass_mix >= 1.0 && trails_over
+    }
+
+    fn calculate_trails_mixes(&mut self) -> [f32; BLOCK_SIZE] {
+        let step = if self.trails {
+            self.bypass_step
+        } else {
+            -self.bypass_step
+        };
+        core::array::from_fn(|_| {
+            self.trails_mix = (self.trails_mix + step).clamp(0.0, 1.0);
+            self.trails_mix
+        })
+    }
+
+    fn detect_trails_silence(&mut self, left: &[f32], right: &[f32]) {
+        if !(self.bypass && self.bypass_mix >= 1.0 && self.trails) {
+            self.trails_silence = 0;
+            return;
+        }
+        let peak = left
+            .iter()
+            .chain(right)
+            .fold(0.0_f32, |peak, x| peak.max(x.abs()));
+        if peak < TRAILS_SILENCE_THRESHOLD {
+            self.trails_silence += left.len();
+        } else {
+            self.trails_silence = 0;
+        }
+    }
+
+    fn calculate_bypass_mixes(&mut self) -> [f32; BLOCK_SIZE] {
+        let step = if self.bypass {
+            self.bypass_step
+        } else {
+            -self.bypass_step
+        };
+        core::array::from_fn(|_| {
+            self.bypass_mix = (self.bypass_mix + step).clamp(0.0, 1.0);
+            self.bypass_mix
+        })
+    }
+
+    /// Run given number of silent blocks through the whole chain.
+    ///
+    /// This lets smoothed parameters reach their targets, DC blockers settle
+    /// and the wow and flutter buffer fill, so the first audible block does
+    /// not carry startup transients. It is meant to be called once after the
+    /// initial attributes are set, before the audio starts.
+    pub fn warm_up(&mut self, blocks: usize, random: &mut impl Random) {
+        for _ in 0..blocks {
+            let mut block = [(0.0, 0.0); BLOCK_SIZE];
+            self.process(&mut block, random);
+        }
+    }
+
+    /// Fill the delay tape with silence or low-level noise.
+    ///
+    /// The tape starts silent. Like `warm_up`, this is meant to be called
+    /// once before the audio starts.
+    pub fn prime_tape(&mut self, priming: DelayPriming, random: &mut impl Random) {
+        self.delay.prime(priming, random);
+    }
+
+    /// Mute the output and bring it back up linearly over the given number
+    /// of blocks.
+    ///
+    /// This is a one-shot envelope meant to be started right before the
+    /// audio starts, so residual state of buffers and filters cannot pop on
+    /// boot.
+    pub fn fade_in(&mut self, blocks: usize) {
+        self.fade_in_gain = 0.0;
+        self.fade_in_step = 1.0 / (blocks.max(1) * BLOCK_SIZE) as f32;
+    }
+
+    /// Flush internal state of all the filters, hysteresis and buffers,
+    /// without reallocating them.
+    ///
+    /// The delay tape is cleared only when `clear_delay` is set. Unlike the
+    /// `clear_buffer` attribute, this happens at once and without fading, so
+    /// it is meant for recovery, not for a regular performance.
+    pub fn reset(&mut self, clear_delay: bool) {
+        self.upsampler.reset();
+        self.downsampler.reset();
+        self.upsampler_right.reset();
+        self.downsampler_right.reset();
+        self.capture.reset();
+        self.hysteresis.reset();
+        self.stereo_hysteresis.reset();
+        self.saturation_knee.reset();
+        self.saturation_knee_right.reset();
+        self.wow_flutter.reset();
+        self.delay.reset(clear_delay);
+        self.tone.reset();
+        self.compressor.reset();
+        self.expander.reset();
+        self.feedback_guard.reset();
+        self.correlation.reset();
+        self.band_meter.reset();
+        self.limiter.reset();
+        for dc_blocker in &mut self.dc_blocker {
+            dc_blocker.reset();
+        }
+    }
+
+    fn process_tape(&mut self, buffer: &mut [f32; BLOCK_SIZE], reaction: &mut Reaction) {
+        let mut oversampled_block = [0.0; BLOCK_SIZE * OVERSAMPLING_FACTOR];
+        self.upsampler.process(buffer, &mut oversampled_block);
+        let dry_oversampled_block = oversampled_block;
+        self.hysteresis
+            .process(&mut oversampled_block)
+            .notify(reaction);
+        self.dry_wet
+            .process(&dry_oversampled_block, &mut oversampled_block);
+        self.saturation_knee
+            .process(&dry_oversampled_block, &mut oversampled_block);
+        self.downsampler.process(&oversampled_block, buffer);
+    }
+
+    fn process_stereo_tape(
+        &mut self,
+        buffer_left: &mut [f32; BLOCK_SIZE],
+        buffer_right: &mut [f32; BLOCK_SIZE],
+        reaction: &mut Reaction,
+    ) {
+        let mut oversampled_left = [0.0; BLOCK_SIZE * OVERSAMPLING_FACTOR];
+        let mut oversampled_right = [0.0; BLOCK_SIZE * OVERSAMPLING_FACTOR];
+        self.upsampler.process(buffer_left, &mut oversampled_left);
+        self.upsampler_right
+            .process(buffer_right, &mut oversampled_right);
+        let dry_oversampled_left = oversampled_left;
+        let dry_oversampled_right = oversampled_right;
+        self.stereo_hysteresis
+            .process(&mut oversampled_left, &mut oversampled_right)
+            .notify(reaction);
+        self.dry_wet
+            .process(&dry_oversampled_left, &mut oversampled_left);
+        self.dry_wet
+            .process(&dry_oversampled_right, &mut oversampled_right);
+        self.saturation_knee
+            .process(&dry_oversampled_left, &mut oversampled_left);
+        self.saturation_knee_right
+            .process(&dry_oversampled_right, &mut oversampled_right);
+        self.downsampler.process(&oversampled_left, buffer_left);
+        self.downsampler_right
+            .process(&oversampled_right, buffer_right);
+    }
+
+    fn reset_stereo_tape(&mut self) {
+        self.upsampler_right.reset();
+        self.downsampler_right.reset();
+        self.stereo_hysteresis.reset();
+        self.saturation_knee_right.reset();
+    }
+
+    /// Decimated peaks of the tape, meant for visualization.
+    pub fn snapshot<const M: usize>(&self, out: &mut [f32; M]) {
+        self.delay.snapshot(out);
+    }
+
+    /// Attributes last passed to `set_attributes`.
+    pub fn attributes(&self) -> &Attributes {
+        &self.attributes
+    }
+
     pub fn set_attributes(&mut self, attributes: Attributes) {
+        self.attributes = attributes;
         self.first_stage = if attributes.enable_oscillator {
             FirstStage::Oscillator
         } else {
             FirstStage::PreAmp
         };
 
+        // NOTE: Skipped stages hold stale signal from before the bypass,
+        // which would leak out once they are engaged again.
+        if self.tape_bypass && !attributes.tape_bypass {
+            self.upsampler.reset();
+            self.downsampler.reset();
+            self.hysteresis.reset();
+            self.reset_stereo_tape();
+        }
+        // NOTE: The mono and the stereo tape each keep their magnetisation
+        // only while they run.
+        if self.stereo_tape != attributes.stereo_tape {
+            self.hysteresis.reset();
+            self.reset_stereo_tape();
+        }
+        self.oscillator_mix = attributes.oscillator_mix;
+        self.tape_bypass = attributes.tape_bypass;
+        self.stereo_tape = attributes.stereo_tape;
+        if !self.dc_blocker_enabled && attributes.dc_blocker_enabled {
+            for dc_blocker in &mut self.dc_blocker {
+                dc_blocker.reset();
+            }
+        }
+        self.dc_blocker_enabled = attributes.dc_blocker_enabled;
+        self.input_source = attributes.input_source;
+
+        // NOTE: Stages were not running while fully bypassed. Their state is
+        // stale, but the tape is kept, so the delay continues where it was.
+        let was_stalled = self.is_stalled();
+        self.bypass = attributes.bypass;
+        self.trails = attributes.trails;
+        self.swap_outputs = attributes.swap_outputs;
+        self.band_metering = attributes.band_metering;
+        if was_stalled && !self.is_stalled() {
+            self.reset(false);
+        }
+
         self.pre_amp.set_attributes(attributes.into());
         self.oscillator.set_attributes(&attributes.into());
+        self.capture.set_attributes(attributes.into());
         self.hysteresis.set_attributes(attributes.into());
+        self.stereo_hysteresis.set_attributes(attributes.into());
+        self.dry_wet.set_attributes(attributes.into());
+        self.saturation_knee
+            .set_threshold(attributes.saturation_knee);
+        self.saturation_knee_right
+            .set_threshold(attributes.saturation_knee);
         self.wow_flutter.set_attributes(attributes.into());
         self.delay.set_attributes(attributes.into());
         self.tone.set_attributes(attributes.into());
+        self.output_level.set_attributes(attributes.into());
+        self.metronome.set_attributes(attributes.into());
+        self.limiter.set_attributes(attributes.into());
+        self.clipper.set_attributes(attributes.into());
+        self.compressor.set_attributes(attributes.into());
+        self.expander.set_amount(attributes.noise_reduction);
     }
 }
 
@@ -200,23 +876,114 @@ impl From<Attributes> for OscillatorAttributes {
     fn from(other: Attributes) -> Self {
         Self {
             frequency: other.oscillator,
+            slope: other.oscillator_slope,
+            drive: other.oscillator_drive,
+        }
+    }
+}
+
+impl From<Attributes> for OutputLevelAttributes {
+    fn from(other: Attributes) -> Self {
+        Self {
+            level: other.output_level,
+        }
+    }
+}
+
+impl From<Attributes> for MetronomeAttributes {
+    fn from(other: Attributes) -> Self {
+        Self {
+            beat: other.beat,
+            level: other.click_level,
+            sync: other.reset_impulse,
+        }
+    }
+}
+
+impl From<Attributes> for LimiterAttributes {
+    fn from(other: Attributes) -> Self {
+        Self {
+            enabled: other.limiter,
+            ceiling: other.clip_threshold,
+        }
+    }
+}
+
+impl From<Attributes> for ClipperAttributes {
+    fn from(other: Attributes) -> Self {
+        Self {
+            threshold: other.clip_threshold,
+        }
+    }
+}
+
+impl From<Attributes> for CompressorAttributes {
+    fn from(other: Attributes) -> Self {
+        Self {
+            attack: other.compressor_attack,
+            release: other.compressor_release,
+            detection: match other.compressor_detection {
+                0 => CompressorDetection::Peak,
+                1 => CompressorDetection::Rms,
+                _ => unreachable!(),
+            },
+        }
+    }
+}
+
+impl From<Attributes> for CaptureAttributes {
+    fn from(other: Attributes) -> Self {
+        Self {
+            trigger: other.capture,
+            length: other.capture_length,
+            repeats: other.capture_repeats,
         }
     }
 }
 
 impl From<Attributes> for ToneAttributes {
     fn from(other: Attributes) -> Self {
-        Self { tone: other.tone }
+        Self {
+            tone: other.tone,
+            filter_type: match other.tone_filter_type {
+                0 => ToneFilterType::Iir,
+                1 => ToneFilterType::Fir,
+                _ => unreachable!(),
+            },
+            slew: other.tone_slew,
+            loudness_compensation: other.tone_loudness_compensation,
+            cutoff: other.tone_cutoff,
+        }
+    }
+}
+
+impl From<Attributes> for DryWetAttributes {
+    fn from(other: Attributes) -> Self {
+        Self {
+            mix: other.dry_wet,
+            curve: match other.dry_wet_curve {
+                0 => DryWetCurve::Linear,
+                1 => DryWetCurve::EqualPower,
+                2 => DryWetCurve::ConstantGain,
+                _ => unreachable!(),
+            },
+        }
     }
 }
 
 impl From<Attributes> for HysteresisAttributes {
     fn from(other: Attributes) -> Self {
         Self {
-            dry_wet: other.dry_wet,
             drive: other.drive,
             saturation: other.saturation,
             width: 1.0 - other.bias,
+            auto_makeup: other.auto_makeup,
+            limit: other.hysteresis_limit,
+            recovery: match other.hysteresis_recovery {
+                0 => HysteresisRecovery::Reset,
+                1 => HysteresisRecovery::SoftLimit,
+                _ => unreachable!(),
+            },
         }
     }
 }
@@ -227,6 +994,9 @@ impl From<Attributes> for WowFlutterAttributes {
             wow_depth: other.wow,
             flutter_depth: other.flutter_depth,
             flutter_chance: other.flutter_chance,
+            scrape_depth: other.scrape_depth,
+            decorrelated: other.wow_flutter_decorrelated,
+            frozen: other.wow_flutter_frozen,
         }
     }
 }
@@ -241,36 +1011,51 @@ impl From<Attributes> for DelayAttributes {
                     volume: other.head[0].volume,
                     feedback: other.head[0].feedback,
                     pan: other.head[0].pan,
+                    fine_offset_ms: other.head[0].fine_offset_ms,
                     rewind_forward: other.rewind.then_some(other.rewind_speed[0].1),
                     rewind_backward: other.rewind.then_some(other.rewind_speed[0].0),
+                    feedback_invert: other.head[0].feedback_invert,
+                    feedback_highpass: other.head[0].feedback_highpass,
                 },
                 DelayHeadAttributes {
                     position: other.head[1].position,
                     volume: other.head[1].volume,
                     feedback: other.head[1].feedback,
                     pan: other.head[1].pan,
+                    fine_offset_ms: other.head[1].fine_offset_ms,
                     rewind_forward: other.rewind.then_some(other.rewind_speed[1].1),
                     rewind_backward: other.rewind.then_some(other.rewind_speed[1].0),
+                    feedback_invert: other.head[1].feedback_invert,
+                    feedback_highpass: other.head[1].feedback_highpass,
                 },
                 DelayHeadAttributes {
                     position: other.head[2].position,
                     volume: other.head[2].volume,
                     feedback: other.head[2].feedback,
                     pan: other.head[2].pan,
+                    fine_offset_ms: other.head[2].fine_offset_ms,
                     rewind_forward: other.rewind.then_some(other.rewind_speed[2].1),
                     rewind_backward: other.rewind.then_some(other.rewind_speed[2].0),
+                    feedback_invert: other.head[2].feedback_invert,
+                    feedback_highpass: other.head[2].feedback_highpass,
                 },
                 DelayHeadAttributes {
                     position: other.head[3].position,
                     volume: other.head[3].volume,
                     feedback: other.head[3].feedback,
                     pan: other.head[3].pan,
+                    fine_offset_ms: other.head[3].fine_offset_ms,
                     rewind_forward: other.rewind.then_some(other.rewind_speed[3].1),
                     rewind_backward: other.rewind.then_some(other.rewind_speed[3].0),
+                    feedback_invert: other.head[3].feedback_invert,
+                    feedback_highpass: other.head[3].feedback_highpass,
                 },
             ],
             reset_impulse: other.reset_impulse,
             random_impulse: other.random_impulse,
+            impulse_accent: other.impulse_accent,
+            impulse_threshold: other.impulse_threshold,
+            impulse_subdivisions: other.impulse_subdivisions,
             filter_placement: match other.filter_placement {
                 0 => FilterPlacement::Input,
                 1 => FilterPlacement::Feedback,
@@ -283,8 +1068,57 @@ impl From<Attributes> for DelayAttributes {
                 2 => WowFlutterPlacement::Both,
                 _ => unreachable!(),
             },
+            flutter_placement: other.flutter_placement.map(|placement| match placement {
+                0 => WowFlutterPlacement::Input,
+                1 => WowFlutterPlacement::Read,
+                2 => WowFlutterPlacement::Both,
+                _ => unreachable!(),
+            }),
+            pan_law: match other.pan_law {
+                0 => PanLaw::Linear,
+                1 => PanLaw::EqualPower,
+                _ => unreachable!(),
+            },
+            feedback_topology: match other.feedback_topology {
+                0 => FeedbackTopology::Cumulative,
+                1 => FeedbackTopology::SinglePass,
+                _ => unreachable!(),
+            },
+            feedback_character: other.feedback_character,
+            stereo_offset_ms: other.stereo_offset_ms,
+            position_mode: match other.position_mode {
+                0 => PositionMode::Proportional,
+                1 => PositionMode::BeatRelative,
+                _ => unreachable!(),
+            },
+            beat: other.beat,
+            rewind_beat: other.rewind_beat,
+            gate_width: other.gate_width,
+            separate_heads: other.separate_heads,
+            ping_pong: other.ping_pong,
             reset_buffer: other.clear_buffer,
+            splice_buffer: other.splice_buffer,
             paused: other.paused_delay,
+            freeze: other.frozen_delay,
+            position_crossfade: other.position_crossfade,
+            oversampled_read: other.oversampled_read,
+        }
+    }
+}
+
+impl InputSource {
+    fn pick(self, input: (f32, f32)) -> f32 {
+        let (left, right) = self.split(input);
+        left + right
+    }
+
+    // NOTE: Contributions of the left and right input to the picked signal.
+    fn split(self, (left, right): (f32, f32)) -> (f32, f32) {
+        match self {
+            Self::Left => (left, 0.0),
+            Self::Right => (0.0, right),
+            Self::Sum => (left, right),
+            Self::Blend(balance) => (left * (1.0 - balance), right * balance),
         }
     }
 }
@@ -298,8 +1132,23 @@ impl HysteresisReaction {
 impl DelayReaction {
     fn notify(&mut self, reaction: &mut Reaction) {
         reaction.delay_impulse = self.impulse;
+        reaction.head_gates = self.head_gates;
         reaction.new_position = self.new_position;
         reaction.buffer_reset_progress = self.buffer_reset_progress;
+        reaction.buffer_fill = self.fill;
+        reaction.head_positions = self.head_positions;
+    }
+}
+
+impl LimiterReaction {
+    fn notify(&mut self, reaction: &mut Reaction) {
+        reaction.output_limiting = self.limiting;
+    }
+}
+
+impl FeedbackGuardReaction {
+    fn notify(&mut self, reaction: &mut Reaction) {
+        reaction.runaway_feedback = self.attenuating;
     }
 }
 
@@ -308,3 +1157,1131 @@ impl ClipperReaction {
         reaction.output_clipping |= self.clipping;
     }
 }
+
+impl CorrelationReaction {
+    fn notify(&mut self, reaction: &mut Reaction) {
+        reaction.mono_incompatible = self.mono_incompatible;
+    }
+}
+
+impl BandMeterReaction {
+    fn notify(&mut self, reaction: &mut Reaction) {
+        reaction.band_power = self.band_power;
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    const FS: f32 = 1000.0;
+
+    macro_rules! processor {
+        () => {
+            Processor::new(
+                FS,
+                &mut memory_manager!(4096),
+                &mut memory_manager!(1 << 19),
+            )
+            .unwrap()
+        };
+    }
+
+    struct TestRandom;
+
+    impl Random for TestRandom {
+        fn normal(&mut self) -> f32 {
+            0.5
+        }
+    }
+
+    #[test]
+    fn when_reset_it_outputs_silence_for_silent_input_immediately() {
+        let mut processor = processor!();
+        let mut random = TestRandom;
+
+        let mut attributes = Attributes {
+            pre_amp: 1.0,
+            drive: 0.5,
+            saturation: 0.5,
+            dry_wet: 1.0,
+            speed: 0.1,
+            tone: 0.5,
+            output_level: 1.0,
+            ..Attributes::default()
+        };
+        attributes.head[0] = AttributesHead {
+            position: 0.5,
+            volume: 1.0,
+            feedback: 0.5,
+            ..AttributesHead::default()
+        };
+        processor.set_attributes(attributes);
+
+        for _ in 0..1024 / BLOCK_SIZE {
+            let mut block = [(0.5, 0.5); BLOCK_SIZE];
+            processor.process(&mut block, &mut random);
+        }
+        let mut block = [(0.0, 0.0); BLOCK_SIZE];
+        processor.process(&mut block, &mut random);
+        assert!(block
+            .iter()
+            .any(|(l, r)| l.abs() > 0.001 || r.abs() > 0.001));
+
+        processor.reset(true);
+        block = [(0.0, 0.0); BLOCK_SIZE];
+        processor.process(&mut block, &mut random);
+
+        for (l, r) in &block {
+            assert_relative_eq!(*l, 0.0);
+            assert_relative_eq!(*r, 0.0);
+        }
+    }
+
+    #[test]
+    fn when_reset_with_limiter_enabled_it_outputs_silence_for_silent_input_immediately() {
+        let mut processor = processor!();
+        let mut random = TestRandom;
+        processor.set_attributes(Attributes {
+            limiter: true,
+            ..bypassed_attributes()
+        });
+
+        for _ in 0..10 {
+            let mut block = [(0.0, 4.0); BLOCK_SIZE];
+            processor.process(&mut block, &mut random);
+        }
+
+        processor.reset(true);
+        let mut block = [(0.0, 0.0); BLOCK_SIZE];
+        processor.process(&mut block, &mut random);
+
+        for (l, r) in &block {
+            assert_relative_eq!(*l, 0.0);
+            assert_relative_eq!(*r, 0.0);
+        }
+    }
+
+    fn magnitude(buffer: &[f32], frequency: f32) -> f32 {
+        let mut in_phase = 0.0;
+        let mut quadrature = 0.0;
+        for (i, x) in buffer.iter().enumerate() {
+            let phase = 2.0 * core::f32::consts::PI * frequency * i as f32 / FS;
+            in_phase += x * libm::sinf(phase);
+            quadrature += x * libm::cosf(phase);
+        }
+        libm::sqrtf(in_phase * in_phase + quadrature * quadrature) * 2.0 / buffer.len() as f32
+    }
+
+    fn bypassed_attributes() -> Attributes {
+        let mut attributes = Attributes {
+            pre_amp: 1.0,
+            drive: 1.0,
+            saturation: 1.0,
+            dry_wet: 1.0,
+            speed: 1.0,
+            tone: 0.5,
+            tape_bypass: true,
+            ..Attributes::default()
+        };
+        attributes.head[0].volume = 1.0;
+        attributes
+    }
+
+    #[test]
+    fn when_tape_is_bypassed_sine_passes_without_harmonics() {
+        let mut processor = processor!();
+        processor.set_attributes(bypassed_attributes());
+
+        const FREQUENCY: f32 = 10.0;
+        let mut output = [0.0; 3200];
+        for (i, chunk) in output.chunks_mut(BLOCK_SIZE).enumerate() {
+            let mut block = [(0.0, 0.0); BLOCK_SIZE];
+            for (j, x) in block.iter_mut().enumerate() {
+                let phase =
+                    2.0 * core::f32::consts::PI * FREQUENCY * (i * BLOCK_SIZE + j) as f32 / FS;
+                x.1 = libm::sinf(phase) * 0.25;
+            }
+            processor.process(&mut block, &mut TestRandom);
+            for (j, x) in chunk.iter_mut().enumerate() {
+                *x = block[j].0;
+            }
+        }
+
+        // NOTE: Skip the first half to let DC blockers settle.
+        let settled = &output[1600..];
+        let fundamental = magnitude(settled, FREQUENCY);
+        assert!(fundamental > 0.2, "Fundamental {fundamental}");
+        for harmonic in 2..=5 {
+            let magnitude = magnitude(settled, FREQUENCY * harmonic as f32);
+            assert!(
+                magnitude < fundamental * 0.001,
+                "Harmonic {harmonic} {magnitude}, fundamental {fundamental}"
+            );
+        }
+    }
+
+    // NOTE: Returns magnitudes of the fundamental and of the strongest
+    // harmonic of a sine of given amplitude passed through the tape.
+    fn saturated_sine(amplitude: f32, saturation_knee: Option<f32>) -> (f32, f32) {
+        let mut processor = processor!();
+        processor.set_attributes(Attributes {
+            tape_bypass: false,
+            saturation_knee,
+            ..bypassed_attributes()
+        });
+
+        const FREQUENCY: f32 = 10.0;
+        let mut output = [0.0; 3200];
+        for (i, chunk) in output.chunks_mut(BLOCK_SIZE).enumerate() {
+            let mut block = [(0.0, 0.0); BLOCK_SIZE];
+            for (j, x) in block.iter_mut().enumerate() {
+                let phase =
+                    2.0 * core::f32::consts::PI * FREQUENCY * (i * BLOCK_SIZE + j) as f32 / FS;
+                x.1 = libm::sinf(phase) * amplitude;
+            }
+            processor.process(&mut block, &mut TestRandom);
+            for (j, x) in chunk.iter_mut().enumerate() {
+                *x = block[j].0;
+            }
+        }
+
+        // NOTE: Skip the first half to let DC blockers settle.
+        let settled = &output[1600..];
+        let fundamental = magnitude(settled, FREQUENCY);
+        let harmonic = (2..=5)
+            .map(|harmonic| magnitude(settled, FREQUENCY * harmonic as f32))
+            .fold(0.0, f32::max);
+        (fundamental, harmonic)
+    }
+
+    #[test]
+    fn when_saturation_knee_is_set_only_loud_signal_gets_harmonics() {
+        let (fundamental, harmonic) = saturated_sine(0.02, None);
+        assert!(harmonic > fundamental * 0.1);
+
+        let (fundamental, harmonic) = saturated_sine(0.02, Some(0.1));
+        assert!(fundamental > 0.015, "Fundamental {fundamental}");
+        assert!(
+            harmonic < fundamental * 0.001,
+            "Harmonic {harmonic}, fundamental {fundamental}"
+        );
+
+        let (fundamental, harmonic) = saturated_sine(0.5, Some(0.1));
+        assert!(
+            harmonic > fundamental * 0.1,
+            "Harmonic {harmonic}, fundamental {fundamental}"
+        );
+    }
+
+    // NOTE: Returns magnitude of the intermodulation between a loud sine on
+    // the left and a quiet one on the right, both passed through the tape.
+    fn intermodulation(stereo_tape: bool) -> f32 {
+        let mut processor = processor!();
+        processor.set_attributes(Attributes {
+            tape_bypass: false,
+            stereo_tape,
+            input_source: InputSource::Sum,
+            ..bypassed_attributes()
+        });
+
+        const FREQUENCY_LEFT: f32 = 10.0;
+        const FREQUENCY_RIGHT: f32 = 35.0;
+        let mut output = [0.0; 3200];
+        for (i, chunk) in output.chunks_mut(BLOCK_SIZE).enumerate() {
+            let mut block = [(0.0, 0.0); BLOCK_SIZE];
+            for (j, x) in block.iter_mut().enumerate() {
+                let time = (i * BLOCK_SIZE + j) as f32 / FS;
+                x.0 = libm::sinf(2.0 * core::f32::consts::PI * FREQUENCY_LEFT * time);
+                x.1 = libm::sinf(2.0 * core::f32::consts::PI * FREQUENCY_RIGHT * time) * 0.05;
+            }
+            processor.process(&mut block, &mut TestRandom);
+            for (j, x) in chunk.iter_mut().enumerate() {
+                *x = block[j].0;
+            }
+        }
+
+        let settled = &output[output.len() / 2..];
+        magnitude(settled, FREQUENCY_RIGHT + FREQUENCY_LEFT)
+            .max(magnitude(settled, FREQUENCY_RIGHT - FREQUENCY_LEFT))
+    }
+
+    #[test]
+    fn when_stereo_tape_is_enabled_loud_channel_does_not_modulate_quiet_one() {
+        let mono = intermodulation(false);
+        let stereo = intermodulation(true);
+        assert!(
+            stereo < mono * 0.1,
+            "Stereo intermodulation {stereo}, mono {mono}"
+        );
+    }
+
+    #[test]
+    fn when_tape_is_bypassed_hysteresis_is_not_processed() {
+        let mut processor = processor!();
+        let mut attributes = bypassed_attributes();
+
+        // NOTE: Hysteresis reports clipping of overdriven input. It can only
+        // do so when it runs.
+        processor.set_attributes(attributes);
+        let reaction = processor.process(&mut [(3.0, 3.0); BLOCK_SIZE], &mut TestRandom);
+        assert!(!reaction.hysteresis_clipping);
+
+        attributes.tape_bypass = false;
+        processor.set_attributes(attributes);
+        let reaction = processor.process(&mut [(3.0, 3.0); BLOCK_SIZE], &mut TestRandom);
+        assert!(reaction.hysteresis_clipping);
+    }
+
+    #[test]
+    fn when_clip_threshold_is_set_output_is_bounded_by_it() {
+        let mut processor = processor!();
+        processor.set_attributes(Attributes {
+            clip_threshold: Some(0.5),
+            ..bypassed_attributes()
+        });
+
+        let mut clipping = false;
+        let mut peak: f32 = 0.0;
+        for i in 0..100 {
+            let mut block = [(0.0, 0.0); BLOCK_SIZE];
+            for (j, x) in block.iter_mut().enumerate() {
+                let phase = 2.0 * core::f32::consts::PI * 10.0 * (i * BLOCK_SIZE + j) as f32 / FS;
+                x.1 = libm::sinf(phase) * 4.0;
+            }
+            clipping |= processor
+                .process(&mut block, &mut TestRandom)
+                .output_clipping;
+            for (l, r) in &block {
+                peak = peak.max(l.abs()).max(r.abs());
+            }
+        }
+
+        assert!(clipping);
+        assert_relative_eq!(peak, 0.5);
+    }
+
+    #[test]
+    fn when_limiter_is_enabled_output_is_bounded_without_clipping() {
+        let mut processor = processor!();
+        processor.set_attributes(Attributes {
+            clip_threshold: Some(0.5),
+            limiter: true,
+            ..bypassed_attributes()
+        });
+
+        let mut clipping = false;
+        let mut limiting = false;
+        let mut peak: f32 = 0.0;
+        for i in 0..100 {
+            let mut block = [(0.0, 0.0); BLOCK_SIZE];
+            for (j, x) in block.iter_mut().enumerate() {
+                let phase = 2.0 * core::f32::consts::PI * 10.0 * (i * BLOCK_SIZE + j) as f32 / FS;
+                x.1 = libm::sinf(phase) * 4.0;
+            }
+            let reaction = processor.process(&mut block, &mut TestRandom);
+            clipping |= reaction.output_clipping;
+            limiting |= reaction.output_limiting;
+            for (l, r) in &block {
+                peak = peak.max(l.abs()).max(r.abs());
+            }
+        }
+
+        assert!(limiting);
+        assert!(!clipping);
+        assert_relative_eq!(peak, 0.5, epsilon = 0.01);
+    }
+
+    fn output_peak_for_head_volume(volume: f32) -> f32 {
+        let mut processor = processor!();
+        let mut attributes = bypassed_attributes();
+        attributes.head[0].volume = volume;
+        processor.set_attributes(attributes);
+
+        let mut peak: f32 = 0.0;
+        for i in 0..100 {
+            let mut block = [(0.0, 0.0); BLOCK_SIZE];
+            for (j, x) in block.iter_mut().enumerate() {
+                let phase = 2.0 * core::f32::consts::PI * 10.0 * (i * BLOCK_SIZE + j) as f32 / FS;
+                x.1 = libm::sinf(phase);
+            }
+            processor.process(&mut block, &mut TestRandom);
+            for (l, r) in &block {
+                peak = peak.max(l.abs()).max(r.abs());
+            }
+        }
+
+        peak
+    }
+
+    #[test]
+    fn when_head_volume_exceeds_unity_output_is_louder_but_stays_within_full_scale() {
+        let unity_peak = output_peak_for_head_volume(0.25);
+        let hot_peak = output_peak_for_head_volume(2.0);
+
+        assert!(
+            hot_peak > unity_peak * 2.0 && hot_peak <= 1.0,
+            "Peak grew from {unity_peak} to {hot_peak}"
+        );
+    }
+
+    #[test]
+    fn when_click_is_enabled_it_sounds_on_every_beat() {
+        let mut processor = processor!();
+        let mut attributes = Attributes {
+            beat: Some(0.1),
+            click_level: 0.5,
+            reset_impulse: true,
+            ..bypassed_attributes()
+        };
+        processor.set_attributes(attributes);
+        attributes.reset_impulse = false;
+
+        let mut onsets = [0; 8];
+        let mut found = 0;
+        let mut previous: f32 = 0.0;
+        for i in 0..1024 / BLOCK_SIZE {
+            let mut block = [(0.0, 0.0); BLOCK_SIZE];
+            processor.process(&mut block, &mut TestRandom);
+            processor.set_attributes(attributes);
+            for (j, (l, _)) in block.iter().enumerate() {
+                if l.abs() > 0.2 && previous.abs() <= 0.2 && found < onsets.len() {
+                    onsets[found] = i * BLOCK_SIZE + j;
+                    found += 1;
+                }
+                previous = *l;
+            }
+        }
+
+        let beat = (0.1 * FS) as usize;
+        assert_eq!(found, onsets.len());
+        for (i, onset) in onsets.iter().enumerate() {
+            assert_eq!(*onset, i * beat);
+        }
+    }
+
+    // NOTE: Lengths in these tests are given in samples divisible by 64, so
+    // they hold for any block size dividing it.
+    #[test]
+    fn given_any_block_size_echo_lands_on_its_sample() {
+        let mut processor = processor!();
+        let mut attributes = bypassed_attributes();
+        attributes.head[0].position = 0.1;
+        processor.set_attributes(attributes);
+        // NOTE: Let the head travel to its position.
+        for _ in 0..2048 / BLOCK_SIZE {
+            processor.process(&mut [(0.0, 0.0); BLOCK_SIZE], &mut TestRandom);
+        }
+
+        let mut output = [0.0; 256];
+        for (i, chunk) in output.chunks_mut(BLOCK_SIZE).enumerate() {
+            let mut block = [(0.0, 0.0); BLOCK_SIZE];
+            if i == 0 {
+                block[0].1 = 1.0;
+            }
+            processor.process(&mut block, &mut TestRandom);
+            for (j, x) in chunk.iter_mut().enumerate() {
+                *x = block[j].0;
+            }
+        }
+
+        let (peak_index, _) = output
+            .iter()
+            .enumerate()
+            .fold((0, 0.0), |(index, max), (i, x)| {
+                if x.abs() > max {
+                    (i, x.abs())
+                } else {
+                    (index, max)
+                }
+            });
+        assert!((100..=101).contains(&peak_index), "Echo at {peak_index}");
+    }
+
+    #[test]
+    fn when_feedback_runs_away_it_is_pulled_back_and_warning_is_reported() {
+        let mut processor = processor!();
+        let mut attributes = Attributes {
+            speed: 0.1,
+            ..bypassed_attributes()
+        };
+        attributes.head[0] = AttributesHead {
+            position: 1.0,
+            volume: 1.0,
+            feedback: 1.2,
+            ..AttributesHead::default()
+        };
+        processor.set_attributes(attributes);
+
+        // NOTE: A short burst is enough to make the loop oscillate.
+        for i in 0..64 / BLOCK_SIZE {
+            let mut block: [(f32, f32); BLOCK_SIZE] =
+                core::array::from_fn(|j| (0.0, libm::sinf((i * BLOCK_SIZE + j) as f32)));
+            processor.process(&mut block, &mut TestRandom);
+        }
+
+        let mut runaway_feedback = false;
+        let mut feedback_scale: f32 = 1.0;
+        for _ in 0..64_000 / BLOCK_SIZE {
+            let mut block = [(0.0, 0.0); BLOCK_SIZE];
+            runaway_feedback |= processor
+                .process(&mut block, &mut TestRandom)
+                .runaway_feedback;
+            feedback_scale = feedback_scale.min(processor.feedback_guard.scale());
+        }
+
+        assert!(runaway_feedback);
+        assert!(feedback_scale < 0.9, "Feedback scale {feedback_scale}");
+    }
+
+    // NOTE: Returns the first blocks processed after given number of silent
+    // warm-up blocks.
+    fn first_blocks_after_warm_up(mut processor: Processor, warm_up_blocks: usize) -> [f32; 320] {
+        let mut attributes = Attributes {
+            pre_amp: 1.0,
+            drive: 0.8,
+            saturation: 0.5,
+            bias: 0.5,
+            dry_wet: 0.5,
+            speed: 0.1,
+            tone: 0.5,
+            output_level: 1.0,
+            ..Attributes::default()
+        };
+        attributes.head[0] = AttributesHead {
+            position: 0.05,
+            volume: 1.0,
+            feedback: 0.5,
+            ..AttributesHead::default()
+        };
+        processor.set_attributes(attributes);
+        processor.warm_up(warm_up_blocks, &mut TestRandom);
+
+        let mut output = [0.0; 320];
+        for (i, chunk) in output.chunks_mut(BLOCK_SIZE).enumerate() {
+            let mut block = [(0.0, 0.0); BLOCK_SIZE];
+            for (j, x) in block.iter_mut().enumerate() {
+                let phase = 2.0 * core::f32::consts::PI * 20.0 * (i * BLOCK_SIZE + j) as f32 / FS;
+                x.1 = libm::sinf(phase) * 0.5;
+            }
+            processor.process(&mut block, &mut TestRandom);
+            for (j, x) in chunk.iter_mut().enumerate() {
+                *x = block[j].0;
+            }
+        }
+        output
+    }
+
+    fn max_difference(a: &[f32], b: &[f32]) -> f32 {
+        a.iter()
+            .zip(b)
+            .fold(0.0, |max, (a, b)| f32::max(max, (a - b).abs()))
+    }
+
+    #[test]
+    fn when_warmed_up_first_blocks_are_free_of_startup_transients() {
+        let settled = first_blocks_after_warm_up(processor!(), 1000);
+        let cold = first_blocks_after_warm_up(processor!(), 0);
+        let warmed_up = first_blocks_after_warm_up(processor!(), 100);
+
+        assert!(max_difference(&cold, &settled) > 0.1);
+        assert!(max_difference(&warmed_up, &settled) < 0.001);
+    }
+
+    const LEFT_FREQUENCY: f32 = 10.0;
+    const RIGHT_FREQUENCY: f32 = 40.0;
+
+    // NOTE: Returns magnitudes of the left and right input tones found in the
+    // processed output.
+    fn input_source_magnitudes(mut processor: Processor, input_source: InputSource) -> (f32, f32) {
+        processor.set_attributes(Attributes {
+            input_source,
+            ..bypassed_attributes()
+        });
+
+        let mut output = [0.0; 3200];
+        for (i, chunk) in output.chunks_mut(BLOCK_SIZE).enumerate() {
+            let mut block = [(0.0, 0.0); BLOCK_SIZE];
+            for (j, x) in block.iter_mut().enumerate() {
+                let time = (i * BLOCK_SIZE + j) as f32 / FS;
+                x.0 = libm::sinf(2.0 * core::f32::consts::PI * LEFT_FREQUENCY * time) * 0.2;
+                x.1 = libm::sinf(2.0 * core::f32::consts::PI * RIGHT_FREQUENCY * time) * 0.2;
+            }
+            processor.process(&mut block, &mut TestRandom);
+            for (j, x) in chunk.iter_mut().enumerate() {
+                *x = block[j].0;
+            }
+        }
+
+        // NOTE: Skip the first half to let DC blockers settle.
+        let settled = &output[1600..];
+        (
+            magnitude(settled, LEFT_FREQUENCY),
+            magnitude(settled, RIGHT_FREQUENCY),
+        )
+    }
+
+    fn tone_cutoff_magnitude(mut processor: Processor, tone_cutoff: Option<Cutoff>) -> f32 {
+        processor.set_attributes(Attributes {
+            tone: 0.5,
+            tone_cutoff,
+            ..bypassed_attributes()
+        });
+
+        let mut output = [0.0; 3200];
+        for (i, chunk) in output.chunks_mut(BLOCK_SIZE).enumerate() {
+            let mut block = [(0.0, 0.0); BLOCK_SIZE];
+            for (j, x) in block.iter_mut().enumerate() {
+                let time = (i * BLOCK_SIZE + j) as f32 / FS;
+                let value = libm::sinf(2.0 * core::f32::consts::PI * RIGHT_FREQUENCY * time) * 0.2;
+                *x = (value, value);
+            }
+            processor.process(&mut block, &mut TestRandom);
+            for (j, x) in chunk.iter_mut().enumerate() {
+                *x = block[j].0;
+            }
+        }
+
+        // NOTE: Skip the first half to let DC blockers settle.
+        magnitude(&output[1600..], RIGHT_FREQUENCY)
+    }
+
+    #[test]
+    fn when_tone_cutoff_is_set_it_wins_over_tone() {
+        let unfiltered = tone_cutoff_magnitude(processor!(), None);
+        let filtered = tone_cutoff_magnitude(processor!(), Some(Cutoff::LowPass(10.0)));
+
+        assert!(unfiltered > 0.05, "Unfiltered {unfiltered}");
+        assert!(filtered < unfiltered * 0.1, "Filtered {filtered}");
+    }
+
+    #[test]
+    fn when_left_input_is_selected_only_left_tone_is_processed() {
+        let (left, right) = input_source_magnitudes(processor!(), InputSource::Left);
+        assert!(left > 0.15, "Left {left}");
+        assert!(right < 0.001, "Right {right}");
+    }
+
+    #[test]
+    fn when_right_input_is_selected_only_right_tone_is_processed() {
+        let (left, right) = input_source_magnitudes(processor!(), InputSource::Right);
+        assert!(left < 0.001, "Left {left}");
+        assert!(right > 0.15, "Right {right}");
+    }
+
+    #[test]
+    fn when_inputs_are_summed_both_tones_are_processed() {
+        let (left, right) = input_source_magnitudes(processor!(), InputSource::Sum);
+        assert!(left > 0.15, "Left {left}");
+        assert!(right > 0.15, "Right {right}");
+    }
+
+    #[test]
+    fn when_inputs_are_blended_tones_follow_the_balance() {
+        let (left, right) = input_source_magnitudes(processor!(), InputSource::Blend(0.25));
+        assert_relative_eq!(left / right, 3.0, max_relative = 0.05);
+    }
+
+    const OSCILLATOR_FREQUENCY: f32 = 100.0;
+
+    // NOTE: Returns magnitudes of the input tone and the oscillator tone found
+    // in the processed output.
+    fn oscillator_mix_magnitudes(mut processor: Processor, oscillator_mix: f32) -> (f32, f32) {
+        processor.set_attributes(Attributes {
+            oscillator: OSCILLATOR_FREQUENCY,
+            oscillator_mix,
+            ..bypassed_attributes()
+        });
+
+        let mut output = [0.0; 3200];
+        for (i, chunk) in output.chunks_mut(BLOCK_SIZE).enumerate() {
+            let mut block = [(0.0, 0.0); BLOCK_SIZE];
+            for (j, x) in block.iter_mut().enumerate() {
+                let time = (i * BLOCK_SIZE + j) as f32 / FS;
+                x.1 = libm::sinf(2.0 * core::f32::consts::PI * RIGHT_FREQUENCY * time) * 0.2;
+            }
+            processor.process(&mut block, &mut TestRandom);
+            for (j, x) in chunk.iter_mut().enumerate() {
+                *x = block[j].0;
+            }
+        }
+
+        let settled = &output[1600..];
+        (
+            magnitude(settled, RIGHT_FREQUENCY),
+            magnitude(settled, OSCILLATOR_FREQUENCY),
+        )
+    }
+
+    #[test]
+    fn when_oscillator_mix_is_zero_only_input_is_processed() {
+        let (input, oscillator) = oscillator_mix_magnitudes(processor!(), 0.0);
+        assert!(input > 0.15, "Input {input}");
+        assert!(oscillator < 0.001, "Oscillator {oscillator}");
+    }
+
+    #[test]
+    fn when_oscillator_is_mixed_both_input_and_oscillator_tones_are_processed() {
+        let (input, oscillator) = oscillator_mix_magnitudes(processor!(), 0.25);
+        assert!(input > 0.15, "Input {input}");
+        assert!(oscillator > 0.15, "Oscillator {oscillator}");
+    }
+
+    fn sub_bass_magnitude(mut processor: Processor, dc_blocker_enabled: bool) -> f32 {
+        const FREQUENCY: f32 = 1.0;
+
+        processor.set_attributes(Attributes {
+            dc_blocker_enabled,
+            ..bypassed_attributes()
+        });
+
+        let mut output = [0.0; 4000];
+        for (i, chunk) in output.chunks_mut(BLOCK_SIZE).enumerate() {
+            let mut block = [(0.0, 0.0); BLOCK_SIZE];
+            for (j, x) in block.iter_mut().enumerate() {
+                let time = (i * BLOCK_SIZE + j) as f32 / FS;
+                x.1 = libm::sinf(2.0 * core::f32::consts::PI * FREQUENCY * time) * 0.2;
+            }
+            processor.process(&mut block, &mut TestRandom);
+            for (j, x) in chunk.iter_mut().enumerate() {
+                *x = block[j].0;
+            }
+        }
+
+        // NOTE: Measure over whole periods once the filters settle.
+        magnitude(&output[1000..4000], FREQUENCY)
+    }
+
+    #[test]
+    fn when_dc_blocker_is_enabled_sub_bass_is_attenuated() {
+        let magnitude = sub_bass_magnitude(processor!(), true);
+        assert!(magnitude < 0.15, "Magnitude {magnitude}");
+    }
+
+    #[test]
+    fn when_dc_blocker_is_disabled_sub_bass_passes_unattenuated() {
+        let magnitude = sub_bass_magnitude(processor!(), false);
+        assert_relative_eq!(magnitude, 0.2, max_relative = 0.1);
+    }
+
+    #[test]
+    fn when_output_is_loud_reaction_reports_compressor_gain_reduction() {
+        let mut processor = processor!();
+        processor.set_attributes(bypassed_attributes());
+
+        let mut reaction = processor.process(&mut [(0.0, 0.0); BLOCK_SIZE], &mut TestRandom);
+        assert_relative_eq!(reaction.compressor_gain_reduction, 0.0);
+
+        for i in 0..1024 / BLOCK_SIZE {
+            let mut block = [(0.0, 0.0); BLOCK_SIZE];
+            for (j, x) in block.iter_mut().enumerate() {
+                let time = (i * BLOCK_SIZE + j) as f32 / FS;
+                x.1 = libm::sinf(2.0 * core::f32::consts::PI * 50.0 * time);
+            }
+            reaction = processor.process(&mut block, &mut TestRandom);
+        }
+        assert!(
+            reaction.compressor_gain_reduction > 1.0,
+            "Gain reduction {}",
+            reaction.compressor_gain_reduction
+        );
+    }
+
+    // NOTE: Left input is silent and the right carries a sine. The processed
+    // output has the sine on both channels, so dry and processed signals
+    // differ the most on the left.
+    fn bypass_input(i: usize) -> [(f32, f32); BLOCK_SIZE] {
+        core::array::from_fn(|j| {
+            let time = (i * BLOCK_SIZE + j) as f32 / FS;
+            (
+                0.0,
+                libm::sinf(2.0 * core::f32::consts::PI * 10.0 * time) * 0.5,
+            )
+        })
+    }
+
+    #[test]
+    fn when_bypassed_output_equals_input() {
+        let mut processor = processor!();
+        processor.set_attributes(Attributes {
+            bypass: true,
+            ..bypassed_attributes()
+        });
+
+        // NOTE: Let the crossfade finish.
+        processor.process(&mut bypass_input(0), &mut TestRandom);
+
+        for i in 1..10 {
+            let input = bypass_input(i);
+            let mut block = input;
+            processor.process(&mut block, &mut TestRandom);
+            for ((l, r), (input_l, input_r)) in block.iter().zip(&input) {
+                assert_relative_eq!(*l, *input_l);
+                assert_relative_eq!(*r, *input_r);
+            }
+        }
+    }
+
+    // NOTE: Returns energy of the left and right output of a head panned
+    // hard left.
+    fn panned_output_energy(swap_outputs: bool) -> (f32, f32) {
+        let mut processor = processor!();
+        let mut attributes = Attributes {
+            swap_outputs,
+            ..bypassed_attributes()
+        };
+        attributes.head[0].pan = 0.0;
+        processor.set_attributes(attributes);
+
+        let mut energy = (0.0, 0.0);
+        for i in 0..100 {
+            let mut block = bypass_input(i);
+            processor.process(&mut block, &mut TestRandom);
+            for (l, r) in &block {
+                energy.0 += l * l;
+                energy.1 += r * r;
+            }
+        }
+        energy
+    }
+
+    #[test]
+    fn when_outputs_are_swapped_left_and_right_are_exchanged_after_panning() {
+        let (left, right) = panned_output_energy(false);
+        assert!(left > 1.0, "Left {left}");
+        assert!(right < left * 0.001, "Left {left}, right {right}");
+
+        let (swapped_left, swapped_right) = panned_output_energy(true);
+        assert_relative_eq!(swapped_left, right);
+        assert_relative_eq!(swapped_right, left);
+    }
+
+    #[test]
+    fn when_outputs_are_swapped_bypassed_input_is_exchanged_too() {
+        let mut processor = processor!();
+        processor.set_attributes(Attributes {
+            bypass: true,
+            swap_outputs: true,
+            ..bypassed_attributes()
+        });
+
+        // NOTE: Let the crossfade finish.
+        processor.process(&mut bypass_input(0), &mut TestRandom);
+
+        let input = bypass_input(1);
+        let mut block = input;
+        processor.process(&mut block, &mut TestRandom);
+        for ((l, r), (input_l, input_r)) in block.iter().zip(&input) {
+            assert_relative_eq!(*l, *input_r);
+            assert_relative_eq!(*r, *input_l);
+        }
+    }
+
+    #[test]
+    fn when_fade_in_is_started_first_blocks_are_scaled_up_from_zero() {
+        let mut processor = processor!();
+        processor.set_attributes(Attributes {
+            bypass: true,
+            ..bypassed_attributes()
+        });
+
+        // NOTE: Let the crossfade finish.
+        processor.process(&mut [(0.0, 0.0); BLOCK_SIZE], &mut TestRandom);
+
+        processor.fade_in(4);
+        let mut previous = 0.0;
+        for i in 0..4 {
+            let mut block = [(0.5, -0.5); BLOCK_SIZE];
+            processor.process(&mut block, &mut TestRandom);
+            if i == 0 {
+                assert_relative_eq!(block[0].0, 0.0);
+            }
+            for (l, r) in block {
+                assert!(l >= previous && l < 0.5);
+                assert_relative_eq!(r, -l);
+                previous = l;
+            }
+        }
+
+        let mut block = [(0.5, -0.5); BLOCK_SIZE];
+        processor.process(&mut block, &mut TestRandom);
+        for (l, r) in block {
+            assert_relative_eq!(l, 0.5);
+            assert_relative_eq!(r, -0.5);
+        }
+    }
+
+    #[test]
+    fn when_input_is_processed_its_peak_is_reported_even_when_bypassed() {
+        let mut processor = processor!();
+
+        for bypass in [false, true] {
+            processor.set_attributes(Attributes {
+                bypass,
+                ..bypassed_attributes()
+            });
+            for i in 0..10 {
+                let mut block = bypass_input(i);
+                for (l, _) in &mut block {
+                    *l = -0.2;
+                }
+                let peak = processor.process(&mut block, &mut TestRandom).input_peak;
+                assert_relative_eq!(peak[0], 0.2);
+                assert!(peak[1] > 0.1 && peak[1] <= 0.5, "Peak {}", peak[1]);
+            }
+        }
+    }
+
+    #[test]
+    fn when_bypass_is_toggled_output_does_not_click() {
+        let mut processor = processor!();
+        let mut attributes = bypassed_attributes();
+        processor.set_attributes(attributes);
+
+        let mut output = [0.0; BLOCK_SIZE * 100];
+        for (i, chunk) in output.chunks_mut(BLOCK_SIZE).enumerate() {
+            if i == 50 || i == 75 {
+                attributes.bypass = !attributes.bypass;
+                processor.set_attributes(attributes);
+            }
+            let mut block = bypass_input(i);
+            processor.process(&mut block, &mut TestRandom);
+            for (j, x) in chunk.iter_mut().enumerate() {
+                *x = block[j].0;
+            }
+        }
+
+        let processed_peak = output[BLOCK_SIZE * 25..BLOCK_SIZE * 50]
+            .iter()
+            .fold(0.0, |max: f32, x| max.max(x.abs()));
+        assert!(processed_peak > 0.4, "Processed peak {processed_peak}");
+        let largest_step = output[BLOCK_SIZE * 25..]
+            .windows(2)
+            .fold(0.0, |max: f32, x| max.max((x[1] - x[0]).abs()));
+        assert!(largest_step < 0.1, "Largest step {largest_step}");
+    }
+
+    // NOTE: Returns energy of the output in the first and second half of the
+    // time after input stopped and bypass got engaged.
+    fn output_energy_after_bypass(trails: bool) -> (f32, f32) {
+        let mut processor = processor!();
+        let mut attributes = Attributes {
+            trails,
+            ..bypassed_attributes()
+        };
+        attributes.head[0] = AttributesHead {
+            position: 0.1,
+            volume: 1.0,
+            feedback: 0.7,
+            ..AttributesHead::default()
+        };
+        processor.set_attributes(attributes);
+
+        for i in 0..1600 / BLOCK_SIZE {
+            processor.process(&mut bypass_input(i), &mut TestRandom);
+        }
+
+        attributes.bypass = true;
+        processor.set_attributes(attributes);
+        // NOTE: Let the crossfade finish.
+        processor.process(&mut [(0.0, 0.0); BLOCK_SIZE], &mut TestRandom);
+
+        let mut energy = (0.0, 0.0);
+        for i in 0..3200 / BLOCK_SIZE {
+            let mut block = [(0.0, 0.0); BLOCK_SIZE];
+            processor.process(&mut block, &mut TestRandom);
+            let block_energy: f32 = block.iter().map(|(l, r)| l * l + r * r).sum();
+            if i < 1600 / BLOCK_SIZE {
+                energy.0 += block_energy;
+            } else {
+                energy.1 += block_energy;
+            }
+        }
+        energy
+    }
+
+    #[test]
+    fn when_bypassed_with_trails_echoes_keep_decaying_after_input_stops() {
+        let (early, late) = output_energy_after_bypass(false);
+        assert_relative_eq!(early, 0.0);
+        assert_relative_eq!(late, 0.0);
+
+        let (early, late) = output_energy_after_bypass(true);
+        assert!(early > 0.1, "Early energy {early}");
+        assert!(late > 0.0, "Late energy {late}");
+        assert!(late < early, "Early energy {early}, late energy {late}");
+    }
+
+    #[test]
+    fn when_trails_are_disabled_while_bypassed_echoes_fade_out() {
+        let mut processor = processor!();
+        let mut attributes = Attributes {
+            trails: true,
+            ..bypassed_attributes()
+        };
+        attributes.head[0] = AttributesHead {
+            position: 0.1,
+            volume: 1.0,
+            feedback: 0.7,
+            ..AttributesHead::default()
+        };
+        processor.set_attributes(attributes);
+        for i in 0..50 {
+            processor.process(&mut bypass_input(i), &mut TestRandom);
+        }
+        attributes.bypass = true;
+        processor.set_attributes(attributes);
+        let mut block = [(0.0, 0.0); BLOCK_SIZE];
+        processor.process(&mut block, &mut TestRandom);
+        let energy: f32 = block.iter().map(|(l, r)| l * l + r * r).sum();
+        assert!(energy > 0.1, "Energy {energy}");
+
+        attributes.trails = false;
+        processor.set_attributes(attributes);
+        let mut block = [(0.0, 0.0); BLOCK_SIZE];
+        processor.process(&mut block, &mut TestRandom);
+
+        let fading: f32 = block[..5].iter().map(|(l, r)| l * l + r * r).sum();
+        assert!(fading > 0.0, "Fading energy {fading}");
+        assert_relative_eq!(block[BLOCK_SIZE - 1].0, 0.0);
+        assert_relative_eq!(block[BLOCK_SIZE - 1].1, 0.0);
+    }
+
+    #[test]
+    fn when_trails_fall_silent_while_bypassed_processing_stalls() {
+        let mut processor = processor!();
+        let mut attributes = Attributes {
+            trails: true,
+            ..bypassed_attributes()
+        };
+        attributes.head[0] = AttributesHead {
+            position: 0.1,
+            volume: 1.0,
+            feedback: 0.0,
+            ..AttributesHead::default()
+        };
+        processor.set_attributes(attributes);
+        for i in 0..50 {
+            processor.process(&mut bypass_input(i), &mut TestRandom);
+        }
+
+        attributes.bypass = true;
+        processor.set_attributes(attributes);
+        processor.process(&mut [(0.0, 0.0); BLOCK_SIZE], &mut TestRandom);
+        assert!(!processor.is_stalled());
+
+        // NOTE: The last echo may come up to the whole length later and the
+        // DC blockers take a while to settle, then the silence must last for
+        // another length.
+        let blocks = 4 * processor.delay.length_samples() / BLOCK_SIZE;
+        for _ in 0..blocks {
+            processor.process(&mut [(0.0, 0.0); BLOCK_SIZE], &mut TestRandom);
+        }
+        assert!(processor.is_stalled());
+
+        attributes.bypass = false;
+        processor.set_attributes(attributes);
+        assert!(!processor.is_stalled());
+    }
+
+    #[test]
+    fn when_signal_is_processed_block_by_block_it_stays_continuous_across_boundaries() {
+        let mut processor = processor!();
+        processor.set_attributes(Attributes {
+            tape_bypass: false,
+            ..bypassed_attributes()
+        });
+
+        // NOTE: The whole length is processed regardless of the block size.
+        const LENGTH: usize = 2048;
+        let mut output = [0.0; LENGTH];
+        for (i, chunk) in output.chunks_mut(BLOCK_SIZE).enumerate() {
+            let mut block = [(0.0, 0.0); BLOCK_SIZE];
+            for (j, x) in block.iter_mut().enumerate() {
+                let time = (i * BLOCK_SIZE + j) as f32 / FS;
+                x.1 = libm::sinf(2.0 * core::f32::consts::PI * 5.0 * time) * 0.5;
+            }
+            processor.process(&mut block, &mut TestRandom);
+            for (j, x) in chunk.iter_mut().enumerate() {
+                *x = block[j].0;
+            }
+        }
+
+        // NOTE: Skip the first half to let DC blockers settle.
+        let settled = &output[LENGTH / 2..];
+        let peak = settled.iter().fold(0.0, |max: f32, x| max.max(x.abs()));
+        assert!(peak > 0.1, "Peak {peak}");
+        let largest_step = settled
+            .windows(2)
+            .fold(0.0, |max: f32, x| max.max((x[1] - x[0]).abs()));
+        assert!(largest_step < 0.02, "Largest step {largest_step}");
+    }
+
+    #[test]
+    fn when_attributes_are_set_they_can_be_read_back() {
+        let mut processor = processor!();
+
+        let mut attributes = Attributes {
+            pre_amp: 0.7,
+            speed: 2.5,
+            input_source: InputSource::Sum,
+            clip_threshold: Some(0.5),
+            ..Attributes::default()
+        };
+        attributes.head[2].position = 0.3;
+        processor.set_attributes(attributes);
+
+        let read_back = processor.attributes();
+        assert_relative_eq!(read_back.pre_amp, 0.7);
+        assert_relative_eq!(read_back.speed, 2.5);
+        assert_eq!(read_back.input_source, InputSource::Sum);
+        assert_eq!(read_back.clip_threshold, Some(0.5));
+        assert_relative_eq!(read_back.head[2].position, 0.3);
+    }
+
+    // NOTE: The tape of the delay takes all of the SDRAM on the hardware,
+    // nothing else can be allocated there.
+    #[test]
+    fn when_sdram_fits_only_the_tape_it_initializes() {
+        let tape_size = crate::math::upper_power_of_two((FS * 305.0) as usize);
+        assert_eq!(tape_size, 1 << 19);
+
+        let processor = Processor::new(
+            FS,
+            &mut memory_manager!(4096),
+            &mut memory_manager!(1 << 19),
+        );
+
+        assert!(processor.is_ok());
+    }
+
+    #[test]
+    fn when_sdram_is_too_small_it_fails_to_initialize() {
+        let mut stack_manager = memory_manager!(4096);
+        let mut sdram_manager = memory_manager!(64);
+
+        let processor = Processor::new(FS, &mut stack_manager, &mut sdram_manager);
+
+        assert!(matches!(processor, Err(InsufficientMemory)));
+    }
+
+    #[test]
+    fn when_stack_memory_is_too_small_it_fails_to_initialize() {
+        let mut stack_manager = memory_manager!(512);
+        let mut sdram_manager = memory_manager!(64);
+
+        let processor = Processor::new(FS, &mut stack_manager, &mut sdram_manager);
+
+        assert!(matches!(processor, Err(InsufficientMemory)));
+    }
+
+    #[test]
+    fn when_stack_memory_cannot_fit_oversampling_it_fails_to_initialize() {
+        let mut stack_manager = memory_manager!(4);
+        let mut sdram_manager = memory_manager!(64);
+
+        let processor = Processor::new(FS, &mut stack_manager, &mut sdram_manager);
+
+        assert!(matches!(processor, Err(InsufficientMemory)));
+    }
+}
@@ -2,6 +2,9 @@
 
 use core::fmt;
 
+// Number of samples on each side of the wrap that get faded out.
+const SEAM_LENGTH: usize = 64;
+
 pub struct RingBuffer {
     buffer: &'static mut [f32],
     mask: usize,
@@ -44,6 +47,26 @@ impl RingBuffer {
         self.buffer[index]
     }
 
+    /// Read a sample, fading it out next to the wrap of the buffer.
+    ///
+    /// Reads reaching beyond the oldest sample continue over to the newest
+    /// one. Both sides of this seam are attenuated, so a head reading across
+    /// it passes through silence instead of a step.
+    pub fn peek_across_seam(&self, relative_index: usize) -> f32 {
+        let value = self.peek(relative_index);
+        let index = relative_index & self.mask;
+        let distance_to_seam = if relative_index > self.mask {
+            index.min(self.mask - index)
+        } else {
+            self.mask - index
+        };
+        if distance_to_seam < SEAM_LENGTH {
+            value * distance_to_seam as f32 / SEAM_LENGTH as f32
+        } else {
+            value
+        }
+    }
+
     pub fn peek_mut(&mut self, relative_index: usize) -> &mut f32 {
         let index = self.write_index.wrapping_sub(relative_index) & self.mask;
         &mut self.buffer[index]
@@ -53,11 +76,31 @@ impl RingBuffer {
         self.buffer.len()
     }
 
+    /// Decimate the whole buffer into `M` bins, keeping peak of each.
+    ///
+    /// Bins are ordered from the oldest to the newest sample. The write index
+    /// is read only once, so writes happening in the meantime may tear the
+    /// snapshot, but it would never read out of the buffer.
+    pub fn snapshot<const M: usize>(&self, out: &mut [f32; M]) {
+        let write_index = self.write_index;
+        let bin_size = (self.buffer.len() / M).max(1);
+        for (i, bin) in out.iter_mut().enumerate() {
+            let start = write_index + 1 + i * bin_size;
+            *bin = (start..start + bin_size)
+                .map(|j| self.buffer[j & self.mask].abs())
+                .fold(0.0, f32::max);
+        }
+    }
+
     pub fn reset(&mut self, start: usize, size: usize) {
         for x in self.buffer[start..start + size].iter_mut() {
             *x = 0.0;
         }
     }
+
+    pub fn clear(&mut self) {
+        self.reset(0, self.buffer.len());
+    }
 }
 
 fn is_power_of_2(n: usize) -> bool {
@@ -151,6 +194,26 @@ mod tests {
         assert_relative_eq!(buffer.peek(2), 10.0);
     }
 
+    #[test]
+    fn snapshot_decimates_buffer_into_peaks() {
+        static mut MEMORY: [MaybeUninit<u32>; 8] = unsafe { MaybeUninit::uninit().assume_init() };
+        let mut memory_manager = MemoryManager::from(unsafe { &mut MEMORY[..] });
+
+        let slice = memory_manager.allocate(8).unwrap();
+        let mut buffer = RingBuffer::from(slice);
+
+        for x in [0.1, -0.4, 0.2, 0.3, -0.9, 0.5, 0.0, 0.7, 0.6, -0.2] {
+            buffer.write(x);
+        }
+
+        let mut snapshot = [0.0; 4];
+        buffer.snapshot(&mut snapshot);
+        assert_relative_eq!(snapshot[0], 0.3);
+        assert_relative_eq!(snapshot[1], 0.9);
+        assert_relative_eq!(snapshot[2], 0.7);
+        assert_relative_eq!(snapshot[3], 0.6);
+    }
+
     #[test]
     fn follow_reads_and_writes_throughout_the_buffer() {
         static mut MEMORY: [MaybeUninit<u32>; 4] = unsafe { MaybeUninit::uninit().assume_init() };
@@ -0,0 +1,101 @@
+//! Soft knee letting the tape saturation in only on loud signals.
+//!
+//! Real tape has headroom. Quiet passages go through it clean and only loud
+//! ones saturate. The knee follows the level of the signal entering the
+//! hysteresis and, as it rises above the threshold, blends from the dry
+//! signal to the saturated one.
+
+#[allow(unused_imports)]
+use micromath::F32Ext as _;
+
+use crate::one_pole_filter::OnePoleFilter;
+
+// Slow enough to ride over single cycles of low tones, fast enough to let
+// the saturation in on the attack of a loud note.
+const LEVEL_CUTOFF: f32 = 5.0;
+
+#[derive(Debug)]
+#[cfg_attr(feature = "defmt", derive(defmt::Format))]
+pub struct SaturationKnee {
+    mean_square: OnePoleFilter,
+    threshold: Option<f32>,
+}
+
+impl SaturationKnee {
+    pub fn new(sample_rate: f32) -> Self {
+        Self {
+            mean_square: OnePoleFilter::new(sample_rate, LEVEL_CUTOFF),
+            threshold: None,
+        }
+    }
+
+    /// RMS level above which the saturation starts blending in. With
+    /// `None`, the saturated signal passes untouched.
+    pub fn set_threshold(&mut self, threshold: Option<f32>) {
+        if threshold.is_none() {
+            self.mean_square.reset();
+        }
+        self.threshold = threshold.map(|threshold| threshold.max(f32::EPSILON));
+    }
+
+    pub fn process(&mut self, dry_buffer: &[f32], wet_buffer: &mut [f32]) {
+        let Some(threshold) = self.threshold else {
+            return;
+        };
+        for (dry, wet) in dry_buffer.iter().zip(wet_buffer.iter_mut()) {
+            let level = self.mean_square.tick(dry * dry).sqrt();
+            *wet = dry + (*wet - dry) * saturation_amount(level, threshold);
+        }
+    }
+
+    pub fn reset(&mut self) {
+        self.mean_square.reset();
+    }
+}
+
+// NOTE: Below the threshold the signal stays dry, at its double it is fully
+// saturated. The transition is smoothed, so there is no audible corner.
+fn saturation_amount(level: f32, threshold: f32) -> f32 {
+    let x = ((level - threshold) / threshold).clamp(0.0, 1.0);
+    x * x * (3.0 - 2.0 * x)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn when_level_is_below_threshold_dry_signal_passes() {
+        let mut knee = SaturationKnee::new(1000.0);
+        knee.set_threshold(Some(0.5));
+
+        let dry = [0.1; 1000];
+        let mut wet = [0.3; 1000];
+        knee.process(&dry, &mut wet);
+
+        assert_relative_eq!(wet[999], 0.1);
+    }
+
+    #[test]
+    fn when_level_is_well_above_threshold_saturated_signal_passes() {
+        let mut knee = SaturationKnee::new(1000.0);
+        knee.set_threshold(Some(0.2));
+
+        let dry = [0.5; 1000];
+        let mut wet = [0.3; 1000];
+        knee.process(&dry, &mut wet);
+
+        assert_relative_eq!(wet[999], 0.3);
+    }
+
+    #[test]
+    fn when_threshold_is_not_set_saturated_signal_passes() {
+        let mut knee = SaturationKnee::new(1000.0);
+
+        let dry = [0.1; 10];
+        let mut wet = [0.3; 10];
+        knee.process(&dry, &mut wet);
+
+        assert_relative_eq!(wet[9], 0.3);
+    }
+}
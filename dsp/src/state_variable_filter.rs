@@ -40,6 +40,11 @@ impl StateVariableFilter {
         self
     }
 
+    pub fn reset(&mut self) {
+        self.delay_1 = 0.0;
+        self.delay_2 = 0.0;
+    }
+
     // https://www.earlevel.com/main/2003/03/02/the-digital-state-variable-filter/
     //
     //             +----------------------------------------------------------+
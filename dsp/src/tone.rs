@@ -1,12 +1,71 @@
+use core::f32::consts::PI;
+
 #[allow(unused_imports)]
 use micromath::F32Ext;
 
+use crate::linear_phase_filter::{self, LinearPhaseFilter};
 use crate::linkwitz_riley_filter::LinkwitzRileyFilter;
 
+// Linkwitz-Riley filter attenuates its corner frequency by 6 dB. The -3 dB
+// point of the low-pass is found at this ratio of it, and of the high-pass at
+// its inverse. Both apply to pre-warped frequencies.
+const LOW_PASS_3DB_RATIO: f32 = 0.802_243;
+
+// Time constant of the cutoff following the tone, in seconds. Fast enough to
+// feel immediate, slow enough to avoid zipper noise when the tone sweeps.
+const DEFAULT_SLEW: f32 = 0.01;
+
+// Coefficients of the slewing filter are recalculated once per this many
+// samples, keeping the cost of the tangent low.
+const SLEW_INTERVAL: usize = 4;
+
+// Relative change of the FIR cutoff below which the filter is not designed
+// again. Designing it is expensive and such a small difference is inaudible.
+const FIR_REDESIGN_THRESHOLD: f32 = 0.01;
+
+// Band over which the loudness of the unfiltered signal is considered. Pink
+// noise carries equal power in each of its octaves.
+const AUDIBLE_BAND: (f32, f32) = (20.0, 20_000.0);
+
+// Extreme tones leave only a few octaves, making up for all of them would
+// boost the rest too much.
+const MAX_COMPENSATION: f32 = 4.0;
+
 #[derive(Clone, Copy, Debug)]
 #[cfg_attr(feature = "defmt", derive(defmt::Format))]
 pub struct Attributes {
     pub tone: f32,
+    pub filter_type: FilterType,
+    /// Time constant in seconds of the IIR cutoff following the tone. When
+    /// `None`, `DEFAULT_SLEW` is used. The FIR is always designed at once.
+    pub slew: Option<f32>,
+    /// Make up for the loudness removed by the filter, so sweeping the tone
+    /// keeps the level of broadband signals. See `Tone::compensation`.
+    pub loudness_compensation: bool,
+    /// Exact -3 dB point overriding `tone`. See `Tone2::set_cutoff_hz`.
+    pub cutoff: Option<Cutoff>,
+}
+
+/// Exact corner frequency in Hz, where the signal is attenuated by 3 dB.
+#[derive(Clone, Copy, Debug, PartialEq)]
+#[cfg_attr(feature = "defmt", derive(defmt::Format))]
+pub enum Cutoff {
+    LowPass(f32),
+    HighPass(f32),
+}
+
+/// Linkwitz-Riley IIR filters are cheap, but they shift phase differently
+/// across frequencies. Linear-phase FIR keeps transients intact, but delays
+/// the signal by `linear_phase_filter::LATENCY` samples. Its short kernel
+/// resolves cutoffs only from `linear_phase_filter::MIN_RELATIVE_CUTOFF` of
+/// the sample rate up. Lower low-pass cutoffs are clamped to it, lower
+/// high-pass cutoffs only attenuate the band below it partially.
+#[derive(Clone, Copy, Debug, Default, PartialEq)]
+#[cfg_attr(feature = "defmt", derive(defmt::Format))]
+pub enum FilterType {
+    #[default]
+    Iir,
+    Fir,
 }
 
 #[derive(Debug)]
@@ -15,13 +74,48 @@ pub struct Tone2 {
     sample_rate: f32,
     pub tone_1: Tone,
     pub tone_2: Tone,
+    /// Filters the right channel while `tone_2` filters the left one, when
+    /// the tone is applied on the output instead of the feedback loop.
+    pub tone_3: Tone,
+    last_fir_design: Option<FirDesign>,
+}
+
+#[derive(Clone, Copy, Debug, PartialEq)]
+#[cfg_attr(feature = "defmt", derive(defmt::Format))]
+enum FirDesign {
+    Flat,
+    Low(f32),
+    High(f32),
+}
+
+impl FirDesign {
+    fn is_close_to(self, other: Self) -> bool {
+        match (self, other) {
+            (Self::Flat, Self::Flat) => true,
+            (Self::Low(a), Self::Low(b)) | (Self::High(a), Self::High(b)) => {
+                (a - b).abs() <= b * FIR_REDESIGN_THRESHOLD
+            }
+            _ => false,
+        }
+    }
 }
 
 #[derive(Debug)]
 #[cfg_attr(feature = "defmt", derive(defmt::Format))]
 pub struct Tone {
+    filter_type: FilterType,
     lpf: LinkwitzRileyFilter,
     hpf: LinkwitzRileyFilter,
+    fir: LinearPhaseFilter,
+    sample_rate: f32,
+    // Current cutoffs of the low-pass and high-pass, `None` until the first
+    // target is set.
+    cutoff: Option<(f32, f32)>,
+    target_cutoff: (f32, f32),
+    slew_coefficient: f32,
+    samples_until_slew: usize,
+    loudness_compensation: bool,
+    compensation: f32,
 }
 
 impl Tone2 {
@@ -35,32 +129,121 @@ impl Tone2 {
             sample_rate,
             tone_1: Tone::new(sample_rate),
             tone_2: Tone::new(sample_rate),
+            tone_3: Tone::new(sample_rate),
+            last_fir_design: None,
+        }
+    }
+
+    fn tones_mut(&mut self) -> [&mut Tone; 3] {
+        [&mut self.tone_1, &mut self.tone_2, &mut self.tone_3]
+    }
+
+    pub fn reset(&mut self) {
+        for tone in self.tones_mut() {
+            tone.reset();
         }
     }
 
     pub fn set_attributes(&mut self, attributes: Attributes) {
+        if let Some(cutoff) = attributes.cutoff {
+            for tone in self.tones_mut() {
+                tone.filter_type = attributes.filter_type;
+                tone.loudness_compensation = attributes.loudness_compensation;
+            }
+            self.set_cutoff_hz(cutoff);
+            return;
+        }
+
         let a = 13.73;
-        if attributes.tone < 0.4 {
-            self.tone_1.hpf.set_frequency(0.0);
-            self.tone_2.hpf.set_frequency(0.0);
+        let (lpf_cutoff, hpf_cutoff) = if attributes.tone < 0.4 {
             let phase = attributes.tone / 0.4;
             let voct = phase * 10.645;
-            let cutoff = a * libm::powf(2.0, voct);
-            self.tone_1.lpf.set_frequency(cutoff);
-            self.tone_2.lpf.set_frequency(cutoff);
+            (a * libm::powf(2.0, voct), 0.0)
         } else if attributes.tone < 0.6 {
-            self.tone_1.lpf.set_frequency(self.sample_rate * 0.48);
-            self.tone_2.lpf.set_frequency(self.sample_rate * 0.48);
-            self.tone_1.hpf.set_frequency(0.0);
-            self.tone_2.hpf.set_frequency(0.0);
+            (self.sample_rate * 0.48, 0.0)
         } else {
-            self.tone_1.lpf.set_frequency(self.sample_rate * 0.48);
-            self.tone_2.lpf.set_frequency(self.sample_rate * 0.48);
             let phase = (attributes.tone - 0.6) / 0.4;
             let voct = phase * 10.0;
-            let cutoff = a * libm::powf(2.0, voct);
-            self.tone_1.hpf.set_frequency(cutoff);
-            self.tone_2.hpf.set_frequency(cutoff);
+            (self.sample_rate * 0.48, a * libm::powf(2.0, voct))
+        };
+
+        let slew = attributes.slew.unwrap_or(DEFAULT_SLEW);
+        for tone in self.tones_mut() {
+            tone.filter_type = attributes.filter_type;
+            tone.loudness_compensation = attributes.loudness_compensation;
+            tone.set_slew(slew);
+            tone.set_target_cutoff(lpf_cutoff, hpf_cutoff);
+        }
+        if attributes.filter_type == FilterType::Fir {
+            self.set_fir_tone(attributes.tone);
+        }
+    }
+
+    /// Set the filter by its -3 dB point instead of the normalized tone.
+    ///
+    /// Unlike `set_attributes`, the cutoff is not slewed. This is precise
+    /// for `FilterType::Iir`. The slope of the linear-phase
+    /// FIR depends on its cutoff, so there it is only approximated.
+    pub fn set_cutoff_hz(&mut self, cutoff: Cutoff) {
+        let nyquist_limit = self.sample_rate * 0.48;
+        let (lpf_cutoff, hpf_cutoff) = match cutoff {
+            Cutoff::LowPass(frequency) => (
+                self.corner_for_3db_point(frequency, LOW_PASS_3DB_RATIO),
+                0.0,
+            ),
+            Cutoff::HighPass(frequency) => (
+                nyquist_limit,
+                self.corner_for_3db_point(frequency, 1.0 / LOW_PASS_3DB_RATIO),
+            ),
+        };
+
+        for tone in self.tones_mut() {
+            tone.set_cutoff(lpf_cutoff, hpf_cutoff);
+        }
+
+        self.design_fir(match cutoff {
+            Cutoff::LowPass(frequency) => FirDesign::Low(frequency),
+            Cutoff::HighPass(frequency) => FirDesign::High(frequency),
+        });
+    }
+
+    fn corner_for_3db_point(&self, frequency: f32, ratio: f32) -> f32 {
+        let nyquist_limit = self.sample_rate * 0.48;
+        let warped = libm::tanf(PI * frequency.clamp(0.0, nyquist_limit) / self.sample_rate);
+        let corner = libm::atanf(warped / ratio) * self.sample_rate / PI;
+        corner.min(nyquist_limit)
+    }
+
+    fn set_fir_tone(&mut self, tone: f32) {
+        let a = 13.73;
+        self.design_fir(if tone < 0.4 {
+            let voct = (tone / 0.4) * 10.645;
+            FirDesign::Low(a * libm::powf(2.0, voct))
+        } else if tone < 0.6 {
+            FirDesign::Flat
+        } else {
+            let voct = ((tone - 0.6) / 0.4) * 10.0;
+            FirDesign::High(a * libm::powf(2.0, voct))
+        });
+    }
+
+    // NOTE: Designing the FIR is expensive, so it is done only when the
+    // cutoff moves noticeably.
+    fn design_fir(&mut self, design: FirDesign) {
+        if self
+            .last_fir_design
+            .is_some_and(|last| design.is_close_to(last))
+        {
+            return;
+        }
+        self.last_fir_design = Some(design);
+
+        for fir in self.tones_mut().map(|t| &mut t.fir) {
+            match design {
+                FirDesign::Flat => fir.set_all_pass(),
+                FirDesign::Low(frequency) => fir.set_low_pass(frequency),
+                FirDesign::High(frequency) => fir.set_high_pass(frequency),
+            }
         }
     }
 }
@@ -68,13 +251,129 @@ impl Tone2 {
 impl Tone {
     fn new(sample_rate: f32) -> Self {
         Self {
+            filter_type: FilterType::default(),
             lpf: LinkwitzRileyFilter::new(sample_rate),
             hpf: LinkwitzRileyFilter::new(sample_rate),
+            fir: LinearPhaseFilter::new(sample_rate),
+            sample_rate,
+            cutoff: None,
+            target_cutoff: (0.0, 0.0),
+            slew_coefficient: 1.0,
+            samples_until_slew: 0,
+            loudness_compensation: false,
+            compensation: 1.0,
         }
     }
 
+    fn set_slew(&mut self, slew: f32) {
+        self.slew_coefficient = if slew > 0.0 {
+            1.0 - libm::expf(-(SLEW_INTERVAL as f32) / (self.sample_rate * slew))
+        } else {
+            1.0
+        };
+    }
+
+    // NOTE: The first target is set at once, so the filter does not sweep
+    // open after the start.
+    fn set_target_cutoff(&mut self, lpf_cutoff: f32, hpf_cutoff: f32) {
+        self.target_cutoff = (lpf_cutoff, hpf_cutoff);
+        if self.cutoff.is_none() {
+            self.set_cutoff(lpf_cutoff, hpf_cutoff);
+        } else if self.filter_type == FilterType::Fir {
+            self.update_compensation(self.target_cutoff);
+        }
+    }
+
+    fn set_cutoff(&mut self, lpf_cutoff: f32, hpf_cutoff: f32) {
+        self.target_cutoff = (lpf_cutoff, hpf_cutoff);
+        self.cutoff = Some(self.target_cutoff);
+        self.lpf.set_frequency(lpf_cutoff);
+        self.hpf.set_frequency(hpf_cutoff);
+        self.update_compensation(self.target_cutoff);
+    }
+
+    // NOTE: Power of pink noise is proportional to the number of octaves it
+    // spans. The gain restores the power of the octaves cut off.
+    fn update_compensation(&mut self, (lpf_cutoff, hpf_cutoff): (f32, f32)) {
+        self.compensation = if self.loudness_compensation {
+            let (low, high) = AUDIBLE_BAND;
+            let passed_low = hpf_cutoff.clamp(low, high);
+            let passed_high = lpf_cutoff.clamp(passed_low, high);
+            let passed = libm::log2f(passed_high / passed_low);
+            let total = libm::log2f(high / low);
+            if passed * MAX_COMPENSATION * MAX_COMPENSATION > total {
+                libm::sqrtf(total / passed)
+            } else {
+                MAX_COMPENSATION
+            }
+        } else {
+            1.0
+        };
+    }
+
+    /// Gain making up for the loudness removed by the filter. It is 1.0
+    /// unless the compensation is enabled.
+    ///
+    /// The gain is not applied by `tick`, so the caller can keep it out of
+    /// feedback loops, where it would boost the signal that passed.
+    pub fn compensation(&self) -> f32 {
+        self.compensation
+    }
+
+    // NOTE: Cutoffs are slewed in Hz. Once they get within a fraction of a
+    // Hz from the target, they jump to it and the slewing stops.
+    fn slew_cutoff(&mut self) {
+        let Some((lpf_cutoff, hpf_cutoff)) = self.cutoff else {
+            return;
+        };
+        if (lpf_cutoff, hpf_cutoff) == self.target_cutoff {
+            return;
+        }
+
+        if self.samples_until_slew > 0 {
+            self.samples_until_slew -= 1;
+            return;
+        }
+        self.samples_until_slew = SLEW_INTERVAL - 1;
+
+        let (lpf_target, hpf_target) = self.target_cutoff;
+        let step = |current: f32, target: f32| {
+            let next = current + (target - current) * self.slew_coefficient;
+            if (target - next).abs() < 0.1 {
+                target
+            } else {
+                next
+            }
+        };
+        let cutoff = (step(lpf_cutoff, lpf_target), step(hpf_cutoff, hpf_target));
+        self.cutoff = Some(cutoff);
+        self.lpf.set_frequency(cutoff.0);
+        self.hpf.set_frequency(cutoff.1);
+        self.update_compensation(cutoff);
+    }
+
+    /// Delay in samples introduced by the filter.
+    pub fn latency(&self) -> usize {
+        match self.filter_type {
+            FilterType::Iir => 0,
+            FilterType::Fir => linear_phase_filter::LATENCY,
+        }
+    }
+
+    fn reset(&mut self) {
+        self.lpf.reset();
+        self.hpf.reset();
+        self.fir.reset();
+    }
+
     pub fn tick(&mut self, x: f32) -> f32 {
-        self.lpf.tick(self.hpf.tick(x).high_pass).low_pass
+        match self.filter_type {
+            FilterType::Iir => {
+                self.slew_cutoff();
+                self.lpf.tick(self.hpf.tick(x).high_pass).low_pass
+            }
+            FilterType::Fir => self.fir.tick(x),
+        }
     }
 
     pub fn process(&mut self, buffer: &mut [f32]) {
@@ -83,3 +382,253 @@ impl Tone {
         }
     }
 }
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::BLOCK_SIZE;
+
+    const SAMPLE_RATE: u32 = 48_000;
+
+    fn wrap(phase: f32) -> f32 {
+        libm::atan2f(libm::sinf(phase), libm::cosf(phase))
+    }
+
+    // NOTE: Returns phase lag of the output in range (-PI, PI].
+    fn measure_phase(tone: &mut Tone, frequency: u32) -> f32 {
+        let angle = |n: u32| 2.0 * PI * ((frequency * n) % SAMPLE_RATE) as f32 / SAMPLE_RATE as f32;
+
+        for n in 0..SAMPLE_RATE / 10 {
+            tone.tick(libm::sinf(angle(n)));
+        }
+
+        let mut in_phase = 0.0;
+        let mut quadrature = 0.0;
+        for n in SAMPLE_RATE / 10..SAMPLE_RATE / 10 + SAMPLE_RATE {
+            let y = tone.tick(libm::sinf(angle(n)));
+            in_phase += y * libm::sinf(angle(n));
+            quadrature += y * libm::cosf(angle(n));
+        }
+
+        libm::atan2f(-quadrature, in_phase)
+    }
+
+    fn measure_gain(tone: &mut Tone, frequency: u32) -> f32 {
+        let angle = |n: u32| 2.0 * PI * ((frequency * n) % SAMPLE_RATE) as f32 / SAMPLE_RATE as f32;
+
+        for n in 0..SAMPLE_RATE / 10 {
+            tone.tick(libm::sinf(angle(n)));
+        }
+
+        let mut in_phase = 0.0;
+        let mut quadrature = 0.0;
+        for n in SAMPLE_RATE / 10..SAMPLE_RATE / 10 + SAMPLE_RATE / 5 {
+            let y = tone.tick(libm::sinf(angle(n)));
+            in_phase += y * libm::sinf(angle(n));
+            quadrature += y * libm::cosf(angle(n));
+        }
+
+        libm::sqrtf(in_phase * in_phase + quadrature * quadrature) * 2.0 / (SAMPLE_RATE / 5) as f32
+    }
+
+    // NOTE: Sweeps the frequency in 1 % steps and interpolates the point
+    // where the gain crosses -3 dB.
+    fn find_3db_point(tone: &mut Tone, from: u32, to: u32) -> f32 {
+        let target = core::f32::consts::FRAC_1_SQRT_2;
+        let mut previous: Option<(f32, f32)> = None;
+        for step in 0..=100 {
+            let frequency = from + (to - from) * step / 100;
+            tone.reset();
+            let gain = measure_gain(tone, frequency);
+            if let Some((previous_frequency, previous_gain)) = previous {
+                if (previous_gain - target) * (gain - target) <= 0.0 {
+                    let ratio = (target - previous_gain) / (gain - previous_gain);
+                    return previous_frequency + (frequency as f32 - previous_frequency) * ratio;
+                }
+            }
+            previous = Some((frequency as f32, gain));
+        }
+        panic!("Gain never crossed -3 dB");
+    }
+
+    #[test]
+    fn when_low_pass_cutoff_is_set_in_hz_its_3db_point_lands_there() {
+        let mut tone = Tone2::new(SAMPLE_RATE as f32);
+        tone.set_cutoff_hz(Cutoff::LowPass(1000.0));
+
+        let point = find_3db_point(&mut tone.tone_1, 500, 2000);
+
+        assert_relative_eq!(point, 1000.0, max_relative = 0.01);
+    }
+
+    #[test]
+    fn when_high_pass_cutoff_is_set_in_hz_its_3db_point_lands_there() {
+        let mut tone = Tone2::new(SAMPLE_RATE as f32);
+        tone.set_cutoff_hz(Cutoff::HighPass(200.0));
+
+        let point = find_3db_point(&mut tone.tone_1, 100, 400);
+
+        assert_relative_eq!(point, 200.0, max_relative = 0.01);
+    }
+
+    fn phases(filter_type: FilterType) -> [(u32, f32); 4] {
+        let mut tone = Tone2::new(SAMPLE_RATE as f32);
+        tone.set_attributes(Attributes {
+            tone: 0.25,
+            filter_type,
+            slew: None,
+            loudness_compensation: false,
+            cutoff: None,
+        });
+        [100, 300, 500, 800].map(|frequency| {
+            tone.tone_1.reset();
+            (frequency, measure_phase(&mut tone.tone_1, frequency))
+        })
+    }
+
+    #[test]
+    fn when_fir_tone_moves_slightly_it_is_not_designed_again() {
+        let mut tone = Tone2::new(SAMPLE_RATE as f32);
+        let mut attributes = Attributes {
+            tone: 0.25,
+            filter_type: FilterType::Fir,
+            slew: None,
+            loudness_compensation: false,
+            cutoff: None,
+        };
+        tone.set_attributes(attributes);
+        let designed = tone.last_fir_design;
+
+        attributes.tone = 0.2501;
+        tone.set_attributes(attributes);
+        assert_eq!(tone.last_fir_design, designed);
+
+        attributes.tone = 0.26;
+        tone.set_attributes(attributes);
+        assert_ne!(tone.last_fir_design, designed);
+    }
+
+    #[test]
+    fn when_fir_is_used_group_delay_is_constant_across_pass_band() {
+        for (frequency, phase) in phases(FilterType::Fir) {
+            let omega = 2.0 * PI * frequency as f32 / SAMPLE_RATE as f32;
+            let expected = omega * linear_phase_filter::LATENCY as f32;
+            assert!(
+                wrap(phase - expected).abs() < 0.01,
+                "Phase {phase} at {frequency} Hz, expected {expected}"
+            );
+        }
+    }
+
+    #[test]
+    fn when_iir_is_used_group_delay_varies_across_pass_band() {
+        let delays = phases(FilterType::Iir).map(|(frequency, phase)| {
+            let omega = 2.0 * PI * frequency as f32 / SAMPLE_RATE as f32;
+            phase / omega
+        });
+
+        let min = delays.iter().copied().fold(f32::MAX, f32::min);
+        let max = delays.iter().copied().fold(f32::MIN, f32::max);
+        assert!(max - min > 1.0, "Delays in samples {delays:?}");
+    }
+
+    // NOTE: White noise from a linear congruential generator, shaped by
+    // Paul Kellet's economy pink filter.
+    fn pink_noise<const N: usize>() -> [f32; N] {
+        let mut seed: u32 = 1;
+        let mut b = [0.0; 3];
+        core::array::from_fn(|_| {
+            seed = seed.wrapping_mul(1_664_525).wrapping_add(1_013_904_223);
+            let white = (seed >> 8) as f32 / (1 << 24) as f32 * 2.0 - 1.0;
+            b[0] = 0.997_65 * b[0] + white * 0.099_046;
+            b[1] = 0.963 * b[1] + white * 0.296_516_4;
+            b[2] = 0.57 * b[2] + white * 1.052_691_3;
+            (b[0] + b[1] + b[2] + white * 0.184_8) * 0.2
+        })
+    }
+
+    fn rms_of_filtered(input: &[f32], tone_value: f32, loudness_compensation: bool) -> f32 {
+        let mut tone = Tone2::new(SAMPLE_RATE as f32);
+        tone.set_attributes(Attributes {
+            tone: tone_value,
+            filter_type: FilterType::Iir,
+            slew: None,
+            loudness_compensation,
+            cutoff: None,
+        });
+        let compensation = tone.tone_1.compensation();
+        let mut power = 0.0;
+        for (i, x) in input.iter().enumerate() {
+            let y = tone.tone_1.tick(*x) * compensation;
+            // NOTE: Skip the start to let the filter settle.
+            if i >= input.len() / 4 {
+                power += y * y;
+            }
+        }
+        libm::sqrtf(power / (input.len() - input.len() / 4) as f32)
+    }
+
+    #[test]
+    fn when_loudness_is_compensated_level_of_pink_noise_holds_across_tones() {
+        let input = pink_noise::<{ SAMPLE_RATE as usize }>();
+        let flat = rms_of_filtered(&input, 0.5, true);
+
+        let mut uncompensated_deviation: f32 = 0.0;
+        for tone in [0.2, 0.3, 0.35, 0.65, 0.7, 0.8] {
+            let compensated = rms_of_filtered(&input, tone, true);
+            assert_relative_eq!(compensated, flat, max_relative = 0.2);
+
+            let uncompensated = rms_of_filtered(&input, tone, false);
+            uncompensated_deviation =
+                uncompensated_deviation.max((1.0 - uncompensated / flat).abs());
+        }
+        assert!(
+            uncompensated_deviation > 0.3,
+            "Deviation {uncompensated_deviation}"
+        );
+    }
+
+    #[test]
+    fn when_tone_sweeps_quickly_cutoff_glides_within_blocks() {
+        let mut tone = Tone2::new(SAMPLE_RATE as f32);
+        tone.set_attributes(Attributes {
+            tone: 0.1,
+            filter_type: FilterType::Iir,
+            slew: None,
+            loudness_compensation: false,
+            cutoff: None,
+        });
+        let (start, _) = tone.tone_1.cutoff.unwrap();
+
+        // NOTE: The tone jumps to its end within a few blocks.
+        let mut cutoffs = [0.0; 4096];
+        for (i, block) in cutoffs.chunks_mut(BLOCK_SIZE).enumerate() {
+            tone.set_attributes(Attributes {
+                tone: (0.1 + i as f32 * 0.1).min(0.4),
+                filter_type: FilterType::Iir,
+                slew: None,
+                loudness_compensation: false,
+                cutoff: None,
+            });
+            for cutoff in block.iter_mut() {
+                tone.tone_1.tick(0.0);
+                *cutoff = tone.tone_1.cutoff.unwrap().0;
+            }
+        }
+        let (end, _) = tone.tone_1.target_cutoff;
+
+        let largest_step = cutoffs
+            .windows(2)
+            .fold(0.0, |max: f32, x| max.max((x[1] - x[0]).abs()));
+        assert!(
+            largest_step < (end - start) * 0.05,
+            "Largest step {largest_step}, from {start} to {end}"
+        );
+        let changes_within_first_block = cutoffs[BLOCK_SIZE..2 * BLOCK_SIZE]
+            .windows(2)
+            .filter(|x| x[1] > x[0])
+            .count();
+        assert!(changes_within_first_block >= BLOCK_SIZE / SLEW_INTERVAL - 1);
+        assert_relative_eq!(cutoffs[cutoffs.len() - 1], end, max_relative = 0.01);
+    }
+}
@@ -15,8 +15,8 @@ const CONTROL_SAMPLE_RATE: f32 = 1000.0;
 #[cfg_attr(feature = "defmt", derive(defmt::Format))]
 pub struct Attributes {
     pub depth: f32,
-    // Assuming 48 kHz sample rate and buffers of 32 samples,
-    // the dice should be thrown
+    // Assuming 48 kHz sample rate and blocks of `BLOCK_SIZE` samples,
+    // 32 by default, the dice should be thrown
     //   48000 / 32 = 1500 times a second.
     // With chance of X, successful throw is one in
     //   1 / X.
@@ -1,17 +1,20 @@
 //! Wow and flutter simulate variable velocity of tape.
 //!
 //! Changes in speed are caused by mechanical imperfections. Wow represents slow
-//! changes (below 4 Hz), whole flutter fast (above 4 Hz).
+//! changes (below 4 Hz), whole flutter fast (above 4 Hz). Scrape flutter adds
+//! even faster shimmer, in tens of Hz.
 
 #[allow(unused_imports)]
 use micromath::F32Ext as _;
 
 mod flutter;
 mod ornstein_uhlenbeck;
+mod scrape;
 mod wavefolder;
 mod wow;
 
 use self::flutter::{Attributes as FlutterAttributes, Flutter};
+use self::scrape::{Attributes as ScrapeAttributes, Scrape};
 use self::wow::{Attributes as WowAttributes, Wow};
 use crate::math;
 use crate::random::Random;
@@ -21,6 +24,13 @@ use sirena::memory_manager::MemoryManager;
 
 const MAX_DEPTH_IN_SECONDS: usize = 1;
 
+// Time it takes to move between correlated and decorrelated modulation.
+const DECORRELATION_FADE_IN_SECONDS: f32 = 0.5;
+
+// The right channel starts a quarter of the cycle away from the left, where
+// the two sines are not correlated at all.
+const RIGHT_PHASE: f32 = 0.75;
+
 #[derive(Debug)]
 #[cfg_attr(feature = "defmt", derive(defmt::Format))]
 pub struct WowFlutter {
@@ -28,6 +38,24 @@ pub struct WowFlutter {
     buffer: RingBuffer,
     wow: Wow,
     flutter: Flutter,
+    scrape: Scrape,
+    wow_right: Wow,
+    flutter_right: Flutter,
+    scrape_right: Scrape,
+    decorrelated: bool,
+    decorrelation: f32,
+    frozen: bool,
+    // Last delays of the left and right channel, held while frozen.
+    held: (ComponentDelays, ComponentDelays),
+}
+
+/// Delay of a single sample, split into the slow wow and the fast flutter.
+/// Scrape flutter is counted in the flutter.
+#[derive(Default, Clone, Copy, Debug)]
+#[cfg_attr(feature = "defmt", derive(defmt::Format))]
+pub struct ComponentDelays {
+    pub wow: f32,
+    pub flutter: f32,
 }
 
 #[derive(Default, Clone, Copy, Debug)]
@@ -36,72 +64,205 @@ pub struct Attributes {
     pub wow_depth: f32,
     pub flutter_depth: f32,
     pub flutter_chance: f32,
+    pub scrape_depth: f32,
+    /// Modulate the right channel independently from the left one.
+    pub decorrelated: bool,
+    /// Hold the current delay. The modulation pauses, so once unfrozen it
+    /// continues from where it stopped, without a pitch jump.
+    pub frozen: bool,
 }
 
 impl WowFlutter {
-    pub fn new(sample_rate: u32, memory_manager: &mut MemoryManager) -> Self {
-        Self {
+    /// Returns `None` if there is not enough space in the memory manager to
+    /// allocate a buffer of `MAX_DEPTH_IN_SECONDS`.
+    pub fn new(sample_rate: u32, memory_manager: &mut MemoryManager) -> Option<Self> {
+        Some(Self {
             sample_rate,
-            buffer: Self::allocate_buffer(Self::buffer_size(sample_rate), memory_manager),
+            buffer: Self::allocate_buffer(Self::buffer_size(sample_rate), memory_manager)?,
             wow: Wow::new(sample_rate),
             flutter: Flutter::new(sample_rate),
-        }
+            scrape: Scrape::new(sample_rate),
+            wow_right: {
+                let mut wow = Wow::new(sample_rate);
+                wow.set_phase(RIGHT_PHASE);
+                wow
+            },
+            flutter_right: Flutter::new(sample_rate),
+            scrape_right: {
+                let mut scrape = Scrape::new(sample_rate);
+                scrape.set_phase(RIGHT_PHASE);
+                scrape
+            },
+            decorrelated: false,
+            decorrelation: 0.0,
+            frozen: false,
+            held: (ComponentDelays::default(), ComponentDelays::default()),
+        })
     }
 
     fn buffer_size(sample_rate: u32) -> usize {
         sample_rate as usize * MAX_DEPTH_IN_SECONDS
     }
 
-    fn allocate_buffer(size: usize, memory_manager: &mut MemoryManager) -> RingBuffer {
-        let slice = memory_manager
-            .allocate(math::upper_power_of_two(size))
-            .unwrap();
-        RingBuffer::from(slice)
+    fn allocate_buffer(size: usize, memory_manager: &mut MemoryManager) -> Option<RingBuffer> {
+        let slice = memory_manager.allocate(math::upper_power_of_two(size))?;
+        Some(RingBuffer::from(slice))
     }
 
     pub fn populate_delays(&mut self, buffer: &mut [f32], random: &mut impl Random) {
+        if self.frozen {
+            buffer.fill(self.held.0.sum());
+            return;
+        }
+
         self.roll_dice(random);
         for x in buffer.iter_mut() {
-            *x = self.pop_delay(random);
+            let delays = self.pop_delays(random);
+            *x = delays.sum();
+            self.held = (delays, delays);
+        }
+    }
+
+    /// Populate delays of both channels.
+    ///
+    /// Unless the modulation is decorrelated, both channels receive the same
+    /// delays as `populate_delays` would give.
+    pub fn populate_stereo_delays(
+        &mut self,
+        buffer_left: &mut [f32],
+        buffer_right: &mut [f32],
+        random: &mut impl Random,
+    ) {
+        if self.frozen {
+            buffer_left.fill(self.held.0.sum());
+            buffer_right.fill(self.held.1.sum());
+            return;
+        }
+
+        self.roll_stereo_dice(random);
+        for (l, r) in buffer_left.iter_mut().zip(buffer_right.iter_mut()) {
+            let (delays_left, delays_right) = self.pop_stereo_delays(random);
+            *l = delays_left.sum();
+            *r = delays_right.sum();
+        }
+    }
+
+    /// Populate delays of both channels, keeping wow and flutter apart, so
+    /// they can be applied on different places.
+    pub fn populate_stereo_component_delays(
+        &mut self,
+        buffer_left: &mut [ComponentDelays],
+        buffer_right: &mut [ComponentDelays],
+        random: &mut impl Random,
+    ) {
+        if self.frozen {
+            buffer_left.fill(self.held.0);
+            buffer_right.fill(self.held.1);
+            return;
+        }
+
+        self.roll_stereo_dice(random);
+        for (l, r) in buffer_left.iter_mut().zip(buffer_right.iter_mut()) {
+            (*l, *r) = self.pop_stereo_delays(random);
         }
     }
 
+    /// Whether the right channel currently departs from the left one, even
+    /// if it is only fading out.
+    #[must_use]
+    pub fn is_decorrelated(&self) -> bool {
+        self.decorrelation > 0.0 || self.decorrelated
+    }
+
     fn roll_dice(&mut self, random: &mut impl Random) {
         self.flutter.roll_dice(random);
     }
 
-    fn pop_delay(&mut self, random: &mut impl Random) -> f32 {
+    fn roll_stereo_dice(&mut self, random: &mut impl Random) {
+        self.roll_dice(random);
+        if self.is_decorrelated() {
+            self.flutter_right.roll_dice(random);
+        }
+    }
+
+    // NOTE: Unless the modulation is decorrelated, the right channel gets
+    // the same delays as the left.
+    fn pop_stereo_delays(
+        &mut self,
+        random: &mut impl Random,
+    ) -> (ComponentDelays, ComponentDelays) {
+        let left = self.pop_delays(random);
+        let right = if self.is_decorrelated() {
+            let own = self.pop_right_delays(random);
+            ComponentDelays {
+                wow: left.wow + (own.wow - left.wow) * self.decorrelation,
+                flutter: left.flutter + (own.flutter - left.flutter) * self.decorrelation,
+            }
+        } else {
+            left
+        };
+
+        let step = 1.0 / (self.sample_rate as f32 * DECORRELATION_FADE_IN_SECONDS);
+        self.decorrelation = if self.decorrelated {
+            (self.decorrelation + step).min(1.0)
+        } else {
+            (self.decorrelation - step).max(0.0)
+        };
+
+        self.held = (left, right);
+        (left, right)
+    }
+
+    fn pop_delays(&mut self, random: &mut impl Random) -> ComponentDelays {
         let wow_delay = self.wow.pop(random) * self.sample_rate as f32;
         let flutter_delay = self.flutter.pop() * self.sample_rate as f32;
-        wow_delay + flutter_delay
+        let scrape_delay = self.scrape.pop(random) * self.sample_rate as f32;
+        ComponentDelays {
+            wow: wow_delay,
+            flutter: flutter_delay + scrape_delay,
+        }
     }
 
-    /// Feed the buffer with incoming signal.
-    ///
-    /// This should be called when process is not. For instance if `pop_delay`
-    /// is called directly. Feeding the buffer will prevent pops after the
-    /// signal path is rerouted directly to process again.
-    pub fn dry_process(&mut self, buffer: &mut [f32]) {
-        for x in buffer.iter_mut() {
-            self.buffer.write(*x);
+    fn pop_right_delays(&mut self, random: &mut impl Random) -> ComponentDelays {
+        let wow_delay = self.wow_right.pop(random) * self.sample_rate as f32;
+        let flutter_delay = self.flutter_right.pop() * self.sample_rate as f32;
+        let scrape_delay = self.scrape_right.pop(random) * self.sample_rate as f32;
+        ComponentDelays {
+            wow: wow_delay,
+            flutter: flutter_delay + scrape_delay,
         }
     }
 
+    /// Delay the signal by given amounts of samples.
+    ///
+    /// The sample is written before it is read, so the signal passes through
+    /// unchanged with zero delays. That allows to keep calling this even when
+    /// the modulation is disabled, so it can be faded in without a pop.
     pub fn process(&mut self, buffer: &mut [f32], delays: &[f32]) {
         for (d, x) in delays.iter().zip(buffer.iter_mut()) {
-            let a = self.buffer.peek(*d as usize);
-            let b = self.buffer.peek(*d as usize + 1);
-            let delayed = a + (b - a) * d.fract();
-
             self.buffer.write(*x);
 
-            *x = delayed;
+            let a = self.buffer.peek(*d as usize);
+            let b = self.buffer.peek(*d as usize + 1);
+            *x = a + (b - a) * d.fract();
         }
     }
 
     pub fn set_attributes(&mut self, attributes: Attributes) {
         self.wow.set_attributes(&attributes.into());
         self.flutter.set_attributes(&attributes.into());
+        self.scrape.set_attributes(&attributes.into());
+        self.wow_right.set_attributes(&attributes.into());
+        self.flutter_right.set_attributes(&attributes.into());
+        self.scrape_right.set_attributes(&attributes.into());
+        self.decorrelated = attributes.decorrelated;
+        self.frozen = attributes.frozen;
+    }
+
+    /// Forget the signal stored for modulation. The modulation itself keeps
+    /// running.
+    pub fn reset(&mut self) {
+        self.buffer.clear();
     }
 
     pub fn buffer_reset(&mut self, start: usize, size: usize) {
@@ -114,6 +275,13 @@ impl WowFlutter {
     }
 }
 
+impl ComponentDelays {
+    #[must_use]
+    pub fn sum(self) -> f32 {
+        self.wow + self.flutter
+    }
+}
+
 impl From<Attributes> for WowAttributes {
     fn from(other: Attributes) -> Self {
         Self {
@@ -130,3 +298,190 @@ impl From<Attributes> for FlutterAttributes {
         }
     }
 }
+
+impl From<Attributes> for ScrapeAttributes {
+    fn from(other: Attributes) -> Self {
+        Self {
+            depth: other.scrape_depth,
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::BLOCK_SIZE;
+    use core::mem::MaybeUninit;
+
+    const SAMPLE_RATE: u32 = 1000;
+
+    struct TestRandom;
+
+    impl Random for TestRandom {
+        fn normal(&mut self) -> f32 {
+            use rand::prelude::*;
+            let mut rng = rand::thread_rng();
+            rng.gen()
+        }
+    }
+
+    // NOTE: Xorshift, so the drift of wow is reproducible.
+    struct SeededRandom(u32);
+
+    impl Random for SeededRandom {
+        fn normal(&mut self) -> f32 {
+            self.0 ^= self.0 << 13;
+            self.0 ^= self.0 >> 17;
+            self.0 ^= self.0 << 5;
+            (self.0 >> 8) as f32 / (1 << 24) as f32
+        }
+    }
+
+    // NOTE: Returns energy of the delay modulation in the given band.
+    fn modulation_energy(
+        memory_manager: &mut MemoryManager,
+        scrape_depth: f32,
+        band: (usize, usize),
+    ) -> f32 {
+        let mut wow_flutter = WowFlutter::new(SAMPLE_RATE, memory_manager).unwrap();
+        // NOTE: Depth is filtered, let it reach the destination.
+        for _ in 0..10_000 {
+            wow_flutter.set_attributes(Attributes {
+                scrape_depth,
+                ..Attributes::default()
+            });
+        }
+
+        let mut delays = [0.0; SAMPLE_RATE as usize];
+        for chunk in delays.chunks_mut(BLOCK_SIZE) {
+            wow_flutter.populate_delays(chunk, &mut TestRandom);
+        }
+        let mean = delays.iter().sum::<f32>() / delays.len() as f32;
+
+        // NOTE: With a second long window, each bin is 1 Hz wide.
+        let mut energy = 0.0;
+        for frequency in band.0..band.1 {
+            let mut in_phase = 0.0;
+            let mut quadrature = 0.0;
+            for (i, x) in delays.iter().enumerate() {
+                let phase =
+                    2.0 * core::f32::consts::PI * frequency as f32 * i as f32 / SAMPLE_RATE as f32;
+                in_phase += (x - mean) * libm::sinf(phase);
+                quadrature += (x - mean) * libm::cosf(phase);
+            }
+            energy += in_phase * in_phase + quadrature * quadrature;
+        }
+        energy
+    }
+
+    fn stereo_correlation(memory_manager: &mut MemoryManager, decorrelated: bool) -> f32 {
+        let mut wow_flutter = WowFlutter::new(SAMPLE_RATE, memory_manager).unwrap();
+        // NOTE: Depth is filtered, let it reach the destination.
+        for _ in 0..10_000 {
+            wow_flutter.set_attributes(Attributes {
+                wow_depth: 0.01,
+                decorrelated,
+                ..Attributes::default()
+            });
+        }
+
+        // NOTE: Wow takes over 10 seconds to cycle.
+        let mut left = [0.0; 20 * SAMPLE_RATE as usize];
+        let mut right = [0.0; 20 * SAMPLE_RATE as usize];
+        let mut random = SeededRandom(0x1234_5678);
+        for (l, r) in left
+            .chunks_mut(BLOCK_SIZE)
+            .zip(right.chunks_mut(BLOCK_SIZE))
+        {
+            wow_flutter.populate_stereo_delays(l, r, &mut random);
+        }
+
+        let mean_left = left.iter().sum::<f32>() / left.len() as f32;
+        let mean_right = right.iter().sum::<f32>() / right.len() as f32;
+        let (mut covariance, mut variance_left, mut variance_right) = (0.0, 0.0, 0.0);
+        for (l, r) in left.iter().zip(right.iter()) {
+            covariance += (l - mean_left) * (r - mean_right);
+            variance_left += (l - mean_left) * (l - mean_left);
+            variance_right += (r - mean_right) * (r - mean_right);
+        }
+        covariance / libm::sqrtf(variance_left * variance_right)
+    }
+
+    #[test]
+    fn when_modulation_is_decorrelated_channels_drift_apart() {
+        static mut MEMORY: [MaybeUninit<u32>; 4096] =
+            unsafe { MaybeUninit::uninit().assume_init() };
+        let mut memory_manager = MemoryManager::from(unsafe { &mut MEMORY[..] });
+
+        let correlated = stereo_correlation(&mut memory_manager, false);
+        let decorrelated = stereo_correlation(&mut memory_manager, true);
+
+        assert_relative_eq!(correlated, 1.0, epsilon = 0.001);
+        assert!(decorrelated.abs() < 0.5, "Correlation {decorrelated}");
+    }
+
+    #[test]
+    fn when_scrape_is_engaged_modulation_appears_in_high_rate_band() {
+        static mut MEMORY: [MaybeUninit<u32>; 4096] =
+            unsafe { MaybeUninit::uninit().assume_init() };
+        let mut memory_manager = MemoryManager::from(unsafe { &mut MEMORY[..] });
+
+        const HIGH_RATE_BAND: (usize, usize) = (20, 100);
+        const LOW_RATE_BAND: (usize, usize) = (1, 10);
+        let disengaged = modulation_energy(&mut memory_manager, 0.0, HIGH_RATE_BAND);
+        let engaged = modulation_energy(&mut memory_manager, 0.001, HIGH_RATE_BAND);
+        let engaged_low = modulation_energy(&mut memory_manager, 0.001, LOW_RATE_BAND);
+
+        assert_relative_eq!(disengaged, 0.0);
+        assert!(engaged > 1.0, "Engaged {engaged}");
+        assert!(
+            engaged > engaged_low * 10.0,
+            "Engaged {engaged}, low {engaged_low}"
+        );
+    }
+
+    #[test]
+    fn when_modulation_is_frozen_and_released_delay_stays_continuous() {
+        static mut MEMORY: [MaybeUninit<u32>; 4096] =
+            unsafe { MaybeUninit::uninit().assume_init() };
+        let mut memory_manager = MemoryManager::from(unsafe { &mut MEMORY[..] });
+        let mut wow_flutter = WowFlutter::new(SAMPLE_RATE, &mut memory_manager).unwrap();
+        let mut random = SeededRandom(0x1234_5678);
+
+        let attributes = |frozen| Attributes {
+            wow_depth: 0.01,
+            flutter_depth: 0.001,
+            flutter_chance: 0.5,
+            frozen,
+            ..Attributes::default()
+        };
+        for _ in 0..10_000 {
+            wow_flutter.set_attributes(attributes(false));
+        }
+
+        let mut delays = [0.0; 3 * SAMPLE_RATE as usize];
+        for (i, chunk) in delays.chunks_mut(BLOCK_SIZE).enumerate() {
+            let frozen = (SAMPLE_RATE as usize / BLOCK_SIZE..2 * SAMPLE_RATE as usize / BLOCK_SIZE)
+                .contains(&i);
+            wow_flutter.set_attributes(attributes(frozen));
+            wow_flutter.populate_delays(chunk, &mut random);
+        }
+
+        let largest_step = |delays: &[f32]| {
+            delays
+                .windows(2)
+                .fold(0.0, |max: f32, x| max.max((x[1] - x[0]).abs()))
+        };
+        let frozen = &delays[SAMPLE_RATE as usize..2 * SAMPLE_RATE as usize - BLOCK_SIZE];
+        assert_relative_eq!(largest_step(frozen), 0.0);
+        assert!(frozen[0].abs() > 0.0);
+
+        let running = largest_step(&delays[..SAMPLE_RATE as usize]);
+        let transitions =
+            largest_step(&delays[SAMPLE_RATE as usize - BLOCK_SIZE..2 * SAMPLE_RATE as usize]);
+        assert!(
+            transitions <= running,
+            "Transitions {transitions}, running {running}"
+        );
+    }
+}
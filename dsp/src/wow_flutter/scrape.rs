@@ -0,0 +1,110 @@
+#[allow(unused_imports)]
+use micromath::F32Ext as _;
+
+use super::ornstein_uhlenbeck::OrnsteinUhlenbeck;
+use super::wavefolder;
+use crate::one_pole_filter::OnePoleFilter;
+use crate::random::Random;
+use crate::trigonometry;
+
+// Smoothening of the depth attribute to make sure that scrape does not
+// appear too abruptly, causing pops.
+const DEPTH_CUTOFF: f32 = 0.5;
+const CONTROL_SAMPLE_RATE: f32 = 1000.0;
+
+// Scrape flutter comes from the tape rubbing against heads and guides. It is
+// much faster than the regular flutter, introducing a shimmer rather than an
+// audible change of pitch.
+const BASE_FREQUENCY: f32 = 40.0;
+const ORNSTEIN_UHLENBECK_NOISE: f32 = 5.0;
+const ORNSTEIN_UHLENBECK_SPRING: f32 = 20.0;
+const PHASE_DRIFT: f32 = 0.3;
+
+#[cfg_attr(feature = "defmt", derive(defmt::Format))]
+pub struct Attributes {
+    pub depth: f32,
+}
+
+#[derive(Debug)]
+#[cfg_attr(feature = "defmt", derive(defmt::Format))]
+pub struct Scrape {
+    sample_rate: f32,
+    depth: f32,
+    depth_filter: OnePoleFilter,
+    phase: f32,
+    ornstein_uhlenbeck: OrnsteinUhlenbeck,
+}
+
+impl Scrape {
+    pub fn new(sample_rate: u32) -> Self {
+        let ornstein_uhlenbeck = {
+            let mut ornstein_uhlenbeck = OrnsteinUhlenbeck::new(sample_rate as f32);
+            ornstein_uhlenbeck.noise = ORNSTEIN_UHLENBECK_NOISE;
+            ornstein_uhlenbeck.spring = ORNSTEIN_UHLENBECK_SPRING;
+            ornstein_uhlenbeck
+        };
+        Self {
+            sample_rate: sample_rate as f32,
+            depth: 0.0,
+            depth_filter: OnePoleFilter::new(CONTROL_SAMPLE_RATE, DEPTH_CUTOFF),
+            phase: 0.5, // Start the offset sine wave on 0.0
+            ornstein_uhlenbeck,
+        }
+    }
+
+    pub fn pop(&mut self, random: &mut impl Random) -> f32 {
+        let x = (trigonometry::cos(self.phase) + 1.0) * self.depth / 2.0;
+
+        let drift = self.ornstein_uhlenbeck.pop(random) * PHASE_DRIFT;
+        self.phase += (BASE_FREQUENCY / self.sample_rate) * (1.0 + drift);
+        while self.phase > 1.0 {
+            self.phase -= 1.0;
+        }
+        while self.phase < 0.0 {
+            self.phase += 1.0;
+        }
+
+        wavefolder::fold(x, 0.0, 1000.0)
+    }
+
+    /// Move to the given point of the cycle, from 0.0 to 1.0.
+    pub fn set_phase(&mut self, phase: f32) {
+        self.phase = phase;
+    }
+
+    pub fn set_attributes(&mut self, attributes: &Attributes) {
+        self.depth = self.depth_filter.tick(attributes.depth);
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    const SAMPLE_RATE: u32 = 1000;
+
+    struct TestRandom;
+
+    impl Random for TestRandom {
+        fn normal(&mut self) -> f32 {
+            use rand::prelude::*;
+            let mut rng = rand::thread_rng();
+            rng.gen()
+        }
+    }
+
+    #[test]
+    fn it_stays_within_given_depth() {
+        let mut scrape = Scrape::new(SAMPLE_RATE);
+
+        // Depth is filtered, let it reach the destination.
+        for _ in 0..10000 {
+            scrape.set_attributes(&Attributes { depth: 1.0 });
+        }
+
+        for _ in 0..SAMPLE_RATE {
+            let x = scrape.pop(&mut TestRandom);
+            assert!((0.0..=1.0).contains(&x), "{x}");
+        }
+    }
+}
@@ -80,6 +80,11 @@ impl Wow {
         wavefolder::fold(self.modulation_filter.tick(target).low_pass, 0.0, 1000.0)
     }
 
+    /// Move to the given point of the cycle, from 0.0 to 1.0.
+    pub fn set_phase(&mut self, phase: f32) {
+        self.phase = phase;
+    }
+
     pub fn set_attributes(&mut self, attributes: &Attributes) {
         self.depth = self.depth_filter.tick(attributes.depth);
     }
@@ -18,9 +18,11 @@ mod app {
 
     use kaseta_control::{DesiredOutput, InputSnapshot, Save, Store};
     use kaseta_dsp::processor::{
-        Attributes as ProcessorAttributes, Processor, Reaction as ProcessorReaction,
+        Attributes as ProcessorAttributes, InsufficientMemory, Processor,
+        Reaction as ProcessorReaction,
     };
-    use kaseta_firmware::system::audio::{Audio, SAMPLE_RATE};
+    use kaseta_dsp::BLOCK_SIZE;
+    use kaseta_firmware::system::audio::{Audio, BLOCK_LENGTH, SAMPLE_RATE};
     use kaseta_firmware::system::inputs::Inputs;
     use kaseta_firmware::system::outputs::Outputs;
     use kaseta_firmware::system::randomizer::Randomizer;
@@ -30,6 +32,17 @@ mod app {
     // Single blinks on the PCB's LED signalize the first revision.
     const BLINKS: u8 = 3;
 
+    const _: () = assert!(
+        BLOCK_LENGTH == BLOCK_SIZE,
+        "Audio interface must deliver blocks of the size the DSP processes"
+    );
+
+    // Silent blocks processed before the audio starts, half a second.
+    const WARM_UP_BLOCKS: usize = SAMPLE_RATE as usize / 2 / BLOCK_SIZE;
+
+    // Blocks over which the output fades in once the audio starts, 50 ms.
+    const FADE_IN_BLOCKS: usize = SAMPLE_RATE as usize / 20 / BLOCK_SIZE;
+
     // Slice for shorter buffers that will be stored in the main memory.
     #[link_section = ".sram"]
     static mut MEMORY: [MaybeUninit<u32>; 96 * 1024] =
@@ -93,7 +106,7 @@ mod app {
         let mut status_led = system.status_led;
         let sdram = system.sdram;
         let mut audio = system.audio;
-        let randomizer = system.randomizer;
+        let mut randomizer = system.randomizer;
         let mut inputs = system.inputs;
         let flash = system.flash;
         let mut outputs = system.outputs;
@@ -101,9 +114,15 @@ mod app {
         // Signalize that the firmware is uploaded.
         status_led.on();
 
-        let processor = initialize_dsp_processor(sdram);
+        let Ok(mut processor) = initialize_dsp_processor(sdram) else {
+            signal_insufficient_memory(&mut status_led);
+        };
         let mut storage = Storage::new(flash);
-        let (control, save) = initialize_control_store(&mut inputs, &mut storage, system.frequency);
+        let (mut control, save) =
+            initialize_control_store(&mut inputs, &mut storage, system.frequency);
+        processor.set_attributes(control.dsp_attributes());
+        processor.warm_up(WARM_UP_BLOCKS, &mut randomizer);
+        processor.fade_in(FADE_IN_BLOCKS);
 
         defmt::info!("Initialization was completed, starting tasks");
 
@@ -150,7 +169,7 @@ mod app {
         let mut set_leds = |leds| {
             outputs.set(&DesiredOutput {
                 display: leds,
-                impulse_led: true,
+                impulse_led: 1.0,
                 impulse_trigger: false,
             });
         };
@@ -313,7 +332,9 @@ mod app {
         if let Some(snapshot) = dequeue_last(input_snapshot_consumer) {
             let result = control.apply_input_snapshot(snapshot);
             if let Some(save) = result.save {
-                let _ = save_producer.enqueue(save);
+                if save_producer.enqueue(save).is_ok() {
+                    control.confirm_save(save);
+                }
             }
             let _ = processor_attributes_producer.enqueue(result.dsp_attributes);
         }
@@ -387,8 +408,24 @@ mod app {
         }
     }
 
+    // NOTE: The module cannot do anything useful without the processor. Keep
+    // blinking rapidly so the failure can be told apart from a regular boot.
+    fn signal_insufficient_memory(status_led: &mut LedUser) -> ! {
+        const MS: u32 = 480_000_000 / 1000;
+        const STEP: u32 = 100 * MS;
+
+        defmt::error!("Not enough memory to initialize the DSP processor");
+
+        loop {
+            status_led.on();
+            cortex_m::asm::delay(STEP);
+            status_led.off();
+            cortex_m::asm::delay(STEP);
+        }
+    }
+
     #[allow(clippy::cast_precision_loss)]
-    fn initialize_dsp_processor(sdram: SDRAM) -> Processor {
+    fn initialize_dsp_processor(sdram: SDRAM) -> Result<Processor, InsufficientMemory> {
         let mut sdram_manager = initialize_sdram_manager(sdram);
         let mut stack_manager = initialize_stack_manager();
         Processor::new(SAMPLE_RATE as f32, &mut stack_manager, &mut sdram_manager)
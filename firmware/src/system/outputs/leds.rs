@@ -2,6 +2,7 @@ use crate::system::hal::gpio;
 
 pub struct Leds {
     pins: Pins,
+    impulse_accumulator: f32,
 }
 
 pub struct Pins {
@@ -33,7 +34,10 @@ type ImpulsePin = gpio::gpioc::PC12<gpio::Output>;
 impl Leds {
     #[must_use]
     pub fn new(pins: Pins) -> Self {
-        Self { pins }
+        Self {
+            pins,
+            impulse_accumulator: 0.0,
+        }
     }
 
     pub fn set_display_config(&mut self, config: DisplayConfig) {
@@ -47,7 +51,16 @@ impl Leds {
         self.pins.display.7.set_state(config[7].into());
     }
 
-    pub fn set_impulse(&mut self, on: bool) {
+    /// Dim the impulse LED by switching it on only on some of the calls.
+    ///
+    /// The brightness is accumulated over calls and the LED is lit
+    /// whenever the sum crosses one, so the duty follows the brightness.
+    pub fn set_impulse(&mut self, brightness: f32) {
+        self.impulse_accumulator += brightness.clamp(0.0, 1.0);
+        let on = self.impulse_accumulator >= 1.0;
+        if on {
+            self.impulse_accumulator -= 1.0;
+        }
         self.pins.impulse.set_state(on.into());
     }
 }
@@ -1,6 +1,6 @@
 pub use daisy::flash::Flash;
 
-use kaseta_control::{Save, SaveStore};
+use kaseta_control::{Save, SaveStore, SAVE_VERSION};
 
 const NUM_SECTORS: usize = 2048;
 
@@ -51,6 +51,9 @@ impl Storage {
         if let Some(latest) = latest_store {
             let save = latest.save();
             defmt::info!("Loaded save version={:?}: {:?}", latest.version(), save);
+            if save.version != SAVE_VERSION {
+                defmt::info!("Migrated save from layout version={:?}", save.version);
+            }
             self.version = latest.version() + 1;
             save
         } else {